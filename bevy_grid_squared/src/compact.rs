@@ -0,0 +1,253 @@
+//! Compact serialization of [`Square`] as a `(x, y)` tuple instead of a
+//! struct.
+//!
+//! Handy for large maps keyed by squares:
+//!
+//! ```ignore
+//! #[serde(with = "bevy_grid_squared::compact::map_keys")]
+//! squares: HashMap<Square, Tile>,
+//! ```
+//!
+//! See [`SquareMap`] for which maps are supported.
+//! Deserialization accepts both the compact tuple and the struct form, so
+//! that files written before can still be read.
+//! This relies on [`Deserializer::deserialize_any`], which is supported by
+//! self-describing formats such as RON or JSON.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::SerializeTuple,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::Square;
+
+/// Serializes as `(x, y)`, deserializes from either `(x, y)` or `{x, y}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompactSquare(pub Square);
+
+impl Serialize for CompactSquare {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(&self.0.x)?;
+        tuple.serialize_element(&self.0.y)?;
+        tuple.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for CompactSquare {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(CompactSquareVisitor)
+    }
+}
+
+/// Maps keyed by [`Square`] that can be written with [`map_keys`].
+pub trait SquareMap {
+    /// The value stored under each square.
+    type Value: Serialize;
+
+    /// Iterates over all the entries in the map.
+    fn entries(&self) -> impl Iterator<Item = (&Square, &Self::Value)>;
+}
+
+impl<V: Serialize, H> SquareMap
+    for bevy::utils::hashbrown::HashMap<Square, V, H>
+{
+    type Value = V;
+
+    fn entries(&self) -> impl Iterator<Item = (&Square, &V)> {
+        self.iter()
+    }
+}
+
+impl<V: Serialize, H> SquareMap for std::collections::HashMap<Square, V, H> {
+    type Value = V;
+
+    fn entries(&self) -> impl Iterator<Item = (&Square, &V)> {
+        self.iter()
+    }
+}
+
+impl<V: Serialize> SquareMap for std::collections::BTreeMap<Square, V> {
+    type Value = V;
+
+    fn entries(&self) -> impl Iterator<Item = (&Square, &V)> {
+        self.iter()
+    }
+}
+
+struct CompactSquareVisitor;
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum Field {
+    X,
+    Y,
+}
+
+impl<'de> Visitor<'de> for CompactSquareVisitor {
+    type Value = CompactSquare;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a square as (x, y) or (x: x, y: y)")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> Result<Self::Value, A::Error> {
+        let x = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let y = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+        Ok(CompactSquare(Square { x, y }))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> Result<Self::Value, A::Error> {
+        let (mut x, mut y) = (None, None);
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::X if x.is_some() => {
+                    return Err(de::Error::duplicate_field("x"))
+                }
+                Field::Y if y.is_some() => {
+                    return Err(de::Error::duplicate_field("y"))
+                }
+                Field::X => x = Some(map.next_value()?),
+                Field::Y => y = Some(map.next_value()?),
+            }
+        }
+
+        Ok(CompactSquare(Square {
+            x: x.ok_or_else(|| de::Error::missing_field("x"))?,
+            y: y.ok_or_else(|| de::Error::missing_field("y"))?,
+        }))
+    }
+}
+
+/// Use with `#[serde(with = "...")]` on maps that are keyed by [`Square`].
+pub mod map_keys {
+    use super::*;
+
+    /// Writes the keys as [`CompactSquare`].
+    pub fn serialize<M, S>(map: &M, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        M: SquareMap,
+        S: Serializer,
+    {
+        serializer.collect_map(
+            map.entries()
+                .map(|(square, value)| (CompactSquare(*square), value)),
+        )
+    }
+
+    /// Reads the keys in either the compact or the struct form.
+    pub fn deserialize<'de, M, V, D>(deserializer: D) -> Result<M, D::Error>
+    where
+        M: FromIterator<(Square, V)>,
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+
+    struct MapVisitor<M, V>(PhantomData<(M, V)>);
+
+    impl<'de, M, V> Visitor<'de> for MapVisitor<M, V>
+    where
+        M: FromIterator<(Square, V)>,
+        V: Deserialize<'de>,
+    {
+        type Value = M;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a map keyed by squares")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(
+            self,
+            mut map: A,
+        ) -> Result<Self::Value, A::Error> {
+            let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+            while let Some((CompactSquare(square), value)) = map.next_entry()? {
+                entries.push((square, value));
+            }
+
+            Ok(entries.into_iter().collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::sq;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Map {
+        #[serde(with = "map_keys")]
+        squares: BTreeMap<Square, u8>,
+    }
+
+    fn example() -> Map {
+        Map {
+            squares: [(sq(-274, -338), 1), (sq(0, 0), 2), (sq(12, -3), 3)]
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn it_round_trips_compact_form() {
+        let map = example();
+
+        let ron = ron::to_string(&map).unwrap();
+        assert!(ron.contains("(-274,-338):1"), "{ron}");
+        assert_eq!(map, ron::from_str(&ron).unwrap());
+
+        let json = serde_json::to_string(&CompactSquare(sq(1, -2))).unwrap();
+        assert_eq!("[1,-2]", json);
+        assert_eq!(
+            CompactSquare(sq(1, -2)),
+            serde_json::from_str(&json).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_round_trips_struct_form() {
+        #[derive(Serialize)]
+        struct OldMap {
+            squares: BTreeMap<Square, u8>,
+        }
+
+        let map = example();
+
+        let old_ron = ron::to_string(&OldMap {
+            squares: map.squares.clone(),
+        })
+        .unwrap();
+        assert!(old_ron.contains("(x:-274,y:-338):1"), "{old_ron}");
+        assert_eq!(map, ron::from_str(&old_ron).unwrap());
+
+        let json = serde_json::to_string(&sq(1, -2)).unwrap();
+        assert_eq!(
+            CompactSquare(sq(1, -2)),
+            serde_json::from_str(&json).unwrap()
+        );
+    }
+}
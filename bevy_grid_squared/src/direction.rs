@@ -4,7 +4,6 @@ use bevy::{
     math::Vec2,
     reflect::{std_traits::ReflectDefault, Reflect},
 };
-use serde::{Deserialize, Serialize};
 use strum::EnumIter;
 
 use crate::Square;
@@ -15,12 +14,14 @@ use crate::Square;
     Debug,
     PartialEq,
     Eq,
+    Hash,
+    PartialOrd,
+    Ord,
     Reflect,
     Default,
     EnumIter,
-    Serialize,
-    Deserialize,
 )]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[reflect(Default)]
 pub enum GridDirection {
     /// ↑
@@ -60,6 +61,23 @@ impl GridDirection {
             BottomRight => TopLeft,
         }
     }
+
+    /// Returns this direction and the two directions that are 45° off it.
+    /// E.g. for → that's ↗, → and ↘.
+    pub fn with_adjacent(self) -> [Self; 3] {
+        use GridDirection::*;
+
+        match self {
+            Top => [TopLeft, Top, TopRight],
+            Bottom => [BottomRight, Bottom, BottomLeft],
+            Left => [BottomLeft, Left, TopLeft],
+            Right => [TopRight, Right, BottomRight],
+            TopLeft => [Left, TopLeft, Top],
+            TopRight => [Top, TopRight, Right],
+            BottomLeft => [Bottom, BottomLeft, Left],
+            BottomRight => [Right, BottomRight, Bottom],
+        }
+    }
 }
 
 impl Square {
@@ -125,47 +143,71 @@ impl Square {
             .map(move |direction| self.neighbor(direction))
     }
 
+    /// The three squares in front of someone standing here and facing the
+    /// given direction: straight ahead and the two diagonally ahead.
+    #[inline]
+    pub fn squares_ahead(self, facing: GridDirection) -> [Self; 3] {
+        facing
+            .with_adjacent()
+            .map(|direction| self.neighbor(direction))
+    }
+
+    /// Whether the other square lies within the arc in front of someone
+    /// standing here and facing the given direction.
+    /// The arc is made of the same directions as [`Square::squares_ahead`]
+    /// but it reaches arbitrarily far.
+    ///
+    /// The same square is considered ahead.
+    #[inline]
+    pub fn is_ahead(self, facing: GridDirection, other: Self) -> bool {
+        self.direction_to(other)
+            .is_none_or(|direction| facing.with_adjacent().contains(&direction))
+    }
+
     /// Given a square, returns the direction to the other square.
     /// They don't have to be neighbors, works at arbitrary distance.
     /// If they are the same square then returns `None`.
+    ///
+    /// The circle is split into eight equally wide 45° sectors centered at
+    /// the directions.
     pub fn direction_to(self, other: Self) -> Option<GridDirection> {
+        use std::f32::consts::{FRAC_PI_4, TAU};
+
         use GridDirection::*;
 
         if self == other {
             return None;
         }
 
-        // angle between the diff and the top vector
-        #[allow(clippy::approx_constant)]
-        #[allow(renamed_and_removed_lints)] // the lint was cutting edge
-        #[allow(illegal_floating_point_literal_pattern)]
-        let direction = match (Vec2::from(other) - Vec2::from(self))
-            .angle_between(Vec2::new(0.0, 1.0))
-        {
-            // 15 deg = pi/12 ~= 0.2618
-
-            // +- PI/12 is top
-            -0.2618..=0.2618 => Top,
-            // PI +- PI/12 is bottom
-            -3.1416..=-2.8798 | 2.8798..=3.1416 => Bottom,
-            // PI/2 +- PI/12 is right
-            1.3090..=1.8326 => Right,
-            // -PI/2 +- PI/12 is left
-            -1.8326..=-1.3090 => Left,
-            // PI/12 to PI/2 is top right
-            0.2618..=1.3090 => TopRight,
-            // -PI/2 to -PI/12 is top left
-            -1.3090..=-0.2618 => TopLeft,
-            // -PI to -PI/2 is bottom left
-            -3.1416..=-1.8326 => BottomLeft,
-            // PI/2 to PI is bottom right
-            1.8326..=3.1416 => BottomRight,
-
-            _ => return None,
+        let delta = Vec2::from(other) - Vec2::from(self);
+        // counter-clockwise from →
+        let angle = delta.y.atan2(delta.x).rem_euclid(TAU);
+        // rem_euclid can round a tiny negative angle up to 2π, hence the modulo
+        let direction = match (angle / FRAC_PI_4).round() as u8 % 8 {
+            0 => Right,
+            1 => TopRight,
+            2 => Top,
+            3 => TopLeft,
+            4 => Left,
+            5 => BottomLeft,
+            6 => Bottom,
+            _ => BottomRight,
         };
 
         Some(direction)
     }
+
+    /// The neighbor in the [`Square::direction_to`] the other square.
+    /// Returns `self` if they are the same square.
+    ///
+    /// Useful for chasing without pathfinding.
+    #[inline]
+    pub fn step_towards(self, other: Self) -> Self {
+        match self.direction_to(other) {
+            Some(direction) => self.neighbor(direction),
+            None => self,
+        }
+    }
 }
 
 impl Add<GridDirection> for Square {
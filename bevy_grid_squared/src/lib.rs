@@ -2,6 +2,8 @@
 
 #![feature(const_trait_impl)]
 
+#[cfg(feature = "serde")]
+pub mod compact;
 pub mod direction;
 pub mod shapes;
 
@@ -45,6 +47,13 @@ impl Square {
     pub fn manhattan_distance(self, other: Self) -> i32 {
         (self.x - other.x).abs() + (self.y - other.y).abs()
     }
+
+    /// How many steps it takes to get to the other square if diagonal steps
+    /// are allowed.
+    #[inline]
+    pub fn chebyshev_distance(self, other: Self) -> i32 {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
 }
 
 impl SquareLayout {
@@ -170,6 +179,8 @@ impl Sub<Square> for &Square {
 
 #[cfg(test)]
 mod tests {
+    use strum::IntoEnumIterator;
+
     use super::*;
 
     #[test]
@@ -178,4 +189,77 @@ mod tests {
         assert_eq!(2, sq(0, 2).manhattan_distance(sq(0, 0)));
         assert_eq!(2, sq(0, 0).manhattan_distance(sq(1, 1)));
     }
+
+    #[test]
+    fn it_calcs_chebyshev_distance() {
+        assert_eq!(2, sq(0, 0).chebyshev_distance(sq(0, 2)));
+        assert_eq!(2, sq(0, 2).chebyshev_distance(sq(0, 0)));
+        assert_eq!(1, sq(0, 0).chebyshev_distance(sq(1, 1)));
+        assert_eq!(3, sq(-1, 2).chebyshev_distance(sq(2, 0)));
+        assert_eq!(0, sq(4, 4).chebyshev_distance(sq(4, 4)));
+    }
+
+    #[test]
+    fn it_finds_cardinal_direction() {
+        use GridDirection::*;
+
+        let o = sq(0, 0);
+        assert_eq!(Some(Top), o.direction_to(sq(0, 5)));
+        assert_eq!(Some(Bottom), o.direction_to(sq(0, -1)));
+        assert_eq!(Some(Left), o.direction_to(sq(-3, 0)));
+        assert_eq!(Some(Right), o.direction_to(sq(2, 0)));
+        // within 22.5° of the axis
+        assert_eq!(Some(Right), o.direction_to(sq(5, 2)));
+        assert_eq!(Some(Bottom), o.direction_to(sq(-2, -5)));
+
+        assert_eq!(sq(0, 1), o.step_towards(sq(0, 5)));
+        assert_eq!(sq(1, 0), o.step_towards(sq(5, 2)));
+    }
+
+    #[test]
+    fn it_finds_diagonal_direction() {
+        use GridDirection::*;
+
+        let o = sq(0, 0);
+        assert_eq!(Some(TopRight), o.direction_to(sq(3, 3)));
+        assert_eq!(Some(TopLeft), o.direction_to(sq(-1, 1)));
+        assert_eq!(Some(BottomLeft), o.direction_to(sq(-2, -2)));
+        assert_eq!(Some(BottomRight), o.direction_to(sq(4, -4)));
+        // within 22.5° of the diagonal
+        assert_eq!(Some(TopRight), o.direction_to(sq(2, 3)));
+        assert_eq!(Some(BottomLeft), o.direction_to(sq(-3, -2)));
+
+        assert_eq!(sq(1, 1), o.step_towards(sq(2, 3)));
+        assert_eq!(sq(-1, -1), o.step_towards(sq(-2, -2)));
+    }
+
+    #[test]
+    fn it_has_no_direction_to_same_square() {
+        let o = sq(3, -2);
+        assert_eq!(None, o.direction_to(o));
+        assert_eq!(o, o.step_towards(o));
+    }
+
+    #[test]
+    fn it_steps_onto_neighbor() {
+        for direction in GridDirection::iter() {
+            let neighbor = sq(0, 0).neighbor(direction);
+            assert_eq!(Some(direction), sq(0, 0).direction_to(neighbor));
+            assert_eq!(neighbor, sq(0, 0).step_towards(neighbor));
+        }
+    }
+
+    #[test]
+    fn it_finds_squares_ahead() {
+        assert_eq!(
+            [sq(1, 1), sq(1, 0), sq(1, -1)],
+            sq(0, 0).squares_ahead(GridDirection::Right)
+        );
+
+        let behind = sq(-3, 0);
+        assert!(!sq(0, 0).is_ahead(GridDirection::Right, behind));
+        assert!(sq(0, 0).is_ahead(GridDirection::Left, behind));
+        assert!(sq(0, 0).is_ahead(GridDirection::TopLeft, behind));
+        assert!(sq(0, 0).is_ahead(GridDirection::Right, sq(0, 0)));
+    }
 }
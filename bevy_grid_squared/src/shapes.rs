@@ -1,3 +1,5 @@
+use bevy::utils::HashSet;
+
 use crate::Square;
 
 pub struct ExactSizeSquareIterator<I> {
@@ -35,6 +37,61 @@ pub fn rectangle(
     }
 }
 
+/// Only the border squares of [`rectangle`], each exactly once.
+/// Same bounds as [`rectangle`].
+///
+/// First the bottom row, then the top row and then the left and right
+/// columns without the corners.
+/// If the rectangle is a single row or column, all its squares are the
+/// border.
+pub fn rectangle_outline(
+    [left, right, bottom, top]: [i32; 4],
+) -> impl ExactSizeIterator<Item = Square> {
+    assert!(left <= right, "Left ({left}) not <= right ({right})");
+    assert!(top >= bottom, "Top ({top}) not >= bottom ({bottom})");
+
+    let width = right - left + 1;
+    let height = top - bottom + 1;
+    let count = if width == 1 || height == 1 {
+        width * height
+    } else {
+        2 * width + 2 * height - 4
+    };
+
+    let bottom_row = (left..=right).map(move |x| Square::new(x, bottom));
+    let top_row = (left..=right)
+        .filter(move |_| top != bottom)
+        .map(move |x| Square::new(x, top));
+    let columns = ((bottom + 1)..top).flat_map(move |y| {
+        std::iter::once(Square::new(left, y))
+            .chain((right != left).then_some(Square::new(right, y)))
+    });
+
+    ExactSizeSquareIterator {
+        iter: bottom_row.chain(top_row).chain(columns),
+        count: count as usize,
+    }
+}
+
+/// Squares in rings of increasing [Chebyshev distance] from the center,
+/// starting with the center itself.
+/// Within a ring, the squares are ordered as in [`rectangle_outline`].
+///
+/// The iterator is infinite, pair it with a predicate to find e.g. the
+/// nearest passable square.
+///
+/// [Chebyshev distance]: https://en.wikipedia.org/wiki/Chebyshev_distance
+pub fn spiral(center: Square) -> impl Iterator<Item = Square> {
+    (0..).flat_map(move |ring| {
+        rectangle_outline([
+            center.x - ring,
+            center.x + ring,
+            center.y - ring,
+            center.y + ring,
+        ])
+    })
+}
+
 /// An implementation of [Bresenham's circle algorithm].
 ///
 /// This uses four quadrants, so calling `next()` will return a point for
@@ -49,6 +106,91 @@ pub fn bresenham_circle(
     bresenham_circle::BresenhamCircle::new(center, radius)
 }
 
+/// Squares on the line from `from` to `to`, both ends included, as given by
+/// [Bresenham's line algorithm].
+///
+/// Where the line passes exactly between two squares the tie is broken in
+/// favor of stepping along both axes, therefore the line from `to` to `from`
+/// is not always the same squares in reverse.
+///
+/// [Bresenham's line algorithm]: http://members.chello.at/~easyfilter/bresenham.html
+pub fn bresenham_line(
+    from: Square,
+    to: Square,
+) -> impl Iterator<Item = Square> {
+    let dx = (to.x - from.x).abs();
+    let dy = -(to.y - from.y).abs();
+    let step_x = if from.x < to.x { 1 } else { -1 };
+    let step_y = if from.y < to.y { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    let mut next = Some(from);
+    std::iter::from_fn(move || {
+        let current = next?;
+        next = (current != to).then(|| {
+            let mut square = current;
+            let error2 = 2 * error;
+            if error2 >= dy {
+                error += dy;
+                square.x += step_x;
+            }
+            if error2 <= dx {
+                error += dx;
+                square.y += step_y;
+            }
+            square
+        });
+
+        Some(current)
+    })
+}
+
+/// Returns every square that can be reached from `start` by stepping to
+/// ↑, ↓, ←, → neighbors, as long as each stepped on square is within the
+/// `[left, right, bottom, top]` bounds and `is_passable`.
+///
+/// If the start square itself is out of bounds or not passable, the result is
+/// empty.
+///
+/// The search is iterative with an explicit stack so that it can be used on
+/// large maps without blowing up the call stack.
+pub fn flood_fill(
+    start: Square,
+    is_passable: impl Fn(Square) -> bool,
+    [left, right, bottom, top]: [i32; 4],
+) -> HashSet<Square> {
+    let in_bounds = |square: Square| {
+        square.x >= left
+            && square.x <= right
+            && square.y >= bottom
+            && square.y <= top
+    };
+
+    let mut reached = HashSet::new();
+    if !in_bounds(start) || !is_passable(start) {
+        return reached;
+    }
+
+    let mut stack = vec![start];
+    reached.insert(start);
+
+    while let Some(square) = stack.pop() {
+        for neighbor in square.neighbors_no_diagonal() {
+            if !in_bounds(neighbor)
+                || reached.contains(&neighbor)
+                || !is_passable(neighbor)
+            {
+                continue;
+            }
+
+            reached.insert(neighbor);
+            stack.push(neighbor);
+        }
+    }
+
+    reached
+}
+
 impl<I> Iterator for ExactSizeSquareIterator<I>
 where
     I: Iterator<Item = Square>,
@@ -197,6 +339,113 @@ mod tests {
         assert!(iter.next().is_none());
     }
 
+    #[test]
+    fn rect_outline_has_perimeter_size() {
+        for width in 1..=6 {
+            for height in 1..=6 {
+                let bounds = [-2, width - 3, 1, height];
+                let outline: Vec<_> = rectangle_outline(bounds).collect();
+
+                let expected = if width == 1 || height == 1 {
+                    width * height
+                } else {
+                    2 * (width + height) - 4
+                };
+                assert_eq!(expected as usize, outline.len());
+                assert_eq!(
+                    outline.len(),
+                    rectangle_outline(bounds).len(),
+                    "{bounds:?}"
+                );
+
+                let unique: HashSet<_> = outline.iter().copied().collect();
+                assert_eq!(outline.len(), unique.len(), "{bounds:?}");
+
+                let [left, right, bottom, top] = bounds;
+                for corner in [
+                    sq(left, bottom),
+                    sq(right, bottom),
+                    sq(left, top),
+                    sq(right, top),
+                ] {
+                    assert!(unique.contains(&corner), "{bounds:?}");
+                }
+                // only the border
+                assert!(unique.iter().all(|square| square.x == left
+                    || square.x == right
+                    || square.y == bottom
+                    || square.y == top));
+            }
+        }
+    }
+
+    #[test]
+    fn rect_outline_of_single_square() {
+        assert_eq!(
+            vec![sq(4, -4)],
+            rectangle_outline([4, 4, -4, -4]).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn spiral_starts_with_center_and_its_neighbors() {
+        let center = sq(3, -2);
+        let mut spiral = spiral(center);
+
+        assert_eq!(Some(center), spiral.next());
+        let first_ring: HashSet<_> = spiral.by_ref().take(8).collect();
+        let expected: HashSet<_> = center.neighbors_with_diagonal().collect();
+        assert_eq!(expected, first_ring);
+
+        // then the next ring
+        let second_ring: Vec<_> = spiral.take(16).collect();
+        assert!(second_ring.iter().all(|square| {
+            (square.x - center.x).abs().max((square.y - center.y).abs()) == 2
+        }));
+    }
+
+    #[test]
+    fn flood_fill_does_not_cross_a_wall() {
+        // a vertical wall at x = 0 splits the map into two regions
+        let bounds = [-5, 5, -5, 5];
+        let is_passable = |square: Square| square.x != 0;
+
+        let left_region = flood_fill(sq(-3, 0), is_passable, bounds);
+        let right_region = flood_fill(sq(3, 0), is_passable, bounds);
+
+        assert_eq!(left_region.len(), 5 * 11);
+        assert_eq!(right_region.len(), 5 * 11);
+        assert!(left_region.is_disjoint(&right_region));
+        assert!(left_region.iter().all(|square| square.x < 0));
+        assert!(right_region.iter().all(|square| square.x > 0));
+    }
+
+    #[test]
+    fn flood_fill_is_empty_when_starting_on_impassable_square() {
+        let bounds = [-5, 5, -5, 5];
+
+        assert!(flood_fill(sq(0, 0), |_| false, bounds).is_empty());
+        assert!(flood_fill(sq(6, 0), |_| true, bounds).is_empty());
+    }
+
+    #[test]
+    fn bresenham_line_connects_ends() {
+        let line: Vec<_> = bresenham_line(sq(0, 0), sq(4, 2)).collect();
+        assert_eq!(
+            vec![sq(0, 0), sq(1, 1), sq(2, 1), sq(3, 2), sq(4, 2)],
+            line
+        );
+
+        let line: Vec<_> = bresenham_line(sq(2, -3), sq(-1, 0)).collect();
+        assert_eq!(vec![sq(2, -3), sq(1, -2), sq(0, -1), sq(-1, 0)], line);
+
+        let line: Vec<_> = bresenham_line(sq(0, 5), sq(0, 2)).collect();
+        assert_eq!(vec![sq(0, 5), sq(0, 4), sq(0, 3), sq(0, 2)], line);
+
+        let line: Vec<_> = bresenham_line(sq(7, 7), sq(7, 7)).collect();
+        assert_eq!(vec![sq(7, 7)], line);
+    }
+
     #[test]
     fn bresenham_circle_contains_rim_of_circle_with_radius_3() {
         let rim: HashSet<_> = vec![
@@ -0,0 +1,231 @@
+//! Human readable names of the inputs bound to an action, e.g. for a controls
+//! menu or a prompt such as "Press Space to interact".
+//!
+//! The labels are derived from the leafwing input types.
+//! Multi-word names are split on the case change so that e.g.
+//! [`GamepadButtonType::DPadUp`] reads "DPad Up".
+
+use bevy::prelude::*;
+use leafwing_input_manager::{
+    axislike::{AxisType, DualAxis, VirtualDPad},
+    input_map::InputMap,
+    user_input::{InputKind, Modifier, UserInput},
+};
+
+use crate::GlobalAction;
+
+impl GlobalAction {
+    /// Labels of all inputs bound to this action in the order they were
+    /// inserted to the map.
+    ///
+    /// - a single key or button is its name, e.g. "W" or "DPad Up"
+    /// - a chord joins its parts with `+`, e.g. "Alt Left+Space"
+    /// - a virtual dpad lists its directions up, left, down, right separated
+    ///   with `/`, e.g. "W/A/S/D"
+    /// - an analog stick is its name, e.g. "Left Stick"
+    pub fn binding_labels(self, input_map: &InputMap<Self>) -> Vec<String> {
+        input_map
+            .get(&self)
+            .map(|inputs| inputs.iter().map(user_input_label).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn user_input_label(input: &UserInput) -> String {
+    match input {
+        UserInput::Single(kind) => input_kind_label(kind),
+        UserInput::Chord(kinds) => kinds
+            .iter()
+            .map(input_kind_label)
+            .collect::<Vec<_>>()
+            .join("+"),
+        UserInput::VirtualDPad(VirtualDPad {
+            up,
+            down,
+            left,
+            right,
+        }) => [up, left, down, right].map(input_kind_label).join("/"),
+        UserInput::VirtualAxis(axis) => [&axis.negative, &axis.positive]
+            .map(input_kind_label)
+            .join("/"),
+    }
+}
+
+fn input_kind_label(kind: &InputKind) -> String {
+    match kind {
+        InputKind::PhysicalKey(key) => key_label(*key),
+        InputKind::GamepadButton(button) => gamepad_button_label(*button),
+        InputKind::DualAxis(dual_axis) => dual_axis_label(dual_axis),
+        InputKind::SingleAxis(single_axis) => {
+            axis_type_label(single_axis.axis_type)
+        }
+        InputKind::Modifier(modifier) => match modifier {
+            Modifier::Alt => "Alt",
+            Modifier::Control => "Ctrl",
+            Modifier::Shift => "Shift",
+            Modifier::Super => "Super",
+        }
+        .to_string(),
+        InputKind::Mouse(button) => {
+            format!("Mouse {}", split_words(&format!("{button:?}")))
+        }
+        InputKind::MouseWheel(_) => "Mouse Wheel".to_string(),
+        InputKind::MouseMotion(_) => "Mouse".to_string(),
+    }
+}
+
+fn key_label(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+
+    // "KeyW" is just "W" and "Digit1" is just "1"
+    let name = name
+        .strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name);
+
+    split_words(name)
+}
+
+fn gamepad_button_label(button: GamepadButtonType) -> String {
+    use GamepadButtonType::*;
+
+    // face buttons are named after the Xbox layout
+    match button {
+        South => "A".to_string(),
+        East => "B".to_string(),
+        West => "X".to_string(),
+        North => "Y".to_string(),
+        other => split_words(&format!("{other:?}")),
+    }
+}
+
+fn dual_axis_label(dual_axis: &DualAxis) -> String {
+    match dual_axis.x.axis_type {
+        AxisType::Gamepad(GamepadAxisType::LeftStickX) => {
+            "Left Stick".to_string()
+        }
+        AxisType::Gamepad(GamepadAxisType::RightStickX) => {
+            "Right Stick".to_string()
+        }
+        other => axis_type_label(other),
+    }
+}
+
+fn axis_type_label(axis_type: AxisType) -> String {
+    match axis_type {
+        AxisType::Gamepad(axis) => split_words(&format!("{axis:?}")),
+        AxisType::MouseWheel(_) => "Mouse Wheel".to_string(),
+        AxisType::MouseMotion(_) => "Mouse".to_string(),
+    }
+}
+
+/// "LeftTrigger2" becomes "Left Trigger 2" but "F1" stays as it is.
+/// "DPad" is kept together.
+fn split_words(name: &str) -> String {
+    let mut words = String::with_capacity(name.len() + 4);
+
+    let mut prev: Option<char> = None;
+    for c in name.chars() {
+        let starts_word = match prev {
+            None => false,
+            Some('D') if words == "D" => false, // DPad
+            Some(prev) => {
+                (c.is_uppercase() && !prev.is_uppercase())
+                    || (c.is_ascii_digit() && prev.is_lowercase())
+            }
+        };
+
+        if starts_word {
+            words.push(' ');
+        }
+        words.push(c);
+        prev = Some(c);
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_labels_single_key() {
+        let mut input_map = InputMap::default();
+        input_map.insert(
+            GlobalAction::Interact,
+            UserInput::Single(InputKind::PhysicalKey(KeyCode::KeyW)),
+        );
+        input_map.insert(
+            GlobalAction::Interact,
+            UserInput::Single(InputKind::PhysicalKey(KeyCode::ArrowUp)),
+        );
+
+        assert_eq!(
+            vec!["W", "Arrow Up"],
+            GlobalAction::Interact.binding_labels(&input_map)
+        );
+        assert!(GlobalAction::Cancel.binding_labels(&input_map).is_empty());
+    }
+
+    #[test]
+    fn it_labels_chord() {
+        let mut input_map = InputMap::default();
+        input_map.insert(
+            GlobalAction::InspectInteract,
+            UserInput::Chord(vec![
+                InputKind::PhysicalKey(KeyCode::KeyA),
+                InputKind::PhysicalKey(KeyCode::KeyW),
+            ]),
+        );
+
+        assert_eq!(
+            vec!["A+W"],
+            GlobalAction::InspectInteract.binding_labels(&input_map)
+        );
+    }
+
+    #[test]
+    fn it_labels_gamepad_button() {
+        let mut input_map = InputMap::default();
+        input_map.insert(
+            GlobalAction::Interact,
+            UserInput::Single(InputKind::GamepadButton(
+                GamepadButtonType::DPadUp,
+            )),
+        );
+        input_map.insert(
+            GlobalAction::Interact,
+            UserInput::Single(InputKind::GamepadButton(
+                GamepadButtonType::South,
+            )),
+        );
+        input_map.insert(
+            GlobalAction::Interact,
+            UserInput::Single(InputKind::GamepadButton(
+                GamepadButtonType::LeftTrigger2,
+            )),
+        );
+
+        assert_eq!(
+            vec!["DPad Up", "A", "Left Trigger 2"],
+            GlobalAction::Interact.binding_labels(&input_map)
+        );
+    }
+
+    #[test]
+    fn it_labels_default_movement() {
+        let labels =
+            GlobalAction::Move.binding_labels(&GlobalAction::input_map());
+
+        assert_eq!(
+            vec![
+                "Left Stick",
+                "W/A/S/D",
+                "DPad Up/DPad Left/DPad Down/DPad Right",
+                "Arrow Up/Arrow Left/Arrow Down/Arrow Right",
+            ],
+            labels
+        );
+    }
+}
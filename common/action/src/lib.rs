@@ -2,15 +2,25 @@
 
 #![deny(missing_docs)]
 
+mod labels;
+mod replay;
+
+use std::time::Duration;
+
 use bevy::prelude::*;
 pub use leafwing_input_manager::{self, action_state::ActionState};
 use leafwing_input_manager::{
     axislike::{DeadZoneShape, DualAxis, VirtualDPad},
+    clashing_inputs::ClashStrategy,
     input_map::InputMap,
-    plugin::InputManagerPlugin,
+    plugin::{InputManagerPlugin, InputManagerSystem},
     user_input::{InputKind, UserInput},
     Actionlike,
 };
+pub use replay::{
+    ActionRecorder, ActionRecording, ActionReplay, RecordedTick, RECORDING_PATH,
+};
+use serde::{Deserialize, Serialize};
 use strum::{EnumIter, IntoEnumIterator};
 
 /// Registers necessary types, inserts resources and adds the dependent
@@ -21,22 +31,57 @@ impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<ActionState<GlobalAction>>()
             .insert_resource(GlobalAction::input_map())
+            // chords such as GlobalAction::InspectInteract consume the
+            // actions they are made of
+            .insert_resource(ClashStrategy::PrioritizeLongest)
             .add_plugins(InputManagerPlugin::<GlobalAction>::default());
 
+        app.init_resource::<StickMapping>();
+
+        app.init_resource::<ActiveInputDevice>()
+            .add_systems(First, update_active_input_device);
+
+        app.add_systems(
+            PreUpdate,
+            (
+                replay::replay
+                    .in_set(InputManagerSystem::ManualControl)
+                    .run_if(resource_exists::<ActionReplay>),
+                replay::record
+                    .after(InputManagerSystem::ManualControl)
+                    .run_if(resource_exists::<ActionRecorder>),
+            ),
+        );
+
         #[cfg(feature = "devtools")]
         {
             use leafwing_input_manager::action_state::ActionData;
 
             app.register_type::<GlobalAction>()
                 .register_type::<ActionState<GlobalAction>>()
-                .register_type::<ActionData>();
+                .register_type::<ActionData>()
+                .register_type::<StickMapping>()
+                .register_type::<ActiveInputDevice>()
+                .register_type::<InputDevice>();
+
+            app.add_systems(Update, replay::toggle);
         }
     }
 }
 
 /// These actions are used throughout the game.
 #[derive(
-    Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect, EnumIter,
+    Actionlike,
+    PartialEq,
+    Eq,
+    Hash,
+    Clone,
+    Copy,
+    Debug,
+    Reflect,
+    EnumIter,
+    Serialize,
+    Deserialize,
 )]
 #[non_exhaustive]
 pub enum GlobalAction {
@@ -52,6 +97,17 @@ pub enum GlobalAction {
     /// This is mainly relevant for actions of gathering information about the
     /// world.
     Inspect,
+    /// Interact pressed while [`GlobalAction::Inspect`] is held.
+    ///
+    /// This is a chord that takes priority over its parts.
+    /// While it's active, neither [`GlobalAction::Inspect`] nor
+    /// [`GlobalAction::Interact`] are pressed.
+    /// See [`inspect_interact_just_pressed`] for the order of presses.
+    InspectInteract,
+    /// When held, the player runs instead of walking.
+    Run,
+    /// Shows or hides the minimap.
+    ToggleMinimap,
 
     /// Numeric input for zero.
     NumZero,
@@ -101,6 +157,59 @@ pub enum MovementAction {
     MoveDownRight,
 }
 
+/// How the analog stick maps to the eight [`MovementAction`]s.
+///
+/// Different controllers drift differently and some players find diagonals
+/// hard to hit.
+#[derive(Resource, Reflect, Debug, Clone, Copy, PartialEq)]
+#[reflect(Resource)]
+pub struct StickMapping {
+    /// If both axes are below this value, the stick is considered at rest.
+    pub dead_zone: f32,
+    /// Width in radians of the sector around each diagonal that maps to that
+    /// diagonal.
+    /// The rest of the circle goes to the closest cardinal direction.
+    ///
+    /// Default is π/4, i.e. all eight directions are equally wide.
+    /// Widening it makes diagonals easier to hit.
+    /// Clamped to `[0, π/2]`.
+    pub diagonal_width: f32,
+}
+
+/// Which kind of device the player is using.
+/// Useful to show keyboard vs gamepad glyphs in prompts.
+///
+/// To avoid flickering the prompts, one stray input from the other device is
+/// not enough to switch.
+/// The other device must produce [`ActiveInputDevice::PRESSES_TO_SWITCH`]
+/// inputs, each within [`ActiveInputDevice::DEBOUNCE_WINDOW`] of the previous.
+#[derive(Resource, Reflect, Default, Debug)]
+#[reflect(Resource)]
+pub struct ActiveInputDevice {
+    device: InputDevice,
+    /// How many inputs in a row came from the other device.
+    other_device_inputs: u8,
+    /// Elapsed time when the other device last produced an input.
+    other_device_last_input_at: Duration,
+}
+
+/// See [`ActiveInputDevice`].
+#[derive(Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputDevice {
+    /// Keyboard is the default until the player touches a gamepad.
+    #[default]
+    Keyboard,
+    /// Any connected gamepad.
+    Gamepad,
+}
+
+/// Runs a system if the player is using a gamepad.
+pub fn using_gamepad() -> impl FnMut(Res<ActiveInputDevice>) -> bool {
+    move |active_device: Res<ActiveInputDevice>| {
+        active_device.get() == InputDevice::Gamepad
+    }
+}
+
 /// Runs a system if cancel action is being held.
 pub fn cancel_just_pressed(
 ) -> impl FnMut(Res<ActionState<GlobalAction>>) -> bool {
@@ -124,11 +233,39 @@ pub fn inspect_pressed() -> impl FnMut(Res<ActionState<GlobalAction>>) -> bool {
     }
 }
 
-/// Runs a system if inspect action was just released.
+/// Runs a system if the player just stopped holding inspect.
+///
+/// The [`GlobalAction::InspectInteract`] chord consumes inspect while it's
+/// held, that doesn't count as releasing it.
+/// Releasing the chord does unless inspect is still held on its own.
 pub fn inspect_just_released(
 ) -> impl FnMut(Res<ActionState<GlobalAction>>) -> bool {
     move |action_state: Res<ActionState<GlobalAction>>| {
-        action_state.just_released(&GlobalAction::Inspect)
+        use GlobalAction::{Inspect, InspectInteract};
+
+        if action_state.pressed(&InspectInteract) {
+            false
+        } else if action_state.just_released(&InspectInteract) {
+            !action_state.pressed(&Inspect)
+        } else {
+            action_state.just_released(&Inspect)
+        }
+    }
+}
+
+/// Runs a system if interact was just pressed while inspect is held.
+///
+/// The combo is recognized on the interact edge.
+/// Pressing inspect while interact is already held does not count because
+/// the lone interact has already fired by then.
+/// Releasing the buttons in any order ends the combo and since the chord
+/// consumes its parts, whichever one is still held is pressed again.
+pub fn inspect_interact_just_pressed(
+) -> impl FnMut(Res<ActionState<GlobalAction>>) -> bool {
+    move |action_state: Res<ActionState<GlobalAction>>| {
+        action_state.just_pressed(&GlobalAction::InspectInteract)
+            // if interact was held first, the chord just consumed it
+            && !action_state.just_released(&GlobalAction::Interact)
     }
 }
 
@@ -148,6 +285,19 @@ pub fn interaction_just_pressed(
     }
 }
 
+/// Runs a system if interaction was just pressed, be it on its own or while
+/// inspect is held.
+/// See [`inspect_interact_just_pressed`].
+pub fn any_interaction_just_pressed(
+) -> impl FnMut(Res<ActionState<GlobalAction>>) -> bool {
+    let mut alone = interaction_just_pressed();
+    let mut while_inspecting = inspect_interact_just_pressed();
+
+    move |action_state: Res<ActionState<GlobalAction>>| {
+        alone(Res::clone(&action_state)) || while_inspecting(action_state)
+    }
+}
+
 /// Any movement action is being held.
 ///
 /// Prefer this to `just_pressed` because sometimes controller input does not
@@ -169,7 +319,69 @@ pub fn numeric_key_pressed(
     }
 }
 
+/// Any action was just pressed, e.g. for "press anything to continue"
+/// prompts.
+///
+/// Movement only counts when pressed with keys or the d-pad.
+/// A drifting analog stick could otherwise press it without the player
+/// touching it.
+pub fn any_action_just_pressed() -> impl FnMut(
+    Res<ActionState<GlobalAction>>,
+    Res<ButtonInput<KeyCode>>,
+    Res<ButtonInput<GamepadButton>>,
+) -> bool {
+    move |action_state: Res<ActionState<GlobalAction>>,
+          keyboard: Res<ButtonInput<KeyCode>>,
+          gamepad_buttons: Res<ButtonInput<GamepadButton>>| {
+        let moved_digitally = || {
+            GlobalAction::digital_move_inputs().any(|input| match input {
+                InputKind::PhysicalKey(key) => keyboard.just_pressed(key),
+                InputKind::GamepadButton(button_type) => gamepad_buttons
+                    .get_just_pressed()
+                    .any(|button| button.button_type == button_type),
+                _ => false,
+            })
+        };
+
+        GlobalAction::iter()
+            .filter(|action| *action != GlobalAction::Move)
+            .any(|action| action_state.just_pressed(&action))
+            || (action_state.pressed(&GlobalAction::Move) && moved_digitally())
+    }
+}
+
+/// Call this when leaving a scene or otherwise changing the game loop.
+///
+/// The frame that begins a transition usually also runs the first systems of
+/// the next game loop.
+/// An action that was just pressed, such as the "Enter" that chose the exit,
+/// would be seen by them as well.
+/// This consumes interaction, cancellation and movement so that they only
+/// register again once released and pressed anew.
+pub fn consume_for_transition(controls: &mut ActionState<GlobalAction>) {
+    for action in [
+        GlobalAction::Interact,
+        GlobalAction::InspectInteract,
+        GlobalAction::Cancel,
+        GlobalAction::Move,
+    ] {
+        controls.consume(&action);
+    }
+}
+
 impl GlobalAction {
+    /// Keys and buttons of the d-pads bound to [`GlobalAction::Move`], i.e.
+    /// everything but the analog stick.
+    fn digital_move_inputs() -> impl Iterator<Item = InputKind> {
+        Self::default_keyboard_input(Self::Move)
+            .into_iter()
+            .filter_map(|input| match input {
+                UserInput::VirtualDPad(dpad) => Some(dpad),
+                _ => None,
+            })
+            .flat_map(|dpad| [dpad.up, dpad.down, dpad.left, dpad.right])
+    }
+
     /// Returns all numeric actions from zero to nine.
     pub fn numerical() -> Vec<Self> {
         vec![
@@ -202,7 +414,7 @@ impl GlobalAction {
         use GamepadButtonType::*;
         use InputKind::{GamepadButton as GPad, PhysicalKey as Kbd};
         use KeyCode::*;
-        use UserInput::Single;
+        use UserInput::{Chord, Single};
 
         match action {
             Self::Interact => {
@@ -240,6 +452,30 @@ impl GlobalAction {
                 Single(GPad(LeftTrigger)),
                 Single(GPad(LeftTrigger2)),
             ],
+            Self::InspectInteract => {
+                let keyboard =
+                    [AltLeft, AltRight].into_iter().flat_map(|alt| {
+                        [Space, Enter].map(|interact| {
+                            Chord(vec![Kbd(alt), Kbd(interact)])
+                        })
+                    });
+                let gamepad = [LeftTrigger, LeftTrigger2]
+                    .map(|trigger| Chord(vec![GPad(trigger), GPad(South)]));
+
+                keyboard.chain(gamepad).collect()
+            }
+            Self::Run => vec![
+                Single(Kbd(ShiftLeft)),
+                Single(Kbd(ShiftRight)),
+                Single(GPad(RightTrigger)),
+                Single(GPad(RightTrigger2)),
+            ],
+            Self::ToggleMinimap => {
+                vec![
+                    Single(Kbd(KeyM)),
+                    Single(GPad(West)), // X
+                ]
+            }
             Self::NumZero => vec![Single(Kbd(Digit0))],
             Self::NumOne => vec![Single(Kbd(Digit1))],
             Self::NumTwo => vec![Single(Kbd(Digit2))],
@@ -257,13 +493,30 @@ impl GlobalAction {
 /// Extends [`ActionState`] with methods specific to this game.
 pub trait ActionStateExt {
     /// Returns the movement action if the action state is in a movement state.
-    fn movement_action(&self) -> Option<MovementAction>;
+    ///
+    /// Uses the default [`StickMapping`].
+    /// Good enough for menus, prefer [`ActionStateExt::movement_action_with`]
+    /// for moving around.
+    fn movement_action(&self) -> Option<MovementAction> {
+        self.movement_action_with(&StickMapping::default())
+    }
+
+    /// Returns the movement action if the action state is in a movement state.
+    /// The analog stick is mapped to eight directions with the given
+    /// [`StickMapping`].
+    fn movement_action_with(
+        &self,
+        mapping: &StickMapping,
+    ) -> Option<MovementAction>;
 }
 
 impl ActionStateExt for ActionState<GlobalAction> {
-    fn movement_action(&self) -> Option<MovementAction> {
+    fn movement_action_with(
+        &self,
+        mapping: &StickMapping,
+    ) -> Option<MovementAction> {
         let axis_pair = self.axis_pair(&GlobalAction::Move)?;
-        from_dual_axis(axis_pair.xy())
+        from_dual_axis(axis_pair.xy(), mapping)
     }
 }
 
@@ -295,46 +548,485 @@ impl MovementAction {
     }
 }
 
-fn from_dual_axis(left_stick: Vec2) -> Option<MovementAction> {
-    // Generated by chat-gpt based on my description and example of the output
-    // enum.
+impl Default for StickMapping {
+    fn default() -> Self {
+        Self {
+            dead_zone: 0.1,
+            diagonal_width: std::f32::consts::FRAC_PI_4,
+        }
+    }
+}
+
+impl ActiveInputDevice {
+    /// How many inputs from the other device flip the active device.
+    pub const PRESSES_TO_SWITCH: u8 = 2;
+    /// Inputs from the other device further apart than this don't count as
+    /// being in a row.
+    pub const DEBOUNCE_WINDOW: Duration = Duration::from_secs(1);
 
-    use MovementAction::*;
+    /// The device the player is using.
+    pub fn get(&self) -> InputDevice {
+        self.device
+    }
+
+    fn register_input(&mut self, device: InputDevice, now: Duration) {
+        if device == self.device {
+            // a stray input from the other device is forgotten
+            self.other_device_inputs = 0;
+            return;
+        }
+
+        if now.saturating_sub(self.other_device_last_input_at)
+            > Self::DEBOUNCE_WINDOW
+        {
+            self.other_device_inputs = 0;
+        }
+
+        self.other_device_inputs += 1;
+        self.other_device_last_input_at = now;
 
-    // Define a small threshold to avoid noise in the analog stick
-    let threshold = 0.1;
+        if self.other_device_inputs >= Self::PRESSES_TO_SWITCH {
+            trace!("Switching active input device to {device:?}");
+            self.device = device;
+            self.other_device_inputs = 0;
+        }
+    }
+}
+
+/// When a [`GlobalAction`] is pressed, we check which kind of device produced
+/// the input.
+fn update_active_input_device(
+    time: Res<Time>,
+    action_state: Res<ActionState<GlobalAction>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
+    mut active_device: ResMut<ActiveInputDevice>,
+) {
+    // stick drift should not switch the device
+    const STICK_THRESHOLD: f32 = 0.5;
+
+    let action_pressed = !action_state.get_just_pressed().is_empty()
+        || action_state.pressed(&GlobalAction::Move);
+    if !action_pressed {
+        return;
+    }
+
+    let now = time.elapsed();
+
+    if keyboard.get_just_pressed().next().is_some() {
+        active_device.register_input(InputDevice::Keyboard, now);
+    }
+
+    let stick_moved = gamepads.iter().any(|gamepad| {
+        [GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY]
+            .into_iter()
+            .filter_map(|axis| {
+                gamepad_axes.get(GamepadAxis::new(gamepad, axis))
+            })
+            .any(|value| value.abs() > STICK_THRESHOLD)
+    });
+    if stick_moved || gamepad_buttons.get_just_pressed().next().is_some() {
+        active_device.register_input(InputDevice::Gamepad, now);
+    }
+}
+
+fn from_dual_axis(
+    left_stick: Vec2,
+    mapping: &StickMapping,
+) -> Option<MovementAction> {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, TAU};
+
+    use MovementAction::*;
 
-    // Check if the stick is within the dead zone
-    if left_stick.x.abs() < threshold && left_stick.y.abs() < threshold {
+    // avoid noise in the analog stick
+    if left_stick.x.abs() < mapping.dead_zone
+        && left_stick.y.abs() < mapping.dead_zone
+    {
         return None;
     }
 
-    // Calculate the angle in radians
-    let angle = left_stick.y.atan2(left_stick.x);
-
-    use std::f32::consts::PI;
-
-    const PPI_OVER_8: f32 = PI / 8.0;
-    const P3PI_OVER_8: f32 = 3.0 * PPI_OVER_8;
-    const P5PI_OVER_8: f32 = 5.0 * PPI_OVER_8;
-    const P7PI_OVER_8: f32 = 7.0 * PPI_OVER_8;
-    const NPI_OVER_8: f32 = -PPI_OVER_8;
-    const N3PI_OVER_8: f32 = -P3PI_OVER_8;
-    const N5PI_OVER_8: f32 = -P5PI_OVER_8;
-    const N7PI_OVER_8: f32 = -P7PI_OVER_8;
-
-    let action = match angle {
-        N3PI_OVER_8..NPI_OVER_8 => MoveDownRight, // ↘
-        NPI_OVER_8..PPI_OVER_8 => MoveRight,      // →
-        PPI_OVER_8..P3PI_OVER_8 => MoveUpRight,   // ↗
-        P3PI_OVER_8..P5PI_OVER_8 => MoveUp,       // ↑
-        P5PI_OVER_8..P7PI_OVER_8 => MoveUpLeft,   // ↖
-        // left direction wraps around the positive and negative boundaries of
-        // the circle
-        P7PI_OVER_8..=PI | ..N7PI_OVER_8 => MoveLeft, // ←
-        N7PI_OVER_8..N5PI_OVER_8 => MoveDownLeft,     // ↙
-        _ => MoveDown,                                // ↓
+    // counter-clockwise from →
+    let angle = left_stick.y.atan2(left_stick.x).rem_euclid(TAU);
+    // which quarter of the circle we are in, starting with ↗
+    let quadrant = (angle / FRAC_PI_2) as u8;
+    // angle within the quadrant, the diagonal is in the middle
+    let within_quadrant = angle - quadrant as f32 * FRAC_PI_2;
+    // rem_euclid can round a tiny negative angle up to 2π
+    let quadrant = quadrant % 4;
+
+    let half_diagonal = mapping.diagonal_width.clamp(0.0, FRAC_PI_2) / 2.0;
+    let action = if (within_quadrant - FRAC_PI_4).abs() < half_diagonal {
+        match quadrant {
+            0 => MoveUpRight,   // ↗
+            1 => MoveUpLeft,    // ↖
+            2 => MoveDownLeft,  // ↙
+            _ => MoveDownRight, // ↘
+        }
+    } else if within_quadrant < FRAC_PI_4 {
+        // closer to the axis the quadrant starts at
+        match quadrant {
+            0 => MoveRight, // →
+            1 => MoveUp,    // ↑
+            2 => MoveLeft,  // ←
+            _ => MoveDown,  // ↓
+        }
+    } else {
+        // closer to the axis the quadrant ends at
+        match quadrant {
+            0 => MoveUp,    // ↑
+            1 => MoveLeft,  // ←
+            2 => MoveDown,  // ↓
+            _ => MoveRight, // →
+        }
     };
 
     Some(action)
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, input::InputPlugin};
+    use leafwing_input_manager::input_mocking::MockInput;
+
+    use super::*;
+
+    #[test]
+    fn it_recognizes_inspect_interact_when_inspect_is_held() {
+        let mut app = prepare_app();
+
+        app.send_input(KeyCode::AltLeft);
+        app.update();
+        assert!(action_state(&app).pressed(&GlobalAction::Inspect));
+
+        app.send_input(KeyCode::Space);
+        app.update();
+        let state = action_state(&app);
+        assert!(state.just_pressed(&GlobalAction::InspectInteract));
+        assert!(!state.pressed(&GlobalAction::Interact));
+        assert!(!state.pressed(&GlobalAction::Inspect));
+        assert!(app
+            .world_mut()
+            .run_system_once(inspect_interact_just_pressed()));
+        assert!(!app.world_mut().run_system_once(interaction_just_pressed()));
+    }
+
+    #[test]
+    fn it_keeps_inspecting_while_interacting() {
+        let mut app = prepare_app();
+        let inspect_released = |app: &mut App| {
+            app.world_mut().run_system_once(inspect_just_released())
+        };
+        let interacted = |app: &mut App| {
+            app.world_mut()
+                .run_system_once(any_interaction_just_pressed())
+        };
+
+        app.send_input(KeyCode::AltLeft);
+        app.update();
+        app.send_input(KeyCode::Space);
+        app.update();
+        assert!(interacted(&mut app));
+        assert!(!inspect_released(&mut app));
+
+        // inspect is pressed again
+        app.release_input(KeyCode::Space);
+        app.update();
+        assert!(!interacted(&mut app));
+        assert!(!inspect_released(&mut app));
+        assert!(action_state(&app).pressed(&GlobalAction::Inspect));
+
+        app.send_input(KeyCode::Space);
+        app.update();
+        assert!(interacted(&mut app));
+
+        // letting go of inspect first ends the inspection
+        app.release_input(KeyCode::AltLeft);
+        app.update();
+        assert!(inspect_released(&mut app));
+        assert!(!app.world_mut().run_system_once(inspect_pressed()));
+    }
+
+    #[test]
+    fn it_does_not_recognize_inspect_interact_on_lone_press() {
+        let mut app = prepare_app();
+
+        app.send_input(KeyCode::Space);
+        app.update();
+        assert!(app.world_mut().run_system_once(interaction_just_pressed()));
+        assert!(!app
+            .world_mut()
+            .run_system_once(inspect_interact_just_pressed()));
+
+        // interact was already handled on its own
+        app.send_input(KeyCode::AltLeft);
+        app.update();
+        assert!(action_state(&app).pressed(&GlobalAction::InspectInteract));
+        assert!(!app
+            .world_mut()
+            .run_system_once(inspect_interact_just_pressed()));
+    }
+
+    #[test]
+    fn it_recognizes_any_action_just_pressed() {
+        let mut app = prepare_app();
+
+        app.update();
+        assert!(!app.world_mut().run_system_once(any_action_just_pressed()));
+
+        app.send_input(KeyCode::Digit7);
+        app.update();
+        assert!(app.world_mut().run_system_once(any_action_just_pressed()));
+
+        // held is not just pressed
+        app.update();
+        assert!(!app.world_mut().run_system_once(any_action_just_pressed()));
+
+        // the stick drifts
+        app.release_input(KeyCode::Digit7);
+        app.update();
+        app.world_mut()
+            .resource_mut::<ActionState<GlobalAction>>()
+            .press(&GlobalAction::Move);
+        assert!(!app.world_mut().run_system_once(any_action_just_pressed()));
+
+        // but walking with keys counts
+        app.reset_inputs();
+        app.update();
+        app.send_input(KeyCode::KeyW);
+        app.update();
+        assert!(app.world_mut().run_system_once(any_action_just_pressed()));
+    }
+
+    #[test]
+    fn it_does_not_leak_interact_through_transition() {
+        let mut app = prepare_app();
+
+        app.send_input(KeyCode::Enter);
+        app.update();
+        // the next scene would see the press that chose the exit
+        assert!(app.world_mut().run_system_once(interaction_just_pressed()));
+
+        consume_for_transition(
+            &mut app.world_mut().resource_mut::<ActionState<GlobalAction>>(),
+        );
+        assert!(!app.world_mut().run_system_once(interaction_just_pressed()));
+
+        // still held, but it does not count until pressed again
+        app.update();
+        assert!(!app.world_mut().run_system_once(interaction_pressed()));
+
+        app.release_input(KeyCode::Enter);
+        app.update();
+        app.send_input(KeyCode::Enter);
+        app.update();
+        assert!(app.world_mut().run_system_once(interaction_just_pressed()));
+    }
+
+    #[test]
+    fn it_switches_device_after_repeated_inputs() {
+        let mut w = prepare_world();
+        let gamepad_a =
+            GamepadButton::new(Gamepad::new(0), GamepadButtonType::South);
+
+        press_gamepad_button(&mut w, gamepad_a);
+        assert_eq!(active_device(&w), InputDevice::Keyboard);
+
+        press_gamepad_button(&mut w, gamepad_a);
+        assert_eq!(active_device(&w), InputDevice::Gamepad);
+    }
+
+    #[test]
+    fn it_ignores_a_stray_key_while_using_gamepad() {
+        let mut w = prepare_world();
+        let gamepad_a =
+            GamepadButton::new(Gamepad::new(0), GamepadButtonType::South);
+
+        press_gamepad_button(&mut w, gamepad_a);
+        press_gamepad_button(&mut w, gamepad_a);
+        assert_eq!(active_device(&w), InputDevice::Gamepad);
+
+        press_key(&mut w, KeyCode::Space);
+        press_gamepad_button(&mut w, gamepad_a);
+        press_key(&mut w, KeyCode::Space);
+        assert_eq!(active_device(&w), InputDevice::Gamepad);
+
+        press_key(&mut w, KeyCode::Space);
+        assert_eq!(active_device(&w), InputDevice::Keyboard);
+    }
+
+    #[test]
+    fn it_maps_stick_to_directions_with_default_settings() {
+        use MovementAction::*;
+
+        let mapping = StickMapping::default();
+
+        assert_eq!(None, stick_at(&mapping, 0.0, 0.05));
+        assert_eq!(Some(MoveRight), stick_at(&mapping, 0.0, 1.0));
+        assert_eq!(Some(MoveUpRight), stick_at(&mapping, 45.0, 1.0));
+        assert_eq!(Some(MoveUp), stick_at(&mapping, 90.0, 1.0));
+        assert_eq!(Some(MoveLeft), stick_at(&mapping, 180.0, 1.0));
+        assert_eq!(Some(MoveDownLeft), stick_at(&mapping, -135.0, 1.0));
+
+        // the boundary between → and ↗ is at 22.5°
+        assert_eq!(Some(MoveRight), stick_at(&mapping, 21.0, 1.0));
+        assert_eq!(Some(MoveUpRight), stick_at(&mapping, 24.0, 1.0));
+        // the boundary between ↗ and ↑ is at 67.5°
+        assert_eq!(Some(MoveUpRight), stick_at(&mapping, 66.0, 1.0));
+        assert_eq!(Some(MoveUp), stick_at(&mapping, 69.0, 1.0));
+        // the boundary between ↙ and ← is at -157.5°
+        assert_eq!(Some(MoveLeft), stick_at(&mapping, -159.0, 1.0));
+        assert_eq!(Some(MoveDownLeft), stick_at(&mapping, -156.0, 1.0));
+        // the boundary between ↓ and ↘ is at -67.5°
+        assert_eq!(Some(MoveDown), stick_at(&mapping, -69.0, 1.0));
+        assert_eq!(Some(MoveDownRight), stick_at(&mapping, -66.0, 1.0));
+    }
+
+    #[test]
+    fn it_maps_stick_to_directions_with_widened_diagonals() {
+        use MovementAction::*;
+
+        let mapping = StickMapping {
+            dead_zone: 0.3,
+            // diagonals are now 60° wide, cardinals 30°
+            diagonal_width: 60.0_f32.to_radians(),
+        };
+
+        assert_eq!(None, stick_at(&mapping, 0.0, 0.25));
+        assert_eq!(Some(MoveRight), stick_at(&mapping, 0.0, 0.35));
+
+        assert_eq!(Some(MoveUpRight), stick_at(&mapping, 21.0, 1.0));
+        assert_eq!(Some(MoveUpRight), stick_at(&mapping, 69.0, 1.0));
+        assert_eq!(Some(MoveDownLeft), stick_at(&mapping, -159.0, 1.0));
+        assert_eq!(Some(MoveDownRight), stick_at(&mapping, -69.0, 1.0));
+
+        assert_eq!(Some(MoveRight), stick_at(&mapping, 14.0, 1.0));
+        assert_eq!(Some(MoveUpRight), stick_at(&mapping, 16.0, 1.0));
+        assert_eq!(Some(MoveUp), stick_at(&mapping, 76.0, 1.0));
+        assert_eq!(Some(MoveLeft), stick_at(&mapping, -166.0, 1.0));
+    }
+
+    fn stick_at(
+        mapping: &StickMapping,
+        degrees: f32,
+        magnitude: f32,
+    ) -> Option<MovementAction> {
+        from_dual_axis(
+            Vec2::from_angle(degrees.to_radians()) * magnitude,
+            mapping,
+        )
+    }
+
+    #[test]
+    fn it_replays_recorded_just_pressed_sequence() {
+        let mut app = prepare_app();
+        app.init_resource::<ActionRecorder>();
+
+        let inputs = [
+            vec![KeyCode::Space],
+            vec![KeyCode::Space],
+            vec![],
+            vec![KeyCode::AltLeft],
+            vec![KeyCode::AltLeft, KeyCode::Enter],
+            vec![],
+            vec![KeyCode::Escape],
+        ];
+        let mut live = vec![];
+        for keys in &inputs {
+            for key in keys {
+                app.send_input(*key);
+            }
+            app.update();
+            live.push(just_pressed(&app));
+            app.reset_inputs();
+        }
+
+        let recording = app
+            .world_mut()
+            .remove_resource::<ActionRecorder>()
+            .unwrap()
+            .recording;
+        assert_eq!(inputs.len(), recording.ticks.len());
+        let ron = ron::ser::to_string(&recording).unwrap();
+        let recording: ActionRecording = ron::de::from_str(&ron).unwrap();
+
+        let mut app = prepare_app();
+        app.insert_resource(ActionReplay::from(recording));
+        let mut replayed = vec![];
+        for _ in &inputs {
+            // live input is ignored during the replay
+            app.send_input(KeyCode::Digit1);
+            app.update();
+            replayed.push(just_pressed(&app));
+            app.reset_inputs();
+        }
+        assert_eq!(live, replayed);
+
+        // the replay ends and the live input is back
+        app.update();
+        assert!(!app.world().contains_resource::<ActionReplay>());
+        app.update();
+        assert!(app.world().contains_resource::<InputMap<GlobalAction>>());
+        app.send_input(KeyCode::Digit1);
+        app.update();
+        assert_eq!(vec![GlobalAction::NumOne], just_pressed(&app));
+    }
+
+    fn just_pressed(app: &App) -> Vec<GlobalAction> {
+        GlobalAction::iter()
+            .filter(|action| action_state(app).just_pressed(action))
+            .collect()
+    }
+
+    fn prepare_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, InputPlugin, Plugin));
+        app.update();
+        app
+    }
+
+    fn action_state(app: &App) -> &ActionState<GlobalAction> {
+        app.world().resource::<ActionState<GlobalAction>>()
+    }
+
+    fn prepare_world() -> World {
+        let mut w = World::new();
+        w.init_resource::<Time>();
+        w.init_resource::<ActionState<GlobalAction>>();
+        w.init_resource::<ButtonInput<KeyCode>>();
+        w.init_resource::<ButtonInput<GamepadButton>>();
+        w.init_resource::<Axis<GamepadAxis>>();
+        w.init_resource::<Gamepads>();
+        w.init_resource::<ActiveInputDevice>();
+        w
+    }
+
+    fn press_key(w: &mut World, key: KeyCode) {
+        w.resource_mut::<ButtonInput<KeyCode>>().press(key);
+        run_frame(w);
+    }
+
+    fn press_gamepad_button(w: &mut World, button: GamepadButton) {
+        w.resource_mut::<ButtonInput<GamepadButton>>().press(button);
+        run_frame(w);
+    }
+
+    fn run_frame(w: &mut World) {
+        w.resource_mut::<Time>()
+            .advance_by(Duration::from_millis(100));
+        w.resource_mut::<ActionState<GlobalAction>>()
+            .press(&GlobalAction::Interact);
+
+        w.run_system_once(update_active_input_device);
+
+        w.resource_mut::<ActionState<GlobalAction>>()
+            .release(&GlobalAction::Interact);
+        w.resource_mut::<ButtonInput<KeyCode>>().reset_all();
+        w.resource_mut::<ButtonInput<GamepadButton>>().reset_all();
+    }
+
+    fn active_device(w: &World) -> InputDevice {
+        w.resource::<ActiveInputDevice>().get()
+    }
+}
@@ -0,0 +1,188 @@
+//! Records what [`GlobalAction`]s were pressed each tick and plays them back.
+//! Handy for reproducing bugs and for integration tests of scenes.
+//!
+//! While [`ActionRecorder`] resource exists, the pressed actions are pushed to
+//! it every tick.
+//! While [`ActionReplay`] resource exists, the recorded ticks are fed into the
+//! [`ActionState`] one by one and live input is ignored.
+//! Once the replay runs out of ticks, live input is restored and the resource
+//! is removed.
+//!
+//! With the `devtools` feature, press `F9` to start and stop recording into
+//! [`RECORDING_PATH`] and `F10` to replay it.
+
+use std::{collections::VecDeque, path::Path};
+
+use bevy::prelude::*;
+use leafwing_input_manager::{
+    action_state::ActionState, axislike::DualAxisData, input_map::InputMap,
+};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+use crate::GlobalAction;
+
+/// Where the devtools toggle saves the recording to and replays it from.
+pub const RECORDING_PATH: &str = "input_recording.ron";
+
+/// Pressed actions tick by tick.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct ActionRecording {
+    /// One entry per tick, in order.
+    pub ticks: Vec<RecordedTick>,
+}
+
+/// What the game saw in a single tick.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct RecordedTick {
+    /// Actions that are held, whether just pressed or not.
+    pub pressed: Vec<GlobalAction>,
+    /// The axis pair of [`GlobalAction::Move`] if it's pressed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub movement: Option<(f32, f32)>,
+}
+
+/// Insert to start recording, remove to stop.
+#[derive(Resource, Default, Debug)]
+pub struct ActionRecorder {
+    /// Grows by a tick every frame.
+    pub recording: ActionRecording,
+}
+
+/// Insert to start the replay.
+///
+/// The replay fully overrides live input: the [`InputMap`] resource is taken
+/// out for the duration of the replay so that the live presses don't interfere
+/// with just pressed and just released transitions of the replayed ones.
+///
+/// The first replayed tick compares against whatever the action state was
+/// before the replay, so start recording with nothing held to reproduce the
+/// same `just_pressed` sequence.
+#[derive(Resource, Debug)]
+pub struct ActionReplay {
+    ticks: VecDeque<RecordedTick>,
+    /// Returned to the world when the replay ends.
+    live_input_map: Option<InputMap<GlobalAction>>,
+}
+
+/// Runs after the [`ActionState`] has been updated from live or replayed
+/// input so that it records what the game sees.
+pub(crate) fn record(
+    action_state: Res<ActionState<GlobalAction>>,
+    mut recorder: ResMut<ActionRecorder>,
+) {
+    let pressed = GlobalAction::iter()
+        .filter(|action| action_state.pressed(action))
+        .collect();
+    let movement = action_state
+        .pressed(&GlobalAction::Move)
+        .then(|| action_state.axis_pair(&GlobalAction::Move))
+        .flatten()
+        .map(|axis_pair| (axis_pair.x(), axis_pair.y()));
+
+    recorder
+        .recording
+        .ticks
+        .push(RecordedTick { pressed, movement });
+}
+
+/// Runs in [`leafwing_input_manager::plugin::InputManagerSystem::ManualControl`]
+/// so that it overwrites what the live input update did in this tick.
+pub(crate) fn replay(
+    mut cmd: Commands,
+    input_map: Option<Res<InputMap<GlobalAction>>>,
+    mut replay: ResMut<ActionReplay>,
+    mut action_state: ResMut<ActionState<GlobalAction>>,
+) {
+    if let Some(input_map) = input_map {
+        // from next tick on, the live input won't update the action state
+        trace!("Replaying recorded actions");
+        replay
+            .live_input_map
+            .get_or_insert_with(|| input_map.clone());
+        cmd.remove_resource::<InputMap<GlobalAction>>();
+    }
+
+    let Some(RecordedTick { pressed, movement }) = replay.ticks.pop_front()
+    else {
+        trace!("Replay finished, restoring live input");
+        if let Some(input_map) = replay.live_input_map.take() {
+            cmd.insert_resource(input_map);
+        }
+        cmd.remove_resource::<ActionReplay>();
+        return;
+    };
+
+    for action in GlobalAction::iter() {
+        // pressing a held action keeps it held and releasing a released one
+        // keeps it released, so the just-transitions are preserved
+        if pressed.contains(&action) {
+            action_state.press(&action);
+        } else {
+            action_state.release(&action);
+        }
+    }
+
+    action_state
+        .action_data_mut_or_default(&GlobalAction::Move)
+        .axis_pair = movement.map(|(x, y)| DualAxisData::new(x, y));
+}
+
+/// `F9` toggles recording, `F10` replays the last recording.
+#[cfg(feature = "devtools")]
+pub(crate) fn toggle(
+    mut cmd: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    recorder: Option<Res<ActionRecorder>>,
+) {
+    if keyboard.just_pressed(KeyCode::F9) {
+        if let Some(recorder) = recorder {
+            info!("Saving input recording to {RECORDING_PATH}");
+            if let Err(err) = recorder.recording.save(RECORDING_PATH) {
+                error!("Cannot save input recording: {err}");
+            }
+            cmd.remove_resource::<ActionRecorder>();
+        } else {
+            info!("Recording input");
+            cmd.init_resource::<ActionRecorder>();
+        }
+    } else if keyboard.just_pressed(KeyCode::F10) {
+        match ActionRecording::load(RECORDING_PATH) {
+            Ok(recording) => {
+                info!("Replaying input from {RECORDING_PATH}");
+                cmd.insert_resource(ActionReplay::from(recording));
+            }
+            Err(err) => error!("Cannot load input recording: {err}"),
+        }
+    }
+}
+
+impl ActionRecording {
+    /// Writes the recording as RON.
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let ron = ron::ser::to_string(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, ron)
+    }
+
+    /// Reads a recording written by [`ActionRecording::save`].
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let ron = std::fs::read_to_string(path)?;
+        ron::de::from_str(&ron).map_err(std::io::Error::other)
+    }
+}
+
+impl From<ActionRecording> for ActionReplay {
+    fn from(ActionRecording { ticks }: ActionRecording) -> Self {
+        Self {
+            ticks: ticks.into(),
+            live_input_map: None,
+        }
+    }
+}
+
+impl ActionReplay {
+    /// How many ticks are yet to be replayed.
+    pub fn remaining(&self) -> usize {
+        self.ticks.len()
+    }
+}
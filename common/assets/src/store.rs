@@ -90,11 +90,8 @@ impl<T: AssetList> AssetStore<T> {
         &self,
         asset_server: &bevy::asset::AssetServer,
     ) -> bool {
-        self.assets.values().all(|h| {
-            asset_server.is_loaded_with_dependencies(&UntypedHandle::Strong(
-                Arc::clone(h),
-            ))
-        })
+        self.handles()
+            .all(|h| asset_server.is_loaded_with_dependencies(&h))
     }
 }
 
@@ -105,6 +102,13 @@ impl<T> AssetStore<T> {
             _phantom: PhantomData,
         }
     }
+
+    /// Handles to all assets in the store, e.g. to wait for them to load.
+    pub fn handles(&self) -> impl Iterator<Item = UntypedHandle> + '_ {
+        self.assets
+            .values()
+            .map(|h| UntypedHandle::Strong(Arc::clone(h)))
+    }
 }
 
 impl<T> Default for AssetStore<T> {
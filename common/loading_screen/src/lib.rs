@@ -10,6 +10,31 @@
 //!
 //! Optionally, a background atlas can be chosen to be displayed during the
 //! loading screen.
+//! Optionally, music can be played during the loading screen.
+//! It fades in with the quad and fades out in parallel with the quad that
+//! reveals the game.
+//!
+//! Optionally, the loading screen can be gated on a set of assets with
+//! [`LoadingScreenSettings::gate`].
+//! It then doesn't enter [`LoadingScreenState::WaitForSignalToFinish`] until
+//! all of them are loaded with their dependencies.
+//! Scenes that used to poll their assets in
+//! [`LoadingScreenState::WaitForSignalToFinish`] before calling [`finish`]
+//! can instead push their handles to the gate and run [`finish`] as soon as
+//! they are in the wait state.
+//! The gate can be extended while the loading screen is fading in, e.g. when
+//! the scene inserts its asset store on entering its loading state.
+//!
+//! Optionally, tips can be shown near the bottom of the loading screen with
+//! [`LoadingScreenSettings::tips`].
+//! They are shown while the player stares at the loading screen and rotate
+//! every [`LoadingScreenSettings::rotate_tips_every`].
+//!
+//! Without an atlas, the loading screen is just a solid color which can look
+//! like the game froze.
+//! Optionally, a small pulsing square can be shown in the middle of the
+//! screen during [`LoadingScreenState::WaitForSignalToFinish`] with
+//! [`LoadingScreenSettings::show_spinner`].
 
 #![feature(trivial_bounds)]
 #![deny(missing_docs)]
@@ -20,11 +45,15 @@ use std::time::Duration;
 
 pub use atlases::LoadingScreenAtlas;
 use bevy::{
-    math::vec3, prelude::*, render::view::RenderLayers, utils::Instant,
+    ecs::system::EntityCommands, math::vec3, prelude::*,
+    render::view::RenderLayers, utils::Instant,
+};
+use bevy_kira_audio::{
+    Audio, AudioControl, AudioInstance, AudioSource, AudioTween,
 };
 use common_visuals::{
     camera::{order, render_layer},
-    PRIMARY_COLOR,
+    BeginInterpolationEvent, ColorInterpolation, PRIMARY_COLOR,
 };
 
 /// Slow fade in is the default, can be changed in [`LoadingScreenSettings`].
@@ -34,6 +63,15 @@ pub const DEFAULT_FADE_LOADING_SCREEN_OUT: Duration =
     Duration::from_millis(100);
 /// How many times to scale the original loading image.
 pub const LOADING_IMAGE_TRANSFORM_SCALE: f32 = 5.0;
+/// How long is each tip shown, can be changed in [`LoadingScreenSettings`].
+pub const DEFAULT_ROTATE_TIPS_EVERY: Duration = Duration::from_secs(4);
+
+const TIP_FONT: &str = common_assets::fonts::PIXEL1;
+const TIP_FONT_SIZE: f32 = 18.0;
+const SPINNER_SIZE_PX: f32 = 16.0;
+/// How long it takes for the spinner to go from dim to bright or back.
+const SPINNER_PULSE: Duration = Duration::from_millis(600);
+const SPINNER_DIM_ALPHA: f32 = 0.2;
 
 /// A state machine where the states are the steps of the loading screen.
 /// They are executed in order and loop back to the beginning.
@@ -65,7 +103,8 @@ pub enum LoadingScreenState {
     ///    image)
     FadeOutQuadToShowAtlas,
     /// 8. If requested, stay on this screen for given amount of time before
-    ///    transitioning to [`LoadingScreenState::WaitForSignalToFinish`].
+    ///    transitioning to [`LoadingScreenState::WaitForSignalToFinish`]. Also
+    ///    waits for all assets in [`LoadingScreenSettings::gate`] to load.
     StareAtLoadingScreen,
     /// 9. Now we wait for the loading to be done, user must [`finish_state`].
     WaitForSignalToFinish,
@@ -100,6 +139,27 @@ pub struct LoadingScreenSettings {
     pub fade_loading_screen_out: Duration,
     /// If bg image not present, this value is ignored.
     pub stare_at_loading_screen_for_at_least: Option<Duration>,
+    /// Looped for the duration of the loading screen.
+    /// Fades in over [`LoadingScreenSettings::fade_loading_screen_in`] and
+    /// out over [`LoadingScreenSettings::fade_loading_screen_out`].
+    pub music: Option<Handle<AudioSource>>,
+    /// [`LoadingScreenState::StareAtLoadingScreen`] doesn't move on to
+    /// [`LoadingScreenState::WaitForSignalToFinish`] until all of these are
+    /// loaded with their dependencies.
+    #[reflect(ignore)]
+    pub gate: Vec<UntypedHandle>,
+    /// Shown one at a time near the bottom of the screen during
+    /// [`LoadingScreenState::StareAtLoadingScreen`] and
+    /// [`LoadingScreenState::WaitForSignalToFinish`].
+    /// If empty, no text is shown.
+    pub tips: Vec<String>,
+    /// How long is each tip shown before the next one.
+    pub rotate_tips_every: Duration,
+    /// If there's no [`LoadingScreenSettings::atlas`], shows a pulsing
+    /// square during [`LoadingScreenState::WaitForSignalToFinish`] so that
+    /// the screen doesn't look frozen.
+    /// Ignored if there's an atlas.
+    pub show_spinner: bool,
 }
 
 /// Set the state to this to open loading screen.
@@ -155,6 +215,20 @@ impl bevy::app::Plugin for Plugin {
             stare_at_loading_screen
                 .run_if(in_state(LoadingScreenState::StareAtLoadingScreen)),
         )
+        .add_systems(
+            OnEnter(LoadingScreenState::StareAtLoadingScreen),
+            show_tip,
+        )
+        .add_systems(
+            Update,
+            rotate_tips.run_if(
+                in_state(LoadingScreenState::StareAtLoadingScreen)
+                    .or_else(in_state(wait_state())),
+            ),
+        )
+        .add_systems(OnExit(wait_state()), hide_tip)
+        .add_systems(OnEnter(wait_state()), show_spinner)
+        .add_systems(OnExit(wait_state()), hide_spinner)
         .add_systems(
             Update,
             wait_for_bg_to_load
@@ -171,6 +245,10 @@ impl bevy::app::Plugin for Plugin {
                 .run_if(in_state(LoadingScreenState::FadeInQuadToRemoveAtlas)),
         )
         .add_systems(OnEnter(LoadingScreenState::RemoveAtlas), remove_bg)
+        .add_systems(
+            OnEnter(LoadingScreenState::FadeOutQuadToShowGame),
+            fade_out_music,
+        )
         .add_systems(
             Update,
             fade_out_quad_to_show_game
@@ -206,9 +284,27 @@ struct LoadingImage;
 #[derive(Component)]
 struct LoadingQuad;
 
+/// Only spawned if there are any [`LoadingScreenSettings::tips`].
+#[derive(Component)]
+struct LoadingTip {
+    /// Index into [`LoadingScreenSettings::tips`].
+    index: usize,
+    timer: Timer,
+}
+
+/// Only spawned if [`LoadingScreenSettings::show_spinner`] and there's no
+/// atlas.
+#[derive(Component)]
+struct LoadingSpinner;
+
+/// Holds the handle to the music playing during the loading screen.
+#[derive(Component)]
+struct LoadingMusic(Handle<AudioInstance>);
+
 fn spawn_loading_screen(
     mut cmd: Commands,
     asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
     settings: Res<LoadingScreenSettings>,
     mut next_state: ResMut<NextState<LoadingScreenState>>,
     mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
@@ -316,6 +412,89 @@ fn spawn_loading_screen(
             });
     }
 
+    if let Some(tip) = settings.tips.first() {
+        cmd.spawn((
+            Name::new("Loading screen tip"),
+            LoadingTip {
+                index: 0,
+                timer: Timer::new(
+                    settings.rotate_tips_every,
+                    TimerMode::Repeating,
+                ),
+            },
+            TargetCamera(camera),
+            RenderLayers::layer(render_layer::LOADING),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    bottom: Val::Percent(8.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                // shown once we stare at the loading screen
+                visibility: Visibility::Hidden,
+                // in front of the quad
+                z_index: ZIndex::Global(2),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Name::new("Loading screen tip text"),
+                RenderLayers::layer(render_layer::LOADING),
+                TextBundle::from_section(
+                    tip.clone(),
+                    TextStyle {
+                        font: asset_server.load(TIP_FONT),
+                        font_size: TIP_FONT_SIZE,
+                        // contrasts with the primary color of the quad
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_justify(JustifyText::Center),
+            ));
+        });
+    }
+
+    if settings.show_spinner && settings.atlas.is_none() {
+        cmd.spawn((
+            Name::new("Loading screen spinner"),
+            LoadingSpinner,
+            TargetCamera(camera),
+            RenderLayers::layer(render_layer::LOADING),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(SPINNER_SIZE_PX),
+                    height: Val::Px(SPINNER_SIZE_PX),
+                    left: Val::Percent(50.0),
+                    top: Val::Percent(50.0),
+                    margin: UiRect::all(Val::Px(-SPINNER_SIZE_PX / 2.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor(
+                    Color::WHITE.with_alpha(SPINNER_DIM_ALPHA),
+                ),
+                // shown once we wait for the signal to finish
+                visibility: Visibility::Hidden,
+                // in front of the quad
+                z_index: ZIndex::Global(2),
+                ..default()
+            },
+        ));
+    }
+
+    if let Some(music) = settings.music.clone() {
+        let instance = audio
+            .play(music)
+            .looped()
+            .fade_in(AudioTween::linear(settings.fade_loading_screen_in))
+            .handle();
+
+        cmd.spawn((Name::new("Loading screen music"), LoadingMusic(instance)));
+    }
+
     trace!("Loading screen spawned, entering next state");
     next_state.set(LoadingScreenState::FadeInQuadWhileBgLoading);
 }
@@ -380,6 +559,7 @@ fn fade_out_quad_to_show_atlas(
 }
 
 fn stare_at_loading_screen(
+    asset_server: Res<AssetServer>,
     mut next_state: ResMut<NextState<LoadingScreenState>>,
     settings: Res<LoadingScreenSettings>,
 
@@ -392,6 +572,14 @@ fn stare_at_loading_screen(
         }
     }
 
+    let is_gate_open = settings
+        .gate
+        .iter()
+        .all(|handle| asset_server.is_loaded_with_dependencies(handle));
+    if !is_gate_open {
+        return;
+    }
+
     // reset local state for next time
     *since = None;
 
@@ -400,6 +588,88 @@ fn stare_at_loading_screen(
     next_state.set(LoadingScreenState::WaitForSignalToFinish);
 }
 
+fn show_tip(mut tip: Query<&mut Visibility, With<LoadingTip>>) {
+    for mut visibility in tip.iter_mut() {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+fn rotate_tips(
+    time: Res<Time>,
+    settings: Res<LoadingScreenSettings>,
+
+    mut tip: Query<(&mut LoadingTip, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (mut tip, children) in tip.iter_mut() {
+        if !tip.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        tip.index = (tip.index + 1) % settings.tips.len().max(1);
+        let Some(next) = settings.tips.get(tip.index) else {
+            continue;
+        };
+
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value.clone_from(next);
+            }
+        }
+    }
+}
+
+/// The tip would otherwise stay on top of the quad while it fades in.
+fn hide_tip(mut tip: Query<&mut Visibility, With<LoadingTip>>) {
+    for mut visibility in tip.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn show_spinner(
+    mut cmd: Commands,
+
+    mut spinner: Query<(Entity, &mut Visibility), With<LoadingSpinner>>,
+) {
+    for (entity, mut visibility) in spinner.iter_mut() {
+        *visibility = Visibility::Inherited;
+        pulse_spinner(&mut cmd.entity(entity), true);
+    }
+}
+
+/// Interpolates the alpha of the spinner towards bright or dim and then back,
+/// until the interpolation is removed.
+fn pulse_spinner(spinner: &mut EntityCommands, brighten: bool) {
+    let entity = spinner.id();
+    let alpha = if brighten { 1.0 } else { SPINNER_DIM_ALPHA };
+
+    BeginInterpolationEvent::of_color(
+        entity,
+        None,
+        Color::WHITE.with_alpha(alpha),
+    )
+    .over(SPINNER_PULSE)
+    .when_finished_do(move |cmd| {
+        // the spinner could have been despawned in the meantime
+        if let Some(mut spinner) = cmd.get_entity(entity) {
+            pulse_spinner(&mut spinner, !brighten);
+        }
+    })
+    .insert_to(spinner);
+}
+
+/// The spinner stops pulsing once the game is loaded.
+fn hide_spinner(
+    mut cmd: Commands,
+
+    mut spinner: Query<(Entity, &mut Visibility), With<LoadingSpinner>>,
+) {
+    for (entity, mut visibility) in spinner.iter_mut() {
+        *visibility = Visibility::Hidden;
+        cmd.entity(entity).remove::<ColorInterpolation>();
+    }
+}
+
 fn fade_in_quad_that_hides_atlas(
     time: Res<Time>,
     mut next_state: ResMut<NextState<LoadingScreenState>>,
@@ -454,18 +724,49 @@ fn fade_out_quad_to_show_game(
     )
 }
 
+/// The music fades out in parallel with the quad so that it doesn't cut
+/// abruptly when the loading screen despawns.
+fn fade_out_music(
+    settings: Res<LoadingScreenSettings>,
+    mut audio_instances: ResMut<Assets<AudioInstance>>,
+
+    music: Query<&LoadingMusic>,
+) {
+    for LoadingMusic(handle) in music.iter() {
+        if let Some(instance) = audio_instances.get_mut(handle) {
+            trace!("Fading out loading screen music");
+            instance.stop(AudioTween::linear(settings.fade_loading_screen_out));
+        }
+    }
+}
+
 fn despawn_loading_screen(
     mut cmd: Commands,
     mut next_state: ResMut<NextState<LoadingScreenState>>,
 
     camera: Query<Entity, (With<LoadingCamera>, Without<LoadingQuad>)>,
     quad: Query<Entity, (Without<LoadingCamera>, With<LoadingQuad>)>,
+    music: Query<Entity, With<LoadingMusic>>,
+    tip: Query<Entity, With<LoadingTip>>,
+    spinner: Query<Entity, With<LoadingSpinner>>,
 ) {
     trace!("Despawning loading screen");
 
     cmd.remove_resource::<LoadingScreenSettings>();
     cmd.entity(camera.single()).despawn_recursive();
     cmd.entity(quad.single()).despawn_recursive();
+    // the music has been faded out with the quad
+    for entity in music.iter() {
+        cmd.entity(entity).despawn();
+    }
+    // only spawned if there were any tips
+    for entity in tip.iter() {
+        cmd.entity(entity).despawn_recursive();
+    }
+    // only spawned if requested and there was no atlas
+    for entity in spinner.iter() {
+        cmd.entity(entity).despawn();
+    }
 
     next_state.set(LoadingScreenState::DoNothing);
 }
@@ -512,6 +813,11 @@ impl Default for LoadingScreenSettings {
             fade_loading_screen_in: DEFAULT_FADE_LOADING_SCREEN_IN,
             fade_loading_screen_out: DEFAULT_FADE_LOADING_SCREEN_OUT,
             stare_at_loading_screen_for_at_least: None,
+            music: None,
+            gate: Vec::new(),
+            tips: Vec::new(),
+            rotate_tips_every: DEFAULT_ROTATE_TIPS_EVERY,
+            show_spinner: false,
         }
     }
 }
@@ -153,6 +153,56 @@ impl<T> PoissonsEquation<T> {
         vec2(gradient_x, gradient_y)
     }
 
+    /// Bilinearly interpolates the four grid points around the position.
+    ///
+    /// Unlike [`GridCoords`], the position is continuous: `x` is the column
+    /// and `y` is the row, both can be fractional.
+    /// Positions outside of the field are clamped to its edges.
+    pub fn sample_value(&self, pos: Vec2) -> f32 {
+        let (x, y, tx, ty) = self.surrounding_cell(pos);
+        let at = |x: usize, y: usize| self.grid[y][x].inner();
+
+        let top = at(x, y).lerp(at(x + 1, y), tx);
+        let bottom = at(x, y + 1).lerp(at(x + 1, y + 1), tx);
+
+        top.lerp(bottom, ty)
+    }
+
+    /// The gradient of [`Self::sample_value`] at the position.
+    /// It changes smoothly as the position moves between the grid points,
+    /// unlike [`Self::gradient_at`] which snaps to the nearest grid point.
+    ///
+    /// Like with [`Self::gradient_at`], the y coordinate of the gradient
+    /// points up even though the rows grow down.
+    pub fn sample_gradient(&self, pos: Vec2) -> Vec2 {
+        let (x, y, tx, ty) = self.surrounding_cell(pos);
+        let at = |x: usize, y: usize| self.grid[y][x].inner();
+
+        let gradient_x =
+            (at(x + 1, y) - at(x, y)).lerp(at(x + 1, y + 1) - at(x, y + 1), ty);
+        let gradient_y =
+            (at(x, y) - at(x, y + 1)).lerp(at(x + 1, y) - at(x + 1, y + 1), tx);
+
+        vec2(gradient_x, gradient_y)
+    }
+
+    /// Returns the top left grid point of the cell that contains the position
+    /// and how far into the cell the position is along each axis.
+    ///
+    /// The cell always has all four grid points within the field.
+    /// That's why on the last column or row the position is at the far end of
+    /// the cell.
+    fn surrounding_cell(&self, pos: Vec2) -> (usize, usize, f32, f32) {
+        let x = pos.x.clamp(0.0, (self.width - 1) as f32);
+        let y = pos.y.clamp(0.0, (self.height - 1) as f32);
+
+        // In [`Self::new`] we assert that self.width > 2 && self.height > 2
+        let left = (x.floor() as usize).min(self.width - 2);
+        let top = (y.floor() as usize).min(self.height - 2);
+
+        (left, top, x - left as f32, y - top as f32)
+    }
+
     pub(crate) fn set(&mut self, coords: GridCoords, value: f32) {
         let GridCoords { x, y } = coords;
 
@@ -238,3 +288,53 @@ impl From<&GridPoint> for f32 {
         (*point).inner()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The value is `2x - 3y + 10` so the gradient is `(2, 3)` everywhere
+    /// because the y axis of the gradient points up.
+    fn linear_field() -> PoissonsEquation<()> {
+        let mut field = PoissonsEquation::new(5, 4);
+        for y in 0..field.height {
+            for x in 0..field.width {
+                field.set(
+                    GridCoords { x, y },
+                    2.0 * x as f32 - 3.0 * y as f32 + 10.0,
+                );
+            }
+        }
+
+        field
+    }
+
+    #[test]
+    fn it_interpolates_linear_field() {
+        let field = linear_field();
+
+        for pos in [
+            vec2(0.0, 0.0),
+            vec2(1.5, 2.25),
+            vec2(3.9, 0.1),
+            vec2(4.0, 3.0),
+            vec2(2.0, 1.0),
+        ] {
+            let expected = 2.0 * pos.x - 3.0 * pos.y + 10.0;
+            assert!((field.sample_value(pos) - expected).abs() < 1e-4);
+
+            let gradient = field.sample_gradient(pos);
+            assert!(
+                (gradient - vec2(2.0, 3.0)).length() < 1e-4,
+                "{gradient} at {pos}"
+            );
+        }
+
+        // clamped to the edges
+        assert!((field.sample_value(vec2(-1.0, -1.0)) - 10.0).abs() < 1e-4);
+        assert!(
+            (field.sample_gradient(vec2(10.0, 10.0)) - vec2(2.0, 3.0)).length()
+                < 1e-4
+        );
+    }
+}
@@ -3,15 +3,27 @@
 #![deny(missing_docs)]
 
 use std::{
-    borrow::{Borrow, Cow},
+    borrow::Cow,
+    collections::VecDeque,
     fmt::Display,
     marker::PhantomData,
-    sync::{Arc, Mutex},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use bevy::{prelude::*, utils::Instant};
+use bevy::{
+    app::AppExit,
+    prelude::*,
+    tasks::{IoTaskPool, Task},
+    utils::{HashSet, Instant},
+};
 use rusqlite::{named_params, OptionalExtension};
-use rusqlite_migration::{Migrations, M};
+use rusqlite_migration::Migrations;
+pub use rusqlite_migration::M;
 use serde::{de::DeserializeOwned, Serialize};
 
 /// Inits the store.
@@ -19,7 +31,17 @@ pub struct Plugin;
 
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<GlobalStore>();
+        app.init_resource::<GlobalStore>()
+            .add_event::<StoreChanged>();
+
+        app.add_systems(Update, emit_store_changed).add_systems(
+            Last,
+            (
+                process_queued_jobs,
+                flush_queued_jobs.run_if(on_event::<AppExit>()),
+            )
+                .chain(),
+        );
     }
 }
 
@@ -27,30 +49,98 @@ impl bevy::app::Plugin for Plugin {
 #[derive(Resource)]
 pub struct GlobalStore {
     conn: Arc<Mutex<rusqlite::Connection>>,
+    /// The built-in migrations followed by those added with
+    /// [`GlobalStore::add_migrations`].
+    /// The schema version is the number of these that have been applied.
+    migrations: Vec<M<'static>>,
+    /// Work submitted with [`GlobalStore::spawn_set`] and
+    /// [`GlobalStore::spawn_get`].
+    queue: JobQueue,
+    /// Keys registered with [`GlobalStore::watch`] and their pending
+    /// changes.
+    changes: Arc<Changes>,
+    /// Decides when entries set with [`Entry::set_with_ttl`] expire.
+    /// See [`GlobalStore::set_clock`].
+    clock: Clock,
+    /// See [`GlobalStore::set_dialog_history_cap`].
+    dialog_history_cap: Option<usize>,
+}
+
+/// Returns the current time as a duration since some fixed point.
+type Clock = Arc<dyn Fn() -> Duration + Send + Sync>;
+
+/// Emitted when a key registered with [`GlobalStore::watch`] is written to or
+/// removed.
+///
+/// The events are emitted in the [`Update`] schedule.
+/// Multiple writes to the same key produce multiple events.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct StoreChanged {
+    /// The key that changed.
+    pub key: Cow<'static, str>,
+}
+
+/// Writes can happen on any thread, e.g. in [`GlobalStore::spawn_set`].
+/// They send the changed key into the channel that's drained by
+/// [`emit_store_changed`].
+struct Changes {
+    watched: Mutex<HashSet<Cow<'static, str>>>,
+    sender: Sender<Cow<'static, str>>,
+    receiver: Mutex<Receiver<Cow<'static, str>>>,
+}
+
+/// A read submitted with [`GlobalStore::spawn_get`].
+///
+/// Poll it with [`PendingRead::try_take`] until it yields a value.
+pub struct PendingRead<T> {
+    slot: Arc<Mutex<Option<Option<T>>>>,
+}
+
+type Job = Box<dyn FnOnce(&rusqlite::Connection) + Send>;
+
+/// Jobs are applied by a single task at a time, one after another.
+/// That's how we guarantee that they apply in submission order.
+#[derive(Default)]
+struct JobQueue {
+    jobs: Arc<Mutex<VecDeque<Job>>>,
+    /// The task that is currently applying a batch of jobs, if any.
+    worker: Option<Task<()>>,
 }
 
 /// A key-value entry that you can read, write and remove.
 pub struct Entry<'a, T> {
     store: &'a Mutex<rusqlite::Connection>,
+    changes: &'a Changes,
+    clock: &'a Clock,
     key: Cow<'static, str>,
 
     _phantom: PhantomData<T>,
 }
 
+/// A view of the [`GlobalStore`] whose keys are all prefixed with a
+/// namespace, see [`GlobalStore::namespace`].
+///
+/// Subsystems get an isolated view of the store without formatting the keys
+/// by hand.
+/// The keys are stored as `{namespace}.{key}`, so a namespaced entry is the
+/// same as an entry with the full key.
+pub struct NamespacedStore<'a> {
+    store: &'a GlobalStore,
+    /// Ends with the separator.
+    prefix: String,
+}
+
 impl<'a, T: Serialize + DeserializeOwned> Entry<'a, T> {
     /// Get the deserialized value.
+    ///
+    /// Entries set with [`Entry::set_with_ttl`] are absent once they expire.
+    /// Expired entries are removed from db when read.
     pub fn get(&self) -> Option<T> {
         let now = Instant::now();
 
-        let raw_value: String = {
+        let raw_value = {
             let conn = self.store.lock().unwrap();
-            conn.query_row(
-                "SELECT value FROM kv WHERE key = ?",
-                [&self.key],
-                |row| row.get(0),
-            )
-            .optional()
-            .expect("Cannot query SQLite")?
+            select_unexpired(&conn, self.changes, &self.key, (self.clock)())?
         };
 
         let value =
@@ -65,27 +155,87 @@ impl<'a, T: Serialize + DeserializeOwned> Entry<'a, T> {
     }
 
     /// Write a value over the key that is serializable.
+    /// The value never expires, even if the previous one had a TTL.
     pub fn set(&self, value: T) {
         let now = Instant::now();
 
-        let raw_value =
-            serde_json::to_string(&value).expect("Cannot serialize");
-
         {
             let conn = self.store.lock().unwrap();
-            conn.execute(
-                "INSERT INTO kv (key, value) VALUES (?, ?)
-                ON CONFLICT (key) DO UPDATE SET value = excluded.value",
-                [&self.key.borrow(), &raw_value.as_str()],
-            )
-            .expect("Cannot insert into SQLite");
+            upsert(&conn, &self.key, &value, None);
         }
+        self.changes.notify(&self.key);
 
         let ms = now.elapsed().as_millis();
         if ms > 1 {
             warn!("Entry::set({}) took {ms}ms", self.key);
         }
     }
+
+    /// Like [`Entry::set`] but [`Entry::get`] returns [`None`] once the `ttl`
+    /// elapses, e.g. to not greet the player again for a while.
+    ///
+    /// The time is measured by the store's clock, see
+    /// [`GlobalStore::set_clock`].
+    pub fn set_with_ttl(&self, value: T, ttl: Duration) {
+        let now = Instant::now();
+
+        let expires_at = (self.clock)() + ttl;
+        {
+            let conn = self.store.lock().unwrap();
+            upsert(&conn, &self.key, &value, Some(expires_at));
+        }
+        self.changes.notify(&self.key);
+
+        let ms = now.elapsed().as_millis();
+        if ms > 1 {
+            warn!("Entry::set_with_ttl({}) took {ms}ms", self.key);
+        }
+    }
+
+    /// Writes the value only if the current one equals `expected`.
+    /// Returns whether the value was written.
+    ///
+    /// [`None`] expects the entry to be absent or expired.
+    /// The read and the write happen in one transaction while the store is
+    /// locked, so no other write can land in between.
+    /// Useful for counters that several systems update.
+    /// Like with [`Entry::set`], the written value never expires.
+    pub fn compare_and_set(&self, expected: Option<T>, new: T) -> bool
+    where
+        T: PartialEq,
+    {
+        let now = Instant::now();
+
+        let written = {
+            let mut conn = self.store.lock().unwrap();
+            let tx =
+                conn.transaction().expect("Cannot start SQLite transaction");
+            let current =
+                select_unexpired(&tx, self.changes, &self.key, (self.clock)())
+                    .map(|raw_value| {
+                        serde_json::from_str::<T>(&raw_value)
+                            .expect("Cannot deserialize")
+                    });
+
+            let matches = current == expected;
+            if matches {
+                upsert(&tx, &self.key, &new, None);
+            }
+            tx.commit().expect("Cannot commit SQLite transaction");
+
+            matches
+        };
+        if written {
+            self.changes.notify(&self.key);
+        }
+
+        let ms = now.elapsed().as_millis();
+        if ms > 1 {
+            warn!("Entry::compare_and_set({}) took {ms}ms", self.key);
+        }
+
+        written
+    }
 }
 
 impl<'a, T> Entry<'a, T> {
@@ -98,6 +248,7 @@ impl<'a, T> Entry<'a, T> {
             conn.execute("DELETE FROM kv WHERE key = ?", [&self.key])
                 .expect("Cannot delete from SQLite");
         }
+        self.changes.notify(&self.key);
 
         let ms = now.elapsed().as_millis();
         if ms > 1 {
@@ -106,6 +257,73 @@ impl<'a, T> Entry<'a, T> {
     }
 }
 
+impl<'a> NamespacedStore<'a> {
+    /// The entry under the key within this namespace.
+    pub fn entry<T>(&self, key: impl Display) -> Entry<'a, T> {
+        self.store.entry(format!("{}{key}", self.prefix))
+    }
+
+    /// A namespace within this namespace.
+    pub fn namespace(&self, prefix: impl Display) -> NamespacedStore<'a> {
+        NamespacedStore {
+            store: self.store,
+            prefix: format!("{}{prefix}.", self.prefix),
+        }
+    }
+
+    /// Keys within this namespace that start with the prefix, sorted.
+    /// The keys are returned without the namespace.
+    /// Expired entries are left out.
+    pub fn keys_with_prefix(&self, prefix: impl Display) -> Vec<String> {
+        let now = Instant::now();
+
+        let full_prefix = format!("{}{prefix}", self.prefix);
+        let keys = {
+            let conn = self.store.conn.lock().unwrap();
+            select_keys_with_prefix(&conn, &full_prefix, (self.store.clock)())
+        };
+
+        let ms = now.elapsed().as_millis();
+        if ms > 1 {
+            warn!(
+                "NamespacedStore::keys_with_prefix({full_prefix}) took {ms}ms"
+            );
+        }
+
+        keys.into_iter()
+            .map(|key| key[self.prefix.len()..].to_owned())
+            .collect()
+    }
+
+    /// Removes all entries within this namespace whose keys start with the
+    /// prefix.
+    /// Entries outside of the namespace are never removed.
+    pub fn remove_prefix(&self, prefix: impl Display) {
+        let now = Instant::now();
+
+        let full_prefix = format!("{}{prefix}", self.prefix);
+        {
+            let conn = self.store.conn.lock().unwrap();
+            // there's no time before the epoch, so this lists expired too
+            for key in select_keys_with_prefix(&conn, &full_prefix, default()) {
+                conn.execute("DELETE FROM kv WHERE key = ?", [&key])
+                    .expect("Cannot delete from SQLite");
+                self.store.changes.notify(&key);
+            }
+        }
+
+        let ms = now.elapsed().as_millis();
+        if ms > 1 {
+            warn!("NamespacedStore::remove_prefix({full_prefix}) took {ms}ms");
+        }
+    }
+
+    /// Removes all entries within this namespace.
+    pub fn clear(&self) {
+        self.remove_prefix("");
+    }
+}
+
 pub use inspect_ability::InspectAbilityStore;
 mod inspect_ability {
     use super::*;
@@ -152,11 +370,25 @@ mod dialog {
         fn get_last_dialog<T: From<String>>(&self) -> Option<(T, String)>;
 
         /// New dialog entry.
+        ///
+        /// If the history is capped, see
+        /// [`GlobalStore::set_dialog_history_cap`], the oldest entries of the
+        /// namespace over the cap are removed.
         fn insert_dialog(
             &self,
             namespace_and_name: (impl Display, impl Display),
         );
 
+        /// Up to `n` latest node names of the namespace with the time they
+        /// were transitioned to, oldest first.
+        /// The time is as SQLite's `CURRENT_TIMESTAMP`, i.e. UTC
+        /// `YYYY-MM-DD HH:MM:SS`.
+        fn recent_dialogs(
+            &self,
+            namespace: impl Display,
+            n: usize,
+        ) -> Vec<(String, String)>;
+
         /// Access guard state using a unique guard kind id and a unique node
         /// name.
         ///
@@ -169,6 +401,16 @@ mod dialog {
             namespace_and_name: (impl Display, impl Display),
         ) -> Entry<'_, serde_json::Value>;
 
+        /// Like [`DialogStore::guard_state`] but the state is (de)serialized
+        /// as the given type.
+        /// The key is the same, so the untyped and the typed entries can be
+        /// used interchangeably as long as the shape of the state matches.
+        fn typed_guard_state<G: Serialize + DeserializeOwned>(
+            &self,
+            guard_kind: impl Display,
+            namespace_and_name: (impl Display, impl Display),
+        ) -> Entry<'_, G>;
+
         /// Next time dialog is started with this NPC, the player will get
         /// an option to start from this dialog.
         ///
@@ -179,6 +421,18 @@ mod dialog {
             namespace: impl Display,
         ) -> &Self;
 
+        /// Like [`DialogStore::add_dialog_to_npc`] but the dialog is picked
+        /// proportionally more or less often than the others.
+        /// Dialogs added without a weight have weight of 1.
+        ///
+        /// If the NPC already has the dialog, only the weight is updated.
+        fn add_weighted_dialog_to_npc(
+            &self,
+            npc: impl Display,
+            namespace: impl Display,
+            weight: f32,
+        ) -> &Self;
+
         /// Remove the dialog from the NPC's list of dialogs.
         ///
         /// Idempotent.
@@ -194,6 +448,13 @@ mod dialog {
             npc: impl Display,
         ) -> Vec<T>;
 
+        /// List all the dialogs that the NPC has along with their weights.
+        /// See [`DialogStore::add_weighted_dialog_to_npc`].
+        fn list_weighted_dialogs_for_npc<T: From<String>>(
+            &self,
+            npc: impl Display,
+        ) -> Vec<(T, f32)>;
+
         /// Get the last dialog entry's type path.
         fn was_this_the_last_dialog<T: Eq + From<String>>(
             &self,
@@ -235,18 +496,37 @@ mod dialog {
             &self,
             (namespace, node_name): (impl Display, impl Display),
         ) {
-            let conn = self.conn.lock().unwrap();
+            let namespace = namespace.to_string();
+            let mut conn = self.conn.lock().unwrap();
 
             let now = Instant::now();
-            conn.execute(
+            let tx =
+                conn.transaction().expect("Cannot start SQLite transaction");
+            tx.execute(
                 "INSERT INTO dialog_nodes_transitioned_to (namespace, node_name) \
                 VALUES (:namespace, :node_name)",
                 named_params! {
-                    ":namespace": namespace.to_string(),
+                    ":namespace": namespace,
                     ":node_name": node_name.to_string(),
                 },
             )
             .expect("Cannot insert into SQLite");
+            if let Some(cap) = self.dialog_history_cap {
+                tx.execute(
+                    "DELETE FROM dialog_nodes_transitioned_to \
+                    WHERE namespace = :namespace AND id NOT IN ( \
+                        SELECT id FROM dialog_nodes_transitioned_to \
+                        WHERE namespace = :namespace \
+                        ORDER BY id DESC LIMIT :cap \
+                    )",
+                    named_params! {
+                        ":namespace": namespace,
+                        ":cap": cap,
+                    },
+                )
+                .expect("Cannot delete from SQLite");
+            }
+            tx.commit().expect("Cannot commit SQLite transaction");
 
             let ms = now.elapsed().as_millis();
             if ms > 1 {
@@ -254,11 +534,55 @@ mod dialog {
             }
         }
 
+        fn recent_dialogs(
+            &self,
+            namespace: impl Display,
+            n: usize,
+        ) -> Vec<(String, String)> {
+            let conn = self.conn.lock().unwrap();
+
+            let now = Instant::now();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT node_name, created_at FROM dialog_nodes_transitioned_to \
+                    WHERE namespace = :namespace \
+                    ORDER BY id DESC LIMIT :n",
+                )
+                .expect("Cannot prepare SQLite statement");
+            let mut dialogs = stmt
+                .query_map(
+                    named_params! {
+                        ":namespace": namespace.to_string(),
+                        ":n": n,
+                    },
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .expect("Cannot query SQLite")
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .expect("Cannot read SQLite row");
+            dialogs.reverse();
+
+            let ms = now.elapsed().as_millis();
+            if ms > 1 {
+                warn!("recent_dialogs took {ms}ms");
+            }
+
+            dialogs
+        }
+
         fn guard_state(
             &self,
             guard_kind: impl Display,
             (namespace, node_name): (impl Display, impl Display),
         ) -> Entry<'_, serde_json::Value> {
+            self.typed_guard_state(guard_kind, (namespace, node_name))
+        }
+
+        fn typed_guard_state<G: Serialize + DeserializeOwned>(
+            &self,
+            guard_kind: impl Display,
+            (namespace, node_name): (impl Display, impl Display),
+        ) -> Entry<'_, G> {
             self.entry(format!(
                 "dialog.guard_state.{namespace}.{guard_kind}.{node_name}"
             ))
@@ -273,8 +597,12 @@ mod dialog {
 
             let now = Instant::now();
             conn.execute(
-                "INSERT OR IGNORE INTO npc_dialogs \
-                (npc, namespace) VALUES (:npc, :namespace)",
+                // there's no unique constraint on the pair to ignore
+                "INSERT INTO npc_dialogs (npc, namespace) \
+                SELECT :npc, :namespace WHERE NOT EXISTS ( \
+                    SELECT 1 FROM npc_dialogs \
+                    WHERE npc = :npc AND namespace = :namespace \
+                )",
                 named_params! {
                     ":npc": npc.to_string(),
                     ":namespace": namespace.to_string(),
@@ -290,6 +618,40 @@ mod dialog {
             self
         }
 
+        fn add_weighted_dialog_to_npc(
+            &self,
+            npc: impl Display,
+            namespace: impl Display,
+            weight: f32,
+        ) -> &Self {
+            debug_assert!(weight >= 0.0, "Weight mustn't be negative");
+
+            let npc = npc.to_string();
+            let namespace = namespace.to_string();
+            self.add_dialog_to_npc(&npc, &namespace);
+
+            let conn = self.conn.lock().unwrap();
+
+            let now = Instant::now();
+            conn.execute(
+                "UPDATE npc_dialogs SET weight = :weight \
+                WHERE npc = :npc AND namespace = :namespace",
+                named_params! {
+                    ":npc": npc,
+                    ":namespace": namespace,
+                    ":weight": weight,
+                },
+            )
+            .expect("Cannot update SQLite");
+
+            let ms = now.elapsed().as_millis();
+            if ms > 1 {
+                warn!("add_weighted_dialog_to_npc took {ms}ms");
+            }
+
+            self
+        }
+
         fn remove_dialog_from_npc(
             &self,
             npc: impl Display,
@@ -340,124 +702,1092 @@ mod dialog {
             rows.map(|row| String::into(row.expect("Cannot get row")))
                 .collect()
         }
-    }
-}
 
-impl GlobalStore {
-    /// Create a new store.
-    pub fn new() -> Self {
-        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        fn list_weighted_dialogs_for_npc<T: From<String>>(
+            &self,
+            npc: impl Display,
+        ) -> Vec<(T, f32)> {
+            let conn = self.conn.lock().unwrap();
+
+            let now = Instant::now();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT namespace, weight FROM npc_dialogs WHERE npc = :npc",
+                )
+                .expect("Cannot prepare SQLite");
+            let rows = stmt
+                .query_map(
+                    named_params! {
+                        ":npc": npc.to_string(),
+                    },
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .expect("Cannot query SQLite");
 
-        migrate(&mut conn);
+            let ms = now.elapsed().as_millis();
+            if ms > 1 {
+                warn!("list_weighted_dialogs_for_npc took {ms}ms");
+            }
 
-        Self {
-            conn: Arc::new(Mutex::new(conn)),
+            rows.map(|row| {
+                let (namespace, weight): (String, f64) =
+                    row.expect("Cannot get row");
+                (namespace.into(), weight as f32)
+            })
+            .collect()
         }
     }
-
-    fn entry<T>(&self, key: impl Into<Cow<'static, str>>) -> Entry<'_, T> {
-        Entry::new(&self.conn, key)
-    }
 }
 
-impl Default for GlobalStore {
-    fn default() -> Self {
-        Self::new()
+pub use player_stats::PlayerStatsStore;
+mod player_stats {
+    use super::*;
+
+    /// Store the player's progress.
+    pub trait PlayerStatsStore {
+        /// The player stats are stored as a single serialized blob.
+        /// It's up to the caller to make sense of the value.
+        fn player_stats(&self) -> Entry<'_, serde_json::Value>;
     }
-}
 
-impl<'a, T> Entry<'a, T> {
-    fn new(
-        store: &'a Mutex<rusqlite::Connection>,
-        key: impl Into<Cow<'static, str>>,
-    ) -> Self {
-        Self {
-            store,
-            key: key.into(),
-            _phantom: PhantomData,
+    impl PlayerStatsStore for GlobalStore {
+        fn player_stats(&self) -> Entry<'_, serde_json::Value> {
+            self.entry("player_stats")
         }
     }
 }
 
-fn migrate(conn: &mut rusqlite::Connection) {
-    let migrations = Migrations::new(vec![
-        // generic key value table
-        M::up(
-            "CREATE TABLE kv (
-                key TEXT PRIMARY KEY,
-                value TEXT NOT NULL
-            );",
-        ),
-        // dialogs storage
-        M::up(
-            "CREATE TABLE dialog_nodes_transitioned_to (
-                id INTEGER PRIMARY KEY,
-                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-                namespace TEXT NOT NULL,
-                node_name TEXT NOT NULL
-            );",
-        ),
-        M::up(
-            "CREATE INDEX idx_dialog_nodes_transitioned_to_namespace_node_name \
-            ON dialog_nodes_transitioned_to (namespace, node_name);",
-        ),
-        M::up(
-            "CREATE TABLE npc_dialogs (
-                npc TEXT NOT NULL,
-                namespace TEXT NOT NULL
-            );",
-        ),
-        M::up(
-            "CREATE INDEX idx_npc_dialogs_npc_namespace \
-            ON npc_dialogs (npc, namespace);",
-        ),
-        // what have the player already seen using the inspect ability
-        M::up(
-            "CREATE TABLE discovered_with_inspect_ability (
-                label TEXT PRIMARY KEY
-            );",
-        ),
-    ]);
+pub use actor_position::ActorPositionStore;
+mod actor_position {
+    use super::*;
 
-    migrations.to_latest(conn).unwrap();
-}
+    /// Store where actors were when the player left a scene.
+    pub trait ActorPositionStore {
+        /// The square `(x, y)` the actor stood on when the scene was left.
+        fn actor_position(
+            &self,
+            scene: impl Display,
+            actor: impl Display,
+        ) -> Entry<'_, (i32, i32)>;
+    }
 
-#[cfg(test)]
-mod tests {
-    use bevy::math::vec2;
+    impl ActorPositionStore for GlobalStore {
+        fn actor_position(
+            &self,
+            scene: impl Display,
+            actor: impl Display,
+        ) -> Entry<'_, (i32, i32)> {
+            self.entry(format!("actor_position.{scene}.{actor}"))
+        }
+    }
+}
 
+pub use time_of_day::TimeOfDayStore;
+mod time_of_day {
     use super::*;
 
-    #[test]
-    fn it_serializes_i32() {
-        let conn = new_conn();
-        let store = GlobalStore { conn };
+    /// Store the in-game time of day.
+    pub trait TimeOfDayStore {
+        /// Hours since midnight.
+        fn time_of_day(&self) -> Entry<'_, f32>;
+    }
 
-        let entry = store.entry::<i32>("test");
-        assert_eq!(entry.get(), None);
+    impl TimeOfDayStore for GlobalStore {
+        fn time_of_day(&self) -> Entry<'_, f32> {
+            self.entry("time_of_day")
+        }
+    }
+}
 
-        entry.set(42);
+pub use explored::ExploredStore;
+mod explored {
+    use super::*;
 
-        assert_eq!(entry.get(), Some(42));
+    /// Store which squares of a scene the player has seen.
+    pub trait ExploredStore {
+        /// The squares are stored compactly as rows.
+        /// Each row is `(y, first x, count)` and covers `count` squares
+        /// starting at `(first x, y)` in the direction of increasing `x`.
+        fn explored_squares(
+            &self,
+            scene: impl Display,
+        ) -> Entry<'_, Vec<(i32, i32, u32)>>;
     }
 
-    #[test]
-    fn it_serializes_vec2() {
-        let conn = new_conn();
-        let store = GlobalStore { conn };
+    impl ExploredStore for GlobalStore {
+        fn explored_squares(
+            &self,
+            scene: impl Display,
+        ) -> Entry<'_, Vec<(i32, i32, u32)>> {
+            self.namespace("explored").entry(scene)
+        }
+    }
+}
 
-        let entry = store.entry::<Vec2>("test");
-        assert_eq!(entry.get(), None);
+pub use object_tags::ObjectTagStore;
+mod object_tags {
+    use super::*;
 
-        entry.set(vec2(0.0, 1.0));
+    /// Flags of objects in the world, e.g. whether a door was opened or an
+    /// item taken.
+    ///
+    /// Tags are scoped by the scene and the object within that scene.
+    /// Prefer this over formatting [`Entry`] keys by hand.
+    pub trait ObjectTagStore {
+        /// Tag the object.
+        ///
+        /// Idempotent.
+        fn set_tag(
+            &self,
+            scene: impl Display,
+            object: impl Display,
+            tag: impl Display,
+        );
+
+        /// Whether the object has been tagged with the tag.
+        fn has_tag(
+            &self,
+            scene: impl Display,
+            object: impl Display,
+            tag: impl Display,
+        ) -> bool;
+
+        /// Remove the tag from the object.
+        ///
+        /// Idempotent.
+        fn clear_tag(
+            &self,
+            scene: impl Display,
+            object: impl Display,
+            tag: impl Display,
+        );
+
+        /// All tags of the object, sorted.
+        fn list_tags<T: From<String>>(
+            &self,
+            scene: impl Display,
+            object: impl Display,
+        ) -> Vec<T>;
+    }
+
+    impl ObjectTagStore for GlobalStore {
+        fn set_tag(
+            &self,
+            scene: impl Display,
+            object: impl Display,
+            tag: impl Display,
+        ) {
+            let conn = self.conn.lock().unwrap();
+
+            let now = Instant::now();
+            conn.execute(
+                "INSERT INTO object_tags (scene, object, tag) \
+                VALUES (:scene, :object, :tag) \
+                ON CONFLICT DO NOTHING",
+                named_params! {
+                    ":scene": scene.to_string(),
+                    ":object": object.to_string(),
+                    ":tag": tag.to_string(),
+                },
+            )
+            .expect("Cannot insert into SQLite");
+
+            let ms = now.elapsed().as_millis();
+            if ms > 1 {
+                warn!("set_tag took {ms}ms");
+            }
+        }
+
+        fn has_tag(
+            &self,
+            scene: impl Display,
+            object: impl Display,
+            tag: impl Display,
+        ) -> bool {
+            let conn = self.conn.lock().unwrap();
+
+            let now = Instant::now();
+            let value = conn
+                .query_row(
+                    "SELECT 1 FROM object_tags \
+                    WHERE scene = :scene AND object = :object AND tag = :tag",
+                    named_params! {
+                        ":scene": scene.to_string(),
+                        ":object": object.to_string(),
+                        ":tag": tag.to_string(),
+                    },
+                    |_| Ok(()),
+                )
+                .optional()
+                .expect("Cannot query SQLite");
+
+            let ms = now.elapsed().as_millis();
+            if ms > 1 {
+                warn!("has_tag took {ms}ms");
+            }
+
+            value.is_some()
+        }
+
+        fn clear_tag(
+            &self,
+            scene: impl Display,
+            object: impl Display,
+            tag: impl Display,
+        ) {
+            let conn = self.conn.lock().unwrap();
+
+            let now = Instant::now();
+            conn.execute(
+                "DELETE FROM object_tags \
+                WHERE scene = :scene AND object = :object AND tag = :tag",
+                named_params! {
+                    ":scene": scene.to_string(),
+                    ":object": object.to_string(),
+                    ":tag": tag.to_string(),
+                },
+            )
+            .expect("Cannot delete from SQLite");
+
+            let ms = now.elapsed().as_millis();
+            if ms > 1 {
+                warn!("clear_tag took {ms}ms");
+            }
+        }
+
+        fn list_tags<T: From<String>>(
+            &self,
+            scene: impl Display,
+            object: impl Display,
+        ) -> Vec<T> {
+            let conn = self.conn.lock().unwrap();
+
+            let now = Instant::now();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT tag FROM object_tags \
+                    WHERE scene = :scene AND object = :object ORDER BY tag",
+                )
+                .expect("Cannot prepare SQLite");
+            let rows = stmt
+                .query_map(
+                    named_params! {
+                        ":scene": scene.to_string(),
+                        ":object": object.to_string(),
+                    },
+                    |row| row.get(0),
+                )
+                .expect("Cannot query SQLite");
+
+            let ms = now.elapsed().as_millis();
+            if ms > 1 {
+                warn!("list_tags took {ms}ms");
+            }
+
+            rows.map(|row| String::into(row.expect("Cannot get row")))
+                .collect()
+        }
+    }
+}
+
+pub use inventory::InventoryStore;
+mod inventory {
+    use super::*;
+
+    /// Items that the player carries around.
+    ///
+    /// The inventory is global, it's the same in every scene.
+    /// Items are identified by their id, e.g. `"Apple"`.
+    pub trait InventoryStore {
+        /// Give the player more of the item.
+        fn add_item(&self, item: impl Display, quantity: u32);
+
+        /// Take some of the item from the player.
+        ///
+        /// If the player holds less of the item than that, nothing is taken
+        /// and `false` is returned.
+        fn remove_item(&self, item: impl Display, quantity: u32) -> bool;
+
+        /// How many of the item the player holds, zero if none.
+        fn item_count(&self, item: impl Display) -> u32;
+
+        /// All items that the player holds with their quantities, sorted by
+        /// the item id.
+        fn list_items<T: From<String>>(&self) -> Vec<(T, u32)>;
+    }
+
+    impl InventoryStore for GlobalStore {
+        fn add_item(&self, item: impl Display, quantity: u32) {
+            if quantity == 0 {
+                return;
+            }
+
+            let conn = self.conn.lock().unwrap();
+
+            let now = Instant::now();
+            conn.execute(
+                "INSERT INTO inventory (item, quantity) \
+                VALUES (:item, :quantity) \
+                ON CONFLICT (item) DO UPDATE \
+                SET quantity = MIN(quantity + excluded.quantity, :max)",
+                named_params! {
+                    ":item": item.to_string(),
+                    ":quantity": quantity,
+                    ":max": u32::MAX,
+                },
+            )
+            .expect("Cannot insert into SQLite");
+
+            let ms = now.elapsed().as_millis();
+            if ms > 1 {
+                warn!("add_item took {ms}ms");
+            }
+        }
+
+        fn remove_item(&self, item: impl Display, quantity: u32) -> bool {
+            if quantity == 0 {
+                return true;
+            }
+
+            let item = item.to_string();
+            let conn = self.conn.lock().unwrap();
+
+            let now = Instant::now();
+            let updated = conn
+                .execute(
+                    "UPDATE inventory SET quantity = quantity - :quantity \
+                    WHERE item = :item AND quantity >= :quantity",
+                    named_params! {
+                        ":item": item,
+                        ":quantity": quantity,
+                    },
+                )
+                .expect("Cannot update SQLite");
+            conn.execute(
+                "DELETE FROM inventory WHERE item = :item AND quantity = 0",
+                named_params! { ":item": item },
+            )
+            .expect("Cannot delete from SQLite");
+
+            let ms = now.elapsed().as_millis();
+            if ms > 1 {
+                warn!("remove_item took {ms}ms");
+            }
+
+            updated > 0
+        }
+
+        fn item_count(&self, item: impl Display) -> u32 {
+            let conn = self.conn.lock().unwrap();
+
+            let now = Instant::now();
+            let quantity = conn
+                .query_row(
+                    "SELECT quantity FROM inventory WHERE item = :item",
+                    named_params! { ":item": item.to_string() },
+                    |row| row.get(0),
+                )
+                .optional()
+                .expect("Cannot query SQLite");
+
+            let ms = now.elapsed().as_millis();
+            if ms > 1 {
+                warn!("item_count took {ms}ms");
+            }
+
+            quantity.unwrap_or_default()
+        }
+
+        fn list_items<T: From<String>>(&self) -> Vec<(T, u32)> {
+            let conn = self.conn.lock().unwrap();
+
+            let now = Instant::now();
+            let mut stmt = conn
+                .prepare("SELECT item, quantity FROM inventory ORDER BY item")
+                .expect("Cannot prepare SQLite");
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .expect("Cannot query SQLite");
+
+            let ms = now.elapsed().as_millis();
+            if ms > 1 {
+                warn!("list_items took {ms}ms");
+            }
+
+            rows.map(|row| {
+                let (item, quantity): (String, u32) =
+                    row.expect("Cannot get row");
+                (item.into(), quantity)
+            })
+            .collect()
+        }
+    }
+}
+
+impl GlobalStore {
+    /// Create a new in-memory store.
+    pub fn new() -> Self {
+        Self::from_conn(rusqlite::Connection::open_in_memory().unwrap())
+    }
+
+    /// Opens a store backed by a file, creating it if it doesn't exist.
+    /// Only the migrations that haven't been applied to the file yet are run.
+    ///
+    /// If the file is corrupt or not a database at all, it's moved aside with
+    /// a `.corrupt-<unix millis>` suffix for inspection and a fresh store is
+    /// created in its place.
+    /// Any other error, e.g. the file being locked, is returned and the file
+    /// is left alone.
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        let path = path.as_ref();
+        let conn = rusqlite::Connection::open(path)?;
+
+        let err = match integrity_problems(&conn) {
+            Ok(problems) if problems.is_empty() => {
+                return Ok(Self::from_conn(conn));
+            }
+            Ok(problems) => problems.join("\n"),
+            Err(err) if is_corrupt(&err) => err.to_string(),
+            Err(err) => return Err(err),
+        };
+
+        let backup = corrupt_backup_path(path);
+        error!(
+            "Store {path:?} is corrupt, moving it to {backup:?} and starting \
+            fresh: {err}"
+        );
+        drop(conn);
+        std::fs::rename(path, &backup).map_err(|err| {
+            rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CORRUPT),
+                Some(format!("Cannot move corrupt store aside: {err}")),
+            )
+        })?;
+
+        Ok(Self::from_conn(rusqlite::Connection::open(path)?))
+    }
+
+    /// Runs SQLite `PRAGMA integrity_check`.
+    /// Returns the problems found, one per line.
+    pub fn integrity_check(&self) -> Result<(), String> {
+        integrity_check(&self.conn.lock().unwrap())
+    }
+
+    /// Rebuilds the database file to reclaim the space left behind by
+    /// deleted entries.
+    ///
+    /// Blocks until done, which can take a while for a large save.
+    /// Call it when nothing else is going on, e.g. on the main menu.
+    pub fn vacuum(&self) {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute_batch("VACUUM")
+            .expect("Cannot vacuum SQLite");
+    }
+
+    /// How many migrations have been applied to the database.
+    pub fn schema_version(&self) -> u32 {
+        self.conn
+            .lock()
+            .unwrap()
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .expect("Cannot query SQLite")
+    }
+
+    /// Appends migration steps after those already known to the store and
+    /// runs the pending ones.
+    ///
+    /// Crates that need their own tables call this when building their
+    /// plugin.
+    /// Steps are identified by their position, so only ever append new steps
+    /// and keep the order in which plugins add them stable across releases.
+    pub fn add_migrations(
+        &mut self,
+        steps: impl IntoIterator<Item = M<'static>>,
+    ) {
+        self.migrations.extend(steps);
+
+        let mut conn = self.conn.lock().unwrap();
+        migrate(&mut conn, &self.migrations);
+    }
+
+    /// Emit [`StoreChanged`] whenever the key is written to or removed.
+    ///
+    /// This includes writes with [`GlobalStore::spawn_set`] that are applied
+    /// on another thread.
+    /// Those are reported in the first [`Update`] after they've been applied.
+    ///
+    /// Idempotent.
+    pub fn watch(&self, key: impl Into<Cow<'static, str>>) {
+        self.changes.watched.lock().unwrap().insert(key.into());
+    }
+
+    /// Stop emitting [`StoreChanged`] for the key.
+    ///
+    /// Idempotent.
+    pub fn unwatch(&self, key: &str) {
+        self.changes.watched.lock().unwrap().remove(key);
+    }
+
+    /// Replaces the clock that decides when entries set with
+    /// [`Entry::set_with_ttl`] expire.
+    /// The clock returns the current time as a duration since some fixed
+    /// point, e.g. game time.
+    ///
+    /// Defaults to the wall clock time since the UNIX epoch so that the
+    /// expiry survives saving and loading the game.
+    pub fn set_clock(
+        &mut self,
+        clock: impl Fn() -> Duration + Send + Sync + 'static,
+    ) {
+        self.clock = Arc::new(clock);
+    }
+
+    /// Keeps at most this many latest entries of each dialog namespace in the
+    /// history, see [`DialogStore::insert_dialog`].
+    /// Older entries are removed on the next insert into the namespace.
+    ///
+    /// Defaults to [`None`], i.e. the whole history is kept.
+    pub fn set_dialog_history_cap(&mut self, cap: Option<usize>) {
+        debug_assert!(cap != Some(0), "Cap must keep the last dialog");
+        self.dialog_history_cap = cap;
+    }
+
+    /// Like [`Entry::set`] but does not block the schedule.
+    /// The write is applied by a background task on the [`IoTaskPool`].
+    ///
+    /// Writes and reads submitted with [`GlobalStore::spawn_set`] and
+    /// [`GlobalStore::spawn_get`] apply in submission order.
+    /// For example, queued writes to the same key leave the last submitted
+    /// value in the store.
+    /// There's no ordering guarantee relative to the blocking [`Entry`]
+    /// methods.
+    ///
+    /// All queued work is applied before the app exits on [`AppExit`].
+    pub fn spawn_set<T: Serialize>(
+        &self,
+        key: impl Into<Cow<'static, str>>,
+        value: T,
+    ) {
+        let key = key.into();
+        let raw_value =
+            serde_json::to_string(&value).expect("Cannot serialize");
+
+        let changes = Arc::clone(&self.changes);
+        self.queue.push(move |conn| {
+            upsert_raw(conn, &key, &raw_value, None);
+            changes.notify(&key);
+        });
+    }
+
+    /// Like [`Entry::get`] but does not block the schedule.
+    /// See [`GlobalStore::spawn_set`] for ordering guarantees.
+    pub fn spawn_get<T: DeserializeOwned + Send + 'static>(
+        &self,
+        key: impl Into<Cow<'static, str>>,
+    ) -> PendingRead<T> {
+        let key = key.into();
+        let slot = Arc::new(Mutex::new(None));
+
+        let job_slot = Arc::clone(&slot);
+        let changes = Arc::clone(&self.changes);
+        let clock = Arc::clone(&self.clock);
+        self.queue.push(move |conn| {
+            let raw_value = select_unexpired(conn, &changes, &key, clock());
+
+            let value = raw_value.map(|raw_value| {
+                serde_json::from_str(&raw_value).expect("Cannot deserialize")
+            });
+
+            *job_slot.lock().unwrap() = Some(value);
+        });
+
+        PendingRead { slot }
+    }
+
+    /// An isolated view of the store for a subsystem, e.g. dialogs.
+    /// All keys of the view are prefixed with the namespace and a dot.
+    ///
+    /// Two namespaces with different names never share keys, even if one
+    /// name is a prefix of the other.
+    pub fn namespace(&self, prefix: impl Display) -> NamespacedStore<'_> {
+        NamespacedStore {
+            store: self,
+            prefix: format!("{prefix}."),
+        }
+    }
+
+    fn entry<T>(&self, key: impl Into<Cow<'static, str>>) -> Entry<'_, T> {
+        Entry::new(&self.conn, &self.changes, &self.clock, key)
+    }
+
+    fn from_conn(mut conn: rusqlite::Connection) -> Self {
+        let migrations = built_in_migrations();
+        migrate(&mut conn, &migrations);
+
+        Self {
+            conn: Arc::new(Mutex::new(conn)),
+            migrations,
+            queue: default(),
+            changes: default(),
+            clock: Arc::new(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+            }),
+            dialog_history_cap: None,
+        }
+    }
+}
+
+impl Default for GlobalStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> Entry<'a, T> {
+    fn new(
+        store: &'a Mutex<rusqlite::Connection>,
+        changes: &'a Changes,
+        clock: &'a Clock,
+        key: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            store,
+            changes,
+            clock,
+            key: key.into(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl Changes {
+    fn notify(&self, key: &str) {
+        if self.watched.lock().unwrap().contains(key) {
+            // the receiver lives as long as the sender
+            let _ = self.sender.send(key.to_owned().into());
+        }
+    }
+}
+
+impl Default for Changes {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            watched: default(),
+            sender,
+            receiver: Mutex::new(receiver),
+        }
+    }
+}
+
+impl<T> PendingRead<T> {
+    /// Returns [`None`] while the read is still queued.
+    /// Once the read is done, returns the value exactly once.
+    pub fn try_take(&self) -> Option<Option<T>> {
+        self.slot.lock().unwrap().take()
+    }
+}
+
+impl JobQueue {
+    fn push(&self, job: impl FnOnce(&rusqlite::Connection) + Send + 'static) {
+        self.jobs.lock().unwrap().push_back(Box::new(job));
+    }
+
+    /// If no worker is running, spawns one that applies all jobs queued so
+    /// far.
+    fn process(&mut self, conn: &Arc<Mutex<rusqlite::Connection>>) {
+        if self
+            .worker
+            .as_ref()
+            .is_some_and(|worker| !worker.is_finished())
+        {
+            return;
+        }
+        self.worker = None;
+
+        let jobs: Vec<_> = self.jobs.lock().unwrap().drain(..).collect();
+        if jobs.is_empty() {
+            return;
+        }
+
+        let conn = Arc::clone(conn);
+        self.worker = Some(IoTaskPool::get().spawn(async move {
+            let now = Instant::now();
+            let jobs_count = jobs.len();
+
+            let conn = conn.lock().unwrap();
+            for job in jobs {
+                job(&conn);
+            }
+
+            trace!(
+                "Applied {jobs_count} queued store jobs in {}ms",
+                now.elapsed().as_millis()
+            );
+        }));
+    }
+
+    /// Blocks until the running worker is done and then applies all jobs
+    /// queued so far on this thread.
+    fn flush(&mut self, conn: &Mutex<rusqlite::Connection>) {
+        if let Some(worker) = self.worker.take() {
+            bevy::tasks::block_on(worker);
+        }
+
+        let jobs: Vec<_> = self.jobs.lock().unwrap().drain(..).collect();
+        if jobs.is_empty() {
+            return;
+        }
+
+        let conn = conn.lock().unwrap();
+        for job in jobs {
+            job(&conn);
+        }
+    }
+}
+
+/// Hands queued work of [`GlobalStore::spawn_set`] and
+/// [`GlobalStore::spawn_get`] to a background task.
+fn process_queued_jobs(mut store: ResMut<GlobalStore>) {
+    let GlobalStore { conn, queue, .. } = &mut *store;
+    queue.process(conn);
+}
+
+/// Once the app is exiting there are no more frames to hand the queued work
+/// to a background task, so we apply it before the store is dropped.
+fn flush_queued_jobs(mut store: ResMut<GlobalStore>) {
+    let GlobalStore { conn, queue, .. } = &mut *store;
+    queue.flush(conn);
+}
+
+/// Drains the changes to the keys registered with [`GlobalStore::watch`].
+fn emit_store_changed(
+    store: Res<GlobalStore>,
+    mut events: EventWriter<StoreChanged>,
+) {
+    let receiver = store.changes.receiver.lock().unwrap();
+    events.send_batch(receiver.try_iter().map(|key| StoreChanged { key }));
+}
+
+/// Writes the value over the key.
+/// Without `expires_at` the value never expires.
+fn upsert<T: Serialize>(
+    conn: &rusqlite::Connection,
+    key: &str,
+    value: &T,
+    expires_at: Option<Duration>,
+) {
+    let raw_value = serde_json::to_string(value).expect("Cannot serialize");
+    upsert_raw(conn, key, &raw_value, expires_at);
+}
+
+fn upsert_raw(
+    conn: &rusqlite::Connection,
+    key: &str,
+    raw_value: &str,
+    expires_at: Option<Duration>,
+) {
+    conn.execute(
+        "INSERT INTO kv (key, value, expires_at) \
+        VALUES (:key, :value, :expires_at) \
+        ON CONFLICT (key) DO UPDATE \
+        SET value = excluded.value, expires_at = excluded.expires_at",
+        named_params! {
+            ":key": key,
+            ":value": raw_value,
+            ":expires_at": expires_at.map(|at| at.as_millis() as i64),
+        },
+    )
+    .expect("Cannot insert into SQLite");
+}
+
+/// Returns the raw value of the key unless it expired.
+/// Expired entries are deleted.
+fn select_unexpired(
+    conn: &rusqlite::Connection,
+    changes: &Changes,
+    key: &str,
+    now: Duration,
+) -> Option<String> {
+    let (raw_value, expires_at): (String, Option<i64>) = conn
+        .query_row(
+            "SELECT value, expires_at FROM kv WHERE key = ?",
+            [key],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .expect("Cannot query SQLite")?;
+
+    match expires_at {
+        Some(expires_at) if expires_at <= now.as_millis() as i64 => {
+            conn.execute(
+                "DELETE FROM kv WHERE key = ? AND expires_at = ?",
+                rusqlite::params![key, expires_at],
+            )
+            .expect("Cannot delete from SQLite");
+            changes.notify(key);
+
+            None
+        }
+        _ => Some(raw_value),
+    }
+}
+
+/// Keys that start with the prefix and haven't expired by `now`, sorted.
+fn select_keys_with_prefix(
+    conn: &rusqlite::Connection,
+    prefix: &str,
+    now: Duration,
+) -> Vec<String> {
+    // LIKE would treat underscores in keys as wildcards
+    let mut stmt = conn
+        .prepare(
+            "SELECT key FROM kv \
+            WHERE substr(key, 1, length(:prefix)) = :prefix \
+            AND (expires_at IS NULL OR expires_at > :now) \
+            ORDER BY key",
+        )
+        .expect("Cannot prepare SQLite");
+    let rows = stmt
+        .query_map(
+            named_params! {
+                ":prefix": prefix,
+                ":now": now.as_millis() as i64,
+            },
+            |row| row.get(0),
+        )
+        .expect("Cannot query SQLite");
+
+    rows.map(|row| row.expect("Cannot get row")).collect()
+}
+
+/// See [`GlobalStore::integrity_check`].
+fn integrity_check(conn: &rusqlite::Connection) -> Result<(), String> {
+    match integrity_problems(conn) {
+        Ok(problems) if problems.is_empty() => Ok(()),
+        Ok(problems) => Err(problems.join("\n")),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Runs SQLite `PRAGMA integrity_check` and returns the problems it found.
+/// Empty if the database is fine.
+fn integrity_problems(
+    conn: &rusqlite::Connection,
+) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let problems = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(match problems.as_slice() {
+        [ok] if ok == "ok" => vec![],
+        _ => problems,
+    })
+}
+
+/// Whether the error says that the file is damaged rather than e.g. busy.
+fn is_corrupt(err: &rusqlite::Error) -> bool {
+    matches!(
+        err.sqlite_error_code(),
+        Some(
+            rusqlite::ErrorCode::DatabaseCorrupt
+                | rusqlite::ErrorCode::NotADatabase
+        )
+    )
+}
+
+/// A path next to the store that no earlier backup uses.
+fn corrupt_backup_path(path: &Path) -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+
+    (0..)
+        .map(|attempt| {
+            let mut backup = path.as_os_str().to_owned();
+            backup.push(format!(".corrupt-{millis}"));
+            if attempt > 0 {
+                backup.push(format!("-{attempt}"));
+            }
+            PathBuf::from(backup)
+        })
+        .find(|backup| !backup.exists())
+        .expect("Some backup path is free")
+}
+
+/// Runs those of the migrations that haven't been applied yet.
+fn migrate(conn: &mut rusqlite::Connection, migrations: &[M<'static>]) {
+    Migrations::new(migrations.to_vec())
+        .to_latest(conn)
+        .expect("Cannot migrate SQLite");
+}
+
+fn built_in_migrations() -> Vec<M<'static>> {
+    vec![
+        // generic key value table
+        M::up(
+            "CREATE TABLE kv (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );",
+        ),
+        // dialogs storage
+        M::up(
+            "CREATE TABLE dialog_nodes_transitioned_to (
+                id INTEGER PRIMARY KEY,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                namespace TEXT NOT NULL,
+                node_name TEXT NOT NULL
+            );",
+        ),
+        M::up(
+            "CREATE INDEX idx_dialog_nodes_transitioned_to_namespace_node_name \
+            ON dialog_nodes_transitioned_to (namespace, node_name);",
+        ),
+        M::up(
+            "CREATE TABLE npc_dialogs (
+                npc TEXT NOT NULL,
+                namespace TEXT NOT NULL
+            );",
+        ),
+        M::up(
+            "CREATE INDEX idx_npc_dialogs_npc_namespace \
+            ON npc_dialogs (npc, namespace);",
+        ),
+        // what have the player already seen using the inspect ability
+        M::up(
+            "CREATE TABLE discovered_with_inspect_ability (
+                label TEXT PRIMARY KEY
+            );",
+        ),
+        // milliseconds of the store's clock after which the entry is absent
+        M::up("ALTER TABLE kv ADD COLUMN expires_at INTEGER;"),
+        // how likely is the dialog to be picked when talking to the NPC
+        M::up(
+            "ALTER TABLE npc_dialogs ADD COLUMN weight REAL NOT NULL DEFAULT 1.0;",
+        ),
+        // flags of objects in the world such as opened doors
+        M::up(
+            "CREATE TABLE object_tags (
+                scene TEXT NOT NULL,
+                object TEXT NOT NULL,
+                tag TEXT NOT NULL
+            );",
+        ),
+        M::up(
+            "CREATE UNIQUE INDEX idx_object_tags_scene_object_tag \
+            ON object_tags (scene, object, tag);",
+        ),
+        // items the player holds, the same in every scene
+        M::up(
+            "CREATE TABLE inventory (
+                item TEXT PRIMARY KEY,
+                quantity INTEGER NOT NULL
+            );",
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, math::vec2};
+
+    use super::*;
+
+    #[test]
+    fn it_serializes_i32() {
+        let store = GlobalStore::new();
+
+        let entry = store.entry::<i32>("test");
+        assert_eq!(entry.get(), None);
+
+        entry.set(42);
+
+        assert_eq!(entry.get(), Some(42));
+    }
+
+    #[test]
+    fn it_serializes_vec2() {
+        let store = GlobalStore::new();
+
+        let entry = store.entry::<Vec2>("test");
+        assert_eq!(entry.get(), None);
+
+        entry.set(vec2(0.0, 1.0));
 
         assert_eq!(entry.get(), Some(vec2(0.0, 1.0)));
     }
 
+    #[test]
+    fn it_expires_entry_after_ttl() {
+        let clock = Arc::new(Mutex::new(Duration::from_secs(100)));
+        let mut store = GlobalStore::new();
+        {
+            let clock = Arc::clone(&clock);
+            store.set_clock(move || *clock.lock().unwrap());
+        }
+        store.watch("greeted");
+
+        let entry = store.entry::<bool>("greeted");
+        entry.set_with_ttl(true, Duration::from_secs(5 * 60));
+        assert_eq!(entry.get(), Some(true));
+
+        *clock.lock().unwrap() += Duration::from_secs(5 * 60 - 1);
+        assert_eq!(entry.get(), Some(true));
+
+        *clock.lock().unwrap() += Duration::from_secs(1);
+        assert_eq!(entry.get(), None);
+        // it was deleted rather than just hidden
+        let rows: i64 = store
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM kv", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(0, rows);
+        let changes: Vec<_> =
+            store.changes.receiver.lock().unwrap().try_iter().collect();
+        assert_eq!(2, changes.len(), "set and expire");
+
+        // plain set clears the TTL
+        entry.set_with_ttl(true, Duration::from_secs(1));
+        entry.set(false);
+        *clock.lock().unwrap() += Duration::from_secs(60);
+        assert_eq!(entry.get(), Some(false));
+    }
+
+    #[test]
+    fn it_compares_and_sets() {
+        let store = GlobalStore::new();
+        store.watch("counter");
+        let entry = store.entry::<i32>("counter");
+
+        assert!(!entry.compare_and_set(Some(0), 1), "nothing to compare to");
+        assert_eq!(entry.get(), None);
+        assert!(entry.compare_and_set(None, 1));
+        assert_eq!(entry.get(), Some(1));
+
+        // another system got there first
+        assert!(entry.compare_and_set(Some(1), 2));
+        assert!(!entry.compare_and_set(Some(1), 2));
+        assert!(!entry.compare_and_set(None, 2));
+        assert_eq!(entry.get(), Some(2));
+
+        let changes: Vec<_> =
+            store.changes.receiver.lock().unwrap().try_iter().collect();
+        assert_eq!(2, changes.len(), "only successful writes");
+    }
+
     #[test]
     fn it_inserts_dialogs() {
-        let conn = new_conn();
-        let store = GlobalStore { conn };
+        let store = GlobalStore::new();
 
         store.insert_dialog(("ok/dialog.toml", "node1"));
         assert!(store.was_this_the_last_dialog::<String>((
@@ -484,11 +1814,390 @@ mod tests {
         )));
     }
 
-    fn new_conn() -> Arc<Mutex<rusqlite::Connection>> {
-        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+    #[test]
+    fn it_caps_dialog_history_per_namespace() {
+        const CAP: usize = 4;
+
+        let mut store = GlobalStore::new();
+        store.set_dialog_history_cap(Some(CAP));
+
+        store.insert_dialog(("other/dialog.toml", "node0"));
+        for i in 0..CAP + 5 {
+            store.insert_dialog(("ok/dialog.toml", format!("node{i}")));
+        }
+
+        let count = |namespace: &str| -> usize {
+            store
+                .conn
+                .lock()
+                .unwrap()
+                .query_row(
+                    "SELECT COUNT(*) FROM dialog_nodes_transitioned_to \
+                    WHERE namespace = ?1",
+                    [namespace],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(CAP, count("ok/dialog.toml"));
+        assert_eq!(1, count("other/dialog.toml"));
+        assert!(store.was_this_the_last_dialog::<String>((
+            "ok/dialog.toml".to_owned(),
+            format!("node{}", CAP + 4)
+        )));
+
+        let recent: Vec<_> = store
+            .recent_dialogs("ok/dialog.toml", 10)
+            .into_iter()
+            .map(|(node, _)| node)
+            .collect();
+        assert_eq!(vec!["node5", "node6", "node7", "node8"], recent);
+        let recent = store.recent_dialogs("ok/dialog.toml", 2);
+        assert_eq!("node7", recent[0].0);
+        assert_eq!("node8", recent[1].0);
+        assert!(!recent[1].1.is_empty());
+    }
+
+    #[test]
+    fn it_round_trips_typed_guard_state() {
+        #[derive(Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Counter {
+            count: usize,
+        }
+
+        let store = GlobalStore::new();
+        let node = ("ok/dialog.toml", "node1");
+
+        let entry = store.typed_guard_state::<Counter>("counter", node);
+        assert_eq!(None, entry.get());
+
+        entry.set(Counter { count: 1 });
+        assert_eq!(Some(Counter { count: 1 }), entry.get());
+
+        // same key as the untyped state
+        assert_eq!(
+            Some(serde_json::json!({ "count": 1 })),
+            store.guard_state("counter", node).get()
+        );
+    }
+
+    #[test]
+    fn it_lists_weighted_dialogs_for_npc() {
+        let store = GlobalStore::new();
+
+        store
+            .add_dialog_to_npc("Marie", "marie_blabbering")
+            .add_weighted_dialog_to_npc("Marie", "marie_rare", 0.25)
+            .add_dialog_to_npc("Bolt", "bolt_is_mean");
+
+        let mut dialogs =
+            store.list_weighted_dialogs_for_npc::<String>("Marie");
+        dialogs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        assert_eq!(
+            vec![
+                ("marie_blabbering".to_owned(), 1.0),
+                ("marie_rare".to_owned(), 0.25)
+            ],
+            dialogs
+        );
+
+        store.add_weighted_dialog_to_npc("Marie", "marie_rare", 2.0);
+        assert_eq!(
+            vec![("marie_rare".to_owned(), 2.0)],
+            store
+                .list_weighted_dialogs_for_npc::<String>("Marie")
+                .into_iter()
+                .filter(|(namespace, _)| namespace == "marie_rare")
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn it_tags_objects_idempotently() {
+        let store = GlobalStore::new();
+
+        store.set_tag("apartment", "door", "opened");
+        store.set_tag("apartment", "door", "opened");
+        store.set_tag("apartment", "door", "creaky");
+        assert!(store.has_tag("apartment", "door", "opened"));
+        assert_eq!(
+            vec!["creaky".to_owned(), "opened".to_owned()],
+            store.list_tags::<String>("apartment", "door")
+        );
+
+        store.clear_tag("apartment", "door", "opened");
+        store.clear_tag("apartment", "door", "opened");
+        assert!(!store.has_tag("apartment", "door", "opened"));
+        assert_eq!(
+            vec!["creaky".to_owned()],
+            store.list_tags::<String>("apartment", "door")
+        );
+    }
+
+    #[test]
+    fn it_isolates_object_tags_per_scene() {
+        let store = GlobalStore::new();
+
+        store.set_tag("apartment", "door", "opened");
+        store.set_tag("basement", "box", "taken");
+
+        assert!(!store.has_tag("basement", "door", "opened"));
+        assert!(!store.has_tag("apartment", "box", "taken"));
+        assert!(store.list_tags::<String>("basement", "door").is_empty());
 
-        migrate(&mut conn);
+        store.clear_tag("basement", "door", "opened");
+        assert!(store.has_tag("apartment", "door", "opened"));
+    }
+
+    #[test]
+    fn it_adds_and_removes_items() {
+        let store = GlobalStore::new();
+        assert_eq!(0, store.item_count("Apple"));
+
+        store.add_item("Apple", 2);
+        store.add_item("Apple", 3);
+        assert_eq!(5, store.item_count("Apple"));
+
+        assert!(store.remove_item("Apple", 4));
+        assert_eq!(1, store.item_count("Apple"));
+
+        // can't take more than there is
+        assert!(!store.remove_item("Apple", 2));
+        assert_eq!(1, store.item_count("Apple"));
+        assert!(!store.remove_item("Pear", 1));
+        assert_eq!(0, store.item_count("Pear"));
+
+        assert!(store.remove_item("Apple", 1));
+        assert_eq!(0, store.item_count("Apple"));
+        assert!(!store.remove_item("Apple", 1));
+    }
+
+    #[test]
+    fn it_lists_held_items() {
+        let store = GlobalStore::new();
+
+        store.add_item("Pear", 1);
+        store.add_item("Apple", 2);
+        store.add_item("Banana", 0);
+        store.add_item("Cherry", 3);
+        store.remove_item("Cherry", 3);
+        assert_eq!(
+            vec![("Apple".to_owned(), 2), ("Pear".to_owned(), 1)],
+            store.list_items::<String>()
+        );
+    }
+
+    #[test]
+    fn it_isolates_namespaces() {
+        let store = GlobalStore::new();
+        let guards = store.namespace("guard");
+        let guardians = store.namespace("guardian");
+
+        guards.entry::<i32>("state").set(1);
+        guardians.entry::<i32>("state").set(2);
+        guards.entry::<i32>("state_of_mind").set(3);
+        guards.namespace("nested").entry::<i32>("state").set(4);
+        assert_eq!(Some(1), guards.entry::<i32>("state").get());
+        assert_eq!(Some(2), guardians.entry::<i32>("state").get());
+        assert_eq!(Some(1), store.entry::<i32>("guard.state").get());
+        assert_eq!(
+            vec![
+                "nested.state".to_owned(),
+                "state".to_owned(),
+                "state_of_mind".to_owned()
+            ],
+            guards.keys_with_prefix("")
+        );
+        // underscore is not a wildcard
+        assert_eq!(1, guards.keys_with_prefix("state_of_").len());
+        assert!(guards.keys_with_prefix("stat__").is_empty());
+
+        guards.remove_prefix("state");
+        assert_eq!(None, guards.entry::<i32>("state").get());
+        assert_eq!(None, guards.entry::<i32>("state_of_mind").get());
+        assert_eq!(
+            Some(4),
+            guards.namespace("nested").entry::<i32>("state").get()
+        );
+        assert_eq!(Some(2), guardians.entry::<i32>("state").get());
+
+        guards.clear();
+        assert!(guards.keys_with_prefix("").is_empty());
+        assert_eq!(vec!["state".to_owned()], guardians.keys_with_prefix(""));
+    }
+
+    #[test]
+    fn it_applies_queued_jobs_in_submission_order() {
+        IoTaskPool::get_or_init(default);
+
+        let mut store = GlobalStore::new();
+
+        store.spawn_set("test", 1);
+        store.spawn_set("test", 2);
+        let read = store.spawn_get::<i32>("test");
+        store.spawn_set("test", 3);
+        assert_eq!(read.try_take(), None);
+
+        let GlobalStore { conn, queue, .. } = &mut store;
+        queue.process(conn);
+        bevy::tasks::block_on(queue.worker.take().unwrap());
+
+        assert_eq!(read.try_take(), Some(Some(2)));
+        assert_eq!(read.try_take(), None);
+        assert_eq!(store.entry::<i32>("test").get(), Some(3));
+    }
+
+    #[test]
+    fn it_flushes_queued_jobs_on_exit() {
+        IoTaskPool::get_or_init(default);
+
+        let mut app = App::new();
+        app.add_event::<AppExit>().add_plugins(Plugin);
+
+        app.world().resource::<GlobalStore>().spawn_set("test", 1);
+        app.update();
+        let store = app.world().resource::<GlobalStore>();
+        store.spawn_set("test", 2);
+        let read = store.spawn_get::<i32>("test");
+
+        app.world_mut().send_event(AppExit::Success);
+        app.update();
+
+        assert_eq!(read.try_take(), Some(Some(2)));
+        let store = app.world().resource::<GlobalStore>();
+        assert!(store.queue.worker.is_none());
+        assert_eq!(store.entry::<i32>("test").get(), Some(2));
+    }
+
+    #[test]
+    fn it_emits_change_of_watched_key_only() {
+        let mut w = World::default();
+        w.init_resource::<Events<StoreChanged>>();
+        w.insert_resource(GlobalStore::new());
+
+        {
+            let store = w.resource::<GlobalStore>();
+            store.watch("counter");
+            store.entry::<i32>("counter").set(1);
+            store.entry::<i32>("unwatched").set(1);
+        }
+
+        w.run_system_once(emit_store_changed);
+        let events: Vec<_> =
+            w.resource_mut::<Events<StoreChanged>>().drain().collect();
+        assert_eq!(
+            vec![StoreChanged {
+                key: "counter".into()
+            }],
+            events
+        );
+
+        w.resource::<GlobalStore>().entry::<i32>("unwatched").set(2);
+        w.run_system_once(emit_store_changed);
+        assert!(w.resource::<Events<StoreChanged>>().is_empty());
+    }
+
+    #[test]
+    fn it_upgrades_old_db() {
+        let path = std::env::temp_dir().join(format!(
+            "common_store_it_upgrades_old_db_{}.sqlite",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        {
+            // a save from the time when there was only the kv table
+            let mut conn = rusqlite::Connection::open(&path).unwrap();
+            migrate(&mut conn, &built_in_migrations()[..1]);
+            conn.execute(
+                "INSERT INTO kv (key, value) VALUES ('test', '42')",
+                [],
+            )
+            .unwrap();
+        }
+
+        let mut store = GlobalStore::open(&path).unwrap();
+        let built_in = built_in_migrations().len() as u32;
+        assert_eq!(store.schema_version(), built_in);
+        assert_eq!(store.entry::<i32>("test").get(), Some(42));
+        store.insert_dialog(("ok/dialog.toml", "node1"));
+
+        let feature_migrations =
+            || [M::up("CREATE TABLE feature (id INTEGER PRIMARY KEY);")];
+        store.add_migrations(feature_migrations());
+        assert_eq!(store.schema_version(), built_in + 1);
+        drop(store);
+
+        // would fail if the feature table was created again
+        let mut store = GlobalStore::open(&path).unwrap();
+        store.add_migrations(feature_migrations());
+        assert_eq!(store.schema_version(), built_in + 1);
+        assert_eq!(store.entry::<i32>("test").get(), Some(42));
+        assert!(store.was_this_the_last_dialog::<String>((
+            "ok/dialog.toml".to_owned(),
+            "node1"
+        )));
+
+        drop(store);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn it_passes_integrity_check_after_migrations() {
+        let store = GlobalStore::new();
+        assert_eq!(Ok(()), store.integrity_check());
+
+        store.entry::<i32>("test").set(42);
+        store.entry::<i32>("test").remove();
+        store.vacuum();
+        assert_eq!(Ok(()), store.integrity_check());
+    }
+
+    #[test]
+    fn it_moves_corrupt_db_aside() {
+        let path = std::env::temp_dir().join(format!(
+            "common_store_it_moves_corrupt_db_aside_{}.sqlite",
+            std::process::id()
+        ));
+        let backups = || {
+            let name = path.file_name().unwrap().to_string_lossy();
+            let mut backups: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .filter(|backup| {
+                    backup
+                        .file_name()
+                        .unwrap()
+                        .to_string_lossy()
+                        .starts_with(&format!("{name}.corrupt-"))
+                })
+                .collect();
+            backups.sort();
+            backups
+        };
+        backups().into_iter().for_each(|backup| {
+            std::fs::remove_file(backup).unwrap();
+        });
+
+        // corrupted twice, the first backup is kept
+        let garbage = [vec![0xab; 4096], vec![0xcd; 4096]];
+        for garbage in &garbage {
+            std::fs::write(&path, garbage).unwrap();
+            let store = GlobalStore::open(&path).unwrap();
+            assert_eq!(Ok(()), store.integrity_check());
+            assert_eq!(store.entry::<i32>("test").get(), None);
+        }
 
-        Arc::new(Mutex::new(conn))
+        let mut backed_up: Vec<_> = backups()
+            .iter()
+            .map(|backup| std::fs::read(backup).unwrap())
+            .collect();
+        backed_up.sort();
+        assert_eq!(garbage.to_vec(), backed_up);
+
+        std::fs::remove_file(&path).unwrap();
+        backups().into_iter().for_each(|backup| {
+            std::fs::remove_file(backup).unwrap();
+        });
     }
 }
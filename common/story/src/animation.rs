@@ -0,0 +1,490 @@
+//! Which frames of the sprite atlas a character plays depending on what it's
+//! doing and which way it's facing.
+//!
+//! Each character has [`CharacterAnimations`] that map the
+//! [`AnimationState`] and [`GridDirection`] to an [`AnimationClip`].
+//! The clip is a range of frames in the atlas that's played in a loop.
+//! A character with a different atlas layout only needs its own clips, the
+//! movement systems resolve the sprite index with
+//! [`Character::sprite_atlas_index`].
+
+use std::time::Duration;
+
+use bevy_grid_squared::GridDirection;
+use common_assets::character_atlases::WINNIE_COLS;
+
+use crate::Character;
+
+const WINNIE_ROW: usize = WINNIE_COLS as usize;
+
+/// Frame timing of walking up and down.
+const WALK_VERTICAL: FrameTiming = FrameTiming::PerStep {
+    multiple: 5.0,
+    min_secs: 0.2,
+    max_secs: 0.5,
+};
+/// Frame timing of walking sideways or diagonally.
+const WALK_SIDEWAYS: FrameTiming = FrameTiming::PerStep {
+    multiple: 3.5,
+    min_secs: 0.2,
+    max_secs: 0.5,
+};
+/// Slowly alternates between two frames when standing still.
+const BREATHING: FrameTiming = FrameTiming::Fps(0.75);
+
+/// What the character is doing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationState {
+    /// Not moving.
+    Standing {
+        /// How long has the character been standing still.
+        since: Duration,
+    },
+    /// Walking from square to square.
+    Walking {
+        /// How long it takes to move one square.
+        step_time: Duration,
+    },
+}
+
+/// A range of frames in the atlas played in a loop.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnimationClip {
+    /// Index of the first frame in the atlas.
+    pub first: usize,
+    /// How many frames follow the first one in the atlas, including it.
+    pub frames: usize,
+    /// How fast the frames change.
+    /// Irrelevant for a single frame.
+    pub timing: FrameTiming,
+}
+
+/// How fast the frames of an [`AnimationClip`] change.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrameTiming {
+    /// Frames per second.
+    Fps(f32),
+    /// A frame lasts the step time of the walking character times the
+    /// multiple, clamped between the min and max.
+    /// Walking faster therefore animates faster.
+    PerStep {
+        /// Of the step time.
+        multiple: f32,
+        /// Shortest frame duration.
+        min_secs: f32,
+        /// Longest frame duration.
+        max_secs: f32,
+    },
+}
+
+/// A clip for each direction the character can face.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DirectionalClips {
+    top: AnimationClip,
+    bottom: AnimationClip,
+    left: AnimationClip,
+    right: AnimationClip,
+    top_left: AnimationClip,
+    top_right: AnimationClip,
+    bottom_left: AnimationClip,
+    bottom_right: AnimationClip,
+}
+
+/// All clips of a character.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CharacterAnimations {
+    /// Plays when the character is standing still.
+    pub standing: DirectionalClips,
+    /// After standing still for the given duration, these clips play
+    /// instead of [`CharacterAnimations::standing`].
+    pub idle: Option<(Duration, DirectionalClips)>,
+    /// Plays when the character is walking.
+    pub walking: DirectionalClips,
+}
+
+impl AnimationClip {
+    /// Just a single frame.
+    pub const fn still(index: usize) -> Self {
+        Self {
+            first: index,
+            frames: 1,
+            timing: FrameTiming::Fps(1.0),
+        }
+    }
+
+    /// `frames` consecutive frames starting at `first`.
+    pub const fn looped(
+        first: usize,
+        frames: usize,
+        timing: FrameTiming,
+    ) -> Self {
+        Self {
+            first,
+            frames,
+            timing,
+        }
+    }
+
+    /// The frames loop since the app started, the elapsed time is therefore
+    /// the wrapped time of the app rather than the time since the clip began.
+    ///
+    /// The step time is only relevant for [`FrameTiming::PerStep`].
+    pub fn frame_at(&self, elapsed: Duration, step_time: Duration) -> usize {
+        if self.frames <= 1 {
+            return self.first;
+        }
+
+        let elapsed_secs = elapsed.as_secs_f32();
+        let frames_elapsed = match self.timing {
+            FrameTiming::Fps(fps) => elapsed_secs * fps,
+            FrameTiming::PerStep {
+                multiple,
+                min_secs,
+                max_secs,
+            } => {
+                let frame_secs = (step_time.as_secs_f32() * multiple)
+                    .clamp(min_secs, max_secs);
+                elapsed_secs / frame_secs
+            }
+        };
+
+        self.first + frames_elapsed.floor() as usize % self.frames
+    }
+}
+
+impl DirectionalClips {
+    /// The same clip in every direction.
+    pub const fn same(clip: AnimationClip) -> Self {
+        Self::sides(clip, clip, clip, clip)
+    }
+
+    /// Diagonals facing left play the left clip and those facing right play
+    /// the right clip.
+    pub const fn sides(
+        top: AnimationClip,
+        bottom: AnimationClip,
+        left: AnimationClip,
+        right: AnimationClip,
+    ) -> Self {
+        Self {
+            top,
+            bottom,
+            left,
+            right,
+            top_left: left,
+            top_right: right,
+            bottom_left: left,
+            bottom_right: right,
+        }
+    }
+
+    /// Overwrites the clip of a single direction.
+    pub const fn with(
+        mut self,
+        direction: GridDirection,
+        clip: AnimationClip,
+    ) -> Self {
+        match direction {
+            GridDirection::Top => self.top = clip,
+            GridDirection::Bottom => self.bottom = clip,
+            GridDirection::Left => self.left = clip,
+            GridDirection::Right => self.right = clip,
+            GridDirection::TopLeft => self.top_left = clip,
+            GridDirection::TopRight => self.top_right = clip,
+            GridDirection::BottomLeft => self.bottom_left = clip,
+            GridDirection::BottomRight => self.bottom_right = clip,
+        }
+
+        self
+    }
+
+    /// The clip for the given direction.
+    pub fn get(&self, direction: GridDirection) -> AnimationClip {
+        match direction {
+            GridDirection::Top => self.top,
+            GridDirection::Bottom => self.bottom,
+            GridDirection::Left => self.left,
+            GridDirection::Right => self.right,
+            GridDirection::TopLeft => self.top_left,
+            GridDirection::TopRight => self.top_right,
+            GridDirection::BottomLeft => self.bottom_left,
+            GridDirection::BottomRight => self.bottom_right,
+        }
+    }
+}
+
+impl CharacterAnimations {
+    /// The layout most character atlases follow.
+    /// Standing frames are bottom, top, then sideways.
+    /// Each direction has two walking frames.
+    pub const DEFAULT: Self = Self {
+        standing: DirectionalClips::sides(
+            AnimationClip::still(1),
+            AnimationClip::still(0),
+            AnimationClip::still(9),
+            AnimationClip::still(6),
+        ),
+        idle: None,
+        walking: DirectionalClips::sides(
+            AnimationClip::looped(2, 2, WALK_VERTICAL),
+            AnimationClip::looped(4, 2, WALK_VERTICAL),
+            AnimationClip::looped(10, 2, WALK_SIDEWAYS),
+            AnimationClip::looped(7, 2, WALK_SIDEWAYS),
+        ),
+    };
+
+    /// Resolves the clip for what the character is doing.
+    pub fn clip(
+        &self,
+        state: AnimationState,
+        direction: GridDirection,
+    ) -> AnimationClip {
+        match state {
+            AnimationState::Standing { since } => match self.idle {
+                Some((after, idle)) if since >= after => idle.get(direction),
+                _ => self.standing.get(direction),
+            },
+            AnimationState::Walking { .. } => self.walking.get(direction),
+        }
+    }
+}
+
+impl Character {
+    /// The clips of the character's sprite atlas.
+    pub fn animations(self) -> CharacterAnimations {
+        use GridDirection::*;
+
+        let default = CharacterAnimations::DEFAULT;
+
+        match self {
+            Self::Winnie => {
+                let standing = default
+                    .standing
+                    .with(TopRight, AnimationClip::still(WINNIE_ROW + 6))
+                    .with(TopLeft, AnimationClip::still(WINNIE_ROW + 9));
+
+                CharacterAnimations {
+                    standing,
+                    // after a few seconds, winnie puts her hands in her
+                    // pockets
+                    idle: Some((
+                        Duration::from_secs(6),
+                        standing.with(Bottom, AnimationClip::still(WINNIE_ROW)),
+                    )),
+                    walking: default
+                        .walking
+                        .with(
+                            TopRight,
+                            AnimationClip::looped(
+                                WINNIE_ROW + 7,
+                                2,
+                                WALK_SIDEWAYS,
+                            ),
+                        )
+                        .with(
+                            TopLeft,
+                            AnimationClip::looped(
+                                WINNIE_ROW + 10,
+                                2,
+                                WALK_SIDEWAYS,
+                            ),
+                        ),
+                }
+            }
+            Self::WhiteCat => CharacterAnimations {
+                standing: DirectionalClips::sides(
+                    AnimationClip::still(3),
+                    AnimationClip::still(0),
+                    AnimationClip::still(0),
+                    AnimationClip::still(3),
+                ),
+                idle: None,
+                walking: DirectionalClips::sides(
+                    AnimationClip::looped(4, 2, WALK_VERTICAL),
+                    AnimationClip::looped(1, 2, WALK_VERTICAL),
+                    AnimationClip::looped(1, 2, WALK_SIDEWAYS),
+                    AnimationClip::looped(4, 2, WALK_SIDEWAYS),
+                ),
+            },
+            Self::Cooper => CharacterAnimations {
+                standing: DirectionalClips::same(AnimationClip::looped(
+                    0, 2, BREATHING,
+                )),
+                idle: None,
+                walking: DirectionalClips::same(AnimationClip::still(0)),
+            },
+            Self::Otter => {
+                let breathing = AnimationClip::looped(0, 2, BREATHING);
+
+                CharacterAnimations {
+                    standing: DirectionalClips::sides(
+                        AnimationClip::still(2),
+                        breathing,
+                        AnimationClip::still(2),
+                        breathing,
+                    )
+                    .with(TopRight, AnimationClip::still(2))
+                    .with(BottomLeft, breathing),
+                    idle: None,
+                    walking: DirectionalClips::sides(
+                        AnimationClip::still(2),
+                        AnimationClip::looped(0, 2, WALK_VERTICAL),
+                        AnimationClip::looped(5, 2, WALK_SIDEWAYS),
+                        AnimationClip::looped(3, 2, WALK_SIDEWAYS),
+                    ),
+                }
+            }
+            // after a few seconds, samizdat puts her hands in her pockets
+            Self::Samizdat => CharacterAnimations {
+                idle: Some((
+                    Duration::from_secs(3),
+                    DirectionalClips::same(AnimationClip::still(12)),
+                )),
+                ..default
+            },
+            Self::Phoebe => CharacterAnimations {
+                idle: Some((
+                    Duration::from_secs(3),
+                    DirectionalClips::same(AnimationClip::still(13)),
+                )),
+                ..default
+            },
+            _ => default,
+        }
+    }
+
+    /// The current sprite index in the atlas.
+    ///
+    /// The elapsed time is the wrapped time of the app, see
+    /// [`AnimationClip::frame_at`].
+    pub fn sprite_atlas_index(
+        self,
+        state: AnimationState,
+        direction: GridDirection,
+        elapsed: Duration,
+    ) -> usize {
+        let step_time = match state {
+            AnimationState::Walking { step_time } => step_time,
+            AnimationState::Standing { .. } => self.default_step_time(),
+        };
+
+        self.animations()
+            .clip(state, direction)
+            .frame_at(elapsed, step_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use GridDirection::*;
+
+    use super::*;
+
+    fn standing(secs: u64) -> AnimationState {
+        AnimationState::Standing {
+            since: Duration::from_secs(secs),
+        }
+    }
+
+    fn walking(character: Character) -> AnimationState {
+        AnimationState::Walking {
+            step_time: character.default_step_time(),
+        }
+    }
+
+    #[test]
+    fn it_reproduces_winnie_frames() {
+        let winnie = Character::Winnie;
+        let at = |state, direction, millis| {
+            winnie.sprite_atlas_index(
+                state,
+                direction,
+                Duration::from_millis(millis),
+            )
+        };
+
+        assert_eq!(0, at(standing(0), Bottom, 0));
+        assert_eq!(1, at(standing(0), Top, 0));
+        assert_eq!(6, at(standing(0), Right, 0));
+        assert_eq!(6, at(standing(0), BottomRight, 0));
+        assert_eq!(9, at(standing(0), Left, 0));
+        assert_eq!(9, at(standing(0), BottomLeft, 0));
+        assert_eq!(18, at(standing(0), TopRight, 0));
+        assert_eq!(21, at(standing(0), TopLeft, 0));
+
+        // hands in pockets only when facing the camera
+        assert_eq!(0, at(standing(5), Bottom, 0));
+        assert_eq!(12, at(standing(6), Bottom, 0));
+        assert_eq!(1, at(standing(6), Top, 0));
+        assert_eq!(18, at(standing(6), TopRight, 0));
+
+        // 35ms step time clamps frames to 200ms
+        assert_eq!(2, at(walking(winnie), Top, 100));
+        assert_eq!(3, at(walking(winnie), Top, 300));
+        assert_eq!(2, at(walking(winnie), Top, 500));
+        assert_eq!(4, at(walking(winnie), Bottom, 100));
+        assert_eq!(5, at(walking(winnie), Bottom, 300));
+        assert_eq!(7, at(walking(winnie), Right, 100));
+        assert_eq!(8, at(walking(winnie), BottomRight, 300));
+        assert_eq!(10, at(walking(winnie), Left, 100));
+        assert_eq!(11, at(walking(winnie), BottomLeft, 300));
+        assert_eq!(19, at(walking(winnie), TopRight, 100));
+        assert_eq!(20, at(walking(winnie), TopRight, 300));
+        assert_eq!(22, at(walking(winnie), TopLeft, 100));
+        assert_eq!(23, at(walking(winnie), TopLeft, 300));
+    }
+
+    #[test]
+    fn it_reproduces_white_cat_frames() {
+        let cat = Character::WhiteCat;
+        let at = |state, direction, millis| {
+            cat.sprite_atlas_index(
+                state,
+                direction,
+                Duration::from_millis(millis),
+            )
+        };
+
+        for direction in [Bottom, Left, TopLeft, BottomLeft] {
+            assert_eq!(0, at(standing(0), direction, 0));
+            assert_eq!(0, at(standing(10), direction, 0));
+        }
+        for direction in [Top, Right, TopRight, BottomRight] {
+            assert_eq!(3, at(standing(0), direction, 0));
+            assert_eq!(3, at(standing(10), direction, 0));
+        }
+
+        // 120ms step time makes vertical frames 500ms (clamped) and sideways
+        // frames 420ms
+        assert_eq!(1, at(walking(cat), Bottom, 450));
+        assert_eq!(2, at(walking(cat), Bottom, 550));
+        assert_eq!(4, at(walking(cat), Top, 450));
+        assert_eq!(5, at(walking(cat), Top, 550));
+        assert_eq!(2, at(walking(cat), Left, 450));
+        assert_eq!(1, at(walking(cat), BottomLeft, 850));
+        assert_eq!(5, at(walking(cat), Right, 450));
+        assert_eq!(4, at(walking(cat), TopRight, 850));
+
+        // slower step makes the frames longer
+        let slow = AnimationState::Walking {
+            step_time: cat.slow_step_time(),
+        };
+        assert_eq!(4, at(slow, Right, 450));
+    }
+
+    #[test]
+    fn it_alternates_breathing_frames_every_few_seconds() {
+        // the same as (3 * secs / 4) % 2
+        let frames: Vec<_> = (0..8)
+            .map(|secs| {
+                Character::Cooper.sprite_atlas_index(
+                    standing(0),
+                    Bottom,
+                    Duration::from_secs(secs),
+                )
+            })
+            .collect();
+
+        assert_eq!(vec![0, 0, 1, 0, 1, 1, 0, 1], frames);
+    }
+}
@@ -154,6 +154,7 @@ fn play_next(
                     play: AtlasAnimationStep::Forward,
                     on_last_frame: AtlasAnimationEnd::DespawnRecursiveItself,
                     extra_steps: event.emoji.extra_steps(),
+                    ..default()
                 })
                 .insert(AtlasAnimationTimer::new_fps(event.emoji.fps()));
         }
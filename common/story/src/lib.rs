@@ -7,12 +7,12 @@
 #![feature(let_chains)]
 #![allow(clippy::too_many_arguments)]
 
+pub mod animation;
 pub mod emoji;
 
 use std::time::Duration;
 
 use bevy::prelude::*;
-use bevy_grid_squared::GridDirection;
 use common_assets::{character_atlases::WINNIE_COLS, store::AssetList};
 use serde::{Deserialize, Serialize};
 use strum::{
@@ -232,6 +232,11 @@ impl Character {
         }
     }
 
+    /// How long does it take to move one square if running.
+    pub fn run_step_time(self) -> Duration {
+        self.default_step_time() * 2 / 3
+    }
+
     /// How long does it take to move one square if walking slowly.
     pub fn slow_step_time(self) -> Duration {
         match self {
@@ -241,99 +246,4 @@ impl Character {
             _ => Duration::from_millis(120),
         }
     }
-
-    /// Based on the current character and direction they are facing, what's the
-    /// current sprite index in the atlas if they are standing and not moving.
-    ///
-    /// You can also provide how long the character has been standing still.
-    pub fn standing_sprite_atlas_index(
-        self,
-        direction: GridDirection,
-        time: &Time,
-        how_long: Option<Duration>,
-    ) -> usize {
-        use GridDirection::*;
-
-        let how_long = how_long.unwrap_or_default();
-
-        match (self, direction) {
-            (Self::WhiteCat, Bottom | Left | TopLeft | BottomLeft) => 0,
-            (Self::WhiteCat, Top | Right | TopRight | BottomRight) => 3,
-
-            // two standing still frames
-            (Self::Cooper, _) => {
-                (3 * time.elapsed_wrapped().as_secs() as usize / 4) % 2
-            }
-
-            (Self::Winnie, TopRight) => WINNIE_COLS as usize + 6,
-            (Self::Winnie, TopLeft) => WINNIE_COLS as usize + 9,
-            // after a few seconds, winnie puts her hands in her pockets
-            (Self::Winnie, Bottom) if how_long.as_secs() > 5 => {
-                WINNIE_COLS as usize
-            }
-
-            (Self::Otter, TopRight | Top | TopLeft | Left) => 2,
-            (Self::Otter, BottomRight | Bottom | BottomLeft | Right) => {
-                (3 * time.elapsed_wrapped().as_secs() as usize / 4) % 2
-            }
-
-            // after a few seconds, samizdat puts her hands in her pockets
-            (Self::Samizdat, _) if how_long.as_secs() > 2 => 12,
-
-            (Self::Phoebe, _) if how_long.as_secs() > 2 => 13,
-
-            (_, Bottom) => 0,
-            (_, Top) => 1,
-            (_, Right | TopRight | BottomRight) => 6,
-            (_, Left | TopLeft | BottomLeft) => 9,
-        }
-    }
-
-    /// Based on the current character, direction they are walking,
-    /// how much time has elapsed since the beginning and how fast they
-    /// walk, returns the index of the sprite in the atlas.
-    #[inline]
-    pub fn walking_sprite_atlas_index(
-        self,
-        direction: GridDirection,
-        time: &Time,
-        step_time: Duration,
-    ) -> usize {
-        use GridDirection::*;
-
-        // How often we change walking frame based on how fast we're walking
-        // from square to square.
-        let step_secs = step_time.as_secs_f32();
-        let animation_step_time = match direction {
-            Top | Bottom => step_secs * 5.0,
-            _ => step_secs * 3.5,
-        }
-        .clamp(0.2, 0.5);
-
-        // right now, each sprite has 3 walking frames
-        let extra = (time.elapsed_seconds_wrapped() / animation_step_time)
-            .floor() as usize
-            % 2;
-
-        match (self, direction) {
-            (Self::WhiteCat, Bottom | Left | TopLeft | BottomLeft) => 1 + extra,
-            (Self::WhiteCat, Top | Right | TopRight | BottomRight) => 4 + extra,
-
-            (Self::Cooper, _) => 0,
-
-            (Self::Winnie, TopRight) => WINNIE_COLS as usize + 7 + extra,
-            (Self::Winnie, TopLeft) => WINNIE_COLS as usize + 10 + extra,
-
-            (Self::Otter, TopRight | Right | BottomRight) => 3 + extra,
-            (Self::Otter, TopLeft | Left | BottomLeft) => 5 + extra,
-            (Self::Otter, Bottom) => extra,
-            (Self::Otter, Top) => 2,
-
-            // defaults
-            (_, Top) => 2 + extra,
-            (_, Bottom) => 4 + extra,
-            (_, Right | TopRight | BottomRight) => 7 + extra,
-            (_, Left | TopLeft | BottomLeft) => 10 + extra,
-        }
-    }
 }
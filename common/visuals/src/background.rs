@@ -0,0 +1,54 @@
+//! The color the screen is cleared with, i.e. what's visible where nothing
+//! is drawn.
+//!
+//! A scene that wants a color other than [`PRIMARY_COLOR`] inserts
+//! [`SceneBackground`] when it loads and removes it when it exits.
+//! The color is applied to [`ClearColor`] and restored to [`PRIMARY_COLOR`]
+//! once the resource is removed.
+//! Prefer this to inserting [`ClearColor`] by hand so that no scene forgets
+//! to restore it.
+
+use bevy::prelude::*;
+
+use crate::PRIMARY_COLOR;
+
+/// The [`ClearColor`] of the current scene.
+/// See the module docs.
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct SceneBackground(pub Color);
+
+/// Sets [`ClearColor`] to the [`SceneBackground`].
+pub(crate) fn apply(
+    background: Res<SceneBackground>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    clear_color.0 = background.0;
+}
+
+/// Sets [`ClearColor`] back to [`PRIMARY_COLOR`] when [`SceneBackground`] is
+/// removed.
+pub(crate) fn restore(mut clear_color: ResMut<ClearColor>) {
+    clear_color.0 = PRIMARY_COLOR;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_restores_primary_color_on_scene_exit() {
+        let mut w = World::default();
+        w.insert_resource(ClearColor(PRIMARY_COLOR));
+        let apply = w.register_system(apply);
+        let restore = w.register_system(restore);
+
+        w.insert_resource(SceneBackground(Color::WHITE));
+        w.run_system(apply).unwrap();
+        assert_eq!(Color::WHITE, w.resource::<ClearColor>().0);
+
+        w.remove_resource::<SceneBackground>();
+        w.run_system(restore).unwrap();
+        assert_eq!(PRIMARY_COLOR, w.resource::<ClearColor>().0);
+    }
+}
@@ -7,11 +7,14 @@ use bevy::{
     ecs::{
         component::Component,
         entity::Entity,
+        event::{Event, EventReader},
         query::With,
-        system::{Commands, Query},
+        system::{Commands, Query, Res},
     },
     hierarchy::DespawnRecursiveExt,
+    reflect::Reflect,
     render::{camera::Camera, view::RenderLayers},
+    time::Time,
     utils::default,
 };
 use bevy_pixel_camera::{PixelViewport, PixelZoom};
@@ -19,6 +22,11 @@ use bevy_pixel_camera::{PixelViewport, PixelZoom};
 /// One pixel is 3x3 pixels on screen.
 pub const PIXEL_ZOOM: i32 = 3;
 
+/// [`CameraZoom`] doesn't zoom out further than this.
+pub const MIN_PIXEL_ZOOM: i32 = 1;
+/// [`CameraZoom`] doesn't zoom in further than this.
+pub const MAX_PIXEL_ZOOM: i32 = 8;
+
 /// What's shown on screen with [`PIXEL_ZOOM`].
 pub const PIXEL_VISIBLE_WIDTH: f32 = 640.0;
 /// What's shown on screen [`PIXEL_ZOOM`].
@@ -29,6 +37,29 @@ pub const PIXEL_VISIBLE_HEIGHT: f32 = 360.0;
 #[derive(Component)]
 pub struct MainCamera;
 
+/// Smoothly changes the [`PixelZoom`] of the [`MainCamera`], e.g. to
+/// emphasize something in a cutscene.
+///
+/// The zoom eases towards the target in [`zoom`], but the [`PixelZoom`] is
+/// always set to the nearest whole number to keep the pixels pixel-perfect.
+/// Only the projection changes, the camera translation is left to the
+/// systems that track the player.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+pub struct CameraZoom {
+    /// Clamped between [`MIN_PIXEL_ZOOM`] and [`MAX_PIXEL_ZOOM`].
+    pub target: f32,
+    /// What portion of the distance to the target the zoom travels per
+    /// second.
+    /// The higher the snappier.
+    pub smoothing: f32,
+    /// Eased towards the target.
+    current: f32,
+}
+
+/// Sets the [`CameraZoom::target`] of the [`MainCamera`].
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SetCameraZoomEvent(pub f32);
+
 pub mod render_layer {
     //! Render layers are assigned to entities and cameras to decide what is
     //! rendered where.
@@ -43,6 +74,10 @@ pub mod render_layer {
     pub const LOADING: usize = 21;
     /// Letterboxing quads are rendered to this layer.
     pub const CUTSCENE_LETTERBOXING: usize = 22;
+    /// The overlay quad of [`crate::scene_tint::SceneTint`].
+    pub const SCENE_TINT: usize = 23;
+    /// The particles of [`crate::weather::Weather`].
+    pub const WEATHER: usize = 24;
 }
 
 pub mod order {
@@ -51,6 +86,10 @@ pub mod order {
 
     /// The main camera in each scene
     pub const DEFAULT: isize = 1;
+    /// Rain and snow fall in front of the world and are tinted along with it.
+    pub const WEATHER: isize = 3;
+    /// The scene tint overlay is above the world but below everything else.
+    pub const SCENE_TINT: isize = 5;
     /// The camera that renders the letterboxing quads is above the main camera
     /// but the dialog is rendered on top of it.
     pub const CUTSCENE_LETTERBOXING: isize = 10;
@@ -69,6 +108,7 @@ pub fn spawn(mut cmd: Commands) {
         Name::from("Main camera"),
         MainCamera,
         PixelZoom::Fixed(PIXEL_ZOOM),
+        CameraZoom::default(),
         PixelViewport,
         RenderLayers::from_layers(&[
             0, // for FPS and other debug tools
@@ -90,3 +130,113 @@ pub fn spawn(mut cmd: Commands) {
 pub fn despawn(mut cmd: Commands, camera: Query<Entity, With<MainCamera>>) {
     cmd.entity(camera.single()).despawn_recursive();
 }
+
+/// Eases the [`CameraZoom`] towards its target and applies it as
+/// [`PixelZoom`].
+pub fn zoom(
+    time: Res<Time>,
+    mut events: EventReader<SetCameraZoomEvent>,
+
+    mut camera: Query<(&mut CameraZoom, &mut PixelZoom), With<MainCamera>>,
+) {
+    let target = events.read().last().map(|SetCameraZoomEvent(z)| *z);
+
+    for (mut zoom, mut pixel_zoom) in camera.iter_mut() {
+        if let Some(target) = target {
+            zoom.set_zoom(target);
+        }
+
+        let target = zoom
+            .target
+            .clamp(MIN_PIXEL_ZOOM as f32, MAX_PIXEL_ZOOM as f32);
+        if zoom.current == target {
+            continue;
+        }
+
+        let lerp_factor = (zoom.smoothing * time.delta_seconds()).min(1.0);
+        zoom.current += (target - zoom.current) * lerp_factor;
+        if (target - zoom.current).abs() < 0.01 {
+            zoom.current = target;
+        }
+
+        let whole = zoom.current.round() as i32;
+        // changing the component makes the pixel camera update the
+        // projection, so we only do it when necessary
+        if !matches!(*pixel_zoom, PixelZoom::Fixed(z) if z == whole) {
+            *pixel_zoom = PixelZoom::Fixed(whole);
+        }
+    }
+}
+
+impl CameraZoom {
+    /// Zoom that the camera eases towards.
+    /// Clamped between [`MIN_PIXEL_ZOOM`] and [`MAX_PIXEL_ZOOM`].
+    pub fn set_zoom(&mut self, target: f32) {
+        self.target =
+            target.clamp(MIN_PIXEL_ZOOM as f32, MAX_PIXEL_ZOOM as f32);
+    }
+
+    /// The eased zoom before rounding.
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+}
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self {
+            target: PIXEL_ZOOM as f32,
+            smoothing: 4.0,
+            current: PIXEL_ZOOM as f32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::{event::Events, world::World};
+
+    use super::*;
+
+    #[test]
+    fn it_converges_to_target_zoom() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+        w.init_resource::<Events<SetCameraZoomEvent>>();
+        let camera = w
+            .spawn((
+                MainCamera,
+                CameraZoom {
+                    smoothing: 5.0,
+                    ..default()
+                },
+                PixelZoom::Fixed(PIXEL_ZOOM),
+            ))
+            .id();
+        w.send_event(SetCameraZoomEvent(6.0));
+
+        let system_id = w.register_system(zoom);
+        // two seconds at 60 fps
+        for _ in 0..120 {
+            w.resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+            w.run_system(system_id).unwrap();
+        }
+
+        let zoom = w.get::<CameraZoom>(camera).unwrap();
+        assert!((6.0 - zoom.current()).abs() < 0.05, "{zoom:?}");
+        assert!(matches!(
+            w.get::<PixelZoom>(camera).unwrap(),
+            PixelZoom::Fixed(6)
+        ));
+
+        w.send_event(SetCameraZoomEvent(100.0));
+        w.run_system(system_id).unwrap();
+        assert_eq!(
+            MAX_PIXEL_ZOOM as f32,
+            w.get::<CameraZoom>(camera).unwrap().target
+        );
+    }
+}
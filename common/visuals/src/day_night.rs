@@ -0,0 +1,193 @@
+//! The [`TimeOfDay`] drives the [`SceneTint`], dark at night and clear during
+//! the day.
+//!
+//! [`TimeOfDay`] is always present and it's not tied to any scene.
+//! It only advances over real time if [`TimeOfDay::hours_per_second`] is set.
+//!
+//! A scene opts in by inserting [`DayNightCycle`] and removes it with the
+//! [`SceneTint`] when it's left.
+//! While the cycle is present, it overwrites the tint whenever the time of
+//! day or the cycle changes, so don't combine it with a
+//! [`crate::scene_tint::SceneTintTransition`].
+
+use bevy::prelude::*;
+
+use crate::scene_tint::SceneTint;
+
+/// How many hours there are in a day.
+pub const HOURS_IN_DAY: f32 = 24.0;
+
+/// Hours since midnight.
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct TimeOfDay {
+    /// Between 0 (inclusive) and [`HOURS_IN_DAY`] (exclusive).
+    pub hours: f32,
+    /// How many in-game hours pass per real second.
+    /// Zero by default, i.e. the time of day only changes when set.
+    pub hours_per_second: f32,
+}
+
+/// Tints keyed by the hour of the day at which they apply fully.
+/// Between the keys the tints are interpolated linearly, wrapping around
+/// midnight.
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct DayNightCycle {
+    /// Sorted by the hour.
+    keys: Vec<(f32, SceneTint)>,
+}
+
+impl TimeOfDay {
+    /// Time of day that doesn't advance on its own.
+    pub fn at(hours: f32) -> Self {
+        Self {
+            hours: hours.rem_euclid(HOURS_IN_DAY),
+            hours_per_second: 0.0,
+        }
+    }
+}
+
+impl Default for TimeOfDay {
+    fn default() -> Self {
+        Self::at(12.0)
+    }
+}
+
+impl DayNightCycle {
+    /// At least one key must be given.
+    /// Hours are wrapped into a single day.
+    pub fn new(keys: impl IntoIterator<Item = (f32, SceneTint)>) -> Self {
+        let mut keys: Vec<_> = keys
+            .into_iter()
+            .map(|(hours, tint)| (hours.rem_euclid(HOURS_IN_DAY), tint))
+            .collect();
+        debug_assert!(!keys.is_empty(), "Cycle needs at least one key");
+        keys.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        Self { keys }
+    }
+
+    /// The tint at the given hour.
+    pub fn sample(&self, hours: f32) -> SceneTint {
+        let hours = hours.rem_euclid(HOURS_IN_DAY);
+        let ((prev_at, prev), (next_at, next)) = keys_around(&self.keys, hours);
+
+        let span = next_at - prev_at;
+        if span <= f32::EPSILON || hours == prev_at {
+            // exactly on a key, return it as configured
+            return prev;
+        }
+
+        prev.lerp(next, (hours - prev_at) / span)
+    }
+}
+
+impl Default for DayNightCycle {
+    /// Dark blue at midnight, warm at dawn and dusk, clear at noon.
+    fn default() -> Self {
+        Self::new([
+            (0.0, SceneTint::new(Color::srgb(0.05, 0.06, 0.2), 0.55)),
+            (6.0, SceneTint::new(Color::srgb(0.9, 0.55, 0.5), 0.2)),
+            (12.0, SceneTint::new(Color::WHITE, 0.0)),
+            (19.0, SceneTint::new(Color::srgb(0.95, 0.45, 0.2), 0.25)),
+        ])
+    }
+}
+
+/// The last key before or at the hour and the first key after it.
+///
+/// The keys must be sorted by the hour and there must be at least one.
+/// The hour must be within a single day, see [`HOURS_IN_DAY`].
+/// Around midnight, the hour of the key from the previous day is negative and
+/// that of the key from the next day is over [`HOURS_IN_DAY`], so that the
+/// hour always lies between the two.
+pub fn keys_around<T: Copy>(
+    keys: &[(f32, T)],
+    hours: f32,
+) -> ((f32, T), (f32, T)) {
+    let next_index = keys.partition_point(|(at, _)| *at <= hours);
+    let prev = match next_index {
+        0 => {
+            let (at, value) = keys[keys.len() - 1];
+            (at - HOURS_IN_DAY, value)
+        }
+        i => keys[i - 1],
+    };
+    let next = match keys.get(next_index) {
+        Some(key) => *key,
+        None => {
+            let (at, value) = keys[0];
+            (at + HOURS_IN_DAY, value)
+        }
+    };
+
+    (prev, next)
+}
+
+/// Advances the [`TimeOfDay`] if it's not paused.
+pub(crate) fn advance(time: Res<Time>, mut time_of_day: ResMut<TimeOfDay>) {
+    if time_of_day.hours_per_second == 0.0 {
+        // avoid change detection
+        return;
+    }
+
+    let hours =
+        time_of_day.hours + time.delta_seconds() * time_of_day.hours_per_second;
+    time_of_day.hours = hours.rem_euclid(HOURS_IN_DAY);
+}
+
+/// Sets the [`SceneTint`] according to the [`DayNightCycle`].
+pub(crate) fn apply(
+    mut cmd: Commands,
+    time_of_day: Res<TimeOfDay>,
+    cycle: Res<DayNightCycle>,
+) {
+    cmd.insert_resource(cycle.sample(time_of_day.hours));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_samples_dark_midnight_and_bright_noon() {
+        let night = SceneTint::new(Color::BLACK, 0.8);
+        let day = SceneTint::new(Color::WHITE, 0.0);
+        let cycle = DayNightCycle::new([(12.0, day), (0.0, night)]);
+
+        assert_eq!(night, cycle.sample(0.0));
+        assert_eq!(day, cycle.sample(12.0));
+        assert_eq!(night, cycle.sample(24.0));
+
+        // halfway between noon and the next midnight
+        let evening = cycle.sample(18.0);
+        assert!((evening.mix - 0.4).abs() < 0.001);
+        assert_eq!(evening, cycle.sample(6.0));
+    }
+
+    #[test]
+    fn it_advances_time_of_day_and_wraps_around_midnight() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+        w.insert_resource(TimeOfDay {
+            hours: 23.0,
+            hours_per_second: 1.0,
+        });
+        w.insert_resource(DayNightCycle::default());
+        let advance = w.register_system(advance);
+        let apply = w.register_system(apply);
+
+        w.resource_mut::<Time>()
+            .advance_by(std::time::Duration::from_secs(2));
+        w.run_system(advance).unwrap();
+        w.run_system(apply).unwrap();
+
+        let hours = w.resource::<TimeOfDay>().hours;
+        assert!((hours - 1.0).abs() < 0.001, "{hours}");
+        assert_eq!(
+            DayNightCycle::default().sample(1.0),
+            *w.resource::<SceneTint>()
+        );
+    }
+}
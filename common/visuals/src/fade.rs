@@ -0,0 +1,132 @@
+//! Fades the screen to black and back, e.g. when the player falls asleep.
+//! Use [`fade_to_black`] and [`fade_from_black`].
+//!
+//! Unlike the loading screen, this doesn't spawn its own camera.
+//! The quad is a top-most UI node rendered by whichever camera renders the UI
+//! by default, usually the [`crate::camera::MainCamera`].
+//! It's not tied to any scene so it survives scene transitions and can cover
+//! the gap between them.
+//!
+//! There's only ever one quad.
+//! Calling the functions while a fade is in progress retargets the existing
+//! quad instead of stacking another one on top.
+//! The retargeted fade starts from the current color of the quad and only the
+//! latest call emits [`FadeComplete`].
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::BeginInterpolationEvent;
+
+/// Emitted when a fade started with [`fade_to_black`] or [`fade_from_black`]
+/// is done.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FadeComplete {
+    /// The screen is now black and the quad stays until
+    /// [`fade_from_black`] is called.
+    ToBlack,
+    /// The quad has been despawned.
+    FromBlack,
+}
+
+/// Marks the full-screen quad.
+#[derive(Component)]
+pub(crate) struct FadeQuad;
+
+/// Fades the screen to black over the given duration.
+/// The screen stays black until [`fade_from_black`] is called.
+pub fn fade_to_black(cmd: &mut Commands, over: Duration) {
+    cmd.add(move |w: &mut World| {
+        let quad = get_or_spawn_quad(w, Color::NONE);
+
+        BeginInterpolationEvent::of_color(quad, None, Color::BLACK)
+            .over(over)
+            .when_finished_do(|cmd| {
+                cmd.add(|w: &mut World| {
+                    w.send_event(FadeComplete::ToBlack);
+                });
+            })
+            .insert_to(&mut w.commands().entity(quad));
+    });
+}
+
+/// Fades the black screen out over the given duration and despawns the quad.
+/// If the screen is not faded to black yet, it starts from black.
+pub fn fade_from_black(cmd: &mut Commands, over: Duration) {
+    cmd.add(move |w: &mut World| {
+        let quad = get_or_spawn_quad(w, Color::BLACK);
+
+        BeginInterpolationEvent::of_color(quad, None, Color::NONE)
+            .over(over)
+            .when_finished_do(move |cmd| {
+                cmd.entity(quad).despawn_recursive();
+                cmd.add(|w: &mut World| {
+                    w.send_event(FadeComplete::FromBlack);
+                });
+            })
+            .insert_to(&mut w.commands().entity(quad));
+    });
+}
+
+/// Spawned quad starts with the given color.
+fn get_or_spawn_quad(w: &mut World, initial_color: Color) -> Entity {
+    if let Some(quad) =
+        w.query_filtered::<Entity, With<FadeQuad>>().iter(w).next()
+    {
+        return quad;
+    }
+
+    w.spawn((
+        Name::new("Fade quad"),
+        FadeQuad,
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                left: Val::Percent(0.0),
+                top: Val::Percent(0.0),
+                ..default()
+            },
+            background_color: initial_color.into(),
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        },
+    ))
+    .id()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::ColorInterpolation;
+
+    #[test]
+    fn it_does_not_stack_quads() {
+        let mut w = World::default();
+        let quad_count = |w: &mut World| {
+            w.query_filtered::<(), With<FadeQuad>>().iter(w).count()
+        };
+
+        w.run_system_once(|mut cmd: Commands| {
+            fade_to_black(&mut cmd, Duration::from_millis(500));
+            fade_to_black(&mut cmd, Duration::from_millis(500));
+        });
+        w.flush();
+        assert_eq!(1, quad_count(&mut w));
+
+        w.run_system_once(|mut cmd: Commands| {
+            fade_from_black(&mut cmd, Duration::from_millis(500));
+        });
+        w.flush();
+        assert_eq!(1, quad_count(&mut w));
+
+        let interpolation = w
+            .query_filtered::<&ColorInterpolation, With<FadeQuad>>()
+            .single(&w);
+        assert_eq!(Color::NONE, interpolation.to);
+    }
+}
@@ -0,0 +1,232 @@
+//! Two black bars above and below the screen for cinematic scenes.
+//! Use [`show_letterbox`] and [`hide_letterbox`] to ease the bars in and out.
+//!
+//! The bars have their own camera which is rendered above the world and the
+//! [`crate::scene_tint`] overlay but below the dialog and loading screen, see
+//! [`order::CUTSCENE_LETTERBOXING`].
+//!
+//! <https://en.wikipedia.org/wiki/Letterboxing_(filming)>
+
+use std::time::Duration;
+
+use bevy::{prelude::*, render::view::RenderLayers, time::Stopwatch};
+
+use crate::{
+    camera::{order, render_layer},
+    EASE_IN_OUT,
+};
+
+/// How long it takes for the bars to ease in with [`show_letterbox`].
+pub const SHOW_LETTERBOX_DURATION: Duration = Duration::from_millis(500);
+/// How long it takes for the bars to ease out with [`hide_letterbox`].
+pub const HIDE_LETTERBOX_DURATION: Duration = Duration::from_millis(250);
+
+/// The bars are spawned while this resource exists with a positive fraction.
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct Letterbox {
+    /// How much of the screen height each of the bars covers.
+    /// Between 0 (no bars) and 0.5 (black screen.)
+    pub fraction: f32,
+}
+
+/// Eases [`Letterbox::fraction`] from its current value to the target value.
+/// Removed once the transition is done.
+/// If the target is zero, the [`Letterbox`] is removed as well.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct LetterboxTransition {
+    from: Option<f32>,
+    to: f32,
+    over: Duration,
+    elapsed: Stopwatch,
+}
+
+#[derive(Component)]
+pub(crate) struct LetterboxCamera;
+
+#[derive(Component)]
+pub(crate) struct LetterboxBar;
+
+/// Eases the bars in until each covers given fraction of the screen height.
+pub fn show_letterbox(cmd: &mut Commands, fraction: f32) {
+    debug_assert!(
+        (0.0..=0.5).contains(&fraction),
+        "Fraction must be in [0, 0.5]"
+    );
+
+    cmd.insert_resource(LetterboxTransition::new(
+        fraction,
+        SHOW_LETTERBOX_DURATION,
+    ));
+}
+
+/// Eases the bars out and then despawns them.
+pub fn hide_letterbox(cmd: &mut Commands) {
+    cmd.insert_resource(LetterboxTransition::new(0.0, HIDE_LETTERBOX_DURATION));
+}
+
+impl LetterboxTransition {
+    /// Transition to the given fraction over the given duration.
+    pub fn new(to: f32, over: Duration) -> Self {
+        debug_assert!(over.as_millis() > 0, "Duration mustn't be zero");
+
+        Self {
+            from: None,
+            to,
+            over,
+            elapsed: default(),
+        }
+    }
+}
+
+/// Eases [`Letterbox`] according to [`LetterboxTransition`].
+pub(crate) fn transition(
+    mut cmd: Commands,
+    time: Res<Time>,
+    mut transition: ResMut<LetterboxTransition>,
+    letterbox: Option<ResMut<Letterbox>>,
+) {
+    let current = letterbox.as_deref().map_or(0.0, |l| l.fraction);
+    let from = *transition.from.get_or_insert(current);
+
+    transition.elapsed.tick(time.delta());
+    let elapsed_fraction = (transition.elapsed.elapsed_secs()
+        / transition.over.as_secs_f32())
+    .min(1.0);
+
+    let fraction =
+        from + (transition.to - from) * EASE_IN_OUT.ease(elapsed_fraction);
+    match letterbox {
+        Some(mut letterbox) => letterbox.fraction = fraction,
+        None => cmd.insert_resource(Letterbox { fraction }),
+    }
+
+    if elapsed_fraction >= 1.0 {
+        cmd.remove_resource::<LetterboxTransition>();
+
+        if transition.to <= 0.0 {
+            cmd.remove_resource::<Letterbox>();
+        }
+    }
+}
+
+/// Spawns the bars when [`Letterbox`] has a positive fraction and keeps their
+/// height in sync.
+/// Despawns them once the fraction reaches zero.
+pub(crate) fn sync_bars(
+    mut cmd: Commands,
+    letterbox: Res<Letterbox>,
+
+    camera: Query<Entity, With<LetterboxCamera>>,
+    mut bars: Query<(Entity, &mut Style), With<LetterboxBar>>,
+) {
+    if letterbox.fraction <= 0.0 {
+        for entity in camera.iter().chain(bars.iter().map(|(e, _)| e)) {
+            cmd.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let height = Val::Percent(letterbox.fraction * 100.0);
+
+    if !bars.is_empty() {
+        for (_, mut style) in bars.iter_mut() {
+            style.height = height;
+        }
+        return;
+    }
+
+    let camera = cmd
+        .spawn((
+            Name::new("Letterbox camera"),
+            LetterboxCamera,
+            RenderLayers::layer(render_layer::CUTSCENE_LETTERBOXING),
+            Camera2dBundle {
+                camera: Camera {
+                    hdr: true,
+                    order: order::CUTSCENE_LETTERBOXING,
+                    clear_color: ClearColorConfig::None,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .id();
+
+    for (name, top, bottom) in [
+        ("Letterbox: top bar", Val::Percent(0.0), Val::Auto),
+        ("Letterbox: bottom bar", Val::Auto, Val::Percent(0.0)),
+    ] {
+        cmd.spawn((
+            Name::new(name),
+            LetterboxBar,
+            TargetCamera(camera),
+            RenderLayers::layer(render_layer::CUTSCENE_LETTERBOXING),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height,
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(0.0),
+                    top,
+                    bottom,
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                ..default()
+            },
+        ));
+    }
+}
+
+/// Despawns the bars when [`Letterbox`] is removed.
+pub(crate) fn despawn_bars(
+    mut cmd: Commands,
+
+    camera: Query<Entity, With<LetterboxCamera>>,
+    bars: Query<Entity, With<LetterboxBar>>,
+) {
+    for entity in camera.iter().chain(bars.iter()) {
+        cmd.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_spawns_bars_only_while_fraction_is_positive() {
+        let mut w = World::default();
+        let sync = w.register_system(sync_bars);
+        let despawn = w.register_system(despawn_bars);
+        let bar_count = |w: &mut World| {
+            w.query_filtered::<(), With<LetterboxBar>>().iter(w).count()
+        };
+
+        w.insert_resource(Letterbox { fraction: 0.15 });
+        w.run_system(sync).unwrap();
+        assert_eq!(2, bar_count(&mut w));
+
+        // the bars are resized rather than spawned again
+        w.resource_mut::<Letterbox>().fraction = 0.1;
+        w.run_system(sync).unwrap();
+        assert_eq!(2, bar_count(&mut w));
+        for style in w.query_filtered::<&Style, With<LetterboxBar>>().iter(&w) {
+            assert_eq!(Val::Percent(10.0), style.height);
+        }
+
+        w.resource_mut::<Letterbox>().fraction = 0.0;
+        w.run_system(sync).unwrap();
+        assert_eq!(0, bar_count(&mut w));
+
+        w.resource_mut::<Letterbox>().fraction = 0.15;
+        w.run_system(sync).unwrap();
+        assert_eq!(2, bar_count(&mut w));
+
+        w.remove_resource::<Letterbox>();
+        w.run_system(despawn).unwrap();
+        assert_eq!(0, bar_count(&mut w));
+    }
+}
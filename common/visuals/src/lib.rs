@@ -2,16 +2,40 @@
 #![deny(missing_docs)]
 #![feature(trivial_bounds)]
 
+pub mod background;
 pub mod camera;
+pub mod day_night;
+pub mod fade;
 #[cfg(feature = "devtools")]
 mod fps;
+pub mod letterbox;
+pub mod menu;
+pub mod outline;
+pub mod parallax;
+pub mod pixel_snap;
+pub mod scene_tint;
+pub mod sway;
 pub mod systems;
+pub mod toast;
 mod types;
+pub mod vignette;
+pub mod weather;
 
 use bevy::{
-    app::{App, FixedUpdate, Last, Update},
+    app::{App, FixedUpdate, Last, PostUpdate, Update},
+    asset::load_internal_asset,
     color::Color,
+    ecs::schedule::{
+        common_conditions::{
+            resource_changed, resource_exists, resource_exists_and_changed,
+            resource_removed,
+        },
+        Condition, IntoSystemConfigs,
+    },
     math::{cubic_splines::CubicSegment, Vec2},
+    render::render_resource::Shader,
+    sprite::Material2dPlugin,
+    transform::TransformSystem,
 };
 use lazy_static::lazy_static;
 pub use types::*;
@@ -24,6 +48,15 @@ lazy_static! {
     /// The ubiquitous "ease-in-out" animation curve.
     pub static ref EASE_IN_OUT: CubicSegment<Vec2> =
         CubicSegment::new_bezier((0.25, 0.1), (0.25, 1.0));
+    /// Starts slow and speeds up towards the end.
+    pub static ref EASE_IN: CubicSegment<Vec2> =
+        CubicSegment::new_bezier((0.42, 0.0), (1.0, 1.0));
+    /// Starts fast and slows down towards the end.
+    pub static ref EASE_OUT: CubicSegment<Vec2> =
+        CubicSegment::new_bezier((0.0, 0.0), (0.58, 1.0));
+    /// Overshoots the target and bounces back, good for popping things in.
+    pub static ref EASE_OUT_BACK: CubicSegment<Vec2> =
+        CubicSegment::new_bezier((0.34, 1.56), (0.64, 1.0));
 }
 
 /// Only registers FPS counter if the `fps` feature is enabled.
@@ -31,7 +64,22 @@ pub struct Plugin;
 
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<BeginInterpolationEvent>();
+        app.add_event::<BeginInterpolationEvent>()
+            .add_event::<InterpolationComplete>()
+            .add_event::<camera::SetCameraZoomEvent>()
+            .add_event::<fade::FadeComplete>()
+            .add_event::<menu::MenuAnimationEvent>()
+            .init_resource::<toast::ToastQueue>()
+            .init_resource::<day_night::TimeOfDay>()
+            .init_resource::<pixel_snap::PixelSnapping>();
+
+        load_internal_asset!(
+            app,
+            outline::OUTLINE_SHADER,
+            "outline.wgsl",
+            Shader::from_wgsl
+        );
+        app.add_plugins(Material2dPlugin::<outline::OutlineMaterial>::default());
 
         app.add_systems(
             FixedUpdate,
@@ -40,7 +88,98 @@ impl bevy::app::Plugin for Plugin {
         .add_systems(Last, systems::recv_begin_interpolation_events)
         .add_systems(
             Update,
-            (systems::begin_atlas_animation_at_random, systems::flicker),
+            (
+                systems::begin_atlas_animation_at_random,
+                systems::desync_atlas_animations,
+                systems::flicker,
+                systems::tint_flicker,
+                camera::zoom,
+                toast::update,
+            ),
+        )
+        .add_systems(Update, (outline::despawn, outline::spawn).chain())
+        .add_systems(
+            PostUpdate,
+            (
+                (parallax::follow_camera, pixel_snap::snap)
+                    .chain()
+                    .before(TransformSystem::TransformPropagate),
+                (sway::sway, pixel_snap::unsnap)
+                    .after(TransformSystem::TransformPropagate),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                scene_tint::transition
+                    .run_if(resource_exists::<scene_tint::SceneTintTransition>),
+                scene_tint::sync_overlay.run_if(
+                    resource_exists_and_changed::<scene_tint::SceneTint>,
+                ),
+                scene_tint::despawn_overlay
+                    .run_if(resource_removed::<scene_tint::SceneTint>()),
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                day_night::advance,
+                day_night::apply
+                    .run_if(resource_exists::<day_night::DayNightCycle>)
+                    .run_if(
+                        resource_changed::<day_night::TimeOfDay>.or_else(
+                            resource_changed::<day_night::DayNightCycle>,
+                        ),
+                    ),
+            )
+                .chain()
+                // so that the overlay is synced on the same frame
+                .before(scene_tint::sync_overlay),
+        )
+        .add_systems(
+            Update,
+            (
+                vignette::transition
+                    .run_if(resource_exists::<vignette::VignetteTransition>),
+                vignette::sync_overlay
+                    .run_if(resource_exists_and_changed::<vignette::Vignette>),
+                vignette::despawn_overlay
+                    .run_if(resource_removed::<vignette::Vignette>()),
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                letterbox::transition
+                    .run_if(resource_exists::<letterbox::LetterboxTransition>),
+                letterbox::sync_bars.run_if(
+                    resource_exists_and_changed::<letterbox::Letterbox>,
+                ),
+                letterbox::despawn_bars
+                    .run_if(resource_removed::<letterbox::Letterbox>()),
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                background::apply.run_if(
+                    resource_exists_and_changed::<background::SceneBackground>,
+                ),
+                background::restore
+                    .run_if(resource_removed::<background::SceneBackground>()),
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                weather::fall.run_if(resource_exists::<weather::Weather>),
+                weather::despawn.run_if(resource_removed::<weather::Weather>()),
+            )
+                .chain(),
         );
 
         #[cfg(feature = "devtools")]
@@ -52,8 +191,33 @@ impl bevy::app::Plugin for Plugin {
                 .register_type::<AtlasAnimationTimer>()
                 .register_type::<TranslationInterpolation>()
                 .register_type::<ColorInterpolation>()
+                .register_type::<InterpolationId>()
                 .register_type::<BeginAtlasAnimation>()
-                .register_type::<Flicker>();
+                .register_type::<DesyncAnimation>()
+                .register_type::<Flicker>()
+                .register_type::<background::SceneBackground>()
+                .register_type::<camera::CameraZoom>()
+                .register_type::<TintFlicker>()
+                .register_type::<day_night::DayNightCycle>()
+                .register_type::<day_night::TimeOfDay>()
+                .register_type::<scene_tint::SceneTint>()
+                .register_type::<scene_tint::SceneTintTransition>()
+                .register_type::<letterbox::Letterbox>()
+                .register_type::<letterbox::LetterboxTransition>()
+                .register_type::<menu::MenuAnimator>()
+                .register_type::<menu::MenuState>()
+                .register_type::<menu::MenuEdge>()
+                .register_type::<outline::Outlined>()
+                .register_type::<parallax::ParallaxLayer>()
+                .register_type::<pixel_snap::PixelSnap>()
+                .register_type::<pixel_snap::PixelSnapping>()
+                .register_type::<sway::Sway>()
+                .register_type::<toast::ToastQueue>()
+                .register_type::<toast::ToastNode>()
+                .register_type::<vignette::Vignette>()
+                .register_type::<vignette::VignetteTransition>()
+                .register_type::<weather::Weather>()
+                .register_type::<weather::WeatherKind>();
 
             app.add_plugins(FrameTimeDiagnosticsPlugin)
                 .add_systems(Startup, fps::spawn)
@@ -0,0 +1,259 @@
+//! Slides a menu in from an edge of the screen when it opens and back out
+//! when it closes.
+//!
+//! Insert [`MenuAnimator`] to the root UI node of a menu and call
+//! [`MenuAnimator::open`] and [`MenuAnimator::close`].
+//! The slide is a [`crate::UiStylePositionInterpolation`] of the root's
+//! [`Style::left`] and [`Style::top`] with the [`EASE_IN_OUT`] curve.
+//! The root is expected to be positioned absolutely.
+//! [`MenuAnimationEvent`] is sent once a slide finishes.
+//! Don't accept input until then, see [`MenuAnimator::is_open`].
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{BeginInterpolationEvent, EASE_IN_OUT};
+
+/// How long a slide takes by default.
+pub const DEFAULT_SLIDE_DURATION: Duration = Duration::from_millis(250);
+
+/// Slides the menu root it's inserted to, see the module docs.
+#[derive(Component, Reflect, Clone, Debug)]
+pub struct MenuAnimator {
+    /// Where the root is while the menu is open, as left and top in pixels.
+    pub on_screen: Vec2,
+    /// Where the root slides in from and out to.
+    pub off_screen: Vec2,
+    /// How long a slide takes.
+    pub over: Duration,
+    state: MenuState,
+}
+
+/// Which part of the slide the menu is in.
+#[derive(Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuState {
+    /// Off screen.
+    #[default]
+    Closed,
+    /// Sliding in.
+    Opening,
+    /// On screen and accepting input.
+    Open,
+    /// Sliding out.
+    Closing,
+}
+
+/// The edge of the screen the menu slides in from.
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuEdge {
+    /// Slides down from above.
+    Top,
+    /// Slides up from below.
+    Bottom,
+    /// Slides right from the left.
+    Left,
+    /// Slides left from the right.
+    Right,
+}
+
+/// Sent when a slide of a [`MenuAnimator`] is done.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuAnimationEvent {
+    /// The menu root with this entity slid in and is open.
+    Opened(Entity),
+    /// The menu root with this entity slid out.
+    /// It's despawned by now if [`MenuAnimator::close`] was asked to.
+    Closed(Entity),
+}
+
+impl MenuAnimator {
+    /// The menu slides in from the edge by the given distance in pixels.
+    /// E.g. a menu that slides in from the bottom starts `distance` below
+    /// `on_screen`.
+    pub fn new(on_screen: Vec2, from: MenuEdge, distance: f32) -> Self {
+        // UI top grows downwards
+        let off_screen = on_screen
+            + match from {
+                MenuEdge::Top => Vec2::new(0.0, -distance),
+                MenuEdge::Bottom => Vec2::new(0.0, distance),
+                MenuEdge::Left => Vec2::new(-distance, 0.0),
+                MenuEdge::Right => Vec2::new(distance, 0.0),
+            };
+
+        Self {
+            on_screen,
+            off_screen,
+            over: DEFAULT_SLIDE_DURATION,
+            state: default(),
+        }
+    }
+
+    /// How long a slide takes.
+    /// Defaults to [`DEFAULT_SLIDE_DURATION`].
+    pub fn over(mut self, over: Duration) -> Self {
+        self.over = over;
+        self
+    }
+
+    /// Which part of the slide the menu is in.
+    pub fn state(&self) -> MenuState {
+        self.state
+    }
+
+    /// Only accept input when this is true.
+    pub fn is_open(&self) -> bool {
+        self.state == MenuState::Open
+    }
+
+    /// Slides the root in from off screen.
+    /// Does nothing if the menu is already open or opening.
+    pub fn open(&mut self, cmd: &mut Commands, root: Entity) {
+        if matches!(self.state, MenuState::Open | MenuState::Opening) {
+            return;
+        }
+        self.state = MenuState::Opening;
+
+        BeginInterpolationEvent::of_ui_style_position(
+            root,
+            Some(self.off_screen),
+            self.on_screen,
+        )
+        .over(self.over)
+        .with_animation_curve(*EASE_IN_OUT)
+        .when_finished_do(move |cmd| {
+            cmd.add(move |w: &mut World| {
+                finish(w, root, MenuState::Open, false);
+            });
+        })
+        .insert(cmd);
+    }
+
+    /// Slides the root out from where it is.
+    /// Optionally despawns it once it's off screen.
+    /// Does nothing if the menu is already closed or closing.
+    pub fn close(
+        &mut self,
+        cmd: &mut Commands,
+        root: Entity,
+        despawn_when_closed: bool,
+    ) {
+        if matches!(self.state, MenuState::Closed | MenuState::Closing) {
+            return;
+        }
+        self.state = MenuState::Closing;
+
+        // starts where the root is in case it's still sliding in
+        BeginInterpolationEvent::of_ui_style_position(
+            root,
+            None,
+            self.off_screen,
+        )
+        .over(self.over)
+        .with_animation_curve(*EASE_IN_OUT)
+        .when_finished_do(move |cmd| {
+            cmd.add(move |w: &mut World| {
+                finish(w, root, MenuState::Closed, despawn_when_closed);
+            });
+        })
+        .insert(cmd);
+    }
+}
+
+/// Runs when a slide is done.
+fn finish(w: &mut World, root: Entity, state: MenuState, despawn: bool) {
+    if let Some(mut animator) = w.get_mut::<MenuAnimator>(root) {
+        animator.state = state;
+    }
+
+    w.send_event(match state {
+        MenuState::Closed => MenuAnimationEvent::Closed(root),
+        _ => MenuAnimationEvent::Opened(root),
+    });
+
+    if despawn {
+        if let Some(root) = w.get_entity_mut(root) {
+            root.despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::systems::interpolate;
+
+    #[test]
+    fn it_slides_menu_in_and_out_before_despawn() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+        w.init_resource::<Events<MenuAnimationEvent>>();
+        let interpolate = w.register_system(interpolate);
+        let over = Duration::from_millis(200);
+        let animator =
+            MenuAnimator::new(Vec2::ZERO, MenuEdge::Bottom, 100.0).over(over);
+        let off_screen = animator.off_screen;
+        let root = w.spawn((NodeBundle::default(), animator)).id();
+
+        let advance_by = |w: &mut World, millis| {
+            w.resource_mut::<Time>()
+                .advance_by(Duration::from_millis(millis));
+            w.run_system(interpolate).unwrap();
+        };
+        let at = |w: &World| {
+            let style = w.get::<Style>(root).unwrap();
+            match (style.left, style.top) {
+                (Val::Px(left), Val::Px(top)) => Vec2::new(left, top),
+                position => panic!("Not in pixels: {position:?}"),
+            }
+        };
+        let events = |w: &mut World| {
+            w.resource_mut::<Events<MenuAnimationEvent>>()
+                .drain()
+                .collect::<Vec<_>>()
+        };
+        let slide = |w: &mut World, open: bool| {
+            w.run_system_once(
+                move |mut cmd: Commands,
+                      mut animators: Query<&mut MenuAnimator>| {
+                    let mut animator = animators.get_mut(root).unwrap();
+                    if open {
+                        animator.open(&mut cmd, root);
+                    } else {
+                        animator.close(&mut cmd, root, true);
+                    }
+                },
+            );
+        };
+
+        slide(&mut w, true);
+        advance_by(&mut w, 100);
+        let halfway = at(&w);
+        assert!(halfway.y > 0.0 && halfway.y < off_screen.y, "{halfway}");
+        assert!(!w.get::<MenuAnimator>(root).unwrap().is_open());
+        assert!(events(&mut w).is_empty());
+
+        advance_by(&mut w, 100);
+        assert_eq!(Vec2::ZERO, at(&w));
+        assert!(w.get::<MenuAnimator>(root).unwrap().is_open());
+        assert_eq!(vec![MenuAnimationEvent::Opened(root)], events(&mut w));
+
+        slide(&mut w, false);
+        advance_by(&mut w, 199);
+        let almost_off_screen = at(&w);
+        assert!(
+            almost_off_screen.distance(off_screen) < 1.0,
+            "{almost_off_screen}"
+        );
+        assert_eq!(
+            MenuState::Closing,
+            w.get::<MenuAnimator>(root).unwrap().state()
+        );
+
+        advance_by(&mut w, 1);
+        assert!(w.get_entity(root).is_none());
+        assert_eq!(vec![MenuAnimationEvent::Closed(root)], events(&mut w));
+    }
+}
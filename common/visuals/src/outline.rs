@@ -0,0 +1,204 @@
+//! Outlines sprites to make them stand out, e.g. when the player inspects
+//! the surroundings.
+//!
+//! Insert [`Outlined`] to a sprite entity to outline it and remove the
+//! component to remove the outline.
+//!
+//! The outline is a child entity that renders the silhouette of the sprite
+//! dilated by [`Outlined::width`] in a single color.
+//! It's positioned just behind the sprite and it has the same
+//! [`RenderLayers`] as the sprite.
+//! The whole image of the sprite is outlined, texture atlases are not
+//! supported.
+//!
+//! ```rust,ignore
+//! cmd.spawn((
+//!     SpriteBundle {
+//!         texture: asset_server.load(assets::MY_OBJECT),
+//!         ..default()
+//!     },
+//!     Outlined::new(Color::WHITE),
+//! ));
+//! ```
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{AsBindGroup, ShaderRef},
+        view::RenderLayers,
+    },
+    sprite::{Material2d, MaterialMesh2dBundle},
+};
+
+/// The silhouette is this much behind the sprite along the z axis.
+const BEHIND_SPRITE: f32 = 0.001;
+
+pub(crate) const OUTLINE_SHADER: Handle<Shader> =
+    Handle::weak_from_u128(0x7a1e_4b8c_93d2_4f06_b5a1_2c8e_6d39_f041);
+
+/// Outlines the sprite of this entity, see the module docs.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+pub struct Outlined {
+    /// Of the outline.
+    pub color: Color,
+    /// In pixels.
+    /// Keep this small, the outline of thin parts of the sprite might have
+    /// gaps when the width is larger than the parts.
+    pub width: f32,
+}
+
+/// Present on entities with [`Outlined`] whose silhouette has been spawned.
+#[derive(Component)]
+pub(crate) struct OutlineSilhouette(Entity);
+
+/// Must match the bindings in the shader.
+#[derive(Asset, TypePath, AsBindGroup, Clone, Debug)]
+pub(crate) struct OutlineMaterial {
+    #[uniform(0)]
+    color: LinearRgba,
+    /// Size of the sprite in pixels, outline width in pixels and flip bits.
+    /// First bit flips horizontally, second bit vertically.
+    #[uniform(1)]
+    params: Vec4,
+    #[texture(2)]
+    #[sampler(3)]
+    texture: Handle<Image>,
+}
+
+impl Outlined {
+    /// One pixel wide outline.
+    pub fn new(color: Color) -> Self {
+        Self { color, width: 1.0 }
+    }
+}
+
+impl Material2d for OutlineMaterial {
+    fn fragment_shader() -> ShaderRef {
+        OUTLINE_SHADER.into()
+    }
+}
+
+/// Spawns silhouettes for sprites that are outlined.
+/// Waits until the image of the sprite is loaded.
+#[allow(clippy::type_complexity)]
+pub(crate) fn spawn(
+    mut cmd: Commands,
+    images: Res<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<OutlineMaterial>>,
+
+    outlined: Query<
+        (
+            Entity,
+            &Outlined,
+            &Sprite,
+            &Handle<Image>,
+            Option<&RenderLayers>,
+        ),
+        (Without<OutlineSilhouette>, Without<TextureAtlas>),
+    >,
+) {
+    for (entity, outlined, sprite, texture, layers) in outlined.iter() {
+        let Some(image) = images.get(texture) else {
+            continue;
+        };
+        let size = sprite.custom_size.unwrap_or_else(|| image.size_f32());
+        let mut silhouette = cmd.spawn((
+            Name::new("Outline"),
+            MaterialMesh2dBundle {
+                mesh: meshes
+                    .add(Rectangle::from_size(size + 2.0 * outlined.width))
+                    .into(),
+                material: materials.add(OutlineMaterial {
+                    color: outlined.color.into(),
+                    params: Vec4::new(
+                        size.x,
+                        size.y,
+                        outlined.width,
+                        (sprite.flip_x as u8 | (sprite.flip_y as u8) << 1)
+                            as f32,
+                    ),
+                    texture: texture.clone(),
+                }),
+                // the sprite is drawn around its anchor
+                transform: Transform::from_translation(
+                    (-sprite.anchor.as_vec() * size).extend(-BEHIND_SPRITE),
+                ),
+                ..default()
+            },
+        ));
+        if let Some(layers) = layers {
+            silhouette.insert(layers.clone());
+        }
+        let silhouette = silhouette.id();
+
+        cmd.entity(entity)
+            .add_child(silhouette)
+            .insert(OutlineSilhouette(silhouette));
+    }
+}
+
+/// Despawns silhouettes of sprites that are no longer outlined.
+/// If the [`Outlined`] changed, the silhouette is despawned too and
+/// [`spawn`] creates a new one.
+#[allow(clippy::type_complexity)]
+pub(crate) fn despawn(
+    mut cmd: Commands,
+
+    silhouettes: Query<
+        (Entity, &OutlineSilhouette),
+        Or<(Without<Outlined>, Changed<Outlined>)>,
+    >,
+) {
+    for (entity, OutlineSilhouette(silhouette)) in silhouettes.iter() {
+        cmd.entity(*silhouette).despawn_recursive();
+        cmd.entity(entity).remove::<OutlineSilhouette>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_spawns_and_despawns_silhouette() {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<Mesh>()
+            .init_asset::<OutlineMaterial>();
+        let w = app.world_mut();
+        let spawn = w.register_system(spawn);
+        let despawn = w.register_system(despawn);
+
+        // white pixel
+        let texture = w.resource_mut::<Assets<Image>>().add(Image::default());
+        let sprite = w
+            .spawn((
+                SpriteBundle {
+                    texture,
+                    ..default()
+                },
+                RenderLayers::layer(1),
+                Outlined::new(Color::WHITE),
+            ))
+            .id();
+
+        w.run_system(despawn).unwrap();
+        w.run_system(spawn).unwrap();
+        let &OutlineSilhouette(silhouette) =
+            w.get::<OutlineSilhouette>(sprite).unwrap();
+        assert_eq!(Some(sprite), w.get::<Parent>(silhouette).map(|p| p.get()));
+        assert_eq!(
+            Some(&RenderLayers::layer(1)),
+            w.get::<RenderLayers>(silhouette)
+        );
+        assert!(w.get::<Transform>(silhouette).unwrap().translation.z < 0.0);
+
+        w.entity_mut(sprite).remove::<Outlined>();
+        w.run_system(despawn).unwrap();
+        w.run_system(spawn).unwrap();
+        assert!(w.get::<OutlineSilhouette>(sprite).is_none());
+        assert!(w.get_entity(silhouette).is_none());
+    }
+}
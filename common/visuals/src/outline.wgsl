@@ -0,0 +1,50 @@
+// Draws the silhouette of a sprite dilated by the outline width.
+// The quad is larger than the sprite by the width on each side and it's
+// rendered just behind the sprite, so only the outline is visible.
+
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+const FLIP_X_BIT: u32 = 1u;
+const FLIP_Y_BIT: u32 = 2u;
+
+@group(2) @binding(0) var<uniform> color: vec4<f32>;
+// size of the sprite in pixels, outline width in pixels and flip bits
+@group(2) @binding(1) var<uniform> params: vec4<f32>;
+@group(2) @binding(2) var texture: texture_2d<f32>;
+@group(2) @binding(3) var texture_sampler: sampler;
+
+// Alpha of the sprite at the given position in pixels.
+fn alpha_at(px: vec2<f32>) -> f32 {
+    let size = params.xy;
+    if (any(px < vec2(0.0)) || any(px > size)) {
+        return 0.0;
+    }
+
+    var uv = px / size;
+    let flip = u32(params.w);
+    if ((flip & FLIP_X_BIT) != 0u) {
+        uv.x = 1.0 - uv.x;
+    }
+    if ((flip & FLIP_Y_BIT) != 0u) {
+        uv.y = 1.0 - uv.y;
+    }
+
+    // not in uniform control flow, hence explicit level
+    return textureSampleLevel(texture, texture_sampler, uv, 0.0).a;
+}
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let w = params.z;
+    let px = mesh.uv * (params.xy + 2.0 * w) - w;
+
+    let alpha = max(
+        max(alpha_at(px + vec2(w, 0.0)), alpha_at(px - vec2(w, 0.0))),
+        max(alpha_at(px + vec2(0.0, w)), alpha_at(px - vec2(0.0, w))),
+    );
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    return vec4(color.rgb, color.a * alpha);
+}
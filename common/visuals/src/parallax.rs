@@ -0,0 +1,99 @@
+//! Backgrounds that move slower than the world as the camera moves, giving
+//! the scene a sense of depth.
+//!
+//! Insert [`ParallaxLayer`] to an entity and it follows the [`MainCamera`]
+//! by the [`ParallaxLayer::factor`].
+//! The translation of the entity at the time of the insertion is where the
+//! layer is when the camera is at the origin.
+//! Godot scenes insert the component for each `ParallaxLayer` node.
+
+use bevy::prelude::*;
+
+use crate::camera::MainCamera;
+
+/// Moves the entity along with the camera, see the module docs.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+pub struct ParallaxLayer {
+    /// How much the layer moves along with the world as the camera moves.
+    /// With 1 the layer is part of the world, with 0 it's stuck to the
+    /// camera.
+    /// Distant backgrounds have something in between.
+    pub factor: Vec2,
+    /// The translation of the layer when the camera is at the origin.
+    /// Remembered on the first run of [`follow_camera`].
+    origin: Option<Vec2>,
+}
+
+impl ParallaxLayer {
+    /// Same as Godot's `motion_scale`.
+    pub fn new(factor: Vec2) -> Self {
+        Self {
+            factor,
+            origin: None,
+        }
+    }
+}
+
+/// Must run after the camera has moved and before the transforms are
+/// propagated.
+pub(crate) fn follow_camera(
+    camera: Query<&Transform, (With<MainCamera>, Without<ParallaxLayer>)>,
+    mut layers: Query<(&mut ParallaxLayer, &mut Transform)>,
+) {
+    let Ok(camera) = camera.get_single() else {
+        return;
+    };
+    let camera = camera.translation.truncate();
+
+    for (mut layer, mut transform) in layers.iter_mut() {
+        let origin = *layer
+            .origin
+            .get_or_insert_with(|| transform.translation.truncate());
+
+        let position = origin + camera * (Vec2::ONE - layer.factor);
+        if transform.translation.truncate() != position {
+            transform.translation = position.extend(transform.translation.z);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_moves_layers_by_factor() {
+        let mut w = World::default();
+        let follow_camera = w.register_system(follow_camera);
+
+        let camera = w.spawn((MainCamera, Transform::default())).id();
+        let world = w
+            .spawn((
+                ParallaxLayer::new(Vec2::ONE),
+                Transform::from_xyz(10.0, 0.0, -100.0),
+            ))
+            .id();
+        let distant = w
+            .spawn((
+                ParallaxLayer::new(Vec2::new(0.25, 0.5)),
+                Transform::from_xyz(10.0, 0.0, -100.0),
+            ))
+            .id();
+        let stuck = w
+            .spawn((
+                ParallaxLayer::new(Vec2::ZERO),
+                Transform::from_xyz(10.0, 0.0, -100.0),
+            ))
+            .id();
+
+        w.run_system(follow_camera).unwrap();
+        w.get_mut::<Transform>(camera).unwrap().translation =
+            Vec3::new(100.0, -40.0, 0.0);
+        w.run_system(follow_camera).unwrap();
+
+        let at = |entity| w.get::<Transform>(entity).unwrap().translation;
+        assert_eq!(Vec3::new(10.0, 0.0, -100.0), at(world));
+        assert_eq!(Vec3::new(85.0, -20.0, -100.0), at(distant));
+        assert_eq!(Vec3::new(110.0, -40.0, -100.0), at(stuck));
+    }
+}
@@ -0,0 +1,158 @@
+//! Sprites that move smoothly end up on sub-pixel positions which makes them
+//! shimmer with the pixel camera.
+//!
+//! Insert [`PixelSnap`] to a sprite entity to render it on the nearest whole
+//! pixel of the screen.
+//! Its [`Transform`] is snapped just before the transforms are propagated and
+//! put back right after, so the gameplay never sees the snapped position
+//! while the rendered [`GlobalTransform`] is on the pixel grid.
+//! Children of snapped sprites move along with them.
+//!
+//! The pixel grid is relative to the [`MainCamera`] and its [`PixelZoom`],
+//! so that a camera that tracks the player doesn't make the snapped sprites
+//! jump back and forth.
+//!
+//! Some effects want smooth motion, set [`PixelSnapping::enabled`] to false
+//! to render all sprites where they are.
+
+use bevy::prelude::*;
+use bevy_pixel_camera::PixelZoom;
+
+use crate::camera::{MainCamera, PIXEL_ZOOM};
+
+/// Renders the sprite on the nearest whole pixel, see the module docs.
+#[derive(Component, Reflect, Default, Debug, Clone, Copy)]
+pub struct PixelSnap {
+    /// Where the gameplay left the sprite before [`snap`], for [`unsnap`] to
+    /// put it back.
+    unsnapped: Option<Vec3>,
+}
+
+/// Toggles [`PixelSnap`] for all sprites.
+#[derive(Resource, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+#[reflect(Resource)]
+pub struct PixelSnapping {
+    /// Enabled by default.
+    pub enabled: bool,
+}
+
+/// Must run before [`TransformSystem::TransformPropagate`].
+///
+/// The world position of a child is calculated with the [`GlobalTransform`]
+/// of its parent from the last frame.
+pub(crate) fn snap(
+    snapping: Res<PixelSnapping>,
+
+    camera: Query<(&Transform, Option<&PixelZoom>), With<MainCamera>>,
+    parents: Query<&GlobalTransform>,
+    mut snapped: Query<
+        (&mut PixelSnap, &mut Transform, Option<&Parent>),
+        Without<MainCamera>,
+    >,
+) {
+    if !snapping.enabled {
+        return;
+    }
+
+    let (origin, zoom) = match camera.get_single() {
+        Ok((camera, Some(PixelZoom::Fixed(zoom)))) => {
+            (camera.translation.truncate(), *zoom)
+        }
+        Ok((camera, _)) => (camera.translation.truncate(), PIXEL_ZOOM),
+        Err(_) => (Vec2::ZERO, PIXEL_ZOOM),
+    };
+    let zoom = zoom.max(1) as f32;
+
+    for (mut snap, mut transform, parent) in snapped.iter_mut() {
+        let parent = parent
+            .and_then(|parent| parents.get(parent.get()).ok())
+            .copied()
+            .unwrap_or_default();
+
+        let position = parent.transform_point(transform.translation).truncate();
+        let delta =
+            origin + ((position - origin) * zoom).round() / zoom - position;
+        if delta == Vec2::ZERO {
+            continue;
+        }
+
+        // back to the space of the parent
+        let offset = parent
+            .affine()
+            .inverse()
+            .transform_vector3(delta.extend(0.0));
+        snap.unsnapped = Some(transform.translation);
+        transform.translation += offset;
+    }
+}
+
+/// Must run after [`TransformSystem::TransformPropagate`].
+/// Puts the [`Transform`] back where the gameplay left it.
+pub(crate) fn unsnap(mut snapped: Query<(&mut PixelSnap, &mut Transform)>) {
+    for (mut snap, mut transform) in snapped.iter_mut() {
+        // don't flag unsnapped sprites as changed
+        if let Some(unsnapped) = snap.unsnapped {
+            transform.translation = unsnapped;
+            snap.unsnapped = None;
+        }
+    }
+}
+
+impl Default for PixelSnapping {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_snaps_to_whole_pixels_relative_to_camera() {
+        let mut w = World::default();
+        w.init_resource::<PixelSnapping>();
+        let snap = w.register_system(snap);
+        let unsnap = w.register_system(unsnap);
+
+        let camera = Vec3::new(0.2, -1.1, 0.0);
+        w.spawn((
+            MainCamera,
+            PixelZoom::Fixed(3),
+            Transform::from_translation(camera),
+        ));
+        let at = Vec3::new(10.4567, -3.21, 5.0);
+        let sprite = w
+            .spawn((PixelSnap::default(), Transform::from_translation(at)))
+            .id();
+        let parent_at = Vec3::new(0.3, 0.0, 0.0);
+        let parent = w.spawn(GlobalTransform::from_translation(parent_at)).id();
+        let child = w
+            .spawn((PixelSnap::default(), Transform::from_translation(at)))
+            .set_parent(parent)
+            .id();
+        let translation =
+            |w: &World, entity| w.get::<Transform>(entity).unwrap().translation;
+        let assert_snapped = |snapped: Vec3, unsnapped: Vec3| {
+            let on_screen = (snapped - camera) * 3.0;
+            assert!((on_screen.x - on_screen.x.round()).abs() < 1e-4);
+            assert!((on_screen.y - on_screen.y.round()).abs() < 1e-4);
+            assert!((snapped - unsnapped).abs().max_element() <= 0.5 / 3.0);
+            assert_eq!(unsnapped.z, snapped.z);
+        };
+
+        w.run_system(snap).unwrap();
+        assert_snapped(translation(&w, sprite), at);
+        // the child is snapped in the world rather than its parent's space
+        assert_snapped(translation(&w, child) + parent_at, at + parent_at);
+
+        // the gameplay position is restored once the transforms propagated
+        w.run_system(unsnap).unwrap();
+        assert_eq!(at, translation(&w, sprite));
+        assert_eq!(at, translation(&w, child));
+
+        w.resource_mut::<PixelSnapping>().enabled = false;
+        w.run_system(snap).unwrap();
+        assert_eq!(at, translation(&w, sprite));
+    }
+}
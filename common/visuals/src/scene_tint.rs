@@ -0,0 +1,173 @@
+//! Each scene has a mood.
+//! Insert [`SceneTint`] to overlay the world with a uniform color.
+//! Remove the resource to remove the overlay.
+//!
+//! The overlay is rendered above the world but below the letterboxing, dialog
+//! and loading screen.
+//! Use [`SceneTintTransition`] to smoothly change the tint, e.g. for day/night.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, render::view::RenderLayers, time::Stopwatch};
+use common_ext::ColorExt;
+
+use crate::camera::{order, render_layer};
+
+/// Uniform tint over the whole world.
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct SceneTint {
+    /// The color of the overlay.
+    pub color: Color,
+    /// How strongly is the tint applied.
+    /// Between 0 (no tint) and 1 (opaque overlay of [`SceneTint::color`]).
+    pub mix: f32,
+}
+
+/// Changes [`SceneTint`] from its current value to the target value over
+/// some time.
+/// Removed once the transition is done.
+///
+/// If there's no [`SceneTint`] when the transition starts, it's inserted with
+/// the target color and zero mix.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct SceneTintTransition {
+    from: Option<SceneTint>,
+    to: SceneTint,
+    over: Duration,
+    elapsed: Stopwatch,
+}
+
+#[derive(Component)]
+pub(crate) struct SceneTintCamera;
+
+#[derive(Component)]
+pub(crate) struct SceneTintQuad;
+
+impl SceneTint {
+    /// A tint of given color and strength.
+    pub fn new(color: Color, mix: f32) -> Self {
+        debug_assert!((0.0..=1.0).contains(&mix), "Mix must be in [0, 1]");
+
+        Self { color, mix }
+    }
+
+    /// Linear interpolation between two tints.
+    /// The `t` is clamped between 0 and 1.
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+
+        Self {
+            color: self.color.lerp(other.color, t),
+            mix: self.mix + (other.mix - self.mix) * t,
+        }
+    }
+
+    /// The color of the overlay quad.
+    fn overlay_color(self) -> Color {
+        self.color.with_alpha(self.color.alpha() * self.mix)
+    }
+}
+
+impl SceneTintTransition {
+    /// Transition to the given tint over the given duration.
+    pub fn new(to: SceneTint, over: Duration) -> Self {
+        debug_assert!(over.as_millis() > 0, "Duration mustn't be zero");
+
+        Self {
+            from: None,
+            to,
+            over,
+            elapsed: default(),
+        }
+    }
+}
+
+/// Lerps [`SceneTint`] according to [`SceneTintTransition`].
+pub(crate) fn transition(
+    mut cmd: Commands,
+    time: Res<Time>,
+    mut transition: ResMut<SceneTintTransition>,
+    tint: Option<ResMut<SceneTint>>,
+) {
+    let current = tint.as_deref().copied().unwrap_or(SceneTint {
+        color: transition.to.color,
+        mix: 0.0,
+    });
+    let from = *transition.from.get_or_insert(current);
+
+    transition.elapsed.tick(time.delta());
+    let elapsed_fraction =
+        transition.elapsed.elapsed_secs() / transition.over.as_secs_f32();
+
+    let new_tint = from.lerp(transition.to, elapsed_fraction);
+    match tint {
+        Some(mut tint) => *tint = new_tint,
+        None => cmd.insert_resource(new_tint),
+    }
+
+    if elapsed_fraction >= 1.0 {
+        cmd.remove_resource::<SceneTintTransition>();
+    }
+}
+
+/// Spawns the overlay when [`SceneTint`] is inserted and keeps its color in
+/// sync.
+pub(crate) fn sync_overlay(
+    mut cmd: Commands,
+    tint: Res<SceneTint>,
+
+    mut quad: Query<&mut BackgroundColor, With<SceneTintQuad>>,
+) {
+    if let Ok(mut bg) = quad.get_single_mut() {
+        bg.0 = tint.overlay_color();
+        return;
+    }
+
+    let camera = cmd
+        .spawn((
+            Name::new("Scene tint camera"),
+            SceneTintCamera,
+            RenderLayers::layer(render_layer::SCENE_TINT),
+            Camera2dBundle {
+                camera: Camera {
+                    hdr: true,
+                    order: order::SCENE_TINT,
+                    clear_color: ClearColorConfig::None,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .id();
+
+    cmd.spawn((
+        Name::new("Scene tint quad"),
+        SceneTintQuad,
+        TargetCamera(camera),
+        RenderLayers::layer(render_layer::SCENE_TINT),
+        NodeBundle {
+            background_color: BackgroundColor(tint.overlay_color()),
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+/// Despawns the overlay when [`SceneTint`] is removed.
+pub(crate) fn despawn_overlay(
+    mut cmd: Commands,
+
+    camera: Query<Entity, With<SceneTintCamera>>,
+    quad: Query<Entity, With<SceneTintQuad>>,
+) {
+    for entity in camera.iter().chain(quad.iter()) {
+        cmd.entity(entity).despawn_recursive();
+    }
+}
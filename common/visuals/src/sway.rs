@@ -0,0 +1,202 @@
+//! Foliage and water that gently move as if in the wind.
+//!
+//! Insert [`Sway`] to a sprite entity to tilt or stretch it back and forth.
+//! Only the [`GlobalTransform`] is changed, after the transforms have been
+//! propagated.
+//! The [`Transform`] that the gameplay works with is left as it is.
+//! Children of swaying sprites move along with them.
+//!
+//! Each sprite starts at a random phase so that a row of plants doesn't move
+//! in sync.
+//! Insert [`SwayRng`] or use [`Sway::with_phase`] for deterministic phases.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, RngCore};
+
+/// Moves the sprite back and forth, see the module docs.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+pub struct Sway {
+    /// How far the sprite moves to each side.
+    /// In radians for [`SwayAxis::Tilt`], otherwise a fraction of the
+    /// sprite's scale.
+    pub amplitude: f32,
+    /// How many times per second the sprite moves back and forth.
+    pub frequency: f32,
+    /// How the sprite moves.
+    pub axis: SwayAxis,
+    /// Where in the cycle the sprite is, in radians.
+    /// Picked at random on the first run of [`sway`] if not set.
+    phase: Option<f32>,
+}
+
+/// See [`Sway::axis`].
+#[derive(Reflect, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwayAxis {
+    /// Rotates the sprite around its anchor, e.g. grass or trees.
+    #[default]
+    Tilt,
+    /// Stretches the sprite horizontally, e.g. ripples on water.
+    Horizontal,
+    /// Stretches the sprite vertically, e.g. bushes.
+    Vertical,
+}
+
+/// If present, [`Sway`] phases are picked with this generator instead of the
+/// thread local one.
+#[derive(Resource)]
+pub struct SwayRng(pub StdRng);
+
+impl Sway {
+    /// Starts at a random phase.
+    pub fn new(amplitude: f32, frequency: f32, axis: SwayAxis) -> Self {
+        Self {
+            amplitude,
+            frequency,
+            axis,
+            phase: None,
+        }
+    }
+
+    /// Starts at the given phase in radians instead of a random one.
+    pub fn with_phase(mut self, phase: f32) -> Self {
+        self.phase = Some(phase.rem_euclid(TAU));
+        self
+    }
+
+    /// Where in the cycle the sprite is, in radians.
+    /// [`None`] until the phase is picked.
+    pub fn phase(&self) -> Option<f32> {
+        self.phase
+    }
+
+    /// The transform to apply on top of the propagated one.
+    fn offset(&self, phase: f32) -> Transform {
+        let wave = self.amplitude * phase.sin();
+        match self.axis {
+            SwayAxis::Tilt => {
+                Transform::from_rotation(Quat::from_rotation_z(wave))
+            }
+            SwayAxis::Horizontal => {
+                Transform::from_scale(Vec3::new(1.0 + wave, 1.0, 1.0))
+            }
+            SwayAxis::Vertical => {
+                Transform::from_scale(Vec3::new(1.0, 1.0 + wave, 1.0))
+            }
+        }
+    }
+}
+
+/// Must run after [`TransformSystem::TransformPropagate`].
+pub(crate) fn sway(
+    time: Res<Time>,
+    mut seeded: Option<ResMut<SwayRng>>,
+
+    mut swaying: Query<(Entity, &mut Sway, &mut Transform)>,
+    children: Query<&Children>,
+    mut transforms: Query<&mut GlobalTransform>,
+) {
+    let mut thread_rng = rand::thread_rng();
+    let rng: &mut dyn RngCore = match seeded.as_deref_mut() {
+        Some(SwayRng(rng)) => rng,
+        None => &mut thread_rng,
+    };
+
+    for (entity, mut sway, mut transform) in swaying.iter_mut() {
+        let advance = time.delta_seconds() * sway.frequency * TAU;
+        let phase = match sway.phase {
+            Some(phase) => (phase + advance).rem_euclid(TAU),
+            None => rng.gen_range(0.0..TAU),
+        };
+        sway.phase = Some(phase);
+        // the global transform is only propagated when the transform
+        // changes, and we don't want to sway on top of the last frame's sway
+        transform.set_changed();
+
+        let Ok(global) = transforms.get(entity) else {
+            continue;
+        };
+        let swayed = global.mul_transform(sway.offset(phase));
+        let delta = swayed.affine() * global.affine().inverse();
+
+        for entity in
+            std::iter::once(entity).chain(children.iter_descendants(entity))
+        {
+            if let Ok(mut global) = transforms.get_mut(entity) {
+                *global = GlobalTransform::from(delta * global.affine());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn it_sways_sprites_out_of_phase() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+        w.insert_resource(SwayRng(StdRng::seed_from_u64(42)));
+        let sway = w.register_system(sway);
+
+        let spawn = |w: &mut World| {
+            w.spawn((
+                Sway::new(0.1, 1.0, SwayAxis::Tilt),
+                Transform::default(),
+                GlobalTransform::default(),
+            ))
+            .id()
+        };
+        let a = spawn(&mut w);
+        let b = spawn(&mut w);
+
+        w.run_system(sway).unwrap();
+        let phase = |w: &World, e| w.get::<Sway>(e).unwrap().phase().unwrap();
+        assert_ne!(phase(&w, a), phase(&w, b));
+
+        // a quarter of a second is a quarter of the cycle
+        let before = phase(&w, a);
+        // as if the transforms were propagated
+        *w.get_mut::<GlobalTransform>(a).unwrap() = default();
+        w.resource_mut::<Time>()
+            .advance_by(Duration::from_millis(250));
+        w.run_system(sway).unwrap();
+        let expected = (before + TAU / 4.0).rem_euclid(TAU);
+        assert!((phase(&w, a) - expected).abs() < 1e-4);
+
+        // the gameplay transform is untouched, only the rendered one tilts
+        assert_eq!(Transform::default(), *w.get::<Transform>(a).unwrap());
+        let (_, rotation, _) = w
+            .get::<GlobalTransform>(a)
+            .unwrap()
+            .to_scale_rotation_translation();
+        let (_, _, angle) = rotation.to_euler(EulerRot::XYZ);
+        assert!((angle - 0.1 * phase(&w, a).sin()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn it_keeps_given_phase() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+        let sway = w.register_system(sway);
+
+        let e = w
+            .spawn((
+                Sway::new(0.1, 1.0, SwayAxis::Vertical).with_phase(1.0),
+                Transform::default(),
+                GlobalTransform::default(),
+            ))
+            .id();
+        w.run_system(sway).unwrap();
+
+        assert_eq!(Some(1.0), w.get::<Sway>(e).unwrap().phase());
+        let scale = w.get::<GlobalTransform>(e).unwrap().compute_transform();
+        assert!((scale.scale.y - (1.0 + 0.1 * 1.0_f32.sin())).abs() < 1e-4);
+    }
+}
@@ -2,17 +2,22 @@
 
 use bevy::prelude::*;
 use common_ext::ColorExt;
+use rand::{Rng, RngCore};
 
 use crate::{
     AtlasAnimation, AtlasAnimationEnd, AtlasAnimationStep, AtlasAnimationTimer,
     BeginAtlasAnimation, BeginAtlasAnimationCond, BeginInterpolationEvent,
-    ColorInterpolation, Flicker, OnInterpolationFinished,
-    TranslationInterpolation, UiStyleHeightInterpolation,
+    ColorInterpolation, DesyncAnimation, DesyncAnimationRng, Flicker,
+    OnInterpolationFinished, TintFlicker, TranslationInterpolation,
+    UiStyleHeightInterpolation, UiStylePositionInterpolation,
 };
 
 /// Advances the animation by one frame.
 /// This requires that the [`AtlasAnimationTimer`] component is present along
 /// with [`TextureAtlas`] and [`AtlasAnimation`].
+///
+/// Each frame is shown for its [`AtlasAnimation::frame_durations`] if set,
+/// otherwise for the uniform duration of the timer.
 pub fn advance_atlas_animation(
     mut cmd: Commands,
     time: Res<Time>,
@@ -27,6 +32,13 @@ pub fn advance_atlas_animation(
 ) {
     for (entity, animation, mut timer, mut atlas, mut visibility) in &mut query
     {
+        let frame_time = animation
+            .frame_duration(atlas.index)
+            .unwrap_or(timer.uniform_frame_time);
+        if timer.inner.duration() != frame_time {
+            timer.inner.set_duration(frame_time);
+        }
+
         timer.inner.tick(time.delta());
         if !timer.inner.just_finished() {
             continue;
@@ -132,6 +144,41 @@ pub fn begin_atlas_animation_at_random(
     }
 }
 
+/// Moves newly animated entities with [`DesyncAnimation`] to a random frame
+/// and a random time into it.
+#[allow(clippy::type_complexity)]
+pub fn desync_atlas_animations(
+    mut seeded: Option<ResMut<DesyncAnimationRng>>,
+
+    mut query: Query<
+        (&AtlasAnimation, &mut AtlasAnimationTimer, &mut TextureAtlas),
+        (
+            With<DesyncAnimation>,
+            Or<(Added<DesyncAnimation>, Added<AtlasAnimationTimer>)>,
+        ),
+    >,
+) {
+    let mut thread_rng = rand::thread_rng();
+    let rng: &mut dyn RngCore = match seeded.as_deref_mut() {
+        Some(DesyncAnimationRng(rng)) => rng,
+        None => &mut thread_rng,
+    };
+
+    for (animation, mut timer, mut atlas) in &mut query {
+        if animation.last < animation.first {
+            continue;
+        }
+
+        atlas.index = rng.gen_range(animation.first..=animation.last);
+
+        let frame_time = animation
+            .frame_duration(atlas.index)
+            .unwrap_or(timer.uniform_frame_time);
+        timer.inner.set_duration(frame_time);
+        timer.inner.set_elapsed(frame_time.mul_f32(rng.gen()));
+    }
+}
+
 /// Flickers the entity with the given chance per second.
 /// The entity will be visible for the given duration if the chance hits.
 /// For the rest of the time, the entity will be hidden.
@@ -154,6 +201,17 @@ pub fn flicker(
     }
 }
 
+/// Sets the sprite color of [`TintFlicker`] entities every frame.
+pub fn tint_flicker(
+    time: Res<Time>,
+
+    mut query: Query<(&mut TintFlicker, &mut Sprite)>,
+) {
+    for (mut flicker, mut sprite) in &mut query {
+        sprite.color = flicker.advance(time.delta());
+    }
+}
+
 /// Receives events to start interpolations.
 ///
 /// This is always run last, so that no `Update` schedule system must explicitly
@@ -175,6 +233,7 @@ pub(crate) fn recv_begin_interpolation_events(
 /// Runs interpolation logic on the entities that have the relevant components.
 /// Must run before `Last` schedule, or at least before the
 /// `recv_begin_interpolation_events`.
+#[allow(clippy::type_complexity)]
 pub fn interpolate(
     mut cmd: Commands,
     time: Res<Time>,
@@ -188,6 +247,10 @@ pub fn interpolate(
         (Entity, &mut Text, &mut ColorInterpolation),
         Without<Sprite>,
     >,
+    mut backgrounds: Query<
+        (Entity, &mut BackgroundColor, &mut ColorInterpolation),
+        (Without<Sprite>, Without<Text>),
+    >,
 
     // translation interpolation
     mut translations: Query<(
@@ -198,6 +261,13 @@ pub fn interpolate(
 
     // Ui style height interpolation
     mut styles: Query<(Entity, &mut Style, &mut UiStyleHeightInterpolation)>,
+
+    // Ui style position interpolation, an entity cannot interpolate its height
+    // and position at once
+    mut style_positions: Query<
+        (Entity, &mut Style, &mut UiStylePositionInterpolation),
+        Without<UiStyleHeightInterpolation>,
+    >,
 ) {
     let dt = time.delta();
 
@@ -249,6 +319,9 @@ pub fn interpolate(
             );
         }
     }
+    for (entity, mut bg, mut interpolation) in backgrounds.iter_mut() {
+        color_interpolation(entity, &mut bg.0, &mut interpolation);
+    }
 
     // translation interpolation
 
@@ -348,4 +421,286 @@ pub fn interpolate(
             };
         }
     }
+
+    // Ui style position interpolation
+
+    for (entity, mut style, mut interpolation) in style_positions.iter_mut() {
+        interpolation.started_at.tick(dt);
+
+        let elapsed_fraction = interpolation.started_at.elapsed_secs()
+            / interpolation.over.as_secs_f32();
+
+        let position = if elapsed_fraction >= 1.0 {
+            cmd.entity(entity).remove::<UiStylePositionInterpolation>();
+
+            match &interpolation.when_finished {
+                Some(OnInterpolationFinished::Custom(fun)) => {
+                    fun(&mut cmd);
+                }
+                Some(OnInterpolationFinished::DespawnRecursiveItself) => {
+                    cmd.entity(entity).despawn_recursive();
+                }
+                None => {}
+            }
+
+            interpolation.to
+        } else {
+            let lerp_factor = interpolation
+                .animation_curve
+                .as_ref()
+                .map(|curve| curve.ease(elapsed_fraction))
+                .unwrap_or(elapsed_fraction);
+
+            let px = |val| match val {
+                Val::Px(px) => px,
+                _ => 0.0,
+            };
+            let current = Vec2::new(px(style.left), px(style.top));
+            let from = interpolation.from.get_or_insert(current);
+            from.lerp(interpolation.to, lerp_factor)
+        };
+
+        style.left = Val::Px(position.x);
+        style.top = Val::Px(position.y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::math::vec2;
+
+    use super::*;
+    use crate::{InterpolationComplete, InterpolationId, EASE_IN, EASE_OUT};
+
+    #[test]
+    fn it_desyncs_identical_animations() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut w = World::default();
+        w.insert_resource(DesyncAnimationRng(StdRng::seed_from_u64(42)));
+
+        let lamps: Vec<_> = (0..8)
+            .map(|_| {
+                w.spawn((
+                    DesyncAnimation,
+                    AtlasAnimation {
+                        last: 7,
+                        ..default()
+                    },
+                    AtlasAnimationTimer::new_fps(5.0),
+                    TextureAtlas::default(),
+                ))
+                .id()
+            })
+            .collect();
+
+        let desync = w.register_system(desync_atlas_animations);
+        w.run_system(desync).unwrap();
+
+        let indices: Vec<_> = lamps
+            .iter()
+            .map(|lamp| w.get::<TextureAtlas>(*lamp).unwrap().index)
+            .collect();
+        assert!(indices.iter().all(|index| *index <= 7));
+        assert!(
+            indices.iter().any(|index| *index != indices[0]),
+            "All lamps start at frame {}",
+            indices[0]
+        );
+
+        // already desynced entities are left alone
+        for lamp in &lamps {
+            w.get_mut::<TextureAtlas>(*lamp).unwrap().index = 0;
+        }
+        w.run_system(desync).unwrap();
+        for lamp in &lamps {
+            assert_eq!(0, w.get::<TextureAtlas>(*lamp).unwrap().index);
+        }
+    }
+
+    #[test]
+    fn it_never_turns_off_tint_flicker_with_full_duty() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+
+        let color_on = Color::WHITE;
+        let color_off = Color::BLACK;
+        let lamp = w
+            .spawn((
+                TintFlicker::new(
+                    color_on,
+                    color_off,
+                    Duration::from_millis(300),
+                )
+                .with_duty(1.0)
+                .with_jitter(0.5),
+                SpriteBundle::default(),
+            ))
+            .id();
+        let half = w
+            .spawn((
+                TintFlicker::new(
+                    color_on,
+                    color_off,
+                    Duration::from_millis(300),
+                ),
+                SpriteBundle::default(),
+            ))
+            .id();
+
+        let flicker = w.register_system(tint_flicker);
+        let color = |w: &World, entity| w.get::<Sprite>(entity).unwrap().color;
+
+        let mut half_was_off = false;
+        for _ in 0..100 {
+            w.resource_mut::<Time>()
+                .advance_by(Duration::from_millis(16));
+            w.run_system(flicker).unwrap();
+
+            assert_eq!(color_on, color(&w, lamp));
+            half_was_off |= color(&w, half) == color_off;
+        }
+        assert!(half_was_off);
+    }
+
+    #[test]
+    fn it_holds_frames_for_their_own_durations() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+
+        let entity = w
+            .spawn((
+                AtlasAnimation {
+                    last: 2,
+                    on_last_frame: AtlasAnimationEnd::RemoveTimer,
+                    frame_durations: vec![
+                        Duration::from_millis(100),
+                        Duration::from_millis(100),
+                        Duration::from_millis(500),
+                    ],
+                    ..default()
+                },
+                // the uniform frame time is overridden by the durations
+                AtlasAnimationTimer::new(
+                    Duration::from_secs(1),
+                    TimerMode::Repeating,
+                ),
+                TextureAtlas::default(),
+                Visibility::default(),
+            ))
+            .id();
+
+        let advance = w.register_system(advance_atlas_animation);
+        let index_after = |w: &mut World, ms: u64| {
+            w.resource_mut::<Time>()
+                .advance_by(Duration::from_millis(ms));
+            w.run_system(advance).unwrap();
+            w.get::<TextureAtlas>(entity).unwrap().index
+        };
+
+        assert_eq!(0, index_after(&mut w, 50));
+        assert_eq!(1, index_after(&mut w, 50));
+        assert_eq!(1, index_after(&mut w, 90));
+        assert_eq!(2, index_after(&mut w, 10));
+        // the last frame is held longer
+        assert_eq!(2, index_after(&mut w, 450));
+        assert!(w.get::<AtlasAnimationTimer>(entity).is_some());
+        index_after(&mut w, 50);
+        assert!(w.get::<AtlasAnimationTimer>(entity).is_none());
+    }
+
+    #[test]
+    fn it_follows_animation_curve_of_each_interpolation() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+        w.init_resource::<Events<BeginInterpolationEvent>>();
+
+        let ease_in = w.spawn(TransformBundle::default()).id();
+        let ease_out = w.spawn(TransformBundle::default()).id();
+        let to = vec2(100.0, 0.0);
+        w.send_event(
+            BeginInterpolationEvent::of_translation(ease_in, None, to)
+                .with_animation_curve(*EASE_IN),
+        );
+        w.send_event(
+            BeginInterpolationEvent::of_translation(ease_out, None, to)
+                .with_animation_curve(*EASE_OUT),
+        );
+
+        let recv = w.register_system(recv_begin_interpolation_events);
+        let interpolate = w.register_system(interpolate);
+        w.run_system(recv).unwrap();
+
+        let advance_by = |w: &mut World, by: Duration| {
+            w.resource_mut::<Time>().advance_by(by);
+            w.run_system(interpolate).unwrap();
+        };
+        let x = |w: &World, entity| {
+            w.get::<Transform>(entity).unwrap().translation.x
+        };
+
+        advance_by(&mut w, Duration::from_millis(500));
+        let (ease_in_x, ease_out_x) = (x(&w, ease_in), x(&w, ease_out));
+        assert!(ease_in_x < 50.0, "Ease in at {ease_in_x}");
+        assert!(ease_out_x > 50.0, "Ease out at {ease_out_x}");
+
+        advance_by(&mut w, Duration::from_millis(500));
+        assert_eq!(to.x, x(&w, ease_in));
+        assert_eq!(to.x, x(&w, ease_out));
+        assert!(w.get::<TranslationInterpolation>(ease_in).is_none());
+        assert!(w.get::<TranslationInterpolation>(ease_out).is_none());
+    }
+
+    #[test]
+    fn it_emits_interpolation_complete_once() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+        w.init_resource::<Events<BeginInterpolationEvent>>();
+        w.init_resource::<Events<InterpolationComplete>>();
+        let recv = w.register_system(recv_begin_interpolation_events);
+        let interpolate = w.register_system(interpolate);
+        let completed = |w: &mut World| {
+            w.resource_mut::<Events<InterpolationComplete>>()
+                .drain()
+                .map(|InterpolationComplete { id }| id)
+                .collect::<Vec<_>>()
+        };
+
+        let finishes = w.spawn(TransformBundle::default()).id();
+        let despawned = w.spawn(TransformBundle::default()).id();
+        for (entity, id) in [(finishes, 1), (despawned, 2)] {
+            w.send_event(
+                BeginInterpolationEvent::of_translation(
+                    entity,
+                    None,
+                    vec2(10.0, 0.0),
+                )
+                .with_on_complete(InterpolationId(id)),
+            );
+        }
+        w.run_system(recv).unwrap();
+
+        for _ in 0..3 {
+            w.resource_mut::<Time>()
+                .advance_by(Duration::from_millis(400));
+            w.run_system(interpolate).unwrap();
+            w.flush();
+
+            if w.get_entity(despawned).is_some() {
+                assert!(completed(&mut w).is_empty());
+                w.despawn(despawned);
+                assert_eq!(vec![InterpolationId(2)], completed(&mut w));
+            }
+        }
+
+        assert_eq!(vec![InterpolationId(1)], completed(&mut w));
+        assert!(w.get::<TranslationInterpolation>(finishes).is_none());
+
+        // nothing is left to complete
+        w.resource_mut::<Time>().advance_by(Duration::from_secs(1));
+        w.run_system(interpolate).unwrap();
+        assert!(completed(&mut w).is_empty());
+    }
 }
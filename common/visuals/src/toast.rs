@@ -0,0 +1,306 @@
+//! Small notifications that slide in from the top right corner, stay for a
+//! while and slide back out, e.g. "Game saved" or "Item acquired".
+//! Use [`show_toast`].
+//!
+//! Like the [`crate::fade`] quad, toasts are top-level UI nodes without a
+//! target camera that are not tied to any scene.
+//! A toast shown right before a scene transition is still on screen in the
+//! next scene.
+//! The HUD daybar and notifications live in the top left corner so the two
+//! don't overlap.
+//!
+//! Toasts on screen at the same time are stacked below each other.
+//! If there are already [`MAX_DISPLAYED_TOASTS`] on screen, the rest wait in
+//! the [`ToastQueue`] for a slot to free up.
+
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::{prelude::*, time::Stopwatch};
+
+use crate::{EASE_IN, EASE_OUT_BACK, PRIMARY_COLOR};
+
+/// How many toasts can be on screen at once.
+pub const MAX_DISPLAYED_TOASTS: usize = 4;
+/// How long it takes for a toast to slide in.
+pub const SLIDE_IN_DURATION: Duration = Duration::from_millis(350);
+/// How long it takes for a toast to slide out.
+pub const SLIDE_OUT_DURATION: Duration = Duration::from_millis(250);
+
+const FONT: &str = common_assets::fonts::PIXEL1;
+const FONT_SIZE: f32 = 18.0;
+const MARGIN_PX: f32 = 10.0;
+const TOAST_WIDTH_PX: f32 = 260.0;
+const TOAST_HEIGHT_PX: f32 = 36.0;
+/// Vertical space between two stacked toasts.
+const TOAST_GAP_PX: f32 = 6.0;
+
+/// Decides the background color of the toast.
+#[derive(Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Default)]
+pub enum ToastSeverity {
+    /// Neutral information, e.g. "Game saved".
+    #[default]
+    Info,
+    /// Something good happened, e.g. "Item acquired".
+    Success,
+    /// Something the player should pay attention to.
+    Warning,
+}
+
+/// Toasts waiting for a free slot on screen.
+/// Push to it with [`show_toast`].
+#[derive(Resource, Reflect, Default, Debug)]
+#[reflect(Resource)]
+pub struct ToastQueue {
+    pending: VecDeque<Toast>,
+}
+
+/// A toast that's not displayed yet.
+#[derive(Reflect, Clone, Debug)]
+pub struct Toast {
+    /// LOCALIZATION
+    pub text: String,
+    /// How long the toast stays on screen once it's slid in.
+    pub duration: Duration,
+    /// Decides the color.
+    pub severity: ToastSeverity,
+}
+
+/// A toast on screen.
+#[derive(Component, Reflect, Debug)]
+pub(crate) struct ToastNode {
+    /// Index from the top of the stack.
+    slot: usize,
+    /// See [`Toast::duration`].
+    hold: Duration,
+    phase: ToastPhase,
+    /// Time spent in the current phase.
+    elapsed: Stopwatch,
+}
+
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+enum ToastPhase {
+    SlidingIn,
+    Holding,
+    SlidingOut,
+}
+
+/// Shows a toast for the given duration.
+/// Slide in and out durations are not included.
+pub fn show_toast(
+    cmd: &mut Commands,
+    text: impl Into<String>,
+    duration: Duration,
+    severity: ToastSeverity,
+) {
+    let toast = Toast {
+        text: text.into(),
+        duration,
+        severity,
+    };
+
+    cmd.add(move |w: &mut World| {
+        w.get_resource_or_insert_with(ToastQueue::default)
+            .pending
+            .push_back(toast);
+    });
+}
+
+/// Slides the toasts on screen and despawns those that are done.
+/// Then spawns queued toasts into the free slots.
+pub(crate) fn update(
+    mut cmd: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut queue: ResMut<ToastQueue>,
+
+    mut toasts: Query<(Entity, &mut ToastNode, &mut Style)>,
+) {
+    let mut taken_slots = [false; MAX_DISPLAYED_TOASTS];
+
+    for (entity, mut toast, mut style) in toasts.iter_mut() {
+        toast.elapsed.tick(time.delta());
+        let elapsed = toast.elapsed.elapsed();
+
+        let slid_in = match toast.phase {
+            ToastPhase::SlidingIn => {
+                let fraction = phase_fraction(elapsed, SLIDE_IN_DURATION);
+                if fraction >= 1.0 {
+                    toast.phase = ToastPhase::Holding;
+                    toast.elapsed.reset();
+                }
+
+                EASE_OUT_BACK.ease(fraction)
+            }
+            ToastPhase::Holding => {
+                if elapsed >= toast.hold {
+                    toast.phase = ToastPhase::SlidingOut;
+                    toast.elapsed.reset();
+                }
+
+                1.0
+            }
+            ToastPhase::SlidingOut => {
+                let fraction = phase_fraction(elapsed, SLIDE_OUT_DURATION);
+                if fraction >= 1.0 {
+                    // the slot is free for a queued toast
+                    cmd.entity(entity).despawn_recursive();
+                    continue;
+                }
+
+                1.0 - EASE_IN.ease(fraction)
+            }
+        };
+
+        style.right = Val::Px(right_px(slid_in));
+        taken_slots[toast.slot] = true;
+    }
+
+    for (slot, _) in taken_slots.iter().enumerate().filter(|(_, t)| !**t) {
+        let Some(toast) = queue.pending.pop_front() else {
+            break;
+        };
+
+        toast.spawn(&mut cmd, &asset_server, slot);
+    }
+}
+
+impl Toast {
+    fn spawn(
+        self,
+        cmd: &mut Commands,
+        asset_server: &AssetServer,
+        slot: usize,
+    ) {
+        let Self {
+            text,
+            duration,
+            severity,
+        } = self;
+
+        cmd.spawn((
+            Name::new("Toast"),
+            ToastNode {
+                slot,
+                hold: duration,
+                phase: ToastPhase::SlidingIn,
+                elapsed: default(),
+            },
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(
+                        MARGIN_PX
+                            + slot as f32 * (TOAST_HEIGHT_PX + TOAST_GAP_PX),
+                    ),
+                    right: Val::Px(right_px(0.0)),
+                    width: Val::Px(TOAST_WIDTH_PX),
+                    height: Val::Px(TOAST_HEIGHT_PX),
+                    align_items: AlignItems::Center,
+                    padding: UiRect::horizontal(Val::Px(MARGIN_PX)),
+                    ..default()
+                },
+                background_color: severity.color().into(),
+                // below the fade quad
+                z_index: ZIndex::Global(i32::MAX - 1),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Name::new("Toast text"),
+                TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font: asset_server.load(FONT),
+                        font_size: FONT_SIZE,
+                        color: Color::WHITE,
+                    },
+                ),
+            ));
+        });
+    }
+}
+
+impl ToastSeverity {
+    fn color(self) -> Color {
+        match self {
+            Self::Info => PRIMARY_COLOR.with_alpha(0.9),
+            Self::Success => Color::srgba(0.1, 0.35, 0.15, 0.9),
+            Self::Warning => Color::srgba(0.5, 0.3, 0.05, 0.9),
+        }
+    }
+}
+
+/// Between 0 and 1.
+fn phase_fraction(elapsed: Duration, over: Duration) -> f32 {
+    (elapsed.as_secs_f32() / over.as_secs_f32()).min(1.0)
+}
+
+/// When not slid in at all, the toast is just outside of the screen.
+fn right_px(slid_in: f32) -> f32 {
+    MARGIN_PX - (1.0 - slid_in) * (TOAST_WIDTH_PX + MARGIN_PX)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn it_stacks_queued_toasts_and_despawns_them() {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<Font>()
+            .init_resource::<ToastQueue>()
+            .insert_resource(Time::<()>::default());
+        let w = app.world_mut();
+        let update = w.register_system(update);
+        let advance_by = |w: &mut World, by: Duration| {
+            w.resource_mut::<Time>().advance_by(by);
+            w.run_system(update).unwrap();
+        };
+
+        w.run_system_once(|mut cmd: Commands| {
+            show_toast(
+                &mut cmd,
+                "Game saved",
+                Duration::from_secs(1),
+                ToastSeverity::Info,
+            );
+            show_toast(
+                &mut cmd,
+                "Item acquired",
+                Duration::from_secs(2),
+                ToastSeverity::Success,
+            );
+        });
+        advance_by(w, Duration::ZERO);
+
+        let mut tops: Vec<_> = w
+            .query_filtered::<&Style, With<ToastNode>>()
+            .iter(w)
+            .map(|style| style.top)
+            .collect();
+        tops.sort_by(|a, b| {
+            let (Val::Px(a), Val::Px(b)) = (a, b) else {
+                panic!("Toasts are positioned in pixels");
+            };
+            a.total_cmp(b)
+        });
+        assert_eq!(
+            vec![
+                Val::Px(MARGIN_PX),
+                Val::Px(MARGIN_PX + TOAST_HEIGHT_PX + TOAST_GAP_PX)
+            ],
+            tops
+        );
+
+        for _ in 0..100 {
+            advance_by(w, Duration::from_millis(100));
+        }
+        assert_eq!(0, w.query::<&ToastNode>().iter(w).count());
+        assert!(w.resource::<ToastQueue>().pending.is_empty());
+    }
+}
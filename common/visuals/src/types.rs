@@ -1,8 +1,16 @@
 use std::{sync::Arc, time::Duration};
 
 use bevy::{
-    ecs::system::EntityCommands, prelude::*, time::Stopwatch, utils::Instant,
+    ecs::{
+        component::{ComponentHooks, StorageType},
+        system::EntityCommands,
+    },
+    prelude::*,
+    time::Stopwatch,
+    utils::Instant,
 };
+use common_ext::ColorExt;
+use rand::{rngs::StdRng, Rng};
 
 use crate::EASE_IN_OUT;
 
@@ -26,6 +34,11 @@ pub struct AtlasAnimation {
     /// The current step is stored in [`AtlasAnimationTimer`].
     /// The current index is stored in [`TextureAtlas`].
     pub extra_steps: Vec<AtlasAnimationStep>,
+    /// How long is each frame shown, starting with [`AtlasAnimation::first`].
+    /// Frames without a duration here are shown for the uniform duration of
+    /// the [`AtlasAnimationTimer`].
+    /// Leave empty for all frames to be shown equally long.
+    pub frame_durations: Vec<Duration>,
 }
 
 /// How should the animation be played?
@@ -77,6 +90,8 @@ pub enum AtlasAnimationEnd {
 #[derive(Component, Reflect)]
 pub struct AtlasAnimationTimer {
     pub(crate) inner: Timer,
+    /// Used for frames that have no [`AtlasAnimation::frame_durations`].
+    pub(crate) uniform_frame_time: Duration,
     /// 0 => means current is [`AtlasAnimation::play`].
     /// 1 => `extra_steps[0]`
     /// and so on...
@@ -118,8 +133,24 @@ pub enum BeginAtlasAnimationCond {
     },
 }
 
+/// Entities with this marker and an [`AtlasAnimationTimer`] start their
+/// animation at a random frame and a random time into that frame.
+/// That way a row of identical sprites doesn't loop in sync.
+///
+/// The phase is picked whenever the marker or the timer is inserted.
+/// See [`DesyncAnimationRng`] for deterministic phases.
+#[derive(Component, Default, Reflect)]
+pub struct DesyncAnimation;
+
+/// If present, [`DesyncAnimation`] phases are picked with this generator
+/// instead of the thread local one.
+#[derive(Resource)]
+pub struct DesyncAnimationRng(pub StdRng);
+
 /// Shows entity at random for a given duration.
 /// Then hides it again.
+///
+/// See [`TintFlicker`] to flicker the color of a sprite instead.
 #[derive(Component, Reflect)]
 pub struct Flicker {
     /// When did the flicker ran last?
@@ -131,6 +162,34 @@ pub struct Flicker {
     pub shown_for: Duration,
 }
 
+/// Cycles the [`Sprite`] color between two tints, e.g. a faulty lamp.
+///
+/// Each cycle starts with the on part that lasts [`TintFlicker::duty`] of the
+/// cycle, followed by the off part.
+#[derive(Component, Reflect, Debug, Clone)]
+pub struct TintFlicker {
+    /// The color while on.
+    pub color_on: Color,
+    /// The color while off.
+    pub color_off: Color,
+    /// How long one cycle takes on average.
+    pub period: Duration,
+    /// Which fraction of each cycle is on.
+    /// Between 0 and 1.
+    pub duty: f32,
+    /// Each cycle is randomly longer or shorter than the period by up to
+    /// this fraction of it.
+    /// Between 0 and 1.
+    pub jitter: f32,
+    /// If true, the off part dims to [`TintFlicker::color_off`] and back
+    /// instead of switching to it.
+    pub smooth: bool,
+    /// How far into the current cycle we are.
+    elapsed: Duration,
+    /// The length of the current cycle with jitter applied.
+    current_period: Duration,
+}
+
 /// Starts an interpolation of some component.
 ///
 /// We use events instead of inserting the component directly because there
@@ -158,6 +217,28 @@ pub struct BeginInterpolationEvent {
     pub animation_curve: Option<CubicSegment<Vec2>>,
     /// Any extra logic when interpolation is done?
     pub(crate) when_finished: Option<OnInterpolationFinished>,
+    /// If set, [`InterpolationComplete`] is emitted with this id once the
+    /// interpolation ends.
+    pub(crate) on_complete: Option<InterpolationId>,
+}
+
+/// Identifies an interpolation in [`InterpolationComplete`].
+/// Chosen by whoever starts the interpolation, see
+/// [`BeginInterpolationEvent::with_on_complete`].
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InterpolationId(pub u32);
+
+/// Emitted once for each interpolation that was started with an
+/// [`InterpolationId`], when the interpolation component is removed.
+///
+/// That's typically when the interpolation is done.
+/// It's also emitted when the interpolation is cut short, i.e. when the
+/// entity is despawned or another interpolation of the same kind replaces it.
+/// That way whoever waits for the event doesn't wait forever.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InterpolationComplete {
+    /// As given to [`BeginInterpolationEvent::with_on_complete`].
+    pub id: InterpolationId,
 }
 
 /// What should happen when the interpolation is done?
@@ -222,40 +303,58 @@ impl BeginInterpolationEvent {
             over,
             animation_curve,
             when_finished,
+            on_complete,
             entity: _,
         } = self;
 
+        // Removing the replaced interpolation first emits its completion, if
+        // any.
+        // Inserting over it would drop it silently.
         match of {
-            InterpolationOf::Color { from, to } => {
-                entity_cmd.insert(ColorInterpolation {
+            InterpolationOf::Color { from, to } => entity_cmd
+                .remove::<ColorInterpolation>()
+                .insert(ColorInterpolation {
                     from,
                     to,
                     over,
                     animation_curve,
                     when_finished,
+                    on_complete,
                     started_at: default(),
-                })
-            }
-            InterpolationOf::Translation { from, to } => {
-                entity_cmd.insert(TranslationInterpolation {
+                }),
+            InterpolationOf::Translation { from, to } => entity_cmd
+                .remove::<TranslationInterpolation>()
+                .insert(TranslationInterpolation {
                     from,
                     to,
                     over,
                     animation_curve,
                     when_finished,
+                    on_complete,
                     started_at: default(),
-                })
-            }
-            InterpolationOf::UiStyleHeight { from, to } => {
-                entity_cmd.insert(UiStyleHeightInterpolation {
+                }),
+            InterpolationOf::UiStyleHeight { from, to } => entity_cmd
+                .remove::<UiStyleHeightInterpolation>()
+                .insert(UiStyleHeightInterpolation {
                     from,
                     to,
                     over,
                     animation_curve,
                     when_finished,
+                    on_complete,
                     started_at: default(),
-                })
-            }
+                }),
+            InterpolationOf::UiStylePosition { from, to } => entity_cmd
+                .remove::<UiStylePositionInterpolation>()
+                .insert(UiStylePositionInterpolation {
+                    from,
+                    to,
+                    over,
+                    animation_curve,
+                    when_finished,
+                    on_complete,
+                    started_at: default(),
+                }),
         };
     }
 
@@ -271,6 +370,7 @@ impl BeginInterpolationEvent {
 
     /// Interpolates the color of [`Sprite`] or [`Text`] (entity cannot have
     /// both).
+    /// UI nodes that are neither have their [`BackgroundColor`] interpolated.
     ///
     /// Defaults to 1 second and lerps from the latest color to the new color
     /// unless the initial color is provided.
@@ -281,6 +381,7 @@ impl BeginInterpolationEvent {
             of: InterpolationOf::Color { from, to },
             animation_curve: None,
             when_finished: None,
+            on_complete: None,
         }
     }
 
@@ -299,6 +400,7 @@ impl BeginInterpolationEvent {
             of: InterpolationOf::Translation { from, to },
             animation_curve: None,
             when_finished: None,
+            on_complete: None,
         }
     }
 
@@ -329,6 +431,30 @@ impl BeginInterpolationEvent {
             of: InterpolationOf::UiStyleHeight { from, to },
             animation_curve: None,
             when_finished: None,
+            on_complete: None,
+        }
+    }
+
+    /// Interpolates [`Style::left`] and [`Style::top`] of a UI element in
+    /// pixels.
+    /// UI layout overwrites the transform of UI nodes, so use this instead of
+    /// [`Self::of_translation`] to move them.
+    ///
+    /// Lerps from the current left and top unless the initial position is
+    /// provided.
+    /// Those that aren't in pixels count as zero.
+    pub fn of_ui_style_position(
+        entity: Entity,
+        from: Option<Vec2>,
+        to: Vec2,
+    ) -> Self {
+        Self {
+            entity,
+            over: Duration::from_secs(1),
+            of: InterpolationOf::UiStylePosition { from, to },
+            animation_curve: None,
+            when_finished: None,
+            on_complete: None,
         }
     }
 
@@ -371,6 +497,13 @@ impl BeginInterpolationEvent {
         )))
     }
 
+    /// Emit [`InterpolationComplete`] with the given id when the interpolation
+    /// ends.
+    pub fn with_on_complete(mut self, id: InterpolationId) -> Self {
+        self.on_complete = Some(id);
+        self
+    }
+
     /// Despawn the entity when interpolation is done.
     pub fn when_finished_despawn_recursive_itself(self) -> Self {
         self.when_finished(OnInterpolationFinished::DespawnRecursiveItself)
@@ -392,8 +525,9 @@ pub enum InterpolationOf {
     /// Interpolate the color of
     /// - [`Text`]
     /// - [`Sprite`]
+    /// - [`BackgroundColor`] of UI nodes that have neither of the above
     ///
-    /// Entity cannot have both.
+    /// Entity cannot have both [`Text`] and [`Sprite`].
     Color {
         /// The color to interpolate from.
         /// If not provided, the latest color is used.
@@ -419,10 +553,18 @@ pub enum InterpolationOf {
         /// The height to interpolate to.
         to: Val,
     },
+    /// Interpolate the left and top of a UI element in pixels.
+    UiStylePosition {
+        /// The initial left and top.
+        /// If not provided, the current ones are used.
+        from: Option<Vec2>,
+        /// The left and top to interpolate to.
+        to: Vec2,
+    },
 }
 
 /// Interpolates the color of a sprite.
-#[derive(Component, Reflect)]
+#[derive(Reflect)]
 pub struct ColorInterpolation {
     /// Can be none on the first run, then we default it to the color of the
     /// sprite.
@@ -434,10 +576,11 @@ pub struct ColorInterpolation {
     pub(crate) animation_curve: Option<CubicSegment<Vec2>>,
     #[reflect(ignore)]
     pub(crate) when_finished: Option<OnInterpolationFinished>,
+    pub(crate) on_complete: Option<InterpolationId>,
 }
 
 /// Interpolates translation of an entity.
-#[derive(Component, Reflect)]
+#[derive(Reflect)]
 pub struct TranslationInterpolation {
     /// Can be none on the first run, then we default it to the position of the
     /// entity.
@@ -449,10 +592,11 @@ pub struct TranslationInterpolation {
     pub(crate) animation_curve: Option<CubicSegment<Vec2>>,
     #[reflect(ignore)]
     pub(crate) when_finished: Option<OnInterpolationFinished>,
+    pub(crate) on_complete: Option<InterpolationId>,
 }
 
 /// Interpolates the height of a UI element.
-#[derive(Component, Reflect)]
+#[derive(Reflect)]
 pub struct UiStyleHeightInterpolation {
     pub(crate) from: Option<Val>,
     pub(crate) to: Val,
@@ -462,8 +606,53 @@ pub struct UiStyleHeightInterpolation {
     pub(crate) animation_curve: Option<CubicSegment<Vec2>>,
     #[reflect(ignore)]
     pub(crate) when_finished: Option<OnInterpolationFinished>,
+    pub(crate) on_complete: Option<InterpolationId>,
 }
 
+/// Interpolates the left and top of a UI element in pixels.
+#[derive(Reflect)]
+pub struct UiStylePositionInterpolation {
+    pub(crate) from: Option<Vec2>,
+    pub(crate) to: Vec2,
+    pub(crate) started_at: Stopwatch,
+    pub(crate) over: Duration,
+    #[reflect(ignore)]
+    pub(crate) animation_curve: Option<CubicSegment<Vec2>>,
+    #[reflect(ignore)]
+    pub(crate) when_finished: Option<OnInterpolationFinished>,
+    pub(crate) on_complete: Option<InterpolationId>,
+}
+
+/// The interpolation components emit [`InterpolationComplete`] when removed,
+/// be it because they're done, replaced or despawned along with their entity.
+macro_rules! impl_interpolation_component {
+    ($($interpolation:ty),+) => {
+        $(
+            impl Component for $interpolation {
+                const STORAGE_TYPE: StorageType = StorageType::Table;
+
+                fn register_component_hooks(hooks: &mut ComponentHooks) {
+                    hooks.on_remove(|mut w, entity, _| {
+                        let on_complete = w
+                            .get::<$interpolation>(entity)
+                            .and_then(|interpolation| interpolation.on_complete);
+                        if let Some(id) = on_complete {
+                            w.send_event(InterpolationComplete { id });
+                        }
+                    });
+                }
+            }
+        )+
+    };
+}
+
+impl_interpolation_component!(
+    ColorInterpolation,
+    TranslationInterpolation,
+    UiStyleHeightInterpolation,
+    UiStylePositionInterpolation
+);
+
 impl Flicker {
     /// Creates a new flicker.
     #[inline]
@@ -481,12 +670,86 @@ impl Flicker {
     }
 }
 
+impl TintFlicker {
+    /// Half of each cycle is on, no jitter and hard switching.
+    pub fn new(color_on: Color, color_off: Color, period: Duration) -> Self {
+        Self {
+            color_on,
+            color_off,
+            period,
+            duty: 0.5,
+            jitter: 0.0,
+            smooth: false,
+            elapsed: Duration::ZERO,
+            current_period: period,
+        }
+    }
+
+    /// See [`TintFlicker::duty`].
+    pub fn with_duty(mut self, duty: f32) -> Self {
+        debug_assert!((0.0..=1.0).contains(&duty), "Duty must be in [0, 1]");
+        self.duty = duty;
+        self
+    }
+
+    /// See [`TintFlicker::jitter`].
+    pub fn with_jitter(mut self, jitter: f32) -> Self {
+        debug_assert!(
+            (0.0..=1.0).contains(&jitter),
+            "Jitter must be in [0, 1]"
+        );
+        self.jitter = jitter;
+        self
+    }
+
+    /// See [`TintFlicker::smooth`].
+    pub fn smooth(mut self) -> Self {
+        self.smooth = true;
+        self
+    }
+
+    /// Moves the cycle forward and returns the current color.
+    pub(crate) fn advance(&mut self, dt: Duration) -> Color {
+        self.elapsed += dt;
+        while self.elapsed >= self.current_period {
+            if self.current_period.is_zero() {
+                self.elapsed = Duration::ZERO;
+                break;
+            }
+
+            self.elapsed -= self.current_period;
+            let jitter = self.jitter.clamp(0.0, 1.0)
+                * rand::thread_rng().gen_range(-1.0..=1.0);
+            self.current_period = self.period.mul_f32(1.0 + jitter);
+        }
+
+        let phase = if self.current_period.is_zero() {
+            0.0
+        } else {
+            self.elapsed.as_secs_f32() / self.current_period.as_secs_f32()
+        };
+
+        let duty = self.duty.clamp(0.0, 1.0);
+        if phase < duty {
+            self.color_on
+        } else if self.smooth {
+            // from on to off and back over the off part of the cycle
+            let t = (phase - duty) / (1.0 - duty);
+            let on = 1.0 - (t * std::f32::consts::PI).sin();
+            self.color_off.lerp(self.color_on, on)
+        } else {
+            self.color_off
+        }
+    }
+}
+
 impl AtlasAnimationTimer {
     /// Creates a new animation timer.
     #[inline]
     pub fn new(duration: Duration, mode: TimerMode) -> Self {
         Self {
             inner: Timer::new(duration, mode),
+            uniform_frame_time: duration,
             current_step: 0,
         }
     }
@@ -494,14 +757,21 @@ impl AtlasAnimationTimer {
     /// How many times a second should we go to the next frame.
     #[inline]
     pub fn new_fps(fps: f32) -> Self {
-        Self {
-            inner: Timer::from_seconds(1.0 / fps, TimerMode::Repeating),
-            current_step: 0,
-        }
+        Self::new(Duration::from_secs_f32(1.0 / fps), TimerMode::Repeating)
     }
 }
 
 impl AtlasAnimation {
+    /// How long should the given atlas index be shown for, if it has its own
+    /// duration.
+    #[inline]
+    pub fn frame_duration(&self, index: usize) -> Option<Duration> {
+        index
+            .checked_sub(self.first)
+            .and_then(|nth| self.frame_durations.get(nth))
+            .copied()
+    }
+
     pub(crate) fn next_step_index_and_frame(
         &self,
         atlas: &TextureAtlas,
@@ -0,0 +1,292 @@
+//! Darkens the edges of the screen, e.g. for tension in dark scenes.
+//! Insert [`Vignette`] to show the overlay and remove it to hide it.
+//!
+//! Unlike the [`crate::scene_tint`] overlay, the vignette is a UI node of
+//! the [`MainCamera`] with a negative global z-index.
+//! That way it's rendered above the world but below the HUD and the rest of
+//! the UI.
+//! Use [`VignetteTransition`] to smoothly change the vignette.
+
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    time::Stopwatch,
+};
+use common_ext::ColorExt;
+
+use crate::camera::MainCamera;
+
+/// Below any other UI node.
+const Z_INDEX: i32 = -1;
+/// The gradient is stretched over the whole screen, so it doesn't need to be
+/// large.
+const TEXTURE_SIZE: u32 = 64;
+/// How far from the center, as a fraction of the distance to the corner,
+/// the darkening starts.
+const INNER_RADIUS: f32 = 0.4;
+
+/// Dark edges of the screen.
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct Vignette {
+    /// How dark the edges are.
+    /// Between 0 (no overlay) and 1 (the corners are [`Vignette::color`]).
+    pub strength: f32,
+    /// The color the edges fade into.
+    pub color: Color,
+}
+
+/// Changes [`Vignette`] from its current value to the target value over some
+/// time.
+/// Removed once the transition is done.
+/// If the target strength is zero, the [`Vignette`] is removed as well.
+///
+/// If there's no [`Vignette`] when the transition starts, it's inserted with
+/// the target color and zero strength.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct VignetteTransition {
+    from: Option<Vignette>,
+    to: Vignette,
+    over: Duration,
+    elapsed: Stopwatch,
+}
+
+#[derive(Component)]
+pub(crate) struct VignetteQuad;
+
+impl Vignette {
+    /// A black vignette of given strength.
+    pub fn new(strength: f32) -> Self {
+        debug_assert!(
+            (0.0..=1.0).contains(&strength),
+            "Strength must be in [0, 1]"
+        );
+
+        Self {
+            strength,
+            color: Color::BLACK,
+        }
+    }
+
+    /// See [`Vignette::color`].
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Linear interpolation between two vignettes.
+    /// The `t` is clamped between 0 and 1.
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+
+        Self {
+            color: self.color.lerp(other.color, t),
+            strength: self.strength + (other.strength - self.strength) * t,
+        }
+    }
+
+    /// The tint of the gradient texture.
+    fn overlay_color(self) -> Color {
+        self.color.with_alpha(self.color.alpha() * self.strength)
+    }
+}
+
+impl VignetteTransition {
+    /// Transition to the given vignette over the given duration.
+    pub fn new(to: Vignette, over: Duration) -> Self {
+        debug_assert!(over.as_millis() > 0, "Duration mustn't be zero");
+
+        Self {
+            from: None,
+            to,
+            over,
+            elapsed: default(),
+        }
+    }
+}
+
+/// Lerps [`Vignette`] according to [`VignetteTransition`].
+pub(crate) fn transition(
+    mut cmd: Commands,
+    time: Res<Time>,
+    mut transition: ResMut<VignetteTransition>,
+    vignette: Option<ResMut<Vignette>>,
+) {
+    let current = vignette.as_deref().copied().unwrap_or(Vignette {
+        color: transition.to.color,
+        strength: 0.0,
+    });
+    let from = *transition.from.get_or_insert(current);
+
+    transition.elapsed.tick(time.delta());
+    let elapsed_fraction =
+        transition.elapsed.elapsed_secs() / transition.over.as_secs_f32();
+
+    let new_vignette = from.lerp(transition.to, elapsed_fraction);
+    match vignette {
+        Some(mut vignette) => *vignette = new_vignette,
+        None => cmd.insert_resource(new_vignette),
+    }
+
+    if elapsed_fraction >= 1.0 {
+        cmd.remove_resource::<VignetteTransition>();
+
+        if transition.to.strength <= 0.0 {
+            cmd.remove_resource::<Vignette>();
+        }
+    }
+}
+
+/// Spawns the overlay when [`Vignette`] has a positive strength and keeps its
+/// color in sync.
+/// Despawns it once the strength reaches zero.
+pub(crate) fn sync_overlay(
+    mut cmd: Commands,
+    vignette: Res<Vignette>,
+    mut images: ResMut<Assets<Image>>,
+
+    camera: Query<Entity, With<MainCamera>>,
+    mut quad: Query<(Entity, &mut UiImage), With<VignetteQuad>>,
+) {
+    if vignette.strength <= 0.0 {
+        for (entity, _) in quad.iter() {
+            cmd.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    if let Ok((_, mut image)) = quad.get_single_mut() {
+        image.color = vignette.overlay_color();
+        return;
+    }
+
+    let mut quad = cmd.spawn((
+        Name::new("Vignette quad"),
+        VignetteQuad,
+        ImageBundle {
+            image: UiImage {
+                color: vignette.overlay_color(),
+                texture: images.add(gradient_texture()),
+                ..default()
+            },
+            z_index: ZIndex::Global(Z_INDEX),
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+    if let Ok(camera) = camera.get_single() {
+        quad.insert(TargetCamera(camera));
+    }
+}
+
+/// Despawns the overlay when [`Vignette`] is removed.
+pub(crate) fn despawn_overlay(
+    mut cmd: Commands,
+
+    quad: Query<Entity, With<VignetteQuad>>,
+) {
+    for entity in quad.iter() {
+        cmd.entity(entity).despawn_recursive();
+    }
+}
+
+/// White texture that is transparent in the middle and gets opaque towards
+/// the corners.
+fn gradient_texture() -> Image {
+    let size = TEXTURE_SIZE as f32;
+    let data = (0..TEXTURE_SIZE)
+        .flat_map(|y| (0..TEXTURE_SIZE).map(move |x| (x, y)))
+        .flat_map(|(x, y)| {
+            // from the center (0, 0) to the corners (±1, ±1)
+            let pos = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) / size * 2.0
+                - Vec2::ONE;
+            let distance = pos.length() / std::f32::consts::SQRT_2;
+            let t = ((distance - INNER_RADIUS) / (1.0 - INNER_RADIUS))
+                .clamp(0.0, 1.0);
+            let alpha = (t * t * 255.0).round() as u8;
+
+            [255, 255, 255, alpha]
+        })
+        .collect();
+
+    Image::new(
+        Extent3d {
+            width: TEXTURE_SIZE,
+            height: TEXTURE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_spawns_overlay_only_while_strength_is_positive() {
+        let mut w = World::default();
+        w.init_resource::<Assets<Image>>();
+        let sync = w.register_system(sync_overlay);
+        let despawn = w.register_system(despawn_overlay);
+        let quad_count = |w: &mut World| {
+            w.query_filtered::<(), With<VignetteQuad>>().iter(w).count()
+        };
+
+        w.insert_resource(Vignette::new(0.0));
+        w.run_system(sync).unwrap();
+        assert_eq!(0, quad_count(&mut w));
+
+        w.resource_mut::<Vignette>().strength = 0.5;
+        w.run_system(sync).unwrap();
+        assert_eq!(1, quad_count(&mut w));
+
+        // the overlay is updated rather than spawned again
+        w.resource_mut::<Vignette>().strength = 0.25;
+        w.run_system(sync).unwrap();
+        assert_eq!(1, quad_count(&mut w));
+        let color = w
+            .query_filtered::<&UiImage, With<VignetteQuad>>()
+            .single(&w)
+            .color;
+        assert_eq!(0.25, color.alpha());
+
+        w.resource_mut::<Vignette>().strength = 0.0;
+        w.run_system(sync).unwrap();
+        assert_eq!(0, quad_count(&mut w));
+
+        w.resource_mut::<Vignette>().strength = 1.0;
+        w.run_system(sync).unwrap();
+        assert_eq!(1, quad_count(&mut w));
+
+        w.remove_resource::<Vignette>();
+        w.run_system(despawn).unwrap();
+        assert_eq!(0, quad_count(&mut w));
+    }
+
+    #[test]
+    fn it_lerps_vignettes() {
+        let from = Vignette::new(0.0);
+        let to = Vignette::new(1.0).with_color(Color::WHITE);
+
+        assert_eq!(0.0, from.lerp(to, -1.0).strength);
+        assert_eq!(1.0, from.lerp(to, 2.0).strength);
+        let halfway = from.lerp(to, 0.5);
+        assert_eq!(0.5, halfway.strength);
+        assert_eq!(Color::BLACK.lerp(Color::WHITE, 0.5), halfway.color);
+    }
+}
@@ -0,0 +1,298 @@
+//! Rain and snow for ambiance.
+//! Insert [`Weather`] to start the precipitation and remove it to stop it.
+//!
+//! The particles are pooled sprites that fall across the screen.
+//! They are rendered by their own camera on the [`render_layer::WEATHER`]
+//! layer, so they are locked to the screen rather than the world.
+//! When a particle falls below the screen, it's hidden and later reused.
+//! There are never more than [`MAX_PARTICLES`] of them.
+//!
+//! The particles move with the virtual clock, hence they stop when the game
+//! is paused.
+//! Insert [`WeatherRng`] for deterministic particle positions.
+
+use bevy::{prelude::*, render::view::RenderLayers};
+use bevy_pixel_camera::{PixelViewport, PixelZoom};
+use rand::{rngs::StdRng, Rng, RngCore};
+
+use crate::camera::{
+    order, render_layer, PIXEL_VISIBLE_HEIGHT, PIXEL_VISIBLE_WIDTH, PIXEL_ZOOM,
+};
+
+/// The pool of particles never grows beyond this.
+pub const MAX_PARTICLES: usize = 256;
+/// How many particles per second are spawned at full intensity.
+const SPAWN_RATE: f32 = 120.0;
+/// Particles spawn and despawn this many pixels outside of the screen so that
+/// they don't pop in and out.
+const MARGIN: f32 = 16.0;
+
+/// Precipitation over the whole screen.
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct Weather {
+    /// What falls from the sky.
+    pub kind: WeatherKind,
+    /// Between 0 (nothing falls) and 1 (a downpour).
+    /// Scales how many particles spawn and how opaque they are.
+    pub intensity: f32,
+}
+
+/// See [`Weather::kind`].
+#[derive(Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WeatherKind {
+    /// Fast thin streaks slanted by the wind.
+    #[default]
+    Rain,
+    /// Slow flakes drifting to the sides.
+    Snow,
+}
+
+/// If present, the particles are positioned with this generator instead of
+/// the thread local one.
+#[derive(Resource)]
+pub struct WeatherRng(pub StdRng);
+
+#[derive(Component)]
+pub(crate) struct WeatherCamera;
+
+/// A pooled particle.
+/// Hidden particles are free to be reused.
+#[derive(Component)]
+pub(crate) struct WeatherParticle {
+    /// In pixels per second.
+    velocity: Vec2,
+}
+
+impl Weather {
+    /// Precipitation of given kind and intensity.
+    pub fn new(kind: WeatherKind, intensity: f32) -> Self {
+        debug_assert!(
+            (0.0..=1.0).contains(&intensity),
+            "Intensity must be in [0, 1]"
+        );
+
+        Self { kind, intensity }
+    }
+}
+
+impl WeatherKind {
+    fn size(self) -> Vec2 {
+        match self {
+            WeatherKind::Rain => Vec2::new(1.0, 6.0),
+            WeatherKind::Snow => Vec2::new(2.0, 2.0),
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            WeatherKind::Rain => Color::srgba(0.7, 0.75, 0.9, 0.6),
+            WeatherKind::Snow => Color::srgba(1.0, 1.0, 1.0, 0.9),
+        }
+    }
+
+    fn velocity(self, rng: &mut dyn RngCore) -> Vec2 {
+        match self {
+            WeatherKind::Rain => {
+                Vec2::new(-40.0, rng.gen_range(-420.0..-360.0))
+            }
+            WeatherKind::Snow => Vec2::new(
+                rng.gen_range(-15.0..15.0),
+                rng.gen_range(-45.0..-30.0),
+            ),
+        }
+    }
+}
+
+/// Moves the particles and spawns new ones based on the [`Weather`]
+/// intensity.
+pub(crate) fn fall(
+    mut cmd: Commands,
+    time: Res<Time>,
+    weather: Res<Weather>,
+    mut seeded: Option<ResMut<WeatherRng>>,
+    mut to_spawn: Local<f32>,
+
+    camera: Query<(), With<WeatherCamera>>,
+    mut particles: Query<(
+        Entity,
+        &mut WeatherParticle,
+        &mut Transform,
+        &mut Sprite,
+        &mut Visibility,
+    )>,
+) {
+    let mut thread_rng = rand::thread_rng();
+    let rng: &mut dyn RngCore = match seeded.as_deref_mut() {
+        Some(WeatherRng(rng)) => rng,
+        None => &mut thread_rng,
+    };
+
+    if camera.is_empty() {
+        cmd.spawn((
+            Name::new("Weather camera"),
+            WeatherCamera,
+            PixelZoom::Fixed(PIXEL_ZOOM),
+            PixelViewport,
+            RenderLayers::layer(render_layer::WEATHER),
+            Camera2dBundle {
+                camera: Camera {
+                    hdr: true,
+                    order: order::WEATHER,
+                    clear_color: ClearColorConfig::None,
+                    ..default()
+                },
+                ..default()
+            },
+        ));
+    }
+
+    let half_width = PIXEL_VISIBLE_WIDTH / 2.0 + MARGIN;
+    let half_height = PIXEL_VISIBLE_HEIGHT / 2.0 + MARGIN;
+    let color = weather
+        .kind
+        .color()
+        .with_alpha(weather.kind.color().alpha() * weather.intensity);
+
+    let mut free = Vec::new();
+    for (entity, particle, mut transform, mut sprite, mut visibility) in
+        particles.iter_mut()
+    {
+        if *visibility == Visibility::Hidden {
+            free.push(entity);
+            continue;
+        }
+
+        transform.translation +=
+            (particle.velocity * time.delta_seconds()).extend(0.0);
+        sprite.color = color;
+
+        if transform.translation.y < -half_height {
+            *visibility = Visibility::Hidden;
+            free.push(entity);
+        }
+    }
+
+    *to_spawn += weather.intensity * SPAWN_RATE * time.delta_seconds();
+    let mut pool_size = particles.iter().len();
+    while *to_spawn >= 1.0 {
+        *to_spawn -= 1.0;
+
+        let velocity = weather.kind.velocity(rng);
+        // rain is slanted, so it needs to start further to the right
+        let slant = velocity.x / velocity.y * 2.0 * half_height;
+        let translation = Vec3::new(
+            rng.gen_range(-half_width..half_width) + slant.max(0.0),
+            half_height,
+            0.0,
+        );
+        let sprite = Sprite {
+            color,
+            custom_size: Some(weather.kind.size()),
+            ..default()
+        };
+
+        if let Some(entity) = free.pop() {
+            let (
+                _,
+                mut particle,
+                mut transform,
+                mut old_sprite,
+                mut visibility,
+            ) = particles.get_mut(entity).expect("Free particle exists");
+            particle.velocity = velocity;
+            transform.translation = translation;
+            *old_sprite = sprite;
+            *visibility = Visibility::Inherited;
+        } else if pool_size < MAX_PARTICLES {
+            pool_size += 1;
+            cmd.spawn((
+                Name::new("Weather particle"),
+                WeatherParticle { velocity },
+                RenderLayers::layer(render_layer::WEATHER),
+                SpriteBundle {
+                    sprite,
+                    transform: Transform::from_translation(translation),
+                    ..default()
+                },
+            ));
+        } else {
+            // all particles are on the screen, skip this one
+            *to_spawn = to_spawn.fract();
+            break;
+        }
+    }
+}
+
+/// Despawns the camera and the particles when [`Weather`] is removed.
+pub(crate) fn despawn(
+    mut cmd: Commands,
+
+    camera: Query<Entity, With<WeatherCamera>>,
+    particles: Query<Entity, With<WeatherParticle>>,
+) {
+    for entity in camera.iter().chain(particles.iter()) {
+        cmd.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn particle_count(w: &mut World) -> usize {
+        w.query_filtered::<(), With<WeatherParticle>>()
+            .iter(w)
+            .count()
+    }
+
+    #[test]
+    fn it_spawns_no_particles_without_intensity() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+        w.insert_resource(WeatherRng(StdRng::seed_from_u64(42)));
+        w.insert_resource(Weather::new(WeatherKind::Rain, 0.0));
+        let fall = w.register_system(fall);
+
+        for _ in 0..100 {
+            w.resource_mut::<Time>()
+                .advance_by(Duration::from_millis(100));
+            w.run_system(fall).unwrap();
+        }
+
+        assert_eq!(0, particle_count(&mut w));
+    }
+
+    #[test]
+    fn it_never_exceeds_particle_cap() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+        w.insert_resource(WeatherRng(StdRng::seed_from_u64(42)));
+        w.insert_resource(Weather::new(WeatherKind::Snow, 1.0));
+        let fall = w.register_system(fall);
+        let despawn = w.register_system(despawn);
+
+        for _ in 0..100 {
+            w.resource_mut::<Time>()
+                .advance_by(Duration::from_millis(500));
+            w.run_system(fall).unwrap();
+            assert!(particle_count(&mut w) <= MAX_PARTICLES);
+        }
+        // snow is slow, so the pool fills up
+        assert_eq!(MAX_PARTICLES, particle_count(&mut w));
+
+        w.remove_resource::<Weather>();
+        w.run_system(despawn).unwrap();
+        assert_eq!(0, particle_count(&mut w));
+        assert_eq!(
+            0,
+            w.query_filtered::<(), With<WeatherCamera>>()
+                .iter(&w)
+                .count()
+        );
+    }
+}
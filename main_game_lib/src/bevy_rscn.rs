@@ -15,10 +15,28 @@
 //! relationships are preserved. Plain nodes are typically components.
 //! See the wiki for current status of what's supported and what custom nodes
 //! are available.
+//!
+//! A Godot `Timer` node inserts a [`SceneTimer`] component to its parent.
+//! When the timer times out, [`SceneTimerFinishedEvent`] is emitted.
+//! Metadata of the `Timer` node can describe what should happen then.
+//!
+//! A Godot `Path2D` node inserts a [`ScenePath`] component to its own entity.
+//!
+//! A Godot `Area2D` node inserts a [`SceneArea`] component to its own entity.
+//! Its `CollisionShape2D` children with a `RectangleShape2D` shape describe
+//! the area and are not spawned.
+//!
+//! Backgrounds such as skies can be drawn with Godot's `CanvasLayer`,
+//! `ParallaxBackground` and `ParallaxLayer` nodes, see [`LayerNode`].
+//!
+//! For tooling that edits the scene and writes it back, a tree can be
+//! converted back to .tscn text with [`TscnTree::to_tscn`].
 
 mod loader;
+mod sorted;
 mod spawner;
 mod tree;
+mod writer;
 
 use std::borrow::Cow;
 
@@ -29,11 +47,13 @@ use bevy::{
     ecs::{
         component::Component,
         entity::Entity,
-        system::{Commands, Query, Res},
+        event::{Event, EventWriter},
+        system::{Commands, Query, Res, ResMut},
     },
     hierarchy::DespawnRecursiveExt,
     math::{Rect, Vec2},
     reflect::TypePath,
+    time::{Time, Timer, TimerMode},
     utils::HashMap,
 };
 use common_ext::QueryExt;
@@ -41,6 +61,8 @@ pub use loader::{LoaderError, TscnLoader};
 use serde::{Deserialize, Serialize};
 pub use spawner::{EntityDescription, EntityDescriptionMap, TscnSpawnHooks};
 
+use crate::loading_watchdog::LoadingWatchdog;
+
 /// A helper component that is always in an entity with
 /// [bevy::prelude::SpatialBundle].
 ///
@@ -59,12 +81,67 @@ pub use spawner::{EntityDescription, EntityDescriptionMap, TscnSpawnHooks};
 )]
 pub struct Point(pub Vec2);
 
+/// Translated from a Godot `Path2D` node.
+/// Inserted into the entity of the `Path2D` node itself.
+///
+/// The points are relative to the entity's translation, i.e. to get world
+/// positions add the global translation of the entity.
+/// Only straight segments are supported, the curve must not have any bezier
+/// control handles.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct ScenePath(pub Vec<Vec2>);
+
+/// Translated from a Godot `Area2D` node.
+/// Inserted into the entity of the `Area2D` node itself.
+///
+/// There's a rectangle for each shape of the area.
+/// The rectangles are relative to the entity's translation, i.e. to get the
+/// world rectangles add the global translation of the entity.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct SceneArea(pub Vec<Rect>);
+
+/// Translated from a Godot `Timer` node.
+/// Inserted into the entity of the parent 2D node.
+/// Each 2D node can have at most one `Timer` child.
+///
+/// Ticked by [`tick_scene_timers`].
+#[derive(Component, Debug)]
+pub struct SceneTimer {
+    /// The name of the `Timer` node.
+    pub name: NodeName,
+    /// Metadata of the `Timer` node.
+    /// Typically describes what should happen when the timer times out.
+    pub metadata: HashMap<String, String>,
+    /// If `autostart` was not set in Godot, the timer is paused.
+    /// Unpause it to start counting down.
+    pub timer: Timer,
+}
+
+/// Emitted by [`tick_scene_timers`] each time a [`SceneTimer`] times out.
+#[derive(Event, Debug, Clone)]
+pub struct SceneTimerFinishedEvent {
+    /// The entity with the [`SceneTimer`] component.
+    pub entity: Entity,
+    /// The name of the `Timer` node.
+    pub name: NodeName,
+}
+
 /// Configure how the scene is converted from godot to bevy.
 #[derive(Serialize, Deserialize)]
 pub struct Config {
     /// We assert each asset path starts with this prefix.
     /// Then we strip it.
     pub asset_path_prefix: String,
+    /// How many Godot pixels make up a single bevy unit.
+    /// Positions are divided by this on import, so a scene authored at 2×
+    /// density is normalized with `2.0`.
+    ///
+    /// Sprites are scaled by `1 / pixels_per_unit` so that they keep their
+    /// size relative to the positions.
+    /// Texture atlas frames are not scaled because they address pixels of
+    /// the texture itself.
+    #[serde(default = "default_pixels_per_unit")]
+    pub pixels_per_unit: f32,
 }
 
 /// A godot scene is a tree of nodes.
@@ -91,6 +168,8 @@ pub struct RscnNode {
     /// - `Node2D`
     /// - `Sprite2D`
     /// - `AnimatedSprite2D`
+    /// - `Path2D`
+    /// - `Area2D`
     ///
     /// and irrelevant for
     /// - `Node`
@@ -100,11 +179,35 @@ pub struct RscnNode {
     /// Any node can have some metadata.
     /// These are relevant when spawning the node into bevy world.
     #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[serde(serialize_with = "sorted::serialize_metadata")]
     #[serde(default)]
     pub metadata: HashMap<String, String>,
+    /// Only relevant for `Timer` nodes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub timer: Option<TimerNode>,
+    /// Only relevant for `Path2D` nodes.
+    /// Points of the curve relative to the node's position.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub path_points: Vec<Vec2>,
+    /// Only relevant for `Area2D` nodes.
+    /// The rectangle shapes of its `CollisionShape2D` children relative to
+    /// the node's position.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub area: Vec<Rect>,
+    /// Only relevant for `CanvasLayer`, `ParallaxBackground` and
+    /// `ParallaxLayer` nodes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub layer: Option<LayerNode>,
     /// These nodes will be spawned as children if they have 2D positional
     /// data. Otherwise, they are treated as components and not entities.
+    ///
+    /// Serialized as a list sorted by name to keep the output stable.
     #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[serde(with = "sorted::children")]
     #[serde(default)]
     pub children: HashMap<NodeName, RscnNode>,
 }
@@ -113,17 +216,24 @@ pub struct RscnNode {
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
 pub struct NodeName(pub String);
 
-/// Either a `Node2D`, `Sprite2D`, or `AnimatedSprite2D` node.
+/// Either a `Node2D`, `Sprite2D`, `AnimatedSprite2D`, `Path2D`, or `Area2D`
+/// node.
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct In2D {
     /// in 2D
     pub position: Vec2,
     /// Or calculated from position if missing.
     /// If a 2D node has a 2D node child called "YSort", then the position
-    /// fed to the [`crate::top_down::layout::ysort`] function is the global
+    /// fed to the [`crate::top_down::TileMap::ysort`] function is the global
     /// position of that "YSort", i.e. the position of the 2D node plus the
     /// position of the "YSort".
     pub z_index: Option<f32>,
+    /// Godot's `visible` property, true by default.
+    /// Hidden nodes are spawned with [`Visibility::Hidden`], visible ones with
+    /// [`Visibility::Inherited`].
+    /// Hence, children of a hidden node are hidden as well and show up once
+    /// their parent is made visible, just like in Godot.
+    pub visible: bool,
     /// for images and animations
     pub texture: Option<SpriteTexture>,
 }
@@ -135,8 +245,6 @@ pub struct SpriteTexture {
     /// E.g. `apartment/cupboard.png`.
     /// The prefix is set in the [`Config`].
     pub path: String,
-    /// Whether the sprite is visible or hidden.
-    pub visible: bool,
     /// Changed by the Self Modulate property in Godot.
     pub color: Option<Color>,
     /// We only support sprite frames that are part of an atlas (single file
@@ -146,6 +254,49 @@ pub struct SpriteTexture {
     pub flip_horizontally: bool,
     /// If the texture should be flipped vertically.
     pub flip_vertically: bool,
+    /// The sprite is spawned with this scale.
+    /// It's `1 / pixels_per_unit`, see [`Config::pixels_per_unit`].
+    pub scale: f32,
+}
+
+/// A node that fires once the wait time elapses.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimerNode {
+    /// How long to wait before the timer times out.
+    pub wait_time: f32,
+    /// If set to true, the timer starts counting down as soon as it's spawned.
+    pub autostart: bool,
+    /// If set to false, the timer restarts after it times out.
+    pub one_shot: bool,
+}
+
+/// Godot nodes that draw backgrounds.
+/// They are 2D nodes and their children are spawned as usual.
+///
+/// Only the properties listed here are supported, others fail the parsing.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum LayerNode {
+    /// `CanvasLayer` is spawned with its `layer` as the z index.
+    /// Unlike in Godot, its children are positioned in the world rather
+    /// than on the screen.
+    Canvas {
+        /// 1 by default.
+        layer: i32,
+    },
+    /// `ParallaxBackground` is a canvas layer that holds the
+    /// [`LayerNode::Parallax`] nodes.
+    ParallaxBackground {
+        /// -100 by default.
+        layer: i32,
+    },
+    /// `ParallaxLayer` is spawned with a
+    /// [`common_visuals::parallax::ParallaxLayer`] component.
+    Parallax {
+        /// How much the layer moves along with the world as the camera
+        /// moves.
+        /// One by default.
+        motion_scale: Vec2,
+    },
 }
 
 /// Atlas animation.
@@ -163,10 +314,38 @@ pub struct SpriteFrames {
     /// Note that we use [`bevy::prelude::Rect`], hence the Y coordinate
     /// has been translated from godot to bevy coordinates.
     pub frames: Vec<Rect>,
+    /// How long is each frame shown in multiples of `1 / fps`, as set in
+    /// Godot.
+    /// Empty if all frames are shown for the same time.
+    #[serde(default)]
+    pub frame_durations: Vec<f32>,
     /// The min size of the texture that fits all the frames.
     pub size: Vec2,
 }
 
+/// An advisory about the scene that doesn't stop it from being converted.
+/// See [`parse_with_diagnostics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// How much attention the issue deserves.
+    pub severity: Severity,
+    /// The node the issue is about.
+    /// Issues that concern the whole scene, such as an unused
+    /// `ext_resource`, are tied to the root node.
+    pub node: NodeName,
+    /// Human readable description of the issue.
+    pub message: String,
+}
+
+/// See [`Diagnostic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Probably fine but worth knowing about.
+    Info,
+    /// Probably a mistake in the scene.
+    Warning,
+}
+
 /// Marks scene as "can be loaded from .tscn".
 pub trait TscnInBevy: Send + Sync + 'static {
     /// Asset path of the `.tscn` file associated with this scene.
@@ -192,11 +371,27 @@ pub struct TscnTreeHandle<T> {
 ///
 /// Aggressively panics on unexpected format.
 pub fn from_tscn(tscn: &str, config: &Config) -> TscnTree {
-    rscn::from_tscn(tscn)
-        .and_then(|scene| tree::from_scene(scene, config))
+    let (tree, _) = parse_with_diagnostics(tscn, config);
+    tree
+}
+
+/// Like [`from_tscn`] but also collects soft issues with the scene such as
+/// unused `ext_resource`s or 2D nodes with no position.
+/// Meant for editor tooling that wants to point them out.
+///
+/// Panics on the same unexpected format as [`from_tscn`].
+pub fn parse_with_diagnostics(
+    tscn: &str,
+    config: &Config,
+) -> (TscnTree, Vec<Diagnostic>) {
+    let mut diagnostics = vec![];
+    let tree = rscn::from_tscn(tscn)
+        .and_then(|scene| tree::from_scene(scene, config, &mut diagnostics))
         .map_err(|e| e.with_source_code(tscn.to_string()))
         .inspect_err(|e| eprintln!("{e:?}")) // miette fancy error printing
-        .expect("Failed to process .tscn file")
+        .expect("Failed to process .tscn file");
+
+    (tree, diagnostics)
 }
 
 /// Run this system on enter to a scene to start loading the `.tscn` file.
@@ -205,11 +400,17 @@ pub fn from_tscn(tscn: &str, config: &Config) -> TscnTree {
 pub fn start_loading_tscn<T: TscnInBevy>(
     mut cmd: Commands,
     asset_server: Res<AssetServer>,
+    watchdog: Option<ResMut<LoadingWatchdog>>,
 ) {
+    let handle = asset_server.load(T::tscn_asset_path());
+    if let Some(mut watchdog) = watchdog {
+        watchdog.watch(&handle);
+    }
+
     let mut e = cmd.spawn(Name::new(".tscn tree handle"));
     e.insert(TscnTreeHandle::<T> {
         entity: e.id(),
-        handle: Some(asset_server.load(T::tscn_asset_path())),
+        handle: Some(handle),
         _phantom: Default::default(),
     });
 }
@@ -229,6 +430,25 @@ pub fn tscn_loaded_but_not_spawned<T: TscnInBevy>(
     }
 }
 
+/// Ticks all [`SceneTimer`]s and emits [`SceneTimerFinishedEvent`]s.
+pub fn tick_scene_timers(
+    time: Res<Time>,
+    mut events: EventWriter<SceneTimerFinishedEvent>,
+
+    mut timers: Query<(Entity, &mut SceneTimer)>,
+) {
+    for (entity, mut scene_timer) in timers.iter_mut() {
+        scene_timer.timer.tick(time.delta());
+
+        for _ in 0..scene_timer.timer.times_finished_this_tick() {
+            events.send(SceneTimerFinishedEvent {
+                entity,
+                name: scene_timer.name.clone(),
+            });
+        }
+    }
+}
+
 impl<T> TscnTreeHandle<T> {
     /// Consume the handle and return the loaded scene.
     /// After this, the handle is useless and the entity is despawned.
@@ -245,14 +465,45 @@ impl<T> TscnTreeHandle<T> {
     }
 }
 
+impl SceneTimer {
+    fn new(
+        name: NodeName,
+        metadata: HashMap<String, String>,
+        node: TimerNode,
+    ) -> Self {
+        let mut timer = Timer::from_seconds(
+            node.wait_time,
+            if node.one_shot {
+                TimerMode::Once
+            } else {
+                TimerMode::Repeating
+            },
+        );
+        if !node.autostart {
+            timer.pause();
+        }
+
+        Self {
+            name,
+            metadata,
+            timer,
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             asset_path_prefix: "res://assets/".to_string(),
+            pixels_per_unit: default_pixels_per_unit(),
         }
     }
 }
 
+fn default_pixels_per_unit() -> f32 {
+    1.0
+}
+
 impl NodeName {
     /// Get the name as a [str].
     pub fn as_str(&self) -> &str {
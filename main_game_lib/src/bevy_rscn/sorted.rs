@@ -0,0 +1,165 @@
+//! Stable serialization of [`RscnNode`] maps.
+//!
+//! The maps are [`HashMap`]s for fast lookup, but their iteration order
+//! depends on the insertion order.
+//! Serialized trees are committed to version control, so the entries are
+//! written sorted by their keys to keep the diffs clean.
+
+use std::fmt;
+
+use bevy::utils::HashMap;
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    Deserializer, Serializer,
+};
+
+use super::{NodeName, RscnNode};
+
+/// Use with `#[serde(with = "...")]` on [`RscnNode::children`].
+///
+/// Writes the children as a list of `(name, node)` pairs sorted by name.
+/// Reads both the list and the map form, so that files written before can
+/// still be read.
+/// This relies on [`Deserializer::deserialize_any`], which is supported by
+/// self-describing formats such as RON or JSON.
+pub(super) mod children {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        children: &HashMap<NodeName, RscnNode>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut sorted: Vec<_> = children.iter().collect();
+        sorted.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+        serializer.collect_seq(sorted)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<NodeName, RscnNode>, D::Error> {
+        deserializer.deserialize_any(ChildrenVisitor)
+    }
+
+    struct ChildrenVisitor;
+
+    impl<'de> Visitor<'de> for ChildrenVisitor {
+        type Value = HashMap<NodeName, RscnNode>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a list of (name, node) pairs or a map of nodes")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(
+            self,
+            mut seq: A,
+        ) -> Result<Self::Value, A::Error> {
+            let mut children =
+                HashMap::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some((name, node)) =
+                seq.next_element::<(NodeName, RscnNode)>()?
+            {
+                if children.contains_key(&name) {
+                    return Err(de::Error::custom(format!(
+                        "duplicate child node {name:?}"
+                    )));
+                }
+                children.insert(name, node);
+            }
+
+            Ok(children)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(
+            self,
+            mut map: A,
+        ) -> Result<Self::Value, A::Error> {
+            let mut children =
+                HashMap::with_capacity(map.size_hint().unwrap_or(0));
+            while let Some((name, node)) = map.next_entry()? {
+                children.insert(name, node);
+            }
+
+            Ok(children)
+        }
+    }
+}
+
+/// Use with `#[serde(serialize_with = "...")]` on [`RscnNode::metadata`].
+/// Writes the map sorted by keys, reading it is unchanged.
+pub(super) fn serialize_metadata<S: Serializer>(
+    metadata: &HashMap<String, String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut sorted: Vec<_> = metadata.iter().collect();
+    sorted.sort_by_key(|(key, _)| key.as_str());
+    serializer.collect_map(sorted)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::default;
+
+    use super::*;
+    use crate::bevy_rscn::TscnTree;
+
+    fn node(children: &[&str]) -> RscnNode {
+        RscnNode {
+            in_2d: None,
+            metadata: children
+                .iter()
+                .map(|name| (format!("key_{name}"), name.to_string()))
+                .collect(),
+            timer: None,
+            path_points: default(),
+            area: default(),
+            layer: None,
+            children: children
+                .iter()
+                .map(|name| (NodeName(name.to_string()), node(&[])))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn it_serializes_children_in_stable_order() {
+        let names: Vec<_> = (0..32).map(|i| format!("Node{i}")).collect();
+        let names: Vec<_> = names.iter().map(String::as_str).collect();
+        let mut reversed = names.clone();
+        reversed.reverse();
+
+        let tree = |names: &[&str]| TscnTree {
+            root_node_name: NodeName("Root".to_owned()),
+            root: node(names),
+        };
+        let a = tree(&names);
+        let b = tree(&reversed);
+        assert_eq!(a, b);
+
+        let config = ron::ser::PrettyConfig::default();
+        let ron_a = ron::ser::to_string_pretty(&a, config.clone()).unwrap();
+        let ron_b = ron::ser::to_string_pretty(&b, config).unwrap();
+        assert_eq!(ron_a, ron_b);
+        assert!(
+            ron_a.find("\"Node10\"").unwrap()
+                < ron_a.find("\"Node2\"").unwrap()
+        );
+
+        assert_eq!(a, ron::from_str(&ron_a).unwrap());
+    }
+
+    #[test]
+    fn it_deserializes_children_written_as_map() {
+        let ron = r#"(
+            root_node_name: ("Root"),
+            root: (
+                children: {
+                    ("Sprite"): (metadata: {"zone": "Bed"}),
+                },
+            ),
+        )"#;
+
+        let tree: TscnTree = ron::from_str(ron).unwrap();
+        let sprite = &tree.root.children[&NodeName("Sprite".to_owned())];
+        assert_eq!("Bed", sprite.metadata["zone"]);
+    }
+}
@@ -5,15 +5,26 @@
 //! - user can provide hooks for custom behavior with [TscnSpawnHooks]
 //! - a plain node called "Point" will insert [Point] component to its parent
 //!   and will not be handled by the hooks
+//! - a `Timer` node will insert [SceneTimer] component to its parent and will
+//!   not be handled by the hooks, hence a node can have at most one `Timer`
+//! - a `Path2D` node will insert [ScenePath] component to its own entity
+//! - an `Area2D` node will insert [SceneArea] component to its own entity
+//! - a `ParallaxLayer` node will insert [ParallaxLayer] component to its own
+//!   entity and canvas layers are spawned with their layer as the z index
+//! - a sprite is scaled by [`SpriteTexture::scale`], hence a scaled sprite
+//!   cannot have 2D children because they'd be scaled with it
 
 use std::time::Duration;
 
 use bevy::utils::EntityHashMap;
-use bevy_rscn::Point;
-use common_visuals::{AtlasAnimation, AtlasAnimationEnd, AtlasAnimationTimer};
+use bevy_rscn::{Point, SceneArea, ScenePath, SceneTimer};
+use common_visuals::{
+    parallax::ParallaxLayer, AtlasAnimation, AtlasAnimationEnd,
+    AtlasAnimationTimer,
+};
 
 use crate::{
-    bevy_rscn::{In2D, NodeName, RscnNode, SpriteTexture, TscnTree},
+    bevy_rscn::{In2D, LayerNode, NodeName, RscnNode, SpriteTexture, TscnTree},
     prelude::*,
 };
 
@@ -21,11 +32,11 @@ use crate::{
 pub type EntityDescriptionMap = EntityHashMap<Entity, EntityDescription>;
 
 /// All components that are managed by the scene spawner implementation.
-#[derive(Default)]
 #[allow(missing_docs)]
 pub struct EntityDescription {
     pub visibility: Visibility,
     pub translation: Vec2,
+    pub scale: f32,
     pub z_index: Option<f32>,
     pub texture: Option<Handle<Image>>,
     pub sprite: Option<Sprite>,
@@ -34,6 +45,22 @@ pub struct EntityDescription {
     pub atlas_animation_timer: Option<AtlasAnimationTimer>,
 }
 
+impl Default for EntityDescription {
+    fn default() -> Self {
+        Self {
+            visibility: default(),
+            translation: default(),
+            scale: 1.0,
+            z_index: default(),
+            texture: default(),
+            sprite: default(),
+            texture_atlas: default(),
+            atlas_animation: default(),
+            atlas_animation_timer: default(),
+        }
+    }
+}
+
 /// Guides the spawning process of a scene.
 ///
 /// Use the [TscnTree::spawn_into] method to spawn the scene into a world.
@@ -130,26 +157,42 @@ fn node_to_entity(
     let In2D {
         position,
         z_index,
+        visible,
         texture,
     } = node.in_2d.expect("only 2D nodes represent entities");
 
     let mut description = EntityDescription {
         translation: position,
         z_index,
+        // children of a hidden node inherit it, so we mustn't force them to
+        // be visible
+        visibility: if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        },
         ..Default::default()
     };
 
     if let Some(SpriteTexture {
         path,
         animation,
-        visible,
         color,
         flip_horizontally,
         flip_vertically,
+        scale,
     }) = texture
     {
+        assert!(
+            scale == 1.0
+                || node.children.values().all(|child| child.in_2d.is_none()),
+            "Node '{}': a scaled sprite cannot have 2D children",
+            name.as_str()
+        );
+
         let texture = ctx.asset_server.load(&path);
         description.texture = Some(texture);
+        description.scale = scale;
         description.sprite = Some(Sprite {
             color: color.unwrap_or(Color::WHITE),
             flip_x: flip_horizontally,
@@ -157,10 +200,6 @@ fn node_to_entity(
             ..default()
         });
 
-        if !visible {
-            description.visibility = Visibility::Hidden;
-        }
-
         if let Some(animation) = animation {
             let mut layout =
                 TextureAtlasLayout::new_empty(animation.size.as_uvec2());
@@ -185,6 +224,11 @@ fn node_to_entity(
                 // Even though the first frame that's shown is the first_index.
                 first: 0,
                 last: frames_count - 1,
+                frame_durations: animation
+                    .frame_durations
+                    .iter()
+                    .map(|ticks| Duration::from_secs_f32(ticks / animation.fps))
+                    .collect(),
                 ..default()
             });
 
@@ -198,8 +242,31 @@ fn node_to_entity(
         }
     }
 
+    match node.layer {
+        Some(
+            LayerNode::Canvas { layer }
+            | LayerNode::ParallaxBackground { layer },
+        ) => {
+            // canvas layers have no z index of their own
+            description.z_index = Some(layer as f32);
+        }
+        Some(LayerNode::Parallax { motion_scale }) => {
+            cmd.entity(entity).insert(ParallaxLayer::new(motion_scale));
+        }
+        None => {}
+    }
+
     ctx.entity_descriptions.insert(entity, description);
 
+    if !node.path_points.is_empty() {
+        cmd.entity(entity).insert(ScenePath(node.path_points));
+    }
+    if !node.area.is_empty() {
+        cmd.entity(entity).insert(SceneArea(node.area));
+    }
+
+    // an entity can only have one timer component
+    let mut timer_name: Option<NodeName> = None;
     for (child_name, child_node) in node.children {
         if child_node.in_2d.is_some() {
             // recursively spawn 2D children
@@ -214,6 +281,22 @@ fn node_to_entity(
                 (child_id, child_name),
                 child_node,
             );
+        } else if let Some(timer) = child_node.timer {
+            if let Some(timer_name) = &timer_name {
+                panic!(
+                    "Node '{}' has more than one Timer: '{}' and '{}'",
+                    name.as_str(),
+                    timer_name.as_str(),
+                    child_name.as_str()
+                );
+            }
+            timer_name = Some(child_name.clone());
+
+            cmd.entity(entity).insert(SceneTimer::new(
+                child_name,
+                child_node.metadata,
+                timer,
+            ));
         } else {
             match child_name.as_str() {
                 "Point" => {
@@ -246,6 +329,7 @@ fn node_to_entity(
     let Some(EntityDescription {
         visibility,
         translation,
+        scale,
         z_index,
         texture,
         sprite,
@@ -263,7 +347,8 @@ fn node_to_entity(
         // fighting between nested nodes (parent vs child)
         transform: Transform::from_translation(
             translation.extend(z_index.unwrap_or(f32::EPSILON)),
-        ),
+        )
+        .with_scale(Vec3::new(scale, scale, 1.0)),
         visibility,
         ..default()
     });
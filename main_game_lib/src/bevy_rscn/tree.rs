@@ -2,15 +2,19 @@ use bevy::{
     color::Color,
     log::warn,
     math::{Rect, Vec2},
-    utils::{default, HashMap},
+    utils::{default, HashMap, HashSet},
 };
+use miette::LabeledSpan;
 use rscn::{
     self,
-    godot::{self, ExtResource, SubResourceId, SubResourceSectionKey},
+    godot::{
+        self, ExtResource, NodeSectionKey, SubResourceId, SubResourceSectionKey,
+    },
 };
 
 use crate::bevy_rscn::{
-    Config, In2D, NodeName, RscnNode, SpriteFrames, SpriteTexture, TscnTree,
+    Config, Diagnostic, In2D, LayerNode, NodeName, RscnNode, Severity,
+    SpriteFrames, SpriteTexture, TimerNode, TscnTree,
 };
 
 struct Properties {
@@ -23,11 +27,20 @@ struct Properties {
     color: Option<Color>,
     flip_horizontally: bool,
     flip_vertically: bool,
+    wait_time: Option<f32>,
+    autostart: bool,
+    one_shot: bool,
+    curve: Option<Vec<Vec2>>,
+    layer: Option<i32>,
+    motion_scale: Option<Vec2>,
+    /// Size of the rectangle shape.
+    shape: Option<Vec2>,
 }
 
 pub(crate) fn from_scene(
     mut scene: godot::Scene,
     conf: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
 ) -> miette::Result<TscnTree> {
     let root_node_index = scene
         .nodes
@@ -46,13 +59,20 @@ pub(crate) fn from_scene(
     );
 
     let root_node_name = parsed_root_node.name;
+    diagnose_unused_ext_resources(&scene, &root_node_name, diagnostics);
+
     let mut root = RscnNode {
         in_2d: Some(In2D {
             position: Vec2::ZERO,
             z_index: None,
+            visible: true,
             texture: None,
         }),
         metadata: default(),
+        timer: None,
+        path_points: default(),
+        area: default(),
+        layer: None,
         children: default(),
     };
 
@@ -75,9 +95,33 @@ pub(crate) fn from_scene(
     // now that the nodes are sorted we can iterate over them and we will be
     // guaranteed that a parent is always added before its children
 
+    // paths of Area2D nodes so that their collision shapes can find them
+    let mut areas = HashSet::new();
+
     let mut nodes = vec![];
     std::mem::swap(&mut nodes, &mut scene.nodes); // to avoid borrow checker
     for parsed_node in nodes {
+        let is_2d = matches!(
+            parsed_node.kind,
+            godot::NodeKind::Node2D
+                | godot::NodeKind::Sprite2D
+                | godot::NodeKind::AnimatedSprite2D
+                | godot::NodeKind::Path2D
+                | godot::NodeKind::ParallaxLayer
+                | godot::NodeKind::Area2D
+        );
+        if is_2d && !parsed_node.section.contains_key(&NodeSectionKey::Position)
+        {
+            // Godot omits the position if it's zero, so it might be on purpose
+            diagnostics.push(Diagnostic {
+                severity: Severity::Info,
+                node: NodeName(parsed_node.name.clone()),
+                message: "2D node has no position, defaults to origin".into(),
+            });
+        }
+
+        reject_unsupported_layer_keys(&parsed_node)?;
+
         let mut properties = default();
 
         for (section_key, section_value) in parsed_node.section {
@@ -90,6 +134,16 @@ pub(crate) fn from_scene(
             )?;
         }
 
+        for (key, value) in &properties.metadata {
+            if value.trim().is_empty() {
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    node: NodeName(parsed_node.name.clone()),
+                    message: format!("Metadata '{key}' has an empty value"),
+                });
+            }
+        }
+
         let Properties {
             z_index,
             position,
@@ -100,12 +154,123 @@ pub(crate) fn from_scene(
             color,
             flip_horizontally,
             flip_vertically,
+            wait_time,
+            autostart,
+            one_shot,
+            curve,
+            layer,
+            motion_scale,
+            shape,
         } = properties;
 
+        let parent = parsed_node
+            .parent
+            .expect("each node except for root should have a parent");
+
+        if let godot::NodeKind::CollisionShape2D = parsed_node.kind {
+            // the shape is not a node of its own but a part of the area
+            let size = shape.unwrap_or_else(|| {
+                panic!(
+                    "Node '{}': CollisionShape2D should have a \
+                    RectangleShape2D shape",
+                    parsed_node.name
+                )
+            });
+            assert!(
+                areas.contains(&parent),
+                "Node '{}': CollisionShape2D must be a child of Area2D",
+                parsed_node.name
+            );
+            assert!(
+                metadata.is_empty(),
+                "Node '{}': CollisionShape2D cannot have metadata",
+                parsed_node.name
+            );
+
+            node_at_path(&mut root, &parent)
+                .area
+                .push(Rect::from_center_size(position, size));
+
+            continue;
+        }
+        assert!(
+            shape.is_none(),
+            "Node '{}': only CollisionShape2D can have a shape",
+            parsed_node.name
+        );
+
+        if let godot::NodeKind::Area2D = parsed_node.kind {
+            areas.insert(if parent == "." {
+                parsed_node.name.clone()
+            } else {
+                format!("{parent}/{}", parsed_node.name)
+            });
+        }
+
+        let timer = if let godot::NodeKind::Timer = parsed_node.kind {
+            Some(TimerNode {
+                // Godot's default
+                wait_time: wait_time.unwrap_or(1.0),
+                autostart,
+                one_shot,
+            })
+        } else {
+            assert!(
+                wait_time.is_none(),
+                "Node '{}': only Timer can have wait_time",
+                parsed_node.name
+            );
+            None
+        };
+
+        let path_points = if let godot::NodeKind::Path2D = parsed_node.kind {
+            curve.unwrap_or_else(|| {
+                // Godot saves a freshly added Path2D without a curve
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    node: NodeName(parsed_node.name.clone()),
+                    message: "Path2D has no curve, its path is empty".into(),
+                });
+                vec![]
+            })
+        } else {
+            assert!(
+                curve.is_none(),
+                "Node '{}': only Path2D can have a curve",
+                parsed_node.name
+            );
+            vec![]
+        };
+
+        let layer = match parsed_node.kind {
+            // Godot's defaults
+            godot::NodeKind::CanvasLayer => Some(LayerNode::Canvas {
+                layer: layer.unwrap_or(1),
+            }),
+            godot::NodeKind::ParallaxBackground => {
+                Some(LayerNode::ParallaxBackground {
+                    layer: layer.unwrap_or(-100),
+                })
+            }
+            godot::NodeKind::ParallaxLayer => Some(LayerNode::Parallax {
+                motion_scale: motion_scale.unwrap_or(Vec2::ONE),
+            }),
+            _ => {
+                assert!(
+                    layer.is_none() && motion_scale.is_none(),
+                    "Node '{}': only canvas and parallax layers can have \
+                    layer or motion_scale",
+                    parsed_node.name
+                );
+                None
+            }
+        };
+
         let in_2d = match parsed_node.kind {
             godot::NodeKind::AnimatedSprite2D => Some(In2D {
                 position,
                 z_index,
+                visible,
                 texture: Some(SpriteTexture {
                     path: path.unwrap_or_else(|| {
                         panic!(
@@ -113,7 +278,6 @@ pub(crate) fn from_scene(
                             parsed_node.name
                         )
                     }),
-                    visible,
                     color,
                     animation: {
                         assert!(animation.is_some());
@@ -121,11 +285,13 @@ pub(crate) fn from_scene(
                     },
                     flip_horizontally,
                     flip_vertically,
+                    scale: 1.0 / conf.pixels_per_unit,
                 }),
             }),
             godot::NodeKind::Sprite2D => Some(In2D {
                 position,
                 z_index,
+                visible,
                 texture: Some(SpriteTexture {
                     path: path.unwrap_or_else(|| {
                         panic!(
@@ -133,7 +299,6 @@ pub(crate) fn from_scene(
                             parsed_node.name
                         )
                     }),
-                    visible,
                     color,
                     animation: {
                         assert!(animation.is_none());
@@ -141,24 +306,39 @@ pub(crate) fn from_scene(
                     },
                     flip_horizontally,
                     flip_vertically,
+                    scale: 1.0 / conf.pixels_per_unit,
                 }),
             }),
-            godot::NodeKind::Node2D => Some(In2D {
+            godot::NodeKind::Node2D
+            | godot::NodeKind::Path2D
+            | godot::NodeKind::CanvasLayer
+            | godot::NodeKind::ParallaxBackground
+            | godot::NodeKind::ParallaxLayer
+            | godot::NodeKind::Area2D => Some(In2D {
                 position,
                 z_index,
+                visible,
                 texture: {
                     assert!(path.is_none());
                     assert!(animation.is_none());
                     None
                 },
             }),
-            godot::NodeKind::Node => {
+            godot::NodeKind::Node | godot::NodeKind::Timer => {
                 assert_eq!(Vec2::ZERO, position);
                 assert!(z_index.is_none());
+                assert!(
+                    visible,
+                    "Node '{}': only 2D nodes can be hidden",
+                    parsed_node.name
+                );
                 assert!(path.is_none());
                 assert!(animation.is_none());
                 None
             }
+            godot::NodeKind::CollisionShape2D => {
+                unreachable!("collision shapes are part of their Area2D")
+            }
             godot::NodeKind::Other(kind) => {
                 panic!("Node kind '{kind}' is not supported")
             }
@@ -167,29 +347,24 @@ pub(crate) fn from_scene(
         let node = RscnNode {
             metadata,
             in_2d,
+            timer,
+            path_points,
+            // filled in by the collision shapes
+            area: default(),
+            layer,
             children: default(),
         };
 
-        let parent = parsed_node
-            .parent
-            .expect("each node except for root should have a parent");
-
-        if parent == "." {
-            root.children.insert(NodeName(parsed_node.name), node);
-        } else {
-            let mut current_parent = &mut root;
-
-            for fragment in parent.split('/') {
-                current_parent = current_parent
-                    .children
-                    .get_mut(&NodeName(fragment.to_string()))
-                    .expect("node path should point to a valid parent node");
-            }
+        node_at_path(&mut root, &parent)
+            .children
+            .insert(NodeName(parsed_node.name), node);
+    }
 
-            current_parent
-                .children
-                .insert(NodeName(parsed_node.name), node);
-        }
+    for path in areas {
+        assert!(
+            !node_at_path(&mut root, &path).area.is_empty(),
+            "Node '{path}': Area2D should have at least one CollisionShape2D"
+        );
     }
 
     Ok(TscnTree {
@@ -198,6 +373,98 @@ pub(crate) fn from_scene(
     })
 }
 
+/// The path is relative to the root node, which itself is `"."`.
+fn node_at_path<'a>(root: &'a mut RscnNode, path: &str) -> &'a mut RscnNode {
+    if path == "." {
+        return root;
+    }
+
+    let mut current = root;
+    for fragment in path.split('/') {
+        current = current
+            .children
+            .get_mut(&NodeName(fragment.to_string()))
+            .expect("node path should point to a valid parent node");
+    }
+
+    current
+}
+
+/// Textures are referenced by nodes directly and by atlas sub resources.
+fn diagnose_unused_ext_resources(
+    scene: &godot::Scene,
+    root_node_name: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let used_by_nodes = scene
+        .nodes
+        .iter()
+        .flat_map(|node| node.section.get(&NodeSectionKey::TextureExtResource));
+    let used_by_sub_resources = scene.sub_resources.iter().flat_map(|res| {
+        res.section.get(&SubResourceSectionKey::AtlasExtResource)
+    });
+    let used: HashSet<_> = used_by_nodes
+        .chain(used_by_sub_resources)
+        .filter_map(|value| value.clone().try_into_ext_resource().ok())
+        .map(|id| id.0)
+        .collect();
+
+    for res in &scene.ext_resources {
+        let id = &res.id().0;
+        if !used.contains(id) {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                node: NodeName(root_node_name.to_owned()),
+                message: format!("ext_resource '{id}' is never used"),
+            });
+        }
+    }
+}
+
+/// Background layers are new to the scenes and Godot has many properties for
+/// them that we don't support.
+/// Rather than warn, we refuse them so that the scene doesn't silently look
+/// different in the game.
+fn reject_unsupported_layer_keys(node: &godot::Node) -> miette::Result<()> {
+    let supported: &[NodeSectionKey] = match node.kind {
+        godot::NodeKind::CanvasLayer | godot::NodeKind::ParallaxBackground => {
+            &[
+                NodeSectionKey::Layer,
+                NodeSectionKey::Visible,
+                NodeSectionKey::StringMetadata,
+            ]
+        }
+        godot::NodeKind::ParallaxLayer => &[
+            NodeSectionKey::MotionScale,
+            NodeSectionKey::Position,
+            NodeSectionKey::ZIndex,
+            NodeSectionKey::Visible,
+            NodeSectionKey::StringMetadata,
+        ],
+        _ => return Ok(()),
+    };
+
+    for (key, value) in &node.section {
+        if supported.contains(key) {
+            continue;
+        }
+
+        let key = match key {
+            NodeSectionKey::Other(key) => key.clone(),
+            key => format!("{key:?}"),
+        };
+        miette::bail! {
+            labels = vec![LabeledSpan::at(value.span(), "this property")],
+            help = format!("Supported properties are {supported:?}"),
+            "Node '{}': property '{key}' of {:?} is not supported",
+            node.name,
+            node.kind,
+        }
+    }
+
+    Ok(())
+}
+
 fn apply_section(
     conf: &Config,
     scene: &godot::Scene,
@@ -211,12 +478,17 @@ fn apply_section(
         color,
         flip_horizontally,
         flip_vertically,
+        wait_time,
+        autostart,
+        one_shot,
+        curve,
+        layer,
+        motion_scale,
+        shape,
     }: &mut Properties,
-    section_key: rscn::godot::NodeSectionKey,
+    section_key: NodeSectionKey,
     section_value: rscn::value::SpannedValue,
 ) -> miette::Result<()> {
-    use rscn::godot::NodeSectionKey;
-
     match section_key {
         NodeSectionKey::SelfModulate => {
             let (r, g, b, a) = section_value.into_self_modulate_color_rgba()?;
@@ -246,13 +518,7 @@ fn apply_section(
         }
         NodeSectionKey::Position => {
             let (x, godot_y) = section_value.into_vector2()?;
-            // This is the conversion from godot to bevy coordinates.
-            // Note that not all Y coords should be converted.
-            // For example sprite atlas positions into textures in bevy follow
-            // the image processing convention where the origin is
-            // at the top left.
-            let bevy_y = -godot_y;
-            *position = Vec2::new(x as _, bevy_y as _);
+            *position = conf.to_bevy_coords(x, godot_y);
         }
         NodeSectionKey::StringMetadata => {
             let (_, map) = section_value.try_into_object()?;
@@ -310,6 +576,88 @@ fn apply_section(
                 .expect("Autoplay always comes after sprite_frames")
                 .should_autoload = true;
         }
+        NodeSectionKey::WaitTime => {
+            let (_, seconds) = section_value.try_into_number()?;
+            assert!(seconds > 0.0, "Timer wait_time must be positive");
+            assert!(
+                wait_time.replace(seconds as _).is_none(),
+                "Node should not have more than one wait_time"
+            );
+        }
+        NodeSectionKey::Autostart => {
+            *autostart = section_value.try_into_bool().map(|(_, b)| b)?;
+        }
+        NodeSectionKey::OneShot => {
+            *one_shot = section_value.try_into_bool().map(|(_, b)| b)?;
+        }
+        NodeSectionKey::Curve => {
+            let id = section_value.try_into_sub_resource()?;
+
+            let res = scene
+                .sub_resources
+                .iter()
+                .find(|res| res.id == id)
+                .expect("sub resource should exist");
+            let Some(section_value) =
+                res.section.get(&SubResourceSectionKey::CurveData)
+            else {
+                panic!("Curve2D should have _data section key")
+            };
+
+            let points = section_value
+                .clone()
+                .try_into_curve2d_points()?
+                .into_iter()
+                .map(|(x, godot_y)| conf.to_bevy_coords(x, godot_y))
+                .collect();
+            assert!(
+                curve.replace(points).is_none(),
+                "Node should not have more than one curve"
+            );
+        }
+        NodeSectionKey::Layer => {
+            let (_, n) = section_value.try_into_number()?;
+            assert!(
+                layer.replace(n as _).is_none(),
+                "Node should not have more than one layer"
+            );
+        }
+        NodeSectionKey::MotionScale => {
+            // a scale, not a position, hence no conversion of coordinates
+            let (x, y) = section_value.into_vector2()?;
+            assert!(
+                motion_scale.replace(Vec2::new(x as _, y as _)).is_none(),
+                "Node should not have more than one motion_scale"
+            );
+        }
+        NodeSectionKey::Shape => {
+            let id = section_value.try_into_sub_resource()?;
+
+            let res = scene
+                .sub_resources
+                .iter()
+                .find(|res| res.id == id)
+                .expect("sub resource should exist");
+            assert_eq!(
+                godot::SubResourceKind::RectangleShape2D,
+                res.kind,
+                "Only RectangleShape2D is supported as a shape"
+            );
+
+            let (w, h) = res
+                .section
+                .get(&SubResourceSectionKey::Size)
+                .map(|size| size.clone().into_vector2())
+                .transpose()?
+                // Godot's default
+                .unwrap_or((20.0, 20.0));
+            // a size, not a position, hence no flipping of the Y
+            let size = Vec2::new(w as _, h as _) / conf.pixels_per_unit;
+            assert!(
+                shape.replace(size).is_none(),
+                "Node should not have more than one shape"
+            );
+        }
         NodeSectionKey::FrameProgress => {
             warn!("Godot's FrameProgress is not supported yet");
         }
@@ -348,15 +696,18 @@ fn apply_section(
             let mut max_y = 0.0f32;
             let mut max_x = 0.0f32;
 
+            // all frames shown equally long is the common case
+            let frame_durations =
+                if anim.frames.iter().all(|(_, duration)| *duration == 1.0) {
+                    vec![]
+                } else {
+                    anim.frames.iter().map(|(_, duration)| *duration).collect()
+                };
+
             let frames = anim
                 .frames
                 .into_iter()
-                .map(|(texture_id, duration)| {
-                    assert!(
-                        duration == 1.0,
-                        "We don't support frame durations"
-                    );
-
+                .map(|(texture_id, _)| {
                     map_texture_to_atlas_rect(
                         conf, scene, path, &mut max_x, &mut max_y, texture_id,
                     )
@@ -368,6 +719,7 @@ fn apply_section(
                     should_endless_loop: anim.loop_,
                     fps: anim.speed,
                     frames,
+                    frame_durations,
                     size: Vec2::new(max_x, max_y),
                     // Can be set by [NodeSectionKey::Autoplay]
                     should_autoload: false,
@@ -385,6 +737,18 @@ fn apply_section(
 }
 
 impl Config {
+    /// This is the conversion from godot to bevy coordinates.
+    /// Note that not all Y coords should be converted.
+    /// For example sprite atlas positions into textures in bevy follow
+    /// the image processing convention where the origin is at the top left.
+    ///
+    /// The Y is flipped first and then the position is scaled down by
+    /// [`Config::pixels_per_unit`].
+    fn to_bevy_coords(&self, x: f64, godot_y: f64) -> Vec2 {
+        let bevy_y = -godot_y;
+        Vec2::new(x as _, bevy_y as _) / self.pixels_per_unit
+    }
+
     fn to_prefixless_path(&self, godot_path: &str) -> String {
         assert!(godot_path.starts_with(&self.asset_path_prefix));
         godot_path[self.asset_path_prefix.len()..].to_owned()
@@ -403,6 +767,13 @@ impl Default for Properties {
             color: None,
             flip_horizontally: false,
             flip_vertically: false,
+            wait_time: None,
+            autostart: false,
+            one_shot: false,
+            curve: None,
+            layer: None,
+            motion_scale: None,
+            shape: None,
         }
     }
 }
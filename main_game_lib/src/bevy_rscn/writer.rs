@@ -0,0 +1,452 @@
+//! Converts a [`TscnTree`] back into the .tscn text, see
+//! [`TscnTree::to_tscn`].
+//!
+//! Only what the tree holds is written.
+//! Resource ids are generated anew, textures get an `ext_resource` each and
+//! every sprite frame and curve gets its own `sub_resource`.
+//! Each rectangle of an area is written as a `CollisionShape2D` child.
+
+use std::fmt::Write;
+
+use bevy::math::{Rect, Vec2};
+
+use crate::bevy_rscn::{
+    Config, LayerNode, NodeName, RscnNode, SpriteFrames, TscnTree,
+};
+
+#[derive(Default)]
+struct Writer {
+    /// Godot paths of textures.
+    /// The id of each is its index plus one.
+    ext_resources: Vec<String>,
+    /// Whole sections, each with its heading.
+    sub_resources: Vec<String>,
+    /// Whole sections, each with its heading.
+    nodes: Vec<String>,
+}
+
+impl TscnTree {
+    /// Writes the tree as a Godot scene that [`crate::bevy_rscn::from_tscn`]
+    /// parses into an equal tree and that Godot can open.
+    ///
+    /// Godot properties that we don't support were dropped on parsing and
+    /// are therefore not written back.
+    /// Children are written sorted by name.
+    pub fn to_tscn(&self, config: &Config) -> String {
+        let mut writer = Writer::default();
+
+        // the root must have no extra data, see the tree parsing
+        writer.nodes.push(format!(
+            "[node name=\"{}\" type=\"Node2D\"]\n",
+            self.root_node_name.as_str()
+        ));
+        for (name, child) in sorted_children(&self.root) {
+            writer.write_node(config, name, child, ".");
+        }
+
+        let Writer {
+            ext_resources,
+            sub_resources,
+            nodes,
+        } = writer;
+
+        let load_steps = ext_resources.len() + sub_resources.len() + 1;
+        let mut tscn = if load_steps > 1 {
+            format!("[gd_scene load_steps={load_steps} format=3]\n")
+        } else {
+            "[gd_scene format=3]\n".to_string()
+        };
+
+        if !ext_resources.is_empty() {
+            tscn.push('\n');
+        }
+        for (index, path) in ext_resources.iter().enumerate() {
+            writeln!(
+                tscn,
+                "[ext_resource type=\"Texture2D\" path=\"{path}\" id=\"{}\"]",
+                index + 1
+            )
+            .expect("Writing to string cannot fail");
+        }
+
+        for section in sub_resources.iter().chain(&nodes) {
+            tscn.push('\n');
+            tscn.push_str(section);
+        }
+
+        tscn
+    }
+}
+
+impl Writer {
+    fn write_node(
+        &mut self,
+        config: &Config,
+        name: &NodeName,
+        node: &RscnNode,
+        parent: &str,
+    ) {
+        let name = name.as_str();
+        let mut section = format!(
+            "[node name=\"{name}\" type=\"{}\" parent=\"{parent}\"]\n",
+            node_kind(node)
+        );
+        let mut key = |key: &str, value: String| {
+            writeln!(section, "{key} = {value}")
+                .expect("Writing to string cannot fail");
+        };
+
+        if let Some(in_2d) = &node.in_2d {
+            if let Some(z_index) = in_2d.z_index {
+                key("z_index", z_index.to_string());
+            }
+            if !in_2d.visible {
+                key("visible", "false".to_string());
+            }
+            if let Some(color) =
+                in_2d.texture.as_ref().and_then(|texture| texture.color)
+            {
+                let color = color.to_srgba();
+                key(
+                    "self_modulate",
+                    format!(
+                        "Color({}, {}, {}, {})",
+                        color.red, color.green, color.blue, color.alpha
+                    ),
+                );
+            }
+            // Godot omits the position if it's zero
+            if in_2d.position != Vec2::ZERO {
+                let (x, y) = config.to_godot_coords(in_2d.position);
+                key("position", format!("Vector2({x}, {y})"));
+            }
+
+            if let Some(texture) = &in_2d.texture {
+                let ext_id = self.ext_resource(config, &texture.path);
+
+                if let Some(animation) = &texture.animation {
+                    let id = self.sprite_frames(&ext_id, animation);
+                    key("sprite_frames", format!("SubResource(\"{id}\")"));
+                    if animation.should_autoload {
+                        key("autoplay", "\"default\"".to_string());
+                    }
+                    if animation.first_index != 0 {
+                        key("frame", animation.first_index.to_string());
+                    }
+                } else {
+                    key("texture", format!("ExtResource(\"{ext_id}\")"));
+                }
+
+                if texture.flip_horizontally {
+                    key("flip_h", "true".to_string());
+                }
+                if texture.flip_vertically {
+                    key("flip_v", "true".to_string());
+                }
+            }
+
+            if !node.path_points.is_empty() {
+                let id = self.curve(config, &node.path_points);
+                key("curve", format!("SubResource(\"{id}\")"));
+            }
+        }
+
+        // Godot omits the defaults
+        match node.layer {
+            Some(LayerNode::Canvas { layer }) if layer != 1 => {
+                key("layer", layer.to_string());
+            }
+            Some(LayerNode::ParallaxBackground { layer }) if layer != -100 => {
+                key("layer", layer.to_string());
+            }
+            Some(LayerNode::Parallax { motion_scale })
+                if motion_scale != Vec2::ONE =>
+            {
+                key(
+                    "motion_scale",
+                    format!("Vector2({}, {})", motion_scale.x, motion_scale.y),
+                );
+            }
+            _ => {}
+        }
+
+        if let Some(timer) = &node.timer {
+            key("wait_time", timer.wait_time.to_string());
+            key("one_shot", timer.one_shot.to_string());
+            key("autostart", timer.autostart.to_string());
+        }
+
+        let mut metadata: Vec<_> = node.metadata.iter().collect();
+        metadata.sort();
+        for (metadata_key, value) in metadata {
+            // the key is not quoted so there's nothing to escape it with
+            assert!(
+                is_identifier(metadata_key),
+                "Node '{name}': metadata key '{}' must be an identifier",
+                metadata_key.escape_debug()
+            );
+            key(&format!("metadata/{metadata_key}"), quoted(value));
+        }
+
+        self.nodes.push(section);
+
+        let path = if parent == "." {
+            name.to_string()
+        } else {
+            format!("{parent}/{name}")
+        };
+        for (index, area) in node.area.iter().enumerate() {
+            self.write_collision_shape(config, index, *area, &path);
+        }
+        for (child_name, child) in sorted_children(node) {
+            self.write_node(config, child_name, child, &path);
+        }
+    }
+
+    /// The area is relative to the parent `Area2D` node.
+    /// The index tells apart the names of the shapes of the same area the
+    /// way Godot does, i.e. "CollisionShape2D", "CollisionShape2D2", ...
+    fn write_collision_shape(
+        &mut self,
+        config: &Config,
+        index: usize,
+        area: Rect,
+        parent: &str,
+    ) {
+        let size = area.size() * config.pixels_per_unit;
+        let id = self.sub_resource(
+            "RectangleShape2D",
+            format!("size = Vector2({}, {})\n", size.x, size.y),
+        );
+
+        let name = match index {
+            0 => "CollisionShape2D".to_owned(),
+            index => format!("CollisionShape2D{}", index + 1),
+        };
+        let mut section = format!(
+            "[node name=\"{name}\" type=\"CollisionShape2D\" \
+            parent=\"{parent}\"]\n"
+        );
+        // Godot omits the position if it's zero
+        if area.center() != Vec2::ZERO {
+            let (x, y) = config.to_godot_coords(area.center());
+            writeln!(section, "position = Vector2({x}, {y})")
+                .expect("Writing to string cannot fail");
+        }
+        writeln!(section, "shape = SubResource(\"{id}\")")
+            .expect("Writing to string cannot fail");
+
+        self.nodes.push(section);
+    }
+
+    /// Returns the id of the texture, the same path has the same id.
+    fn ext_resource(&mut self, config: &Config, path: &str) -> String {
+        let godot_path = format!("{}{path}", config.asset_path_prefix);
+
+        let index = self
+            .ext_resources
+            .iter()
+            .position(|p| p == &godot_path)
+            .unwrap_or_else(|| {
+                self.ext_resources.push(godot_path);
+                self.ext_resources.len() - 1
+            });
+
+        (index + 1).to_string()
+    }
+
+    /// Each frame is an atlas texture sub resource.
+    /// Returns the id of the sprite frames sub resource.
+    fn sprite_frames(
+        &mut self,
+        ext_id: &str,
+        animation: &SpriteFrames,
+    ) -> String {
+        let frames: Vec<_> = animation
+            .frames
+            .iter()
+            .enumerate()
+            .map(|(index, rect)| {
+                let id = self.sub_resource(
+                    "AtlasTexture",
+                    format!(
+                        "atlas = ExtResource(\"{ext_id}\")\n\
+                        region = Rect2({}, {}, {}, {})\n",
+                        rect.min.x,
+                        rect.min.y,
+                        rect.width(),
+                        rect.height()
+                    ),
+                );
+                let duration = animation
+                    .frame_durations
+                    .get(index)
+                    .copied()
+                    .unwrap_or(1.0);
+
+                format!(
+                    "{{\n\"duration\": {duration},\n\
+                    \"texture\": SubResource(\"{id}\")\n}}"
+                )
+            })
+            .collect();
+
+        self.sub_resource(
+            "SpriteFrames",
+            format!(
+                "animations = [{{\n\"frames\": [{}],\n\"loop\": {},\n\
+                \"name\": &\"default\",\n\"speed\": {}\n}}]\n",
+                frames.join(", "),
+                animation.should_endless_loop,
+                animation.fps
+            ),
+        )
+    }
+
+    /// We only support straight segments so the control handles are zero.
+    /// Returns the id of the curve sub resource.
+    fn curve(&mut self, config: &Config, points: &[Vec2]) -> String {
+        let numbers: Vec<_> = points
+            .iter()
+            .map(|point| {
+                let (x, y) = config.to_godot_coords(*point);
+                format!("0, 0, 0, 0, {x}, {y}")
+            })
+            .collect();
+
+        self.sub_resource(
+            "Curve2D",
+            format!(
+                "_data = {{\n\"points\": PackedVector2Array({})\n}}\n\
+                point_count = {}\n",
+                numbers.join(", "),
+                points.len()
+            ),
+        )
+    }
+
+    /// Returns the id of the new sub resource.
+    fn sub_resource(&mut self, kind: &str, keys: String) -> String {
+        let id = format!("{kind}_{}", self.sub_resources.len() + 1);
+        self.sub_resources.push(format!(
+            "[sub_resource type=\"{kind}\" id=\"{id}\"]\n{keys}"
+        ));
+
+        id
+    }
+}
+
+impl Config {
+    /// Inverse of the conversion from Godot to bevy coordinates.
+    fn to_godot_coords(&self, position: Vec2) -> (f32, f32) {
+        let godot = position * self.pixels_per_unit;
+        (godot.x, -godot.y)
+    }
+}
+
+/// Same type as the node was parsed from.
+fn node_kind(node: &RscnNode) -> &'static str {
+    match &node.layer {
+        Some(LayerNode::Canvas { .. }) => return "CanvasLayer",
+        Some(LayerNode::ParallaxBackground { .. }) => {
+            return "ParallaxBackground"
+        }
+        Some(LayerNode::Parallax { .. }) => return "ParallaxLayer",
+        None => {}
+    }
+
+    match &node.in_2d {
+        None if node.timer.is_some() => "Timer",
+        None => "Node",
+        Some(_) if !node.path_points.is_empty() => "Path2D",
+        Some(_) if !node.area.is_empty() => "Area2D",
+        Some(in_2d) => match &in_2d.texture {
+            Some(texture) if texture.animation.is_some() => "AnimatedSprite2D",
+            Some(_) => "Sprite2D",
+            None => "Node2D",
+        },
+    }
+}
+
+/// Godot, and therefore our parser, only accepts identifiers as metadata
+/// keys.
+fn is_identifier(key: &str) -> bool {
+    !key.is_empty()
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Escapes the quotes, backslashes and line breaks the same way Godot does.
+fn quoted(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+fn sorted_children(node: &RscnNode) -> Vec<(&NodeName, &RscnNode)> {
+    let mut children: Vec<_> = node.children.iter().collect();
+    children.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+    children
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bevy_rscn::from_tscn;
+
+    #[test]
+    fn it_round_trips_scenes() {
+        let scenes_dir = format!(
+            "{}/../main_game/assets/scenes",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let config = Config::default();
+
+        for scene in ["twinpeaks_apartment", "building1_player_floor"] {
+            let path = format!("{scenes_dir}/{scene}.tscn");
+            let tscn = std::fs::read_to_string(&path)
+                .unwrap_or_else(|err| panic!("Cannot read {path}: {err}"));
+
+            let tree = from_tscn(&tscn, &config);
+            let written = tree.to_tscn(&config);
+            let reparsed = from_tscn(&written, &config);
+
+            assert_eq!(tree, reparsed, "{scene} differs after round trip");
+            // the output is stable
+            assert_eq!(written, reparsed.to_tscn(&config));
+        }
+    }
+
+    #[test]
+    fn it_escapes_metadata_values() {
+        let config = Config::default();
+        let tscn = r#"[gd_scene format=3]
+
+[node name="Root" type="Node2D"]
+
+[node name="Sign" type="Node" parent="."]
+metadata/label = "Say \"hi\"\nthen \\ leave"
+"#;
+
+        let tree = from_tscn(tscn, &config);
+        assert_eq!(
+            "Say \"hi\"\nthen \\ leave",
+            tree.root.children["Sign"].metadata["label"]
+        );
+
+        let written = tree.to_tscn(&config);
+        assert!(written.contains(r#""Say \"hi\"\nthen \\ leave""#));
+        assert_eq!(tree, from_tscn(&written, &config));
+    }
+}
@@ -15,31 +15,28 @@ pub mod enter_dark_door;
 
 use std::sync::OnceLock;
 
-use bevy::{
-    asset::AssetPath, ecs::system::SystemId, prelude::*,
-    render::view::RenderLayers,
-};
+use bevy::{asset::AssetPath, ecs::system::SystemId, prelude::*};
 use bevy_grid_squared::{GridDirection, Square};
 use bevy_kira_audio::{Audio, AudioControl};
 use common_ext::QueryExt;
 use common_loading_screen::{LoadingScreenSettings, LoadingScreenState};
 use common_store::GlobalStore;
 use common_visuals::{
-    camera::{order, render_layer, MainCamera},
+    camera::MainCamera,
+    letterbox::{hide_letterbox, show_letterbox, Letterbox},
     AtlasAnimation, AtlasAnimationStep, AtlasAnimationTimer,
     BeginInterpolationEvent,
 };
 
-use self::top_down::cameras::{ManualControl, SyncWithPlayer};
+use self::top_down::cameras::{LeadingPlayer, ManualControl, SyncWithPlayer};
 use crate::{
     dialog::{self, fe::portrait::PortraitDialog, StartDialogWhenLoaded},
     prelude::*,
     top_down::{actor::player::TakeAwayPlayerControl, Actor, ActorTarget},
 };
 
-const LETTERBOXING_FADE_IN_DURATION: Duration = from_millis(500);
-const LETTERBOXING_FADE_OUT_DURATION: Duration = from_millis(250);
-const LETTERBOXING_QUAD_FADE_IN_HEIGHT: Val = Val::Percent(15.0);
+/// How much of the screen height each of the letterboxing bars covers.
+const LETTERBOXING_FRACTION: f32 = 0.15;
 
 /// Will be true if there's a cutscene playing.
 pub fn in_cutscene() -> impl FnMut(Option<Res<Cutscene>>) -> bool {
@@ -53,13 +50,8 @@ pub trait IntoCutscene {
 
     /// If true, the cutscene will be letterboxed with two black bars: above and
     /// below the screen.
-    /// A camera and two quads are spawned for this purpose.
-    /// They are despawned when the cutscene ends.
-    ///
-    /// Make sure you run the [`common_visuals::systems::interpolate`]
-    /// system.
-    ///
-    /// <https://en.wikipedia.org/wiki/Letterboxing_(filming)>
+    /// See [`common_visuals::letterbox`].
+    /// The bars are hidden when the cutscene ends.
     fn has_letterboxing() -> bool {
         false
     }
@@ -103,9 +95,9 @@ pub struct Cutscene {
     /// This is the stopwatch that is used to measure time.
     /// It resets after every step.
     stopwatch: Stopwatch,
-    /// If letterboxing was spawned, then this contains the three entities that
-    /// must be despawned when the cutscene ends.
-    letterboxing_entities: Option<[Entity; 3]>,
+    /// If letterboxing was shown, then the cutscene is kept alive until the
+    /// [`Letterbox`] is hidden.
+    has_letterboxing: bool,
 }
 
 /// What are the possible operations that a cutscene can perform?
@@ -232,9 +224,9 @@ pub enum CutsceneStep {
     /// Sets the [`Actor::direction`] property to the given value.
     /// This will be overwritten if the actor starts walking.
     SetActorFacingDirection(Entity, GridDirection),
-    /// Removes the [`SyncWithPlayer`] component from [`MainCamera`] entity and
-    /// adds [`ManualControl`] component. This is useful for manual camera
-    /// control.
+    /// Removes the [`SyncWithPlayer`] and [`LeadingPlayer`] components from
+    /// [`MainCamera`] entity and adds [`ManualControl`] component. This is
+    /// useful for manual camera control.
     ///
     /// [`ManualControl`] must be returned with
     /// [`Self::ReleaseManualMainCameraControl`].
@@ -259,13 +251,6 @@ pub enum Destination {
     Position(Vec2),
 }
 
-#[derive(Component)]
-struct LetterboxingCamera;
-#[derive(Component)]
-struct LetterboxingTopQuad;
-#[derive(Component)]
-struct LetterboxingBottomQuad;
-
 /// Registers all the necessary systems that handle cutscene logic.
 pub struct Plugin;
 
@@ -291,92 +276,16 @@ pub fn spawn_cutscene<Scene: IntoCutscene>(
     debug_assert!(!sequence.is_empty());
     let has_letterboxing = Scene::has_letterboxing();
 
-    let letterboxing_entities = if has_letterboxing {
-        // RenderLayers will be inserted once the cutscene is loaded.
-        // This is to prevent a flicker when the quads are rendered but the
-        // pixel zoom is not yet set.
-        let camera = cmd
-            .spawn((
-                Name::new("Cutscene camera"),
-                LetterboxingCamera,
-                RenderLayers::layer(render_layer::CUTSCENE_LETTERBOXING),
-                Camera2dBundle {
-                    camera: Camera {
-                        hdr: true,
-                        order: order::CUTSCENE_LETTERBOXING,
-                        clear_color: ClearColorConfig::None,
-                        ..default()
-                    },
-                    ..default()
-                },
-            ))
-            .id();
-
-        let mut top_entities = cmd.spawn((
-            Name::new("Letterboxing: top quad"),
-            LetterboxingTopQuad,
-            TargetCamera(camera),
-            RenderLayers::layer(render_layer::CUTSCENE_LETTERBOXING),
-            NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(0.0),
-                    position_type: PositionType::Absolute,
-                    left: Val::Percent(0.0),
-                    top: Val::Percent(0.0),
-                    ..default()
-                },
-                background_color: Color::BLACK.into(),
-                ..default()
-            },
-        ));
-        let top = top_entities.id();
-        BeginInterpolationEvent::of_ui_style_height(
-            top,
-            None,
-            LETTERBOXING_QUAD_FADE_IN_HEIGHT,
-        )
-        .over(LETTERBOXING_FADE_IN_DURATION)
-        .insert_to(&mut top_entities);
-
-        let mut bottom_entities = cmd.spawn((
-            Name::new("Letterboxing: bottom quad"),
-            LetterboxingBottomQuad,
-            TargetCamera(camera),
-            RenderLayers::layer(render_layer::CUTSCENE_LETTERBOXING),
-            NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(0.0),
-                    position_type: PositionType::Absolute,
-                    left: Val::Percent(0.0),
-                    bottom: Val::Percent(0.0),
-                    ..default()
-                },
-                background_color: Color::BLACK.into(),
-                ..default()
-            },
-        ));
-        let bottom = bottom_entities.id();
-        BeginInterpolationEvent::of_ui_style_height(
-            top,
-            None,
-            LETTERBOXING_QUAD_FADE_IN_HEIGHT,
-        )
-        .over(LETTERBOXING_FADE_IN_DURATION)
-        .insert_to(&mut bottom_entities);
-
-        Some([camera, top, bottom])
-    } else {
-        None
-    };
+    if has_letterboxing {
+        show_letterbox(cmd, LETTERBOXING_FRACTION);
+    }
 
     let cutscene = Cutscene {
         is_over: false,
         sequence,
         sequence_index: 0,
         stopwatch: Stopwatch::new(),
-        letterboxing_entities,
+        has_letterboxing,
     };
 
     // this will be picked up by `schedule_current_step` system
@@ -385,15 +294,21 @@ pub fn spawn_cutscene<Scene: IntoCutscene>(
 
 /// Ticks the stopwatch and schedules the system for the current step of the
 /// cutscene.
+/// Once the cutscene is over and the letterboxing is hidden, removes the
+/// cutscene.
 /// This must run only if there's a cutscene resource.
 fn schedule_current_step(
     mut cmd: Commands,
     mut cutscene: ResMut<Cutscene>,
     time: Res<Time>,
+    letterbox: Option<Res<Letterbox>>,
 ) {
     if !cutscene.is_over {
         cutscene.stopwatch.tick(time.delta());
         cutscene.schedule_current_step(&mut cmd);
+    } else if letterbox.is_none() {
+        trace!("Despawning cutscene");
+        cmd.remove_resource::<Cutscene>();
     }
 }
 
@@ -416,32 +331,10 @@ impl Cutscene {
     fn force_despawn(&mut self, cmd: &mut Commands) {
         self.is_over = true;
 
-        if let Some(entities) = self.letterboxing_entities.take() {
-            // smoothly animate quads out and when down, despawn everything
-
-            BeginInterpolationEvent::of_ui_style_height(
-                entities[2],
-                None,
-                Val::Percent(0.0),
-            )
-            .over(LETTERBOXING_FADE_OUT_DURATION)
-            .insert(cmd);
-
-            BeginInterpolationEvent::of_ui_style_height(
-                entities[1],
-                None,
-                Val::Percent(0.0),
-            )
-            .over(LETTERBOXING_FADE_OUT_DURATION)
-            .when_finished_do(move |cmd: &mut Commands| {
-                trace!("Despawning cutscene");
-                cmd.remove_resource::<Cutscene>();
-
-                cmd.entity(entities[0]).despawn_recursive();
-                cmd.entity(entities[1]).despawn_recursive();
-                cmd.entity(entities[2]).despawn_recursive();
-            })
-            .insert(cmd);
+        if self.has_letterboxing {
+            // smoothly animate bars out, the resource is removed by
+            // `schedule_current_step` once they are gone
+            hide_letterbox(cmd);
         } else {
             cmd.remove_resource::<Cutscene>();
         }
@@ -889,7 +782,7 @@ fn claim_manual_main_camera_control(
 
     if let Some(entity) = camera.get_single_or_none() {
         cmd.entity(entity)
-            .remove::<SyncWithPlayer>()
+            .remove::<(SyncWithPlayer, LeadingPlayer)>()
             .insert(ManualControl);
     }
 
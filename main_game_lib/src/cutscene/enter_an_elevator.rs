@@ -13,7 +13,6 @@ use common_visuals::{
     AtlasAnimation, AtlasAnimationEnd, AtlasAnimationStep, AtlasAnimationTimer,
     BeginAtlasAnimation, EASE_IN_OUT,
 };
-use top_down::layout::LAYOUT;
 
 use crate::{
     cutscene::{self, CutsceneStep, IntoCutscene},
@@ -37,8 +36,8 @@ struct EnterAnElevator {
     /// The camera entity.
     /// Will be moved to center on the player.
     camera: Entity,
-    /// Get position player should jump onto.
-    point_in_elevator: Vec2,
+    /// The square player should jump onto.
+    in_elevator: Square,
     /// What dialog to show the player.
     dialog: dialog::DialogRef,
     /// Return true if the player should exit the elevator.
@@ -60,14 +59,12 @@ impl IntoCutscene for EnterAnElevator {
             // has atlas animation component, see layout where it's spawned
             elevator,
             camera,
-            point_in_elevator,
+            in_elevator,
             is_cancelled: cancel_and_exit_elevator,
             on_took_the_elevator: change_global_state,
             dialog,
         } = self;
 
-        let in_elevator = LAYOUT.world_pos_to_square(point_in_elevator);
-
         vec![
             TakeAwayPlayerControl(player),
             SetActorFacingDirection(player, GridDirection::Top),
@@ -164,7 +161,7 @@ pub fn spawn(
     player: Entity,
     elevator: Entity,
     camera: Entity,
-    point_in_elevator: Vec2,
+    in_elevator: Square,
     // LOCALIZATION
     choices: &[(GlobalGameStateTransition, &str)],
 ) {
@@ -278,7 +275,7 @@ pub fn spawn(
         player,
         elevator,
         camera,
-        point_in_elevator,
+        in_elevator,
         dialog: dialog_strong_handle.into(),
         is_cancelled: did_choose_to_cancel_and_exit_the_elevator,
         on_took_the_elevator,
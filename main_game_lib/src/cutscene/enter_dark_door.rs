@@ -1,14 +1,13 @@
 //! Cut scene in which the player goes into door and slowly fades out the
 //! sprite so it's as if they are entering a dark room.
 
-use bevy_grid_squared::{sq, GridDirection};
+use bevy_grid_squared::{sq, GridDirection, Square};
 use common_loading_screen::LoadingScreenSettings;
 use common_story::Character;
 
 use crate::{
     cutscene::{CutsceneStep, IntoCutscene},
     prelude::*,
-    top_down::layout::LAYOUT,
 };
 
 /// Cut scene in which the player goes into door and slowly fades out the
@@ -19,9 +18,9 @@ pub struct EnterDarkDoor {
     pub player: Entity,
     /// We animate it to second frame to simulate door opening.
     pub door: Entity,
-    /// Position at the tip of the door.
+    /// Square at the tip of the door.
     /// If the player is here, they can walk up to the door.
-    pub door_entrance: Vec2,
+    pub door_entrance: Square,
     /// The global game state to change to.
     pub change_global_state_to: GlobalGameState,
     /// Using this transition.
@@ -43,8 +42,6 @@ impl IntoCutscene for EnterDarkDoor {
             loading_screen,
         } = self;
 
-        let walk_towards = LAYOUT.world_pos_to_square(door_entrance);
-
         // walking over several tiles
         let walk_time = Character::Winnie.slow_step_time() * 6;
 
@@ -60,7 +57,7 @@ impl IntoCutscene for EnterDarkDoor {
             Sleep(from_millis(400)),
             BeginSimpleWalkTo {
                 with: player,
-                square: walk_towards + sq(0, 3),
+                square: door_entrance + sq(0, 3),
                 planned: None,
                 step_time: Some(walk_time),
             },
@@ -112,6 +112,8 @@ impl GuardKind {
                 node_name: NodeName::Explicit(namespace, node_name),
                 ..
             } => {
+                // untyped so that a state of unexpected shape falls back to
+                // the default instead of panicking
                 let from_store = store
                     .guard_state(self, (namespace, node_name))
                     .get()
@@ -69,8 +69,8 @@ pub(super) fn system(
         }
         GuardCmd::Despawn(NodeName::Explicit(namespace, node_name)) => {
             store
-                .guard_state(KIND, (namespace, node_name))
-                .set((*state).into());
+                .typed_guard_state(KIND, (namespace, node_name))
+                .set(*state);
         }
         GuardCmd::Despawn(_) => {
             //
@@ -83,8 +83,8 @@ pub(super) fn system(
         GuardCmd::Despawn(NodeName::Explicit(namespace, node_name)) => {
             trace!("Storing state for {namespace}::{node_name}: {state}");
             store
-                .guard_state(KIND, (namespace, node_name))
-                .set((*state).into());
+                .typed_guard_state(KIND, (namespace, node_name))
+                .set(*state);
         }
         GuardCmd::Despawn(_) => {
             //
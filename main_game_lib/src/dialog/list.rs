@@ -41,6 +41,8 @@ pub enum TypedNamespace {
     InitialSamizdat,
     InitialOtter,
     InitialPhoebe,
+    /// Started when an NPC has no other dialog to offer.
+    DefaultGreeting,
 
     // --------------------------------------------------------------
     //
@@ -66,6 +68,7 @@ impl AsRef<str> for TypedNamespace {
             InitialSamizdat => "initial_samizdat",
             InitialOtter => "initial_otter",
             InitialPhoebe => "initial_phoebe",
+            DefaultGreeting => "default_greeting",
         }
     }
 }
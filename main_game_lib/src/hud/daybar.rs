@@ -12,6 +12,7 @@ use common_assets::{
 };
 use common_ext::QueryExt;
 use common_visuals::camera::{MainCamera, PIXEL_ZOOM};
+use serde::{Deserialize, Serialize};
 
 use super::{MARGIN_LEFT_PX, MARGIN_TOP_PX};
 use crate::{player_stats::PlayerStats, prelude::*};
@@ -21,7 +22,18 @@ const TOOLTIP_FONT_SIZE: f32 = 18.0;
 
 /// Unit of time.
 #[derive(
-    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect, Default,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Reflect,
+    Default,
+    Serialize,
+    Deserialize,
 )]
 pub struct Beats(pub isize);
 
@@ -9,6 +9,9 @@ pub mod bevy_rscn;
 pub mod cutscene;
 pub mod dialog;
 pub mod hud;
+pub mod loading_watchdog;
+pub mod logging;
+pub mod pause;
 pub mod player_stats;
 pub mod prelude;
 pub mod state;
@@ -19,6 +22,8 @@ use bevy::{app::AppExit, prelude::*};
 use bevy_kira_audio::AudioPlugin;
 use bevy_pixel_camera::PixelCameraPlugin;
 pub use common_ext;
+use common_store::{GlobalStore, TimeOfDayStore};
+use common_visuals::day_night::TimeOfDay;
 
 use crate::prelude::*;
 
@@ -28,34 +33,12 @@ use crate::prelude::*;
 pub fn windowed_app() -> App {
     let mut app = App::new();
 
+    // read by the log plugin when it's built
+    app.insert_resource(logging::LogSettings::from_env());
+
     app.add_plugins(
         DefaultPlugins
-            .set(bevy::log::LogPlugin {
-                level: bevy::log::Level::INFO,
-                filter: "\
-                info,\
-                wgpu_hal::vulkan::instance=warn,\
-                game=trace,\
-                common_action=trace,\
-                common_assets=trace,\
-                common_loading_screen=trace,\
-                common_physics=trace,\
-                common_store=trace,\
-                common_visuals=trace,\
-                common_story=trace,\
-                main_game_lib=trace,\
-                main_game_lib::top_down=trace,\
-                main_game_lib::top_down::actor::npc=debug,\
-                main_game_lib::top_down::actor=debug,\
-                main_game_lib::top_down::environmental_objects::door=debug,\
-                main_game_lib::top_down::cameras=debug,\
-                main_game_lib::top_down::layout=debug,\
-                main_game_lib::rscn=debug,\
-                scene_top_down=trace,\
-                "
-                .to_string(),
-                ..default()
-            })
+            .set(logging::plugin())
             .set(ImagePlugin::default_nearest())
             .set(WindowPlugin {
                 primary_window: Some({
@@ -78,11 +61,11 @@ pub fn windowed_app() -> App {
         .add_computed_state::<WhichTopDownScene>()
         .add_computed_state::<InTopDownScene>()
         .init_resource::<GlobalGameStateTransition>()
-        // TODO: load from save file
-        .init_resource::<player_stats::PlayerStats>()
+        .init_resource::<loading_watchdog::LoadingWatchdog>()
         .insert_resource(ClearColor(PRIMARY_COLOR))
         .init_asset::<crate::bevy_rscn::TscnTree>()
         .init_asset_loader::<crate::bevy_rscn::TscnLoader>()
+        .add_event::<crate::bevy_rscn::SceneTimerFinishedEvent>()
         .init_asset_loader::<common_assets::ignore_loader::Loader>();
 
     #[cfg(feature = "devtools")]
@@ -113,12 +96,24 @@ pub fn windowed_app() -> App {
         crate::cutscene::Plugin,
         crate::dialog::Plugin,
         crate::hud::Plugin,
+        crate::pause::Plugin,
         crate::top_down::Plugin,
         PixelCameraPlugin,
     ));
 
     info!("Plugins added");
 
+    // the store is inserted by its plugin, we load the stats right away so
+    // that they're available to any system from the very first frame
+    let stats =
+        player_stats::PlayerStats::load(app.world().resource::<GlobalStore>());
+    app.insert_resource(stats);
+    if let Some(hours) =
+        app.world().resource::<GlobalStore>().time_of_day().get()
+    {
+        app.insert_resource(TimeOfDay::at(hours));
+    }
+
     app.add_systems(
         Startup,
         (
@@ -128,7 +123,18 @@ pub fn windowed_app() -> App {
             >,
         ),
     );
+    app.add_systems(
+        OnEnter(InTopDownScene::leaving()),
+        (player_stats::save, save_time_of_day),
+    );
+    app.add_systems(Update, crate::bevy_rscn::tick_scene_timers);
     app.add_systems(OnEnter(GlobalGameState::Exit), exit);
+    // the next load doesn't inherit assets that failed in this one
+    app.add_systems(OnExit(InTopDownScene::loading()), loading_watchdog::reset)
+        .add_systems(
+            OnExit(GlobalGameState::LoadingMeditation),
+            loading_watchdog::reset,
+        );
 
     app
 }
@@ -142,6 +148,11 @@ fn begin_loading_static_assets_on_startup(
     );
 }
 
+/// The [`TimeOfDay`] is loaded when the app is built.
+fn save_time_of_day(store: Res<GlobalStore>, time_of_day: Res<TimeOfDay>) {
+    store.time_of_day().set(time_of_day.hours);
+}
+
 fn exit(mut exit: EventWriter<AppExit>) {
     exit.send(AppExit::Success);
 }
@@ -0,0 +1,280 @@
+//! Reports assets that fail to load or take too long to load.
+//!
+//! A wrong asset path would otherwise show up as a loading screen that never
+//! finishes, without any hint as to why.
+//! Add [`watch`] alongside the system that finishes the loading of a scene.
+//! It watches the assets passed to [`LoadingWatchdog::watch`] and those in
+//! the [`LoadingScreenSettings::gate`].
+//!
+//! An image that failed to load is replaced by a placeholder and removed from
+//! the gate, so that the scene can load.
+//! Other assets cannot be replaced.
+//! Those in the gate are removed from it too, the rest are listed in
+//! [`LoadingWatchdog::failed`] as the scene that waits for them can never
+//! load.
+//!
+//! Run [`reset`] once the loading is over so that the next load doesn't
+//! inherit what's been watched or failed.
+
+use bevy::{
+    asset::{LoadState, RecursiveDependencyLoadState, UntypedAssetId},
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    utils::{HashMap, Instant},
+};
+use common_loading_screen::LoadingScreenSettings;
+
+use crate::prelude::*;
+
+/// See [`LoadingWatchdog::timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Loud enough to be spotted in the scene.
+const PLACEHOLDER_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+/// Assets that [`watch`] waits for to load.
+#[derive(Resource)]
+pub struct LoadingWatchdog {
+    /// Assets that are still loading after this long are reported as
+    /// stalled.
+    /// They are still waited for.
+    pub timeout: Duration,
+    /// When we started watching the asset and whether it's been reported as
+    /// stalled.
+    watched: HashMap<UntypedAssetId, (Instant, bool)>,
+    /// See [`LoadingWatchdog::failed`].
+    failed: Vec<String>,
+}
+
+impl LoadingWatchdog {
+    /// Watches the asset until it loads or fails to load.
+    /// Watching an asset that's already watched does nothing.
+    pub fn watch(&mut self, id: impl Into<UntypedAssetId>) {
+        self.watched
+            .entry(id.into())
+            .or_insert_with(|| (Instant::now(), false));
+    }
+
+    /// Whether the asset hasn't yet loaded or failed to load.
+    pub fn is_watching(&self, id: impl Into<UntypedAssetId>) -> bool {
+        self.watched.contains_key(&id.into())
+    }
+
+    /// Paths of the assets that failed to load and that nobody can stop
+    /// waiting for, i.e. they are neither images nor in the
+    /// [`LoadingScreenSettings::gate`].
+    /// If any, the scene that's loading won't ever finish.
+    pub fn failed(&self) -> &[String] {
+        &self.failed
+    }
+}
+
+/// Logs the path of each watched asset that failed to load or that stalled.
+///
+/// Images that failed to load are replaced by a placeholder.
+/// Failed assets are removed from the [`LoadingScreenSettings::gate`].
+/// The rest of them end up in [`LoadingWatchdog::failed`].
+pub fn watch(
+    asset_server: Res<AssetServer>,
+    mut watchdog: ResMut<LoadingWatchdog>,
+    mut images: ResMut<Assets<Image>>,
+    mut settings: Option<ResMut<LoadingScreenSettings>>,
+) {
+    if let Some(settings) = &settings {
+        for handle in &settings.gate {
+            watchdog.watch(handle.id());
+        }
+    }
+
+    let path_of = |id: UntypedAssetId| {
+        asset_server
+            .get_path(id)
+            .map(|path| path.to_string())
+            .unwrap_or_else(|| format!("{id:?}"))
+    };
+
+    let LoadingWatchdog {
+        timeout,
+        watched,
+        failed,
+    } = &mut *watchdog;
+    let timeout = *timeout;
+    watched.retain(|&id, (since, reported)| {
+        let Some((state, _, recursive_state)) =
+            asset_server.get_load_states(id)
+        else {
+            // all handles were dropped, nobody waits for the asset anymore
+            return false;
+        };
+
+        let is_own_failure = match (state, recursive_state) {
+            (LoadState::Failed(err), _) => {
+                error!("Asset {} failed to load: {err}", path_of(id));
+
+                if let Ok(id) = id.try_typed::<Image>() {
+                    images.insert(id, placeholder_image());
+                    false
+                } else {
+                    true
+                }
+            }
+            (_, RecursiveDependencyLoadState::Failed) => {
+                // the asset itself is fine, the dependency is reported on
+                // its own if watched and replaced if it's an image
+                error!("A dependency of asset {} failed to load", path_of(id));
+                false
+            }
+            (LoadState::Loaded, RecursiveDependencyLoadState::Loaded) => {
+                return false;
+            }
+            _ => {
+                if !*reported && since.elapsed() > timeout {
+                    warn!(
+                        "Asset {} is still loading after {timeout:?}",
+                        path_of(id)
+                    );
+                    *reported = true;
+                }
+
+                return true;
+            }
+        };
+
+        // the asset won't ever load, don't wait for it
+        let gated = settings.as_mut().is_some_and(|settings| {
+            let gate_len = settings.gate.len();
+            settings.gate.retain(|handle| handle.id() != id);
+            gate_len != settings.gate.len()
+        });
+        if is_own_failure && !gated {
+            failed.push(path_of(id));
+        }
+
+        false
+    });
+}
+
+/// Forgets the assets watched and failed during the last load.
+pub fn reset(mut watchdog: ResMut<LoadingWatchdog>) {
+    watchdog.watched.clear();
+    watchdog.failed.clear();
+}
+
+/// A single pixel of [`PLACEHOLDER_COLOR`].
+fn placeholder_image() -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &PLACEHOLDER_COLOR,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+impl Default for LoadingWatchdog {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            watched: default(),
+            failed: default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn it_replaces_missing_image_and_opens_gate() {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_resource::<LoadingWatchdog>()
+            .add_systems(Last, watch);
+
+        let missing: Handle<Image> = app
+            .world()
+            .resource::<AssetServer>()
+            .load("this/path/does/not/exist.png");
+        let mut settings = LoadingScreenSettings::default();
+        settings.gate.push(missing.clone().untyped());
+        app.insert_resource(settings);
+
+        // the loading happens on another thread
+        let gate_is_open = |app: &App| {
+            app.world()
+                .resource::<LoadingScreenSettings>()
+                .gate
+                .is_empty()
+        };
+        for _ in 0..100 {
+            app.update();
+            if gate_is_open(&app) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(gate_is_open(&app));
+        let watchdog = app.world().resource::<LoadingWatchdog>();
+        assert!(!watchdog.is_watching(&missing));
+        assert!(watchdog.failed().is_empty());
+        assert!(app.world().resource::<Assets<Image>>().contains(&missing));
+    }
+
+    #[test]
+    fn it_lists_missing_map_as_failed() {
+        use crate::top_down::TileMap;
+
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<TileMap>()
+            .init_asset_loader::<common_assets::ron_loader::Loader<TileMap>>()
+            .init_resource::<LoadingWatchdog>()
+            .add_systems(Last, watch);
+
+        let missing: Handle<TileMap> = app
+            .world()
+            .resource::<AssetServer>()
+            .load("maps/does_not_exist.ron");
+        app.world_mut()
+            .resource_mut::<LoadingWatchdog>()
+            .watch(&missing);
+
+        // the loading happens on another thread
+        let has_failed = |app: &App| {
+            !app.world()
+                .resource::<LoadingWatchdog>()
+                .failed()
+                .is_empty()
+        };
+        for _ in 0..100 {
+            app.update();
+            if has_failed(&app) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let watchdog = app.world().resource::<LoadingWatchdog>();
+        assert_eq!(["maps/does_not_exist.ron"], watchdog.failed());
+        assert!(!watchdog.is_watching(&missing));
+
+        // the next load starts clean
+        app.world_mut().run_system_once(reset);
+        assert!(app
+            .world()
+            .resource::<LoadingWatchdog>()
+            .failed()
+            .is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+//! Logs go to stderr and, unless disabled, to a daily rotated file in
+//! [`LOG_DIR`].
+//! Some packaged builds discard stderr and the file is the only place to look
+//! at when something goes wrong.
+//!
+//! The per-module [`DEFAULT_FILTER`] can be amended at runtime with the usual
+//! `RUST_LOG` env var.
+//! Its directives are applied on top of the defaults, so e.g.
+//! `RUST_LOG=main_game_lib::top_down::actor=trace` only changes the verbosity
+//! of the actor module.
+
+use std::path::PathBuf;
+
+use bevy::{
+    log::{
+        tracing_subscriber::{self, Layer},
+        BoxedLayer, Level, LogPlugin,
+    },
+    prelude::*,
+};
+use tracing_appender::non_blocking::WorkerGuard;
+
+/// Curated verbosity of our crates and the noisy dependencies.
+pub const DEFAULT_FILTER: &str = "\
+    info,\
+    wgpu_hal::vulkan::instance=warn,\
+    game=trace,\
+    common_action=trace,\
+    common_assets=trace,\
+    common_loading_screen=trace,\
+    common_physics=trace,\
+    common_store=trace,\
+    common_visuals=trace,\
+    common_story=trace,\
+    main_game_lib=trace,\
+    main_game_lib::top_down=trace,\
+    main_game_lib::top_down::actor::npc=debug,\
+    main_game_lib::top_down::actor=debug,\
+    main_game_lib::top_down::environmental_objects::door=debug,\
+    main_game_lib::top_down::cameras=debug,\
+    main_game_lib::top_down::layout=debug,\
+    main_game_lib::rscn=debug,\
+    scene_top_down=trace,\
+    ";
+
+/// Where the log files are written to by default.
+pub const LOG_DIR: &str = "logs";
+
+/// Set this env var to `0`, `false` or `off` to disable logging into a file.
+/// Any other value is used as the directory to write the log files to instead
+/// of [`LOG_DIR`].
+pub const LOG_FILE_ENV: &str = "DCTS_LOG_FILE";
+
+/// The log files are named with this prefix followed by the date.
+const LOG_FILE_PREFIX: &str = "dont_count_the_sheep.log";
+
+/// Must be inserted before the [`LogPlugin`] is added because the file sink
+/// is created when the plugin is built.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct LogSettings {
+    /// The directory to write daily rotated log files to.
+    /// If [`None`], the logs are written to stderr only.
+    pub file_dir: Option<PathBuf>,
+}
+
+/// Keeps the background thread that writes the log file alive.
+/// Once dropped, the remaining logs are flushed.
+#[derive(Resource)]
+struct LogFileGuard {
+    _guard: WorkerGuard,
+}
+
+impl LogSettings {
+    /// Reads [`LOG_FILE_ENV`].
+    pub fn from_env() -> Self {
+        let file_dir = match std::env::var(LOG_FILE_ENV) {
+            Ok(value) if matches!(value.as_str(), "0" | "false" | "off") => {
+                None
+            }
+            Ok(value) if !value.is_empty() => Some(PathBuf::from(value)),
+            _ => Some(PathBuf::from(LOG_DIR)),
+        };
+
+        Self { file_dir }
+    }
+}
+
+/// The log plugin with our curated filter and the file sink.
+pub(crate) fn plugin() -> LogPlugin {
+    let filter = filter_with_overrides(std::env::var("RUST_LOG").ok());
+
+    // bevy prefers `RUST_LOG` over the filter we give it, so we hand it the
+    // curated defaults with the overrides appended
+    if std::env::var_os("RUST_LOG").is_some() {
+        std::env::set_var("RUST_LOG", &filter);
+    }
+
+    LogPlugin {
+        level: Level::INFO,
+        filter,
+        custom_layer: file_layer,
+    }
+}
+
+/// For the same target, the directive that comes later wins.
+fn filter_with_overrides(overrides: Option<String>) -> String {
+    match overrides {
+        Some(overrides) if !overrides.trim().is_empty() => {
+            format!("{DEFAULT_FILTER}{}", overrides.trim())
+        }
+        _ => DEFAULT_FILTER.to_string(),
+    }
+}
+
+/// The per-module filter of the plugin applies to this layer too.
+fn file_layer(app: &mut App) -> Option<BoxedLayer> {
+    let file_dir = app
+        .world()
+        .get_resource::<LogSettings>()
+        .cloned()
+        .unwrap_or_else(LogSettings::from_env)
+        .file_dir?;
+
+    let appender = tracing_appender::rolling::daily(file_dir, LOG_FILE_PREFIX);
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+    app.insert_resource(LogFileGuard { _guard: guard });
+
+    Some(
+        tracing_subscriber::fmt::layer()
+            .with_ansi(false)
+            .with_writer(writer)
+            .boxed(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::log::tracing_subscriber::EnvFilter;
+
+    use super::*;
+
+    #[test]
+    fn it_appends_overrides_to_default_filter() {
+        assert_eq!(DEFAULT_FILTER, filter_with_overrides(None));
+        assert_eq!(DEFAULT_FILTER, filter_with_overrides(Some(" ".into())));
+
+        let filter = filter_with_overrides(Some(
+            "main_game_lib::top_down::actor=trace".into(),
+        ));
+        assert!(filter.starts_with(DEFAULT_FILTER));
+        assert!(filter.ends_with(",main_game_lib::top_down::actor=trace"));
+        assert!(EnvFilter::try_new(&filter).is_ok());
+    }
+}
@@ -0,0 +1,187 @@
+//! Pausing freezes the gameplay without leaving the scene.
+//!
+//! Press [`GlobalAction::Cancel`] in a running top down scene to pause and
+//! press it again to resume.
+//! While paused, the [`Paused`] resource is present and an overlay is shown.
+//!
+//! # What honors the pause
+//! The virtual clock, [`Time<Virtual>`], is paused along with the game.
+//! Therefore
+//! - [`FixedUpdate`] doesn't run at all. That's where actors walk along their
+//!   paths, knockback is applied, interpolations and atlas animations advance
+//!   and the camera follows the player.
+//! - [`Time`] doesn't advance in [`Update`], so timers and stopwatches that
+//!   tick by it halt, e.g. notifications and scene timers.
+//!
+//! Systems that react to input or plan what actors do are gated with
+//! [`not_paused`]:
+//! - player movement
+//! - NPC behavior and path planning, including the ambient crowd
+//! - inspecting and interacting
+//!
+//! UI, menus, dialog and the loading screen keep running.
+//! If something must animate while paused, drive it by [`Time<Real>`].
+
+use common_visuals::camera::MainCamera;
+
+use crate::{cutscene::in_cutscene, prelude::*};
+
+/// Present while the game is paused.
+#[derive(Resource, Reflect, Default, Debug)]
+#[reflect(Resource)]
+pub struct Paused;
+
+/// The root of the pause overlay UI.
+#[derive(Component)]
+pub(crate) struct PauseOverlay;
+
+pub(crate) struct Plugin;
+
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            toggle
+                .run_if(in_top_down_running_state())
+                .run_if(common_action::cancel_just_pressed())
+                .run_if(not(in_cutscene()))
+                .run_if(not(crate::dialog::fe::portrait::in_portrait_dialog())),
+        )
+        .add_systems(OnExit(InTopDownScene::running()), resume);
+
+        #[cfg(feature = "devtools")]
+        app.register_type::<Paused>();
+    }
+}
+
+/// Runs a system if the game is not paused.
+pub fn not_paused() -> impl FnMut(Option<Res<Paused>>) -> bool + Clone {
+    move |paused: Option<Res<Paused>>| paused.is_none()
+}
+
+/// Pauses the game if it's running and resumes it if it's paused.
+pub(crate) fn toggle(
+    mut cmd: Commands,
+    asset_server: Res<AssetServer>,
+    paused: Option<Res<Paused>>,
+    mut time: ResMut<Time<Virtual>>,
+
+    overlay: Query<Entity, With<PauseOverlay>>,
+    camera: Query<Entity, With<MainCamera>>,
+) {
+    if paused.is_some() {
+        trace!("Resuming the game");
+        resume(cmd, time, overlay);
+        return;
+    }
+
+    trace!("Pausing the game");
+    cmd.insert_resource(Paused);
+    time.pause();
+
+    let mut root = cmd.spawn((
+        Name::new("Pause overlay"),
+        PauseOverlay,
+        NodeBundle {
+            background_color: Color::srgba(0.0, 0.0, 0.0, 0.6).into(),
+            focus_policy: bevy::ui::FocusPolicy::Block,
+            z_index: ZIndex::Global(1),
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        },
+    ));
+    if let Ok(camera) = camera.get_single() {
+        root.insert(TargetCamera(camera));
+    }
+    root.with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            // LOCALIZATION
+            "Paused",
+            TextStyle {
+                font: asset_server.load(common_assets::fonts::PIXEL1),
+                font_size: 48.0,
+                color: Color::WHITE,
+            },
+        ));
+    });
+}
+
+/// Resumes the game if it's paused.
+/// Leaving the scene resumes the game too.
+pub(crate) fn resume(
+    mut cmd: Commands,
+    mut time: ResMut<Time<Virtual>>,
+
+    overlay: Query<Entity, With<PauseOverlay>>,
+) {
+    cmd.remove_resource::<Paused>();
+    time.unpause();
+
+    for entity in overlay.iter() {
+        cmd.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::time::{TimePlugin, TimeUpdateStrategy};
+    use bevy_grid_squared::sq;
+
+    use super::*;
+    use crate::top_down::{
+        actor::{
+            self,
+            test_utils::{app_with_assets, spawn_player},
+            ActorSteppedEvent, TerrainSpeed,
+        },
+        Actor, ActorTarget, TileMap,
+    };
+
+    #[test]
+    fn it_does_not_advance_paused_actor() {
+        let mut app = app_with_assets();
+        app.add_plugins(TimePlugin)
+            .init_asset::<Font>()
+            .add_event::<ActorSteppedEvent>()
+            .init_resource::<TerrainSpeed>()
+            .insert_resource(TileMap::default())
+            .insert_resource(TimeUpdateStrategy::ManualDuration(from_millis(
+                100,
+            )))
+            .add_systems(FixedUpdate, actor::animate_movement);
+        let w = app.world_mut();
+        let toggle = w.register_system(toggle);
+
+        let player = spawn_player(w, |builder| {
+            builder.initial_square(sq(0, 0));
+        });
+        w.get_mut::<Actor>(player).unwrap().walking_to =
+            ActorTarget::new(sq(1, 0)).into();
+        let position = |app: &App| {
+            app.world().get::<Transform>(player).unwrap().translation
+        };
+
+        w.run_system(toggle).unwrap();
+        assert!(w.contains_resource::<Paused>());
+        let paused_at = position(&app);
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(paused_at, position(&app));
+
+        let w = app.world_mut();
+        w.run_system(toggle).unwrap();
+        assert!(!w.contains_resource::<Paused>());
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_ne!(paused_at, position(&app));
+    }
+}
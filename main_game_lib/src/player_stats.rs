@@ -2,6 +2,12 @@
 //!
 //! See the wiki for more information about how the traits exactly work etc,
 //! because lots of logic related to them is spread across the codebase.
+//!
+//! The stats are persisted in the [`GlobalStore`] as a single versioned blob.
+//! Missing fields in an older save are filled with defaults.
+
+use common_store::{GlobalStore, PlayerStatsStore};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     hud::{
@@ -12,7 +18,8 @@ use crate::{
 };
 
 /// The main resource for the player's stats.
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[serde(default)]
 #[cfg_attr(feature = "devtools", derive(Reflect, InspectorOptions))]
 #[cfg_attr(feature = "devtools", reflect(Resource, InspectorOptions))]
 pub struct PlayerStats {
@@ -33,7 +40,8 @@ pub struct PlayerStats {
 }
 
 /// List of important locations that the player can visit.
-#[derive(Default)]
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
 #[cfg_attr(feature = "devtools", derive(Reflect))]
 pub struct VisitedLocations {
     downtown: bool,
@@ -46,7 +54,8 @@ pub struct VisitedLocations {
 }
 
 /// List of traits that the player has or can have.
-#[derive(Default)]
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
 #[cfg_attr(feature = "devtools", derive(Reflect))]
 pub struct Traits {
     /// See [`NightOwl`].
@@ -58,6 +67,8 @@ pub struct Traits {
 /// Relates to timekeeping.
 ///
 /// Mutually exclusive with [`EarlyBird`] most of the time.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
 #[cfg_attr(feature = "devtools", derive(Reflect))]
 pub struct NightOwl {
     /// The player saved this many beats on their actions thanks to this trait.
@@ -75,6 +86,8 @@ pub struct NightOwl {
 /// Relates to timekeeping.
 ///
 /// Mutually exclusive with [`NightOwl`] most of the time.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
 #[cfg_attr(feature = "devtools", derive(Reflect))]
 pub struct EarlyBird {
     /// The player saved this many beats on their actions thanks to this trait.
@@ -109,7 +122,72 @@ impl Default for NightOwl {
     }
 }
 
+/// What's actually written into the store.
+///
+/// The stats are kept as a raw value so that we can tell apart a save that
+/// we don't understand from a save that's merely missing some fields.
+#[derive(Serialize, Deserialize)]
+struct SavedPlayerStats {
+    version: u32,
+    stats: serde_json::Value,
+}
+
+/// Persists [`PlayerStats`] into the store.
+pub(crate) fn save(store: Res<GlobalStore>, stats: Res<PlayerStats>) {
+    stats.save(&store);
+}
+
 impl PlayerStats {
+    /// Bump this when the meaning of an existing field changes.
+    /// Adding a new field doesn't require a bump, missing fields load with
+    /// their defaults.
+    const SAVE_VERSION: u32 = 1;
+
+    /// Loads the stats from the store.
+    /// If there's no save or the save cannot be read, returns defaults.
+    pub fn load(store: &GlobalStore) -> Self {
+        let Some(raw) = store.player_stats().get() else {
+            debug!("No player stats saved, starting fresh");
+            return default();
+        };
+
+        let saved: SavedPlayerStats = match serde_json::from_value(raw) {
+            Ok(saved) => saved,
+            Err(err) => {
+                error!("Cannot read saved player stats: {err}");
+                return default();
+            }
+        };
+
+        if saved.version > Self::SAVE_VERSION {
+            error!(
+                "Player stats saved with version {}, but we only know {}",
+                saved.version,
+                Self::SAVE_VERSION
+            );
+            return default();
+        }
+
+        serde_json::from_value(saved.stats).unwrap_or_else(|err| {
+            error!("Cannot deserialize saved player stats: {err}");
+            default()
+        })
+    }
+
+    /// Overwrites whatever stats were saved before.
+    pub fn save(&self, store: &GlobalStore) {
+        let saved = SavedPlayerStats {
+            version: Self::SAVE_VERSION,
+            stats: serde_json::to_value(self)
+                .expect("Player stats are always serializable"),
+        };
+
+        store.player_stats().set(
+            serde_json::to_value(saved)
+                .expect("Saved player stats are always serializable"),
+        );
+    }
+
     /// We can be sure that self is more than 1 because otherwise there's
     /// nothing to discount.
     pub(crate) fn discount_activity(
@@ -258,3 +336,40 @@ impl VisitedLocations {
         notifications.push(Notification::new_location_discovered("Clinic"));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_loads_defaults_for_fields_missing_in_old_save() {
+        let store = GlobalStore::new();
+        store.player_stats().set(serde_json::json!({
+            "version": 1,
+            "stats": { "days_passed": 3 },
+        }));
+
+        let stats = PlayerStats::load(&store);
+        assert_eq!(3, stats.days_passed);
+        assert_eq!(0, stats.spiritual_points);
+        assert_eq!(
+            NightOwl::INITIAL_FULL_DISCOUNT,
+            stats.traits.night_owl.full_discount
+        );
+    }
+
+    #[test]
+    fn it_saves_and_loads_stats() {
+        let store = GlobalStore::new();
+        let stats = PlayerStats {
+            days_passed: 2,
+            material_points: 5,
+            ..default()
+        };
+        stats.save(&store);
+
+        let loaded = PlayerStats::load(&store);
+        assert_eq!(2, loaded.days_passed);
+        assert_eq!(5, loaded.material_points);
+    }
+}
@@ -6,8 +6,8 @@ pub use bevy::{math::vec2, prelude::*, time::Stopwatch};
 #[cfg(feature = "devtools")]
 pub use bevy_inspector_egui::prelude::*;
 pub use common_action::{
-    leafwing_input_manager::action_state::ActionState, ActionStateExt,
-    GlobalAction, MovementAction,
+    consume_for_transition, leafwing_input_manager::action_state::ActionState,
+    ActionStateExt, GlobalAction, MovementAction,
 };
 pub use common_visuals::PRIMARY_COLOR;
 
@@ -16,11 +16,13 @@ mod action;
 pub mod actor;
 pub mod cameras;
 pub mod environmental_objects;
+pub mod explored;
 pub mod inspect_and_interact;
 pub mod layout;
+pub mod minimap;
 mod spawner;
 
-pub use action::TopDownAction;
+pub use action::{exit_zone_interaction, TopDownAction};
 use actor::{emit_movement_events, BeginDialogEvent};
 pub use actor::{npc, player::Player, Actor, ActorMovementEvent, ActorTarget};
 use bevy::prelude::*;
@@ -32,7 +34,7 @@ pub use spawner::TopDownTsncSpawner;
 use self::inspect_and_interact::ChangeHighlightedInspectLabelEvent;
 use crate::{
     cutscene::in_cutscene, in_top_down_loading_state,
-    in_top_down_running_state,
+    in_top_down_running_state, pause::not_paused,
     top_down::inspect_and_interact::ChangeHighlightedInspectLabelEventConsumer,
     InTopDownScene,
 };
@@ -46,7 +48,13 @@ impl bevy::app::Plugin for Plugin {
             .add_event::<TopDownAction>()
             .add_event::<BeginDialogEvent>()
             .add_event::<ChangeHighlightedInspectLabelEvent>()
-            .add_event::<ActorMovementEvent>();
+            .add_event::<inspect_and_interact::InteractedWithObjectEvent>()
+            .add_event::<ActorMovementEvent>()
+            .add_event::<actor::ActorSteppedEvent>();
+
+        app.init_resource::<actor::TerrainSpeed>()
+            .init_resource::<npc::NpcInteraction>()
+            .init_resource::<minimap::Minimap>();
 
         app.add_plugins(environmental_objects::Plugin);
 
@@ -72,45 +80,90 @@ impl bevy::app::Plugin for Plugin {
 
         app.add_systems(
             OnExit(InTopDownScene::running()),
-            layout::systems::remove_resources,
+            (
+                actor::save_positions,
+                explored::save,
+                layout::systems::remove_resources,
+            )
+                .chain(),
         )
         .add_systems(
             OnEnter(InTopDownScene::loading()),
-            layout::systems::start_loading_map,
+            (layout::systems::start_loading_map, explored::load),
         )
         .add_systems(
             First,
             layout::systems::try_insert_map_as_resource
                 .run_if(in_top_down_loading_state()),
         )
+        .add_systems(
+            Update,
+            actor::restore_positions
+                .run_if(in_top_down_loading_state())
+                .run_if(resource_exists::<TileMap>),
+        )
         .add_systems(
             FixedUpdate,
-            actor::animate_movement.run_if(in_top_down_running_state()),
+            (
+                actor::apply_knockback,
+                actor::npc::glide::steer,
+                common_physics::systems::apply_velocity,
+                actor::npc::glide::round_position,
+                actor::animate_movement,
+            )
+                .chain()
+                .run_if(in_top_down_running_state()),
         )
         .add_systems(
             Update,
-            actor::emit_movement_events
+            (actor::emit_movement_events, actor::update_player_zones)
+                .chain()
                 .run_if(in_top_down_running_state())
                 // so that we can emit this event on current frame
                 .after(actor::player::move_around),
         )
         .add_systems(
             Update,
-            actor::player::move_around
+            (actor::player::walk_or_run, actor::player::move_around)
+                .chain()
                 .run_if(in_top_down_running_state())
+                .run_if(not_paused())
                 .run_if(common_action::move_action_pressed())
                 .run_if(not(crate::dialog::fe::portrait::in_portrait_dialog())),
         )
+        .add_systems(
+            Update,
+            explored::reveal
+                .run_if(in_top_down_running_state())
+                .run_if(resource_exists::<TileMap>)
+                .run_if(resource_exists::<explored::Explored>)
+                .after(actor::player::move_around),
+        )
+        .add_systems(
+            Update,
+            actor::crowd::maintain
+                .run_if(in_top_down_running_state())
+                .run_if(resource_exists::<actor::crowd::AmbientCrowd>)
+                .run_if(not_paused())
+                .before(actor::npc::drive_behavior),
+        )
+        .add_systems(
+            OnExit(InTopDownScene::leaving()),
+            actor::crowd::despawn_all,
+        )
         .add_systems(
             Update,
             (
                 actor::npc::drive_behavior,
+                actor::npc::schedule::follow,
                 actor::npc::plan_path
                     .run_if(on_event::<actor::npc::PlanPathEvent>()),
                 actor::npc::run_path,
+                actor::face_target,
             )
                 .chain()
-                .run_if(in_top_down_running_state()),
+                .run_if(in_top_down_running_state())
+                .run_if(not_paused()),
         );
 
         //
@@ -149,7 +202,8 @@ impl bevy::app::Plugin for Plugin {
                     .run_if(common_action::inspect_pressed()),
             )
                 .chain() // easier to reason about
-                .run_if(in_top_down_running_state()),
+                .run_if(in_top_down_running_state())
+                .run_if(not_paused()),
         )
         .add_systems(
             Update,
@@ -161,7 +215,8 @@ impl bevy::app::Plugin for Plugin {
             PreUpdate,
             inspect_and_interact::interact
                 .run_if(in_top_down_running_state())
-                .run_if(common_action::interaction_just_pressed())
+                .run_if(not_paused())
+                .run_if(common_action::any_interaction_just_pressed())
                 // Without this condition, the dialog will start when the player
                 // exists the previous one because:
                 // 1. The interact system runs, interact is just pressed, and so
@@ -185,14 +240,18 @@ impl bevy::app::Plugin for Plugin {
                         crate::dialog::fe::portrait::in_portrait_dialog(),
                     )),
             )
-                .run_if(in_top_down_running_state()),
+                .run_if(in_top_down_running_state())
+                .run_if(not_paused()),
         )
         .add_systems(
             Update,
-            inspect_and_interact::match_interact_label_with_action_event
-                .run_if(in_top_down_running_state())
-                .run_if(on_event::<ActorMovementEvent>())
-                .after(emit_movement_events),
+            (
+                inspect_and_interact::match_interact_label_with_action_event
+                    .run_if(on_event::<ActorMovementEvent>())
+                    .after(emit_movement_events),
+                inspect_and_interact::mark_objects_in_reach,
+            )
+                .run_if(in_top_down_running_state()),
         );
 
         //
@@ -216,6 +275,25 @@ impl bevy::app::Plugin for Plugin {
                 .run_if(in_top_down_running_state()),
         );
 
+        //
+        // Minimap
+        //
+
+        app.add_systems(OnEnter(InTopDownScene::running()), minimap::spawn)
+            .add_systems(OnExit(InTopDownScene::running()), minimap::despawn)
+            .add_systems(
+                Update,
+                (
+                    minimap::toggle,
+                    minimap::redraw
+                        .run_if(resource_exists::<TileMap>)
+                        .run_if(resource_exists::<explored::Explored>),
+                )
+                    .chain()
+                    .run_if(in_top_down_running_state())
+                    .after(explored::reveal),
+            );
+
         #[cfg(feature = "devtools")]
         {
             use bevy_inspector_egui::quick::ResourceInspectorPlugin;
@@ -223,13 +301,35 @@ impl bevy::app::Plugin for Plugin {
 
             app.register_type::<Actor>()
                 .register_type::<ActorMovementEvent>()
+                .register_type::<actor::ActorSteppedEvent>()
+                .register_type::<actor::crowd::AmbientCrowd>()
+                .register_type::<actor::crowd::Passerby>()
                 .register_type::<ActorTarget>()
+                .register_type::<actor::FaceTarget>()
+                .register_type::<actor::Footprint>()
+                .register_type::<actor::Knockback>()
+                .register_type::<actor::Passability>()
+                .register_type::<actor::PersistPosition>()
+                .register_type::<actor::PlayerZones>()
+                .register_type::<actor::TerrainSpeed>()
+                .register_type::<npc::NpcInteraction>()
+                .register_type::<cameras::CameraLead>()
+                .register_type::<cameras::CameraBounds>()
+                .register_type::<environmental_objects::pushable::Pushable>()
+                .register_type::<explored::Explored>()
+                .register_type::<minimap::Minimap>()
+                .register_type::<
+                    environmental_objects::trigger::EnterTrigger<TopDownAction>,
+                >()
                 .register_type::<InspectLabel>()
                 .register_type::<InspectLabelCategory>()
+                .register_type::<inspect_and_interact::InteractedWithObjectEvent>()
                 .register_type::<npc::BehaviorLeaf>()
                 .register_type::<npc::BehaviorPaused>()
+                .register_type::<npc::glide::Glide>()
                 .register_type::<npc::NpcInTheMap>()
                 .register_type::<npc::PlanPathEvent>()
+                .register_type::<npc::schedule::Schedule>()
                 .register_type::<TileKind>()
                 .register_type::<TileMap>()
                 .register_type::<Toolbar>()
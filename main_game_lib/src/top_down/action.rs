@@ -22,3 +22,94 @@ pub enum TopDownAction {
     StartGingerCatDialog,
     StartMeditation,
 }
+
+/// Creates a system that begins the given transition when the given action
+/// is emitted, typically by interacting with an exit zone.
+///
+/// The controls are consumed with [`consume_for_transition`] so that they
+/// don't leak into the next scene.
+/// The loading screen is started with [`TransitionParams::begin`], the rest
+/// is up to the systems that run in the leaving state of the scene.
+///
+/// ```ignore
+/// app.add_systems(
+///     Update,
+///     exit_zone_interaction(
+///         TopDownAction::Exit,
+///         GlobalGameStateTransition::ClinicToDowntown,
+///     )
+///     .run_if(in_scene_running_state(THIS_SCENE))
+///     .run_if(not(in_cutscene())),
+/// );
+/// ```
+pub fn exit_zone_interaction<T: Event + Eq>(
+    action: T,
+    transition: GlobalGameStateTransition,
+) -> impl System<In = (), Out = ()> {
+    IntoSystem::into_system(
+        move |mut transition_params: TransitionParams,
+              mut action_events: EventReader<T>,
+              mut controls: ResMut<ActionState<GlobalAction>>| {
+            let is_triggered =
+                action_events.read().any(|event| event == &action);
+
+            if is_triggered {
+                consume_for_transition(&mut controls);
+                transition_params.begin(transition);
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use common_loading_screen::LoadingScreenState;
+
+    use super::*;
+
+    #[test]
+    fn it_begins_transition_on_exit_zone_interaction() {
+        let mut w = World::default();
+        w.init_resource::<GlobalGameStateTransition>();
+        w.init_resource::<NextState<GlobalGameState>>();
+        w.init_resource::<NextState<LoadingScreenState>>();
+        w.init_resource::<ActionState<GlobalAction>>();
+        w.init_resource::<Events<TopDownAction>>();
+        let exit = w.register_system(exit_zone_interaction(
+            TopDownAction::Exit,
+            GlobalGameStateTransition::ClinicToDowntown,
+        ));
+
+        // another zone
+        w.send_event(TopDownAction::BrewTea);
+        w.run_system(exit).unwrap();
+        assert_eq!(
+            GlobalGameStateTransition::default(),
+            *w.resource::<GlobalGameStateTransition>()
+        );
+
+        // the player interacts with the exit zone
+        w.resource_mut::<ActionState<GlobalAction>>()
+            .press(&GlobalAction::Interact);
+        w.send_event(TopDownAction::Exit);
+        w.run_system(exit).unwrap();
+
+        assert_eq!(
+            GlobalGameStateTransition::ClinicToDowntown,
+            *w.resource::<GlobalGameStateTransition>()
+        );
+        assert!(matches!(
+            w.resource::<NextState<GlobalGameState>>(),
+            NextState::Pending(state)
+                if *state == WhichTopDownScene::Clinic.leaving()
+        ));
+        assert!(matches!(
+            w.resource::<NextState<LoadingScreenState>>(),
+            NextState::Pending(state)
+                if *state == common_loading_screen::start_state()
+        ));
+        assert!(!w
+            .resource::<ActionState<GlobalAction>>()
+            .pressed(&GlobalAction::Interact));
+    }
+}
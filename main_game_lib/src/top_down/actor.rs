@@ -1,7 +1,10 @@
 //! Player and NPC actor types.
 
+pub mod crowd;
 pub mod npc;
 pub mod player;
+#[cfg(test)]
+pub(crate) mod test_utils;
 
 use std::{iter, time::Duration};
 
@@ -10,11 +13,12 @@ use bevy::{
     prelude::*,
     render::view::RenderLayers,
     time::Stopwatch,
-    utils::HashSet,
+    utils::{HashMap, HashSet},
 };
-use bevy_grid_squared::{sq, GridDirection, Square};
+use bevy_grid_squared::{sq, GridDirection, Square, SquareLayout};
 use common_ext::QueryExt;
-use common_story::Character;
+use common_store::{ActorPositionStore, GlobalStore};
+use common_story::{animation::AnimationState, Character};
 use common_visuals::camera::{render_layer, PIXEL_ZOOM};
 use itertools::Itertools;
 use lazy_static::lazy_static;
@@ -22,10 +26,13 @@ use rand::{seq::SliceRandom, thread_rng};
 use serde::{Deserialize, Serialize};
 
 use self::npc::BehaviorTree;
-use crate::top_down::{
-    layout::{ysort, TileIndex, LAYOUT},
-    npc::NpcInTheMap,
-    InspectLabelCategory, Player, TileKind, TileMap,
+use crate::{
+    top_down::{
+        layout::{MapYSort, TileIndex, Walker, LAYOUT},
+        npc::NpcInTheMap,
+        InspectLabelCategory, Player, TileKind, TileMap, ZoneTileKind,
+    },
+    WhichTopDownScene,
 };
 
 /// Use with [`IntoSystemConfigs::run_if`] to run a system only when an actor
@@ -35,6 +42,22 @@ pub fn movement_event_emitted(
     on_event::<ActorMovementEvent>()
 }
 
+/// Use with [`IntoSystemConfigs::run_if`] to run a system only while the
+/// player stands in the given zone.
+///
+/// See [`PlayerZones`].
+pub fn player_in_zone(
+    zone: ZoneTileKind,
+) -> impl FnMut(Option<Res<PlayerZones>>) -> bool + Clone {
+    move |zones: Option<Res<PlayerZones>>| {
+        zones.is_some_and(|zones| zones.contains(zone))
+    }
+}
+
+/// How long a direction pressed mid-step is remembered, see
+/// [`Actor::queued`].
+pub const QUEUED_DIRECTION_EXPIRES_IN: Duration = Duration::from_millis(200);
+
 /// Entity with this component can be moved around.
 #[derive(Component, Reflect, Debug, Deserialize, Serialize)]
 pub struct Actor {
@@ -42,6 +65,23 @@ pub struct Actor {
     pub character: Character,
     /// How fast we move from square to square.
     pub step_time: Duration,
+    /// When standing still, a new direction must be held for this long
+    /// before the actor walks that way.
+    /// Until then, the actor only turns in place.
+    /// See [`Actor::turn_in_place`].
+    ///
+    /// Zero by default, the actor turns and walks at once.
+    #[serde(default)]
+    pub turn_delay: Duration,
+    /// A direction pressed while the next step was already planned, along
+    /// with how long ago it was pressed.
+    ///
+    /// Once the current step is done, the actor walks this way instead of
+    /// taking the planned step, e.g. to turn at an intersection.
+    /// Ignored if older than [`QUEUED_DIRECTION_EXPIRES_IN`] or if the square
+    /// that way cannot be stepped on.
+    #[serde(default)]
+    pub queued: Option<(GridDirection, Stopwatch)>,
     /// If no target then this is the current position.
     /// If there's a target, current position is interpolated between this and
     /// the target.
@@ -114,6 +154,32 @@ pub struct ActorZoneMap {
     map: EntityHashMap<(Character, bool, HashSet<TileKind>)>,
 }
 
+/// Zones the player is standing in right now.
+///
+/// Spares the systems that only care about the player's whereabouts from
+/// keeping track of the [`ActorMovementEvent`]s.
+/// Updated by [`update_player_zones`] each frame.
+/// See also [`player_in_zone`].
+#[derive(Resource, Reflect, Default, Debug)]
+#[reflect(Resource)]
+pub struct PlayerZones {
+    zones: HashSet<ZoneTileKind>,
+}
+
+/// Scales how fast actors walk onto squares with given tile kinds.
+///
+/// For example, a zone with mud can have a multiplier of 0.5, which means it
+/// takes an actor twice as long to step onto such a square.
+/// A road can have a multiplier of 2.0 to halve the time.
+///
+/// Tile kinds that are not present have the multiplier of 1.0.
+/// If a square has multiple tiles with a multiplier, the slowest one wins.
+#[derive(Resource, Reflect, Default, Debug)]
+#[reflect(Resource)]
+pub struct TerrainSpeed {
+    multipliers: HashMap<TileKind, f32>,
+}
+
 /// Some useful events for actors.
 #[derive(Event, Reflect)]
 pub enum ActorMovementEvent {
@@ -133,6 +199,22 @@ pub enum ActorMovementEvent {
     },
 }
 
+/// Emitted by [`animate_movement`] once per square an [`Actor`] arrives to.
+/// Subscribe to it for footstep sounds, dust or tile triggers.
+#[derive(Event, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActorSteppedEvent {
+    /// The actor that made the step.
+    pub entity: Entity,
+    /// The square the actor stepped off of.
+    pub from: Square,
+    /// The square the actor arrived to.
+    pub to: Square,
+    /// The first tile on the arrival square that's neither empty nor an actor,
+    /// e.g. a trail or a zone.
+    /// [`None`] if there's no such tile.
+    pub kind: Option<TileKind>,
+}
+
 /// Identifies an actor in the [`ActorMovementEvent`].
 #[derive(Reflect)]
 pub struct Who {
@@ -161,8 +243,13 @@ pub struct CharacterBundleBuilder {
     initial_direction: GridDirection,
     walking_to: ActorMovement,
     initial_step_time: Option<Duration>,
+    turn_delay: Duration,
     color: Option<Color>,
     behavior_tree: Option<BehaviorTree>,
+    persist_position: bool,
+    interactable: bool,
+    layout: SquareLayout,
+    ysort: MapYSort,
 }
 
 /// Event that's emitted when the player clicks interaction near an NPC.
@@ -283,32 +370,267 @@ pub fn emit_movement_events(
     }
 }
 
+/// Copies the zones of the player from the [`ActorZoneMap`] into
+/// [`PlayerZones`].
+///
+/// Runs after [`emit_movement_events`].
+pub fn update_player_zones(
+    actor_zone_map: Res<ActorZoneMap>,
+    mut player_zones: ResMut<PlayerZones>,
+) {
+    let zones: HashSet<_> = actor_zone_map
+        .map
+        .values()
+        .filter(|(_, is_player, _)| *is_player)
+        .flat_map(|(_, _, active_zones)| active_zones)
+        .filter_map(|tile| match tile {
+            TileKind::Zone(zone) => Some(*zone),
+            _ => None,
+        })
+        .collect();
+
+    // avoids change detection triggering every frame
+    if player_zones.zones != zones {
+        player_zones.zones = zones;
+    }
+}
+
+/// Pushes an [`Actor`] off the grid for a short while, e.g. when bumping into
+/// a door.
+///
+/// While the actor has this component, [`apply_knockback`] moves it instead
+/// of [`animate_movement`] and the player's movement input is ignored.
+/// Once the knockback runs out, the actor is snapped to the square it ended
+/// up on and the component is removed.
+#[derive(Component, Reflect, Debug, Clone)]
+pub struct Knockback {
+    /// In pixels per second.
+    pub velocity: Vec2,
+    /// How long until the actor is snapped back to the grid.
+    pub remaining: Duration,
+}
+
+/// Squares that an [`Actor`] covers relative to the square it stands on, e.g.
+/// a wide NPC or a pushcart.
+///
+/// The anchor square, i.e. [`Actor::current_square`], is always covered
+/// whether it's listed or not.
+/// Actors without this component cover just the anchor.
+/// The actor can only step onto a square if all the squares its footprint
+/// covers from there are walkable.
+///
+/// Y-sorting still uses the anchor, so pick the front-most (lowest) square as
+/// the anchor.
+#[derive(
+    Component,
+    Reflect,
+    Debug,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+)]
+pub struct Footprint(pub Vec<Square>);
+
+/// Which tiles block an [`Actor`] when it moves and when it plans a path.
+/// Actors without this component are blocked by walls and other actors.
+///
+/// E.g. a ghost passes through walls, and in a cutscene an actor can walk
+/// through everything.
+#[derive(
+    Component,
+    Reflect,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Deserialize,
+    Serialize,
+)]
+pub struct Passability {
+    /// Can't walk over [`TileKind::Wall`].
+    pub blocked_by_walls: bool,
+    /// Can't walk over [`TileKind::Actor`] of other actors.
+    pub blocked_by_actors: bool,
+}
+
+/// Keeps an [`Actor`] turned towards another actor while standing still,
+/// e.g. a shopkeeper tracking the player.
+///
+/// Handled by [`face_target`].
+/// Walking takes precedence, the actor faces where they walk.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+pub struct FaceTarget {
+    /// Must be an entity with [`Actor`] component.
+    pub entity: Entity,
+}
+
+/// The square of an NPC with this component is saved into the
+/// [`GlobalStore`] when the scene is left by [`save_positions`] and restored
+/// on the next load by [`restore_positions`].
+/// Keyed by the scene and the character, so each character can only persist
+/// one position per scene.
+///
+/// If the saved square is not walkable anymore, the NPC is placed on the
+/// nearest walkable square.
+#[derive(Component, Reflect, Debug, Clone, Copy, Default)]
+pub struct PersistPosition;
+
+/// Runs when the scene is left.
+pub fn save_positions(
+    store: Res<GlobalStore>,
+    scene: Res<State<WhichTopDownScene>>,
+
+    actors: Query<&Actor, With<PersistPosition>>,
+) {
+    for actor in actors.iter() {
+        let Square { x, y } = actor.current_square();
+        trace!("Saving position of {:?} at {x}, {y}", actor.character);
+        store
+            .actor_position(scene.get(), actor.character.name())
+            .set((x, y));
+    }
+}
+
+/// Runs while the scene is loading and the NPCs are being spawned.
+/// Only affects NPCs that have just been spawned.
+pub fn restore_positions(
+    store: Res<GlobalStore>,
+    scene: Res<State<WhichTopDownScene>>,
+    tilemap: Res<TileMap>,
+
+    mut actors: Query<
+        (
+            Entity,
+            &mut Actor,
+            &mut Transform,
+            Option<&Footprint>,
+            Option<&Passability>,
+        ),
+        Added<PersistPosition>,
+    >,
+) {
+    for (entity, mut actor, mut transform, footprint, passability) in
+        actors.iter_mut()
+    {
+        let Some((x, y)) = store
+            .actor_position(scene.get(), actor.character.name())
+            .get()
+        else {
+            continue;
+        };
+
+        let saved = sq(x, y);
+        let footprint = Footprint::squares_of(footprint);
+        let walker = Walker::new(entity, passability);
+        let Some(square) =
+            tilemap.nearest_walkable_square(saved, walker, footprint)
+        else {
+            warn!(
+                "No walkable square to restore {:?} near {saved}",
+                actor.character
+            );
+            continue;
+        };
+        if square != saved {
+            debug!(
+                "Saved square {saved} of {:?} is not walkable, using {square}",
+                actor.character
+            );
+        }
+
+        // the tiles are recalculated in [`animate_movement`] as they
+        // stand still
+        actor.walking_from = square;
+        actor.walking_to = default();
+        let world_pos = tilemap.layout().square_to_world_pos(square);
+        transform.translation = world_pos.extend(tilemap.ysort(world_pos));
+    }
+}
+
+/// Turns actors with [`FaceTarget`] towards their target.
+pub fn face_target(
+    mut actors: Query<&mut Actor>,
+    facing: Query<(Entity, &FaceTarget)>,
+) {
+    for (entity, FaceTarget { entity: target }) in facing.iter() {
+        let Some(target) = actors.get(*target).ok().map(Actor::current_square)
+        else {
+            continue;
+        };
+        let Ok(actor) = actors.get(entity) else {
+            continue;
+        };
+        if !actor.walking_to.is_still() {
+            continue;
+        }
+
+        let Some(direction) = actor.current_square().direction_to(target)
+        else {
+            continue;
+        };
+        if actor.direction != direction {
+            // only mutably borrow when needed to avoid change detection
+            actors.get_mut(entity).unwrap().direction = direction;
+        }
+    }
+}
+
 /// Actually moves the actors.
 /// Other systems will only edit the `Actor` component to plan the movement.
+/// Gliding NPCs are moved by [`npc::glide::steer`] instead.
 ///
 /// The z is based off y.
-/// See the [`ysort`] for more info.
+/// See the [`TileMap::ysort`] for more info.
 pub fn animate_movement(
     time: Res<Time>,
+    terrain_speed: Res<TerrainSpeed>,
     mut tilemap: ResMut<TileMap>,
+    mut stepped: EventWriter<ActorSteppedEvent>,
 
     mut actors: Query<
-        (Entity, &mut Actor, &mut TextureAtlas, &mut Transform),
-        Without<Player>,
+        (
+            Entity,
+            &mut Actor,
+            &mut TextureAtlas,
+            &mut Transform,
+            Option<&Footprint>,
+            Option<&Passability>,
+        ),
+        (
+            Without<Player>,
+            Without<Knockback>,
+            Without<npc::glide::Glide>,
+        ),
     >,
     mut player: Query<
-        (Entity, &mut Actor, &mut TextureAtlas, &mut Transform),
-        With<Player>,
+        (
+            Entity,
+            &mut Actor,
+            &mut TextureAtlas,
+            &mut Transform,
+            Option<&Footprint>,
+            Option<&Passability>,
+        ),
+        (With<Player>, Without<Knockback>),
     >,
 ) {
-    for (entity, mut actor, sprite, transform) in actors.iter_mut() {
+    for (entity, mut actor, sprite, transform, footprint, passability) in
+        actors.iter_mut()
+    {
         debug_assert!(!actor.is_player());
 
         animate_movement_for_actor(
             &time,
+            &terrain_speed,
             &mut tilemap,
-            entity,
+            &mut stepped,
+            Walker::new(entity, passability),
             &mut actor,
+            Footprint::squares_of(footprint),
             sprite,
             transform,
         );
@@ -317,51 +639,147 @@ pub fn animate_movement(
     // the player goes always last because of how we handle occupied tiles:
     // the later actor has an advantage
     // see `TileMap::replace_actor_tiles`
-    if let Some((entity, mut actor, sprite, transform)) =
-        player.get_single_mut_or_none()
+    if let Some((
+        entity,
+        mut actor,
+        sprite,
+        transform,
+        footprint,
+        passability,
+    )) = player.get_single_mut_or_none()
     {
         debug_assert!(actor.is_player());
         animate_movement_for_actor(
             &time,
+            &terrain_speed,
             &mut tilemap,
-            entity,
+            &mut stepped,
+            Walker::new(entity, passability),
             &mut actor,
+            Footprint::squares_of(footprint),
             sprite,
             transform,
         );
     }
 }
 
+/// Moves actors with [`Knockback`] off the grid.
+///
+/// The actor never enters a square that it couldn't walk onto.
+/// If it would, the knockback stops early.
+/// That's how we guarantee that the square we snap to in the end is
+/// walkable, i.e. we don't teleport through walls.
+pub fn apply_knockback(
+    mut cmd: Commands,
+    time: Res<Time>,
+    mut tilemap: ResMut<TileMap>,
+    mut plan_path: EventWriter<npc::PlanPathEvent>,
+
+    mut actors: Query<(
+        Entity,
+        &mut Actor,
+        &mut Knockback,
+        &mut Transform,
+        Option<&Footprint>,
+        Option<&Passability>,
+        Option<&mut NpcInTheMap>,
+    )>,
+) {
+    for (
+        entity,
+        mut actor,
+        mut knockback,
+        mut transform,
+        footprint,
+        passability,
+        npc,
+    ) in actors.iter_mut()
+    {
+        let footprint = Footprint::squares_of(footprint);
+        let walker = Walker::new(entity, passability);
+
+        let dt = time.delta().min(knockback.remaining);
+        knockback.remaining -= dt;
+
+        let from = transform.translation.truncate();
+        let to = from + knockback.velocity * dt.as_secs_f32();
+
+        // sample the way at least twice per square so that we don't skip
+        // over a thin wall when the knockback is fast
+        let layout = *tilemap.layout();
+        let samples = ((to - from).length() / (layout.square_size / 2.0))
+            .ceil()
+            .max(1.0);
+        let mut reached = from;
+        for i in 1..=samples as usize {
+            let pos = from.lerp(to, i as f32 / samples);
+            let square = layout.world_pos_to_square(pos);
+            if !tilemap.is_walkable_with_footprint(square, walker, footprint) {
+                knockback.remaining = Duration::ZERO;
+                break;
+            }
+            reached = pos;
+        }
+
+        let rounded = (reached * PIXEL_ZOOM as f32).round() / PIXEL_ZOOM as f32;
+        transform.translation = rounded.extend(tilemap.ysort(rounded));
+
+        if !knockback.remaining.is_zero() {
+            continue;
+        }
+
+        // back to the grid
+        let square = layout.world_pos_to_square(reached);
+        trace!("Knockback of {entity:?} is over at {square}");
+        let on_square = layout.square_to_world_pos(square);
+        transform.translation = on_square.extend(tilemap.ysort(on_square));
+        actor.walking_from = square;
+        actor.walking_to = default();
+        tilemap.replace_actor_tiles(walker, &mut actor, footprint);
+
+        // the path was planned from where the actor was before
+        if let Some(mut npc) = npc {
+            npc.replan(entity, &mut plan_path);
+        }
+
+        cmd.entity(entity).remove::<Knockback>();
+    }
+}
+
 /// Moves the actor on screen and changes frames for the sprite.
 fn animate_movement_for_actor(
     time: &Time,
+    terrain_speed: &TerrainSpeed,
     tilemap: &mut TileMap,
-    entity: Entity,
+    stepped: &mut EventWriter<ActorSteppedEvent>,
+    walker: Walker,
     actor: &mut Actor,
+    footprint: &[Square],
     mut sprite: Mut<TextureAtlas>,
     mut transform: Mut<Transform>,
 ) {
     use GridDirection::*;
 
     let current_direction = actor.direction;
-    let step_time = actor.step_time;
 
     let walking_to = match &mut actor.walking_to {
         ActorMovement::Target(walking_to) => walking_to,
         ActorMovement::Still { since } => {
             since.tick(time.delta());
 
-            sprite.index = actor.character.standing_sprite_atlas_index(
+            sprite.index = actor.character.sprite_atlas_index(
+                AnimationState::Standing {
+                    since: since.elapsed(),
+                },
                 current_direction,
-                time,
-                Some(since.elapsed()),
+                time.elapsed_wrapped(),
             );
 
             // we need to update the tiles that the actor occupies because other
             // actors might be moving around it, freeing up some space
             // OPTIMIZE: the logic for replacing standing still tiles can be
             // simplified, and if it was, we could also run it when moving
-            tilemap.replace_actor_tiles(entity, actor);
+            tilemap.replace_actor_tiles(walker, actor, footprint);
 
             // nowhere to move
             return;
@@ -369,6 +787,15 @@ fn animate_movement_for_actor(
     };
 
     walking_to.since.tick(time.delta());
+    if let Some((_, since)) = &mut actor.queued {
+        since.tick(time.delta());
+    }
+
+    // the terrain of the square we're walking to decides how fast we get there
+    // and both the translation and the walking animation must agree on it
+    let step_time = actor
+        .step_time
+        .div_f32(terrain_speed.multiplier_at(tilemap, walking_to.square));
 
     // between 0 and 1, how far we are into the walk from square to square
     let lerp_factor = walking_to.since.elapsed_secs()
@@ -381,7 +808,7 @@ fn animate_movement_for_actor(
         };
 
     // the world pos in pxs where we're walking to
-    let to = LAYOUT.square_to_world_pos(walking_to.square);
+    let to = tilemap.layout().square_to_world_pos(walking_to.square);
 
     if lerp_factor >= 1.0 {
         // reached the target, wat else
@@ -390,17 +817,34 @@ fn animate_movement_for_actor(
 
         let rounded = (to * PIXEL_ZOOM as f32).round() / PIXEL_ZOOM as f32;
         // prevents fractions if camera would want to follow the player
-        transform.translation = rounded.extend(ysort(rounded));
+        transform.translation = rounded.extend(tilemap.ysort(rounded));
 
-        let standing_still_sprite_index = actor
-            .character
-            .standing_sprite_atlas_index(current_direction, time, None);
+        let standing_still_sprite_index = actor.character.sprite_atlas_index(
+            AnimationState::Standing { since: default() },
+            current_direction,
+            time.elapsed_wrapped(),
+        );
+
+        // a fresh direction pressed mid-step takes precedence over the plan
+        let planned = walking_to.planned.take();
+        let queued = actor
+            .queued
+            .take()
+            .filter(|(_, since)| since.elapsed() <= QUEUED_DIRECTION_EXPIRES_IN)
+            .map(|(direction, _)| (new_from.neighbor(direction), direction))
+            .filter(|(square, _)| {
+                tilemap.can_step_with_footprint(
+                    new_from, *square, walker, footprint,
+                )
+            });
 
-        if let Some((new_square, new_direction)) = walking_to.planned.take() {
+        if let Some((new_square, new_direction)) = queued.or(planned) {
             // there's still next target to walk to, let's check whether it's
             // still available
 
-            if tilemap.is_walkable(new_square, entity) {
+            if tilemap.can_step_with_footprint(
+                new_from, new_square, walker, footprint,
+            ) {
                 walking_to.since.reset();
                 walking_to.square = new_square;
                 actor.direction = new_direction;
@@ -417,23 +861,53 @@ fn animate_movement_for_actor(
             actor.walking_to = default();
         }
 
+        stepped.send(ActorSteppedEvent::new(
+            tilemap,
+            walker.entity,
+            actor.walking_from,
+            new_from,
+        ));
         actor.walking_from = new_from;
 
-        tilemap.replace_actor_tiles(entity, actor);
+        tilemap.replace_actor_tiles(walker, actor, footprint);
     } else {
         // we're still walking to the target square, do the animation
 
-        sprite.index = actor.character.walking_sprite_atlas_index(
+        sprite.index = actor.character.sprite_atlas_index(
+            AnimationState::Walking { step_time },
             current_direction,
-            time,
-            step_time,
+            time.elapsed_wrapped(),
         );
 
-        let from = LAYOUT.square_to_world_pos(actor.walking_from);
+        let from = tilemap.layout().square_to_world_pos(actor.walking_from);
         let precise = from.lerp(to, lerp_factor);
         // prevents fractions if camera would want to follow the player
         let rounded = (precise * PIXEL_ZOOM as f32).round() / PIXEL_ZOOM as f32;
-        transform.translation = rounded.extend(ysort(rounded));
+        transform.translation = rounded.extend(tilemap.ysort(rounded));
+    }
+}
+
+impl ActorSteppedEvent {
+    /// Finds the [`ActorSteppedEvent::kind`] of the arrival square.
+    fn new(
+        tilemap: &TileMap,
+        entity: Entity,
+        from: Square,
+        to: Square,
+    ) -> Self {
+        Self {
+            entity,
+            from,
+            to,
+            kind: tilemap.get(to).and_then(|tiles| {
+                tiles
+                    .iter()
+                    .find(|tile| {
+                        !matches!(tile, TileKind::Empty | TileKind::Actor(_))
+                    })
+                    .copied()
+            }),
+        }
     }
 }
 
@@ -448,6 +922,18 @@ impl ActorMovementEvent {
     }
 }
 
+impl PlayerZones {
+    /// Whether the player stands in the zone.
+    pub fn contains(&self, zone: ZoneTileKind) -> bool {
+        self.zones.contains(&zone)
+    }
+
+    /// All zones the player stands in, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = ZoneTileKind> + '_ {
+        self.zones.iter().copied()
+    }
+}
+
 impl Actor {
     /// Get the current square.
     /// That is the square that the actor is about to arrive to if they're
@@ -459,6 +945,57 @@ impl Actor {
             .unwrap_or(self.walking_from)
     }
 
+    /// Turns the actor towards the target square without moving.
+    /// Does nothing if the actor stands on the target square.
+    ///
+    /// See [`Square::direction_to`].
+    pub fn face_towards(&mut self, target: Square) {
+        if let Some(direction) = self.current_square().direction_to(target) {
+            self.direction = direction;
+        }
+    }
+
+    /// Changes how fast the actor walks.
+    ///
+    /// If the actor is in the middle of a step, the time walked so far is
+    /// scaled so that the actor keeps its position on screen and walks the
+    /// rest of the step at the new pace.
+    pub fn set_step_time(&mut self, step_time: Duration) {
+        if let Some(target) = self.walking_to.target_mut() {
+            if !self.step_time.is_zero() {
+                let progress =
+                    target.since.elapsed_secs() / self.step_time.as_secs_f32();
+                target.since.set_elapsed(step_time.mul_f32(progress));
+            }
+        }
+
+        self.step_time = step_time;
+    }
+
+    /// Turns the actor standing still towards the direction and returns
+    /// whether they should stay in place rather than walk.
+    ///
+    /// They stay if they just turned, or if they turned less than
+    /// [`Actor::turn_delay`] ago.
+    /// With zero delay, the actor never stays.
+    pub fn turn_in_place(&mut self, direction: GridDirection) -> bool {
+        if self.turn_delay.is_zero() {
+            return false;
+        }
+        let ActorMovement::Still { since } = &mut self.walking_to else {
+            return false;
+        };
+
+        if self.direction != direction {
+            self.direction = direction;
+            // turning is not standing, the delay counts from now
+            since.reset();
+            true
+        } else {
+            since.elapsed() < self.turn_delay
+        }
+    }
+
     /// Whether the actor is a player.
     ///
     /// This information is duplicated by the [`Player`] component.
@@ -472,6 +1009,33 @@ impl Actor {
         if let Some(target) = self.walking_to.target_mut() {
             target.planned = None;
         }
+        self.queued = None;
+    }
+}
+
+impl TerrainSpeed {
+    /// Set the walking speed multiplier for the given tile kind.
+    /// Must be a positive number.
+    pub fn set(
+        &mut self,
+        kind: impl Into<TileKind>,
+        multiplier: f32,
+    ) -> &mut Self {
+        debug_assert!(multiplier > 0.0, "Multiplier must be positive");
+        self.multipliers.insert(kind.into(), multiplier);
+        self
+    }
+
+    /// The speed multiplier of the slowest tile on the given square.
+    /// Defaults to 1.0.
+    pub fn multiplier_at(&self, tilemap: &TileMap, square: Square) -> f32 {
+        tilemap
+            .get(square)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|tile| self.multipliers.get(tile).copied())
+            .reduce(f32::min)
+            .unwrap_or(1.0)
     }
 }
 
@@ -514,16 +1078,30 @@ impl CharacterBundleBuilder {
             initial_position: default(),
             walking_to: default(),
             initial_step_time: default(),
+            turn_delay: default(),
             color: default(),
             behavior_tree: default(),
+            persist_position: false,
+            interactable: true,
+            layout: LAYOUT,
+            ysort: default(),
         }
     }
 
+    /// Set it to the map of the scene the character is spawned into.
+    /// Its [`TileMap::layout`] converts between squares and world positions
+    /// and [`TileMap::ysort`] gives the character its initial z.
+    /// Defaults to those of [`TileMap::default`].
+    ///
+    /// Must be set before the position of the character.
+    pub fn tilemap(&mut self, tilemap: &TileMap) -> &mut Self {
+        self.layout = *tilemap.layout();
+        self.ysort = tilemap.map_ysort();
+        self
+    }
+
     /// Where to spawn the character.
-    /// Converted into the square by [`LAYOUT`] (see
-    /// the `common_layout` crate).
-    /// The specific layout is provided in the
-    /// [`CharacterBundleBuilder::insert_bundle_into`] method's `T`.
+    /// Converted into the square by [`CharacterBundleBuilder::tilemap`].
     pub fn initial_position(&mut self, initial_position: Vec2) -> &mut Self {
         self.initial_position = initial_position;
         self
@@ -531,7 +1109,7 @@ impl CharacterBundleBuilder {
 
     /// Where to spawn the character.
     pub fn initial_square(&mut self, initial_square: Square) -> &mut Self {
-        self.initial_position = LAYOUT.square_to_world_pos(initial_square);
+        self.initial_position = self.layout.square_to_world_pos(initial_square);
         self
     }
 
@@ -558,7 +1136,7 @@ impl CharacterBundleBuilder {
         walking_to: Square,
     ) -> &mut Self {
         let walking_to = ActorTarget::new(
-            LAYOUT.world_pos_to_square(self.initial_position) + walking_to,
+            self.layout.world_pos_to_square(self.initial_position) + walking_to,
         );
         self.walking_to = ActorMovement::Target(walking_to);
         self
@@ -570,6 +1148,12 @@ impl CharacterBundleBuilder {
         self
     }
 
+    /// See [`Actor::turn_delay`].
+    pub fn turn_delay(&mut self, turn_delay: Duration) -> &mut Self {
+        self.turn_delay = turn_delay;
+        self
+    }
+
     /// What behavior tree to use for the NPC.
     pub fn behavior_tree(
         &mut self,
@@ -579,6 +1163,22 @@ impl CharacterBundleBuilder {
         self
     }
 
+    /// Remember where the NPC stood when the player left the scene and put
+    /// them back there when the player comes back.
+    /// See [`PersistPosition`].
+    pub fn persist_position(&mut self) -> &mut Self {
+        self.persist_position = true;
+        self
+    }
+
+    /// The NPC cannot be inspected nor talked to, e.g. a passerby in the
+    /// [`crowd::AmbientCrowd`].
+    /// Has no effect on the player.
+    pub fn non_interactable(&mut self) -> &mut Self {
+        self.interactable = false;
+        self
+    }
+
     /// Spawns a bundle into the provided entity command queue.
     /// The bundle includes:
     /// - [`Name`] component with the character's name
@@ -603,35 +1203,48 @@ impl CharacterBundleBuilder {
             initial_direction,
             walking_to,
             initial_step_time: step_time,
+            turn_delay,
             color,
             behavior_tree,
+            persist_position,
+            interactable,
+            layout,
+            ysort,
         } = self;
 
         let step_time = step_time.unwrap_or(character.default_step_time());
 
         if matches!(character, Character::Winnie) {
             cmd.insert(Player);
-        } else {
+        } else if interactable {
             cmd.insert((
                 NpcInTheMap::default(),
                 InspectLabelCategory::Npc
                     .into_label(character.name())
                     .with_emit_event_on_interacted(BeginDialogEvent(id.into())),
             ));
+        } else {
+            cmd.insert(NpcInTheMap::default());
         }
 
         if let Some(behavior_tree) = behavior_tree {
             cmd.insert(behavior_tree);
         }
 
+        if persist_position {
+            cmd.insert(PersistPosition);
+        }
+
         cmd.insert((
             Name::from(character.name()),
             RenderLayers::layer(render_layer::OBJ),
             Actor {
                 character,
                 step_time,
+                turn_delay,
+                queued: None,
                 direction: initial_direction,
-                walking_from: LAYOUT.world_pos_to_square(initial_position),
+                walking_from: layout.world_pos_to_square(initial_position),
                 walking_to,
                 // see the method docs
                 occupies: default(),
@@ -645,7 +1258,7 @@ impl CharacterBundleBuilder {
                     ..default()
                 },
                 transform: Transform::from_translation(
-                    initial_position.extend(ysort(initial_position)),
+                    initial_position.extend(ysort.z(initial_position)),
                 ),
                 ..default()
             },
@@ -690,10 +1303,11 @@ lazy_static! {
 }
 
 impl TileMap {
-    fn replace_actor_tiles(&mut self, entity: Entity, actor: &mut Actor) {
+    /// Frees the squares the actor occupies.
+    fn remove_actor_tiles(&mut self, entity: Entity, actor: &mut Actor) {
         for (sq, layer) in actor.occupies.drain(..) {
             // we can't assume it to eq the actor's tile because in some rare
-            // edge cases we evict the actor, see below
+            // edge cases we evict the actor, see `replace_actor_tiles`
             self.map_tile(sq, layer, |current| {
                 if current == TileKind::Actor(entity) {
                     Some(TileKind::Empty)
@@ -702,10 +1316,20 @@ impl TileMap {
                 }
             });
         }
+    }
+
+    fn replace_actor_tiles(
+        &mut self,
+        walker: Walker,
+        actor: &mut Actor,
+        footprint: &[Square],
+    ) {
+        let entity = walker.entity;
+        self.remove_actor_tiles(entity, actor);
 
         let actor_stands_at = actor.current_square();
 
-        let can_move = self.can_actor_move(entity, actor_stands_at);
+        let can_move = self.can_actor_move(walker, actor_stands_at, footprint);
 
         // If the actor cannot move (rare but possible), we have following
         // strategies:
@@ -715,7 +1339,10 @@ impl TileMap {
             //      actors from [top down left right]
             //    - Player must go last in the iteration over all actor movement
 
-            for sq_to_clear in actor_stands_at.neighbors_with_diagonal() {
+            let covered = Footprint::cover(actor_stands_at, footprint);
+            let to_clear =
+                covered.flat_map(|square| square.neighbors_with_diagonal());
+            for sq_to_clear in to_clear {
                 self.map_tiles(sq_to_clear, |tile| {
                     if let TileKind::Actor(a) = tile {
                         if a != entity {
@@ -738,7 +1365,7 @@ impl TileMap {
                     // either has an actor or is walkable
                     self.all_on(*sq, |tile| {
                         matches!(tile, TileKind::Actor(_))
-                            || tile.is_walkable(entity)
+                            || tile.is_walkable(walker)
                     })
                 })
                 .collect_vec();
@@ -755,9 +1382,12 @@ impl TileMap {
         }
 
         // then for the remaining squares that don't have the actor yet
-        for sq_origin in ACTOR_ZONE_AT_ORIGIN.iter().copied() {
-            let sq = sq_origin + actor_stands_at;
-
+        let zone = Footprint::cover(actor_stands_at, footprint)
+            .flat_map(|covered| {
+                ACTOR_ZONE_AT_ORIGIN.iter().map(move |sq| *sq + covered)
+            })
+            .unique();
+        for sq in zone {
             if let Some(layer) =
                 self.add_tile_to_first_empty_layer(sq, TileKind::Actor(entity))
             {
@@ -771,9 +1401,57 @@ impl TileMap {
     }
 
     #[inline]
-    fn can_actor_move(&self, entity: Entity, from: Square) -> bool {
-        from.neighbors_with_diagonal()
-            .any(|neighbor| self.is_walkable(neighbor, entity))
+    fn can_actor_move(
+        &self,
+        walker: Walker,
+        from: Square,
+        footprint: &[Square],
+    ) -> bool {
+        from.neighbors_with_diagonal().any(|neighbor| {
+            self.can_step_with_footprint(from, neighbor, walker, footprint)
+        })
+    }
+}
+
+impl Passability {
+    /// Blocked by walls and other actors.
+    pub const SOLID: Self = Self {
+        blocked_by_walls: true,
+        blocked_by_actors: true,
+    };
+    /// Passes through walls but not through other actors.
+    pub const GHOST: Self = Self {
+        blocked_by_walls: false,
+        blocked_by_actors: true,
+    };
+    /// Nothing blocks the actor.
+    pub const UNBLOCKED: Self = Self {
+        blocked_by_walls: false,
+        blocked_by_actors: false,
+    };
+}
+
+impl Default for Passability {
+    fn default() -> Self {
+        Self::SOLID
+    }
+}
+
+impl Footprint {
+    /// The squares of the footprint if any, otherwise no extra squares.
+    pub fn squares_of(footprint: Option<&Self>) -> &[Square] {
+        footprint
+            .map(|Self(squares)| squares.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// All squares covered when the actor stands on the anchor, including
+    /// the anchor.
+    fn cover(
+        anchor: Square,
+        footprint: &[Square],
+    ) -> impl Iterator<Item = Square> + '_ {
+        iter::once(anchor).chain(footprint.iter().map(move |sq| *sq + anchor))
     }
 }
 
@@ -828,7 +1506,7 @@ impl From<Character> for ActorOrCharacter {
 
 #[cfg(test)]
 mod tests {
-    use bevy::ecs::system::SystemId;
+    use bevy::ecs::system::{RunSystemOnce, SystemId};
     use rand::seq::IteratorRandom;
     use strum::IntoEnumIterator;
 
@@ -1015,6 +1693,231 @@ mod tests {
         assert!(is_actor_alone, "Winnie not alone on {winnie_pos}");
     }
 
+    #[test]
+    fn it_takes_twice_as_long_to_cross_half_speed_terrain() {
+        use crate::top_down::ZoneTileKind;
+
+        let mud = sq(1, 0);
+
+        let mut w = World::default();
+        let mut tilemap = TileMap::default();
+        tilemap.add_tile_to_first_empty_layer(mud, ZoneTileKind::NearbyOcean);
+        w.insert_resource(tilemap);
+        w.insert_resource(Time::<()>::default());
+        let mut terrain_speed = TerrainSpeed::default();
+        terrain_speed.set(ZoneTileKind::NearbyOcean, 0.5);
+        w.insert_resource(terrain_speed);
+        w.init_resource::<Events<ActorSteppedEvent>>();
+
+        let marie = w
+            .spawn(Actor {
+                character: Character::Marie,
+                step_time: STEP_TIME,
+                turn_delay: default(),
+                queued: None,
+                direction: GridDirection::Right,
+                walking_from: sq(0, 0),
+                walking_to: ActorTarget::new(mud).into(),
+                occupies: vec![],
+            })
+            .insert(SpatialBundle::default())
+            .insert(TextureAtlas {
+                index: 0,
+                layout: Character::Marie.sprite_atlas_layout_handle(),
+            })
+            .id();
+        let system_id = w.register_system(animate_movement);
+
+        let advance_by = |w: &mut World, by: Duration| {
+            w.get_resource_mut::<Time>().unwrap().advance_by(by);
+            w.run_system(system_id).unwrap();
+        };
+        let walking_from =
+            |w: &World| w.get::<Actor>(marie).unwrap().walking_from;
+
+        // on regular terrain the actor would've been there by now
+        advance_by(&mut w, STEP_TIME + STEP_TIME / 2);
+        assert_eq!(sq(0, 0), walking_from(&w));
+        let translation = w.get::<Transform>(marie).unwrap().translation;
+        let from = LAYOUT.square_to_world_pos(sq(0, 0));
+        let to = LAYOUT.square_to_world_pos(mud);
+        assert!(translation.x > from.x && translation.x < to.x);
+
+        advance_by(&mut w, STEP_TIME / 2 + Duration::from_millis(1));
+        assert_eq!(mud, walking_from(&w));
+    }
+
+    #[test]
+    fn it_emits_one_stepped_event_per_square() {
+        let mut w = World::default();
+        let mut tilemap = TileMap::default();
+        tilemap.add_tile_to_first_empty_layer(sq(2, 0), TileKind::Trail);
+        w.insert_resource(tilemap);
+        w.insert_resource(Time::<()>::default());
+        w.init_resource::<TerrainSpeed>();
+        w.init_resource::<Events<ActorSteppedEvent>>();
+
+        let marie = w
+            .spawn(Actor {
+                character: Character::Marie,
+                step_time: STEP_TIME,
+                turn_delay: default(),
+                queued: None,
+                direction: GridDirection::Right,
+                walking_from: sq(0, 0),
+                walking_to: ActorTarget {
+                    planned: Some((sq(2, 0), GridDirection::Right)),
+                    ..ActorTarget::new(sq(1, 0))
+                }
+                .into(),
+                occupies: vec![],
+            })
+            .insert(SpatialBundle::default())
+            .insert(TextureAtlas {
+                index: 0,
+                layout: Character::Marie.sprite_atlas_layout_handle(),
+            })
+            .id();
+        let system_id = w.register_system(animate_movement);
+
+        // several ticks per step and then some while standing still
+        for _ in 0..12 {
+            w.get_resource_mut::<Time>()
+                .unwrap()
+                .advance_by(STEP_TIME / 4 + Duration::from_millis(1));
+            w.run_system(system_id).unwrap();
+        }
+
+        let events: Vec<_> = w
+            .resource_mut::<Events<ActorSteppedEvent>>()
+            .drain()
+            .collect();
+        assert_eq!(
+            vec![
+                ActorSteppedEvent {
+                    entity: marie,
+                    from: sq(0, 0),
+                    to: sq(1, 0),
+                    kind: None,
+                },
+                ActorSteppedEvent {
+                    entity: marie,
+                    from: sq(1, 0),
+                    to: sq(2, 0),
+                    kind: Some(TileKind::Trail),
+                },
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn it_tracks_zones_of_player() {
+        use ZoneTileKind::{Bed, Hallway};
+
+        let mut w = World::default();
+        let mut tilemap = TileMap::default();
+        tilemap.add_tile_to_first_empty_layer(sq(1, 0), Bed);
+        tilemap.add_tile_to_first_empty_layer(sq(1, 0), Hallway);
+        tilemap.add_tile_to_first_empty_layer(sq(2, 0), Hallway);
+        w.insert_resource(tilemap);
+        w.init_resource::<ActorZoneMap>();
+        w.init_resource::<PlayerZones>();
+        w.init_resource::<Events<ActorMovementEvent>>();
+        let emit_movement_events = w.register_system(emit_movement_events);
+        let update_player_zones = w.register_system(update_player_zones);
+        let in_bed = w.register_system(player_in_zone(Bed));
+
+        let winnie = w
+            .spawn(Actor {
+                character: Character::Winnie,
+                step_time: STEP_TIME,
+                turn_delay: default(),
+                queued: None,
+                direction: GridDirection::Right,
+                walking_from: sq(0, 0),
+                walking_to: default(),
+                occupies: vec![],
+            })
+            .insert(Transform::default())
+            .id();
+        let step_to = |w: &mut World, to| {
+            w.get_mut::<Actor>(winnie).unwrap().walking_from = to;
+            w.get_mut::<Transform>(winnie).unwrap().set_changed();
+            w.run_system(emit_movement_events).unwrap();
+            w.run_system(update_player_zones).unwrap();
+            let mut zones: Vec<_> =
+                w.resource::<PlayerZones>().iter().collect();
+            zones.sort();
+            zones
+        };
+
+        assert_eq!(Vec::<ZoneTileKind>::new(), step_to(&mut w, sq(0, 0)));
+        assert!(!w.run_system(in_bed).unwrap());
+
+        assert_eq!(vec![Bed, Hallway], step_to(&mut w, sq(1, 0)));
+        assert!(w.run_system(in_bed).unwrap());
+
+        assert_eq!(vec![Hallway], step_to(&mut w, sq(2, 0)));
+        assert!(!w.run_system(in_bed).unwrap());
+
+        assert_eq!(Vec::<ZoneTileKind>::new(), step_to(&mut w, sq(3, 0)));
+    }
+
+    #[test]
+    fn it_snaps_knocked_back_actor_before_wall() {
+        let wall = sq(3, 0);
+
+        let mut w = World::default();
+        let mut tilemap = TileMap::default();
+        tilemap.add_tile_to_first_empty_layer(wall, TileKind::Wall);
+        w.insert_resource(tilemap);
+        w.insert_resource(Time::<()>::default());
+        w.init_resource::<Events<npc::PlanPathEvent>>();
+
+        let start = LAYOUT.square_to_world_pos(sq(0, 0));
+        let marie = w
+            .spawn(Actor {
+                character: Character::Marie,
+                step_time: STEP_TIME,
+                turn_delay: default(),
+                queued: None,
+                direction: GridDirection::Right,
+                walking_from: sq(0, 0),
+                walking_to: default(),
+                occupies: vec![],
+            })
+            .insert(Knockback {
+                // fast enough to get past the wall within a single tick
+                velocity: Vec2::X * LAYOUT.square_size * 10.0,
+                remaining: Duration::from_secs(1),
+            })
+            .insert(SpatialBundle::from_transform(Transform::from_translation(
+                start.extend(0.0),
+            )))
+            .id();
+        let system_id = w.register_system(apply_knockback);
+
+        w.get_resource_mut::<Time>()
+            .unwrap()
+            .advance_by(Duration::from_millis(500));
+        w.run_system(system_id).unwrap();
+
+        assert!(w.get::<Knockback>(marie).is_none());
+        let actor = w.get::<Actor>(marie).unwrap();
+        assert_eq!(sq(2, 0), actor.walking_from);
+        assert!(actor.walking_to.is_still());
+        let translation = w.get::<Transform>(marie).unwrap().translation;
+        assert_eq!(
+            LAYOUT.square_to_world_pos(sq(2, 0)),
+            translation.truncate()
+        );
+        assert_eq!(
+            &[TileKind::Actor(marie)],
+            w.get_resource::<TileMap>().unwrap().get(sq(2, 0)).unwrap()
+        );
+    }
+
     const STEP_TIME: Duration = Duration::from_secs(1);
 
     fn prepare_world() -> (World, SystemId, Entity, Entity) {
@@ -1022,6 +1925,8 @@ mod tests {
 
         w.insert_resource(TileMap::default());
         w.insert_resource(Time::<()>::default());
+        w.init_resource::<TerrainSpeed>();
+        w.init_resource::<Events<ActorSteppedEvent>>();
 
         // both actors start at the same square
 
@@ -1029,6 +1934,8 @@ mod tests {
             .spawn(Actor {
                 character: Character::Bolt,
                 step_time: STEP_TIME,
+                turn_delay: default(),
+                queued: None,
                 direction: GridDirection::Bottom,
                 walking_from: sq(0, 0),
                 walking_to: default(), // we get them moving later
@@ -1044,6 +1951,8 @@ mod tests {
             .spawn(Actor {
                 character: Character::Marie,
                 step_time: STEP_TIME,
+                turn_delay: default(),
+                queued: None,
                 direction: GridDirection::Bottom,
                 walking_from: sq(0, 0),
                 walking_to: default(), // we get them moving later
@@ -1130,4 +2039,114 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn it_faces_towards_target() {
+        let mut actor = Actor {
+            character: Character::Marie,
+            step_time: STEP_TIME,
+            turn_delay: default(),
+            queued: None,
+            direction: GridDirection::Bottom,
+            walking_from: sq(0, 0),
+            walking_to: default(),
+            occupies: vec![],
+        };
+
+        actor.face_towards(sq(1, 1));
+        assert_eq!(GridDirection::TopRight, actor.direction);
+
+        actor.face_towards(sq(-1, 0));
+        assert_eq!(GridDirection::Left, actor.direction);
+
+        actor.face_towards(sq(0, -5));
+        assert_eq!(GridDirection::Bottom, actor.direction);
+
+        // 3:1 is closer to → than to ↗
+        actor.face_towards(sq(3, 1));
+        assert_eq!(GridDirection::Right, actor.direction);
+
+        actor.face_towards(sq(0, 0));
+        assert_eq!(GridDirection::Right, actor.direction, "same square");
+    }
+
+    #[test]
+    fn it_keeps_still_actor_facing_target() {
+        let mut w = World::default();
+        let spawn = |w: &mut World, character, at| {
+            w.spawn(Actor {
+                character,
+                step_time: STEP_TIME,
+                turn_delay: default(),
+                queued: None,
+                direction: GridDirection::Bottom,
+                walking_from: at,
+                walking_to: default(),
+                occupies: vec![],
+            })
+            .id()
+        };
+        let winnie = spawn(&mut w, Character::Winnie, sq(-2, 2));
+        let marie = spawn(&mut w, Character::Marie, sq(0, 0));
+        w.entity_mut(marie).insert(FaceTarget { entity: winnie });
+        let system_id = w.register_system(face_target);
+
+        w.run_system(system_id).unwrap();
+        assert_eq!(
+            GridDirection::TopLeft,
+            w.get::<Actor>(marie).unwrap().direction
+        );
+
+        w.get_mut::<Actor>(winnie).unwrap().walking_from = sq(0, -3);
+        w.run_system(system_id).unwrap();
+        assert_eq!(
+            GridDirection::Bottom,
+            w.get::<Actor>(marie).unwrap().direction
+        );
+    }
+
+    #[test]
+    fn it_restores_position_next_to_new_wall() {
+        let saved = sq(4, 4);
+
+        let mut w = World::default();
+        let store = GlobalStore::new();
+        store
+            .actor_position(
+                WhichTopDownScene::Downtown,
+                Character::Marie.name(),
+            )
+            .set((saved.x, saved.y));
+        w.insert_resource(store);
+        w.insert_resource(State::new(WhichTopDownScene::Downtown));
+        let mut tilemap = TileMap::default();
+        tilemap.add_tile_to_first_empty_layer(saved, TileKind::Wall);
+        w.insert_resource(tilemap);
+
+        let marie = w
+            .spawn(Actor {
+                character: Character::Marie,
+                step_time: STEP_TIME,
+                turn_delay: default(),
+                queued: None,
+                direction: GridDirection::Bottom,
+                walking_from: sq(0, 0),
+                walking_to: default(),
+                occupies: vec![],
+            })
+            .insert((PersistPosition, Transform::default()))
+            .id();
+        w.run_system_once(restore_positions);
+
+        let restored = w.get::<Actor>(marie).unwrap().walking_from;
+        assert_ne!(saved, restored);
+        assert_eq!(
+            1,
+            (restored - saved).x.abs().max((restored - saved).y.abs())
+        );
+        assert_eq!(
+            LAYOUT.square_to_world_pos(restored),
+            w.get::<Transform>(marie).unwrap().translation.truncate()
+        );
+    }
 }
@@ -0,0 +1,246 @@
+//! Background passersby that wander around a part of the map, e.g. shoppers
+//! in the mall or pedestrians downtown.
+//!
+//! A scene opts in by inserting the [`AmbientCrowd`] resource.
+//! Then [`maintain`] keeps [`AmbientCrowd::count`] passersby within the
+//! bounds.
+//! Each wanders around the square it was spawned at with
+//! [`behaviors::Wander`].
+//! Those that wander out of the bounds are replaced by a new one somewhere
+//! else.
+//!
+//! Passersby cannot be talked to nor inspected.
+//!
+//! Entities are pooled.
+//! A passerby that's no longer needed is hidden and stripped of its
+//! [`Actor`] component instead of being despawned, and it's reused for the
+//! next passerby.
+//! The pool goes away with the scene, see [`despawn_all`].
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_grid_squared::{sq, Square};
+use common_story::Character;
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    thread_rng,
+};
+
+use super::{
+    npc::{behaviors, BehaviorTree, NpcInTheMap},
+    Actor, CharacterExt,
+};
+use crate::top_down::TileMap;
+
+/// Configures the passersby of the current scene.
+/// Can be changed at runtime, e.g. to thin out the crowd in the evening.
+#[derive(Resource, Reflect, Debug, Clone)]
+#[reflect(Resource)]
+pub struct AmbientCrowd {
+    /// How many passersby to keep within the bounds.
+    /// See also [`AmbientCrowd::with_density`].
+    pub count: usize,
+    /// `[left, right, bottom, top]` squares, inclusive.
+    /// Same format as the bounds of the [`TileMap`].
+    pub bounds: [i32; 4],
+    /// Each passerby is one of these picked at random.
+    /// Must not contain the player character.
+    pub characters: Vec<Character>,
+    /// How many squares from where it was spawned can a passerby wander in
+    /// each axis.
+    pub wander_radius: i32,
+    /// How long does a passerby stand at each square it wanders to.
+    pub pause: Duration,
+}
+
+/// Marks entities managed by [`maintain`].
+/// Those without the [`Actor`] component are in the pool.
+#[derive(Component, Reflect, Debug, Default)]
+pub struct Passerby;
+
+impl AmbientCrowd {
+    /// Empty crowd, set the count or density.
+    pub fn new(bounds: [i32; 4], characters: Vec<Character>) -> Self {
+        debug_assert!(
+            !characters.contains(&Character::Winnie),
+            "Player cannot be a passerby"
+        );
+
+        Self {
+            count: 0,
+            bounds,
+            characters,
+            wander_radius: 4,
+            pause: Duration::from_secs(2),
+        }
+    }
+
+    /// Sets [`AmbientCrowd::count`].
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Sets [`AmbientCrowd::count`] from how many passersby there should be
+    /// per 100 squares of the bounds.
+    pub fn with_density(mut self, per_hundred_squares: f32) -> Self {
+        debug_assert!(per_hundred_squares >= 0.0);
+
+        let [left, right, bottom, top] = self.bounds;
+        let area = (right - left + 1).max(0) * (top - bottom + 1).max(0);
+        self.count =
+            (area as f32 * per_hundred_squares / 100.0).round() as usize;
+        self
+    }
+
+    /// Whether the square is within the bounds.
+    pub fn contains(&self, square: Square) -> bool {
+        let [left, right, bottom, top] = self.bounds;
+
+        (left..=right).contains(&square.x) && (bottom..=top).contains(&square.y)
+    }
+}
+
+/// Parks passersby that left the bounds or that are over the count.
+/// Then spawns new ones on random walkable squares within the bounds until
+/// there are enough, reusing the parked entities first.
+pub fn maintain(
+    mut cmd: Commands,
+    asset_server: Res<AssetServer>,
+    crowd: Res<AmbientCrowd>,
+    mut tilemap: ResMut<TileMap>,
+
+    mut passersby: Query<(Entity, &mut Actor), With<Passerby>>,
+    parked: Query<Entity, (With<Passerby>, Without<Actor>)>,
+) {
+    let mut active = 0;
+    for (entity, mut actor) in passersby.iter_mut() {
+        if active < crowd.count && crowd.contains(actor.current_square()) {
+            active += 1;
+            continue;
+        }
+
+        trace!("Parking passerby {entity:?}");
+        tilemap.remove_actor_tiles(entity, &mut actor);
+        cmd.entity(entity)
+            .remove::<(Actor, BehaviorTree, NpcInTheMap)>()
+            .insert(Visibility::Hidden);
+    }
+
+    if active >= crowd.count || crowd.characters.is_empty() {
+        return;
+    }
+
+    let mut rng = thread_rng();
+    let [left, right, bottom, top] = crowd.bounds;
+    let squares = (left..=right)
+        .flat_map(|x| (bottom..=top).map(move |y| sq(x, y)))
+        .filter(|square| tilemap.is_walkable(*square, Entity::PLACEHOLDER))
+        .choose_multiple(&mut rng, crowd.count - active);
+
+    let mut parked = parked.iter();
+    for square in squares {
+        let entity = parked.next().unwrap_or_else(|| cmd.spawn(Passerby).id());
+        let character = *crowd
+            .characters
+            .choose(&mut rng)
+            .expect("Characters are not empty");
+        trace!("Passerby {character:?} ({entity:?}) appears at {square}");
+
+        let mut builder = character.bundle_builder();
+        builder
+            .tilemap(&tilemap)
+            .initial_square(square)
+            .non_interactable()
+            .behavior_tree(BehaviorTree::new(behaviors::Wander {
+                home: square,
+                radius: crowd.wander_radius,
+                pause: crowd.pause,
+            }));
+        builder.insert_bundle_into(&asset_server, &mut cmd.entity(entity));
+    }
+}
+
+/// Despawns all passersby, including the pooled ones.
+/// Runs when the scene is left, the next scene starts with its own crowd.
+pub fn despawn_all(
+    mut cmd: Commands,
+    passersby: Query<Entity, With<Passerby>>,
+) {
+    for entity in passersby.iter() {
+        cmd.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_keeps_crowd_size_within_count() {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<Image>()
+            .insert_resource(TileMap::default())
+            .insert_resource(
+                AmbientCrowd::new(
+                    [0, 9, 0, 9],
+                    vec![Character::Cat, Character::Otter],
+                )
+                .with_count(3),
+            );
+        let w = app.world_mut();
+        let maintain = w.register_system(maintain);
+        let active = |w: &mut World| {
+            w.query_filtered::<(), (With<Passerby>, With<Actor>)>()
+                .iter(w)
+                .count()
+        };
+        let pooled = |w: &mut World| {
+            w.query_filtered::<(), With<Passerby>>().iter(w).count()
+        };
+
+        w.run_system(maintain).unwrap();
+        w.run_system(maintain).unwrap();
+        assert_eq!(3, active(w));
+        assert_eq!(3, pooled(w));
+
+        // surplus is parked, not despawned
+        w.resource_mut::<AmbientCrowd>().count = 1;
+        w.run_system(maintain).unwrap();
+        assert_eq!(1, active(w));
+        assert_eq!(3, pooled(w));
+
+        w.resource_mut::<AmbientCrowd>().count = 2;
+        w.run_system(maintain).unwrap();
+        assert_eq!(2, active(w));
+        assert_eq!(3, pooled(w));
+
+        // leaving the bounds parks the passerby and the pool replaces it
+        let left = w
+            .query_filtered::<Entity, (With<Passerby>, With<Actor>)>()
+            .iter(w)
+            .next()
+            .unwrap();
+        w.get_mut::<Actor>(left).unwrap().walking_from = sq(20, 20);
+        w.run_system(maintain).unwrap();
+        assert_eq!(2, active(w));
+        assert_eq!(3, pooled(w));
+        assert!(w.get::<Actor>(left).is_none());
+        assert_eq!(Some(&Visibility::Hidden), w.get::<Visibility>(left));
+
+        // the scene is left
+        let despawn_all = w.register_system(despawn_all);
+        w.run_system(despawn_all).unwrap();
+        assert_eq!(0, pooled(w));
+    }
+
+    #[test]
+    fn it_derives_count_from_density() {
+        let crowd = AmbientCrowd::new([0, 19, 0, 9], vec![Character::Cat])
+            .with_density(2.5);
+
+        assert_eq!(5, crowd.count);
+    }
+}
@@ -1,29 +1,51 @@
 //! NPC logic.
 //!
 //! Behavior trees are run with [`drive_behavior`] system.
+//! NPCs can instead follow a [`schedule::Schedule`] through the day.
+//! Background NPCs can [`glide::Glide`] along their path instead of stepping
+//! from square to square.
 
 pub mod behaviors;
+pub mod glide;
+pub mod schedule;
 
 use std::{
+    mem,
     ops::{AddAssign, Not},
     time::{Duration, Instant},
 };
 
 use bevy::prelude::*;
-use bevy_grid_squared::Square;
+use bevy_grid_squared::{sq, Square};
 use common_ext::QueryExt;
 use common_store::{DialogStore, GlobalStore};
+use common_story::Character;
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    Rng,
+};
 
-use super::{ActorOrCharacter, BeginDialogEvent};
+use super::{
+    ActorOrCharacter, BeginDialogEvent, Footprint, Knockback, Passability,
+};
 use crate::{
     dialog::{self, StartDialogWhenLoaded},
     top_down::{
-        inspect_and_interact::ReadyForInteraction, Actor, ActorTarget, Player,
-        TileMap,
+        inspect_and_interact::{InspectLabel, ReadyForInteraction},
+        layout::Walker,
+        Actor, ActorTarget, Player, TileMap,
     },
 };
 
 const MIN_WAIT_BETWEEN_PATHFINDING_RETRY: Duration = Duration::from_millis(250);
+/// How many times in a row can replanning the blocked path towards the same
+/// goal find no way around.
+/// After that, the NPC gives up and waits for its behavior to plan again.
+const MAX_REPLANS_WHEN_BLOCKED: u8 = 3;
+/// The dialog the player went through last is this many times less likely to
+/// be picked again.
+/// See [`pick_dialog_for_npc`].
+const LAST_DIALOG_WEIGHT_FACTOR: f32 = 0.1;
 
 /// Describes state of an NPC that's positioned in the current map.
 /// As opposed to just an abstract simulation, this NPC is actively moving and
@@ -40,6 +62,34 @@ pub struct NpcInTheMap {
     /// Where are we right now in the planned path queue.
     /// Is zero if empty path and also if there's only one square in the path.
     planned_path_index: usize,
+    /// The square that the NPC ultimately wants to reach.
+    /// The planned path might be only partial, so this is what we replan
+    /// towards when the path gets blocked.
+    goal: Option<Square>,
+    /// How many times in a row replanning the blocked path towards
+    /// [`NpcInTheMap::goal`] found no way around.
+    /// Any path found or a new plan from the behavior resets it.
+    failed_replans: u8,
+    /// Whether the [`PlanPathEvent`] that's yet to be handled by [`plan_path`]
+    /// was sent by [`NpcInTheMap::replan`].
+    replanning: bool,
+    /// Whether the last planning towards [`NpcInTheMap::goal`] found no way
+    /// to make progress.
+    goal_unreachable: bool,
+}
+
+/// How does the player get to interact with NPCs.
+///
+/// Scenes that want pure proximity can turn [`NpcInteraction::require_facing`]
+/// off.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct NpcInteraction {
+    /// The NPC must be in the arc in front of the player to be interacted
+    /// with.
+    /// On by default so that the player cannot talk to someone behind their
+    /// back.
+    pub require_facing: bool,
 }
 
 /// Run path finding algorithm for given entity to given square.
@@ -102,6 +152,8 @@ pub enum BehaviorLeaf {
     Idle,
     /// Drives the pathfinding algorithm to find a path to the given square and
     /// the NPC to move along it.
+    ///
+    /// Fails if the square cannot be walked on or no path leads towards it.
     FindPath {
         /// The square to find a path to.
         to: Square,
@@ -109,6 +161,16 @@ pub enum BehaviorLeaf {
         /// Prevents spamming the pathfinding algorithm too often.
         last_attempt: Option<Instant>,
     },
+    /// Picks a random walkable square around home and then behaves as
+    /// [`BehaviorLeaf::FindPath`] towards it.
+    ///
+    /// Fails if there's no walkable square around home.
+    Wander {
+        /// Center of the area to pick from.
+        home: Square,
+        /// How many squares from home in each axis can the picked square be.
+        radius: i32,
+    },
 }
 
 /// An NPC with this component will not further execute its behavior tree.
@@ -121,10 +183,16 @@ pub struct BehaviorPaused;
 pub fn drive_behavior(
     mut cmd: Commands,
     time: Res<Time>,
+    map: Res<TileMap>,
     mut plan_path: EventWriter<PlanPathEvent>,
 
     mut trees: Query<(Entity, &mut BehaviorTree), Without<BehaviorPaused>>,
-    actors: Query<(&Actor, &NpcInTheMap)>,
+    actors: Query<(
+        &Actor,
+        &NpcInTheMap,
+        Option<&Footprint>,
+        Option<&Passability>,
+    )>,
 ) {
     for (tree_entity, mut tree) in trees.iter_mut() {
         let Some((_visit, leaf)) = tree.unfold_into_leaf(&time) else {
@@ -136,15 +204,7 @@ pub fn drive_behavior(
         match leaf {
             Idle => {}
             FindPath { to, last_attempt } => {
-                if let Some(last_attempt) = last_attempt {
-                    if last_attempt.elapsed()
-                        < MIN_WAIT_BETWEEN_PATHFINDING_RETRY
-                    {
-                        continue;
-                    }
-                }
-
-                let Ok((actor, npc_in_the_map)) = actors.get(tree_entity)
+                let Ok((actor, npc_in_the_map, _, _)) = actors.get(tree_entity)
                 else {
                     // NPC is only virtual or does not exist,
                     // we don't actually have to move it
@@ -167,40 +227,121 @@ pub fn drive_behavior(
                 if npc_in_the_map.planned_path.is_empty()
                     && actor.walking_to.is_still()
                 {
+                    if last_attempt.is_some()
+                        && npc_in_the_map.goal == Some(*to)
+                        && npc_in_the_map.goal_unreachable
+                    {
+                        trace!(
+                            "NPC {:?} cannot reach square {to:?}",
+                            actor.character
+                        );
+                        tree.leaf_finished(BehaviorResult::Failed);
+                        continue;
+                    }
+
+                    if let Some(last_attempt) = last_attempt {
+                        if last_attempt.elapsed()
+                            < MIN_WAIT_BETWEEN_PATHFINDING_RETRY
+                        {
+                            continue;
+                        }
+                    }
+
                     plan_path.send(PlanPathEvent(tree_entity, *to));
                     *last_attempt = Some(Instant::now());
                 }
             }
+            Wander { home, radius } => {
+                let Ok((actor, _, footprint, passability)) =
+                    actors.get(tree_entity)
+                else {
+                    // virtual NPCs don't wander
+                    tree.leaf_finished(BehaviorResult::Ok);
+                    continue;
+                };
+
+                let current_square = actor.current_square();
+                let footprint = Footprint::squares_of(footprint);
+                let walker = Walker::new(tree_entity, passability);
+                let (home, radius) = (*home, *radius);
+                let picked = (-radius..=radius)
+                    .flat_map(|x| {
+                        (-radius..=radius)
+                            .map(move |y| sq(home.x + x, home.y + y))
+                    })
+                    .filter(|square| {
+                        *square != current_square
+                            && map.is_walkable_with_footprint(
+                                *square, walker, footprint,
+                            )
+                    })
+                    .choose(&mut rand::thread_rng());
+
+                match picked {
+                    // next frame this leaf walks there as any other path
+                    Some(square) => *leaf = BehaviorLeaf::find_path_to(square),
+                    None => tree.leaf_finished(BehaviorResult::Failed),
+                }
+            }
         }
     }
 }
 
 /// Plans a path for an NPC within the map.
-/// If no path can be found, the planned path is set to an empty vector.
+/// If no path can be found, the planned path is set to an empty vector and
+/// the goal is marked as unreachable for [`BehaviorLeaf::FindPath`] to fail.
 ///
 /// Condition this to run only on new event.
 pub fn plan_path(
     map: Res<TileMap>,
     mut events: EventReader<PlanPathEvent>,
 
-    mut actors: Query<(Entity, &Actor, &mut NpcInTheMap)>,
+    mut actors: Query<(
+        Entity,
+        &Actor,
+        &mut NpcInTheMap,
+        Option<&Footprint>,
+        Option<&Passability>,
+    )>,
 ) {
     for PlanPathEvent(entity, target_square) in events.read() {
-        let Ok((actor_entity, actor, mut npc_in_the_map)) =
-            actors.get_mut(*entity)
+        let Ok((
+            actor_entity,
+            actor,
+            mut npc_in_the_map,
+            footprint,
+            passability,
+        )) = actors.get_mut(*entity)
         else {
             continue;
         };
 
         trace!("Searching for path to {target_square:?}");
+        let is_replan = mem::take(&mut npc_in_the_map.replanning);
+        npc_in_the_map.goal = Some(*target_square);
         npc_in_the_map.planned_path_index = 0;
-        npc_in_the_map.planned_path = map
-            .find_partial_path(
-                actor_entity,
-                actor.current_square(),
-                *target_square,
-            )
+        let footprint = Footprint::squares_of(footprint);
+        let walker = Walker::new(actor_entity, passability);
+        // no point in searching if e.g. an object was placed on the target
+        let path = map
+            .is_walkable_with_footprint(*target_square, walker, footprint)
+            .then(|| {
+                map.find_partial_path_with_footprint(
+                    walker,
+                    footprint,
+                    actor.current_square(),
+                    *target_square,
+                )
+            })
+            .flatten()
             .unwrap_or_default(); // no path
+        if is_replan && path.is_empty() {
+            npc_in_the_map.failed_replans += 1;
+        } else {
+            npc_in_the_map.failed_replans = 0;
+        }
+        npc_in_the_map.goal_unreachable = path.is_empty();
+        npc_in_the_map.planned_path = path;
         trace!("Found path of len {}", npc_in_the_map.planned_path.len());
     }
 }
@@ -209,18 +350,39 @@ pub fn plan_path(
 ///
 /// We only do this if the behavior tree is not paused.
 /// E.g. when the NPC enters a dialog, we don't want it to move.
+///
+/// If the next square on the path is not walkable anymore, e.g. a door closed
+/// or another actor stepped in, the rest of the path is thrown away and we
+/// plan a new one towards the goal.
+///
+/// Gliding NPCs run their path with [`glide::steer`] instead.
 pub fn run_path(
     map: Res<TileMap>,
+    mut plan_path: EventWriter<PlanPathEvent>,
 
     mut actors: Query<
-        (Entity, &mut Actor, &mut NpcInTheMap),
-        Without<BehaviorPaused>,
+        (
+            Entity,
+            &mut Actor,
+            &mut NpcInTheMap,
+            Option<&Footprint>,
+            Option<&Passability>,
+        ),
+        (
+            Without<BehaviorPaused>,
+            Without<Knockback>,
+            Without<glide::Glide>,
+        ),
     >,
 ) {
-    for (actor_entity, mut actor, mut npc_in_the_map) in actors.iter_mut() {
+    for (actor_entity, mut actor, mut npc_in_the_map, footprint, passability) in
+        actors.iter_mut()
+    {
         if npc_in_the_map.planned_path.is_empty() {
             continue;
         }
+        let footprint = Footprint::squares_of(footprint);
+        let walker = Walker::new(actor_entity, passability);
 
         match actor.walking_to.target_mut() {
             Some(target) if target.planned.is_some() => continue,
@@ -231,9 +393,13 @@ pub fn run_path(
                 else {
                     continue;
                 };
-                if !map.is_walkable(planned_square, actor_entity) {
-                    // we'll need to replan
-                    npc_in_the_map.reset_path();
+                if !map.can_step_with_footprint(
+                    target.square,
+                    planned_square,
+                    walker,
+                    footprint,
+                ) {
+                    npc_in_the_map.replan(actor_entity, &mut plan_path);
                     continue;
                 }
                 let Some(direction) =
@@ -250,9 +416,13 @@ pub fn run_path(
                 else {
                     continue;
                 };
-                if !map.is_walkable(planned_square, actor_entity) {
-                    // we'll need to replan
-                    npc_in_the_map.reset_path();
+                if !map.can_step_with_footprint(
+                    actor.walking_from,
+                    planned_square,
+                    walker,
+                    footprint,
+                ) {
+                    npc_in_the_map.replan(actor_entity, &mut plan_path);
                     continue;
                 }
                 let Some(direction) =
@@ -269,24 +439,44 @@ pub fn run_path(
 }
 
 /// NPCs close to the player are marked as ready for interaction.
+/// Unless [`NpcInteraction::require_facing`] is turned off, the NPC must also
+/// be in front of the player, see [`Square::is_ahead`].
+/// NPCs don't notice the player through walls, see
+/// [`TileMap::has_line_of_sight`].
+/// NPCs whose [`InspectLabel`] has a range set must be within that many
+/// steps of the player instead.
 ///
 /// This allows the player to [`begin_dialog`] as an interaction with the NPC
 /// will emit the [`BeginDialogEvent`].
 pub(crate) fn mark_nearby_as_ready_for_interaction(
     mut cmd: Commands,
+    settings: Res<NpcInteraction>,
+    map: Res<TileMap>,
 
-    player: Query<&GlobalTransform, With<Player>>,
-    actors: Query<(Entity, &GlobalTransform), (With<Actor>, Without<Player>)>,
+    player: Query<(&GlobalTransform, &Actor), With<Player>>,
+    actors: Query<
+        (Entity, &GlobalTransform, Option<&InspectLabel>),
+        (With<Actor>, Without<Player>),
+    >,
 ) {
-    let Some(player) = player.get_single_or_none() else {
+    let Some((player, player_actor)) = player.get_single_or_none() else {
         return;
     };
     let player = player.translation().truncate();
+    let player_square = map.layout().world_pos_to_square(player);
+
+    for (entity, transform, label) in actors.iter() {
+        let position = transform.translation().truncate();
+        let square = map.layout().world_pos_to_square(position);
+        let is_nearby = match label.and_then(InspectLabel::range) {
+            Some(range) => player_square.chebyshev_distance(square) <= range,
+            None => position.distance(player) < 30.0,
+        };
+        let is_faced = !settings.require_facing
+            || player_square.is_ahead(player_actor.direction, square);
 
-    for (entity, transform) in actors.iter() {
-        let distance = transform.translation().truncate().distance(player);
-
-        if distance < 30.0 {
+        if is_nearby && is_faced && map.has_line_of_sight(player_square, square)
+        {
             cmd.entity(entity).insert(ReadyForInteraction);
         } else {
             cmd.entity(entity).remove::<ReadyForInteraction>();
@@ -328,19 +518,15 @@ pub(crate) fn begin_dialog(
 
             let character = actor.character;
 
-            let dialogs =
-                store.list_dialogs_for_npc::<dialog::Namespace>(character);
-
-            if dialogs.is_empty() {
-                return;
-            }
+            let dialog =
+                pick_dialog_for_npc(&store, character, &mut rand::thread_rng());
 
             let start_dialog = StartDialogWhenLoaded::portrait()
                 .on_finished(Box::new(move |cmd: &mut Commands| {
                     trace!("Removing BehaviorPaused from {character}");
                     cmd.entity(entity).remove::<BehaviorPaused>();
                 }))
-                .add_namespaces(dialogs);
+                .add_namespace(dialog);
             cmd.insert_resource(start_dialog);
 
             {
@@ -359,15 +545,14 @@ pub(crate) fn begin_dialog(
             }
         }
         Some(BeginDialogEvent(ActorOrCharacter::Character(character))) => {
-            let dialogs =
-                store.list_dialogs_for_npc::<dialog::Namespace>(character);
-
-            if dialogs.is_empty() {
-                return;
-            }
+            let dialog = pick_dialog_for_npc(
+                &store,
+                *character,
+                &mut rand::thread_rng(),
+            );
 
             let start_dialog =
-                StartDialogWhenLoaded::portrait().add_namespaces(dialogs);
+                StartDialogWhenLoaded::portrait().add_namespace(dialog);
             cmd.insert_resource(start_dialog);
 
             player.remove_planned_step();
@@ -376,6 +561,43 @@ pub(crate) fn begin_dialog(
     };
 }
 
+/// Picks one of the dialogs the NPC has at random, respecting their weights.
+/// See [`DialogStore::add_weighted_dialog_to_npc`].
+///
+/// The dialog the player went through last is deprioritized so that the NPC
+/// doesn't repeat itself.
+/// If the NPC has no dialogs, falls back to
+/// [`dialog::TypedNamespace::DefaultGreeting`].
+pub(crate) fn pick_dialog_for_npc(
+    store: &GlobalStore,
+    npc: Character,
+    rng: &mut impl Rng,
+) -> dialog::Namespace {
+    let last_dialog = store
+        .get_last_dialog::<dialog::Namespace>()
+        .map(|(namespace, _)| namespace);
+
+    let dialogs: Vec<_> = store
+        .list_weighted_dialogs_for_npc::<dialog::Namespace>(npc)
+        .into_iter()
+        .map(|(namespace, weight)| {
+            if last_dialog.as_ref() == Some(&namespace) {
+                (namespace, weight * LAST_DIALOG_WEIGHT_FACTOR)
+            } else {
+                (namespace, weight)
+            }
+        })
+        .collect();
+
+    match dialogs.choose_weighted(rng, |(_, weight)| *weight) {
+        Ok((namespace, _)) => namespace.clone(),
+        Err(err) => {
+            trace!("No dialog picked for {npc}: {err}");
+            dialog::TypedNamespace::DefaultGreeting.into()
+        }
+    }
+}
+
 impl BehaviorLeaf {
     /// Creates a new find path leaf node.
     pub fn find_path_to(square: Square) -> Self {
@@ -525,6 +747,37 @@ impl NpcInTheMap {
         self.planned_path_index = 0;
         self.planned_path.clear();
     }
+
+    /// Throws away the blocked path and asks for a new one towards the goal
+    /// unless we've already tried too many times.
+    pub(super) fn replan(
+        &mut self,
+        entity: Entity,
+        plan_path: &mut EventWriter<PlanPathEvent>,
+    ) {
+        self.reset_path();
+
+        let Some(goal) = self.goal else {
+            return;
+        };
+
+        if self.failed_replans >= MAX_REPLANS_WHEN_BLOCKED {
+            trace!("Giving up on replanning path to {goal:?}");
+            return;
+        }
+
+        trace!("Path blocked, replanning to {goal:?}");
+        self.replanning = true;
+        plan_path.send(PlanPathEvent(entity, goal));
+    }
+}
+
+impl Default for NpcInteraction {
+    fn default() -> Self {
+        Self {
+            require_facing: true,
+        }
+    }
 }
 
 impl BehaviorNode {
@@ -553,3 +806,352 @@ where
         Self::new(tree)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_grid_squared::{sq, GridDirection};
+    use common_story::Character;
+    use smallvec::smallvec;
+
+    use super::*;
+    use crate::top_down::{actor::test_utils::spawn_actor_at, TileKind};
+
+    #[test]
+    fn it_replans_path_when_blocked_mid_traversal() {
+        let mut w = World::default();
+        w.insert_resource(TileMap::default());
+        w.init_resource::<Events<PlanPathEvent>>();
+
+        let npc = w
+            .spawn((
+                Actor {
+                    character: Character::Marie,
+                    step_time: Duration::from_secs(1),
+                    turn_delay: default(),
+                    queued: None,
+                    direction: GridDirection::Right,
+                    walking_from: sq(0, 0),
+                    walking_to: default(),
+                    occupies: vec![],
+                },
+                NpcInTheMap::default(),
+            ))
+            .id();
+        let plan_path_id = w.register_system(plan_path);
+        let run_path_id = w.register_system(run_path);
+
+        let goal = sq(6, 0);
+        let obstacle = sq(3, 0);
+
+        w.send_event(PlanPathEvent(npc, goal));
+        w.run_system(plan_path_id).unwrap();
+        assert!(w
+            .get::<NpcInTheMap>(npc)
+            .unwrap()
+            .planned_path
+            .contains(&obstacle));
+
+        // something steps in the way after the path was planned
+        w.resource_mut::<TileMap>()
+            .squares
+            .insert(obstacle, smallvec![TileKind::Wall]);
+
+        let mut visited = vec![];
+        for _ in 0..50 {
+            w.run_system(run_path_id).unwrap();
+            w.run_system(plan_path_id).unwrap();
+
+            // teleports the actor to the square it's walking to
+            let mut actor = w.get_mut::<Actor>(npc).unwrap();
+            if let Some(target) = actor.walking_to.target() {
+                let square = target.square;
+                let planned = target.planned;
+
+                visited.push(square);
+                actor.walking_from = square;
+                actor.walking_to = match planned {
+                    Some((next, _)) => ActorTarget::new(next).into(),
+                    None => default(),
+                };
+            }
+
+            if actor.walking_from == goal {
+                break;
+            }
+        }
+
+        assert_eq!(goal, w.get::<Actor>(npc).unwrap().walking_from);
+        assert!(!visited.contains(&obstacle), "Walked through {visited:?}");
+        // the original plan and at least one replan
+        assert!(w.resource::<Events<PlanPathEvent>>().len() >= 2);
+    }
+
+    #[test]
+    fn it_keeps_replanning_as_long_as_detours_are_found() {
+        let mut w = World::default();
+        w.insert_resource(TileMap::default());
+        w.init_resource::<Events<PlanPathEvent>>();
+
+        let npc = spawn_actor_at(&mut w, sq(0, 0));
+        w.entity_mut(npc).insert(NpcInTheMap::default());
+        let plan_path_id = w.register_system(plan_path);
+        let run_path_id = w.register_system(run_path);
+
+        let goal = sq(12, 0);
+        w.send_event(PlanPathEvent(npc, goal));
+        w.run_system(plan_path_id).unwrap();
+
+        // more blocks than replans that may fail in a row, each of them after
+        // the NPC made a step on the detour around the previous one
+        let mut blocks = 0;
+        let mut stepped_since_block = false;
+        for _ in 0..100 {
+            let npc_in_the_map = w.get::<NpcInTheMap>(npc).unwrap();
+            let ahead = npc_in_the_map
+                .planned_path
+                .get(npc_in_the_map.planned_path_index)
+                .copied()
+                .filter(|ahead| *ahead != goal);
+            if let Some(ahead) = ahead
+                && stepped_since_block
+                && blocks <= MAX_REPLANS_WHEN_BLOCKED
+            {
+                w.resource_mut::<TileMap>()
+                    .squares
+                    .insert(ahead, smallvec![TileKind::Wall]);
+                blocks += 1;
+                stepped_since_block = false;
+            }
+
+            w.run_system(run_path_id).unwrap();
+            w.run_system(plan_path_id).unwrap();
+
+            // teleports the actor to the square it's walking to
+            let mut actor = w.get_mut::<Actor>(npc).unwrap();
+            if let Some(target) = actor.walking_to.target() {
+                let square = target.square;
+                let planned = target.planned;
+
+                stepped_since_block = true;
+                actor.walking_from = square;
+                actor.walking_to = match planned {
+                    Some((next, _)) => ActorTarget::new(next).into(),
+                    None => default(),
+                };
+            }
+
+            if actor.walking_from == goal {
+                break;
+            }
+        }
+
+        assert_eq!(MAX_REPLANS_WHEN_BLOCKED + 1, blocks);
+        assert_eq!(goal, w.get::<Actor>(npc).unwrap().walking_from);
+        // the original plan and a replan per block
+        assert_eq!(
+            1 + blocks as usize,
+            w.resource::<Events<PlanPathEvent>>().len()
+        );
+    }
+
+    #[test]
+    fn it_skips_unreachable_patrol_waypoint() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+        w.insert_resource(TileMap::default());
+        w.init_resource::<Events<PlanPathEvent>>();
+
+        let first = sq(3, 0);
+        let blocked = sq(6, 0);
+        let last = sq(3, 3);
+        // an object was placed on the waypoint
+        w.resource_mut::<TileMap>()
+            .squares
+            .insert(blocked, smallvec![TileKind::Wall]);
+
+        let npc = w
+            .spawn((
+                Actor {
+                    character: Character::Marie,
+                    step_time: Duration::from_secs(1),
+                    turn_delay: default(),
+                    queued: None,
+                    direction: GridDirection::Right,
+                    walking_from: sq(0, 0),
+                    walking_to: default(),
+                    occupies: vec![],
+                },
+                NpcInTheMap::default(),
+                BehaviorTree::new(behaviors::Patrol {
+                    waypoints: vec![first, blocked, last],
+                    loop_: false,
+                    pause: Duration::ZERO,
+                }),
+            ))
+            .id();
+        let drive_behavior_id = w.register_system(drive_behavior);
+        let plan_path_id = w.register_system(plan_path);
+        let run_path_id = w.register_system(run_path);
+
+        let mut visited = vec![];
+        for _ in 0..100 {
+            w.run_system(drive_behavior_id).unwrap();
+            w.run_system(plan_path_id).unwrap();
+            w.run_system(run_path_id).unwrap();
+
+            // teleports the actor to the square it's walking to
+            let mut actor = w.get_mut::<Actor>(npc).unwrap();
+            if let Some(target) = actor.walking_to.target() {
+                let square = target.square;
+                let planned = target.planned;
+
+                visited.push(square);
+                actor.walking_from = square;
+                actor.walking_to = match planned {
+                    Some((next, _)) => ActorTarget::new(next).into(),
+                    None => default(),
+                };
+            }
+
+            if w.get::<BehaviorTree>(npc).is_none() {
+                break;
+            }
+        }
+
+        assert!(w.get::<BehaviorTree>(npc).is_none(), "Patrol never ended");
+        assert_eq!(last, w.get::<Actor>(npc).unwrap().walking_from);
+        assert!(visited.contains(&first), "Never went to {first:?}");
+        assert!(!visited.contains(&blocked), "Walked through {visited:?}");
+    }
+
+    #[test]
+    fn it_does_not_interact_with_npc_behind_player() {
+        let mut w = World::default();
+        w.init_resource::<NpcInteraction>();
+        w.insert_resource(TileMap::default());
+
+        let player = spawn_actor_at(&mut w, sq(0, 0));
+        w.entity_mut(player).insert(Player);
+        let ahead = spawn_actor_at(&mut w, sq(2, 1));
+        let behind = spawn_actor_at(&mut w, sq(-2, 0));
+
+        let mark = w.register_system(mark_nearby_as_ready_for_interaction);
+        let is_ready =
+            |w: &World, npc| w.get::<ReadyForInteraction>(npc).is_some();
+
+        w.run_system(mark).unwrap();
+        assert!(is_ready(&w, ahead));
+        assert!(!is_ready(&w, behind));
+
+        w.resource_mut::<NpcInteraction>().require_facing = false;
+        w.run_system(mark).unwrap();
+        assert!(is_ready(&w, ahead));
+        assert!(is_ready(&w, behind));
+    }
+
+    #[test]
+    fn it_interacts_with_npc_within_its_label_range() {
+        use crate::top_down::InspectLabelCategory;
+
+        let mut w = World::default();
+        w.insert_resource(NpcInteraction {
+            require_facing: false,
+        });
+        w.insert_resource(TileMap::default());
+
+        let player = spawn_actor_at(&mut w, sq(0, 0));
+        w.entity_mut(player).insert(Player);
+        let adjacent_only = spawn_actor_at(&mut w, sq(2, 0));
+        w.entity_mut(adjacent_only).insert(
+            InspectLabelCategory::Npc.into_label("Marie").with_range(1),
+        );
+        let far_reaching = spawn_actor_at(&mut w, sq(0, 2));
+        w.entity_mut(far_reaching).insert(
+            InspectLabelCategory::Npc.into_label("Marie").with_range(3),
+        );
+
+        let mark = w.register_system(mark_nearby_as_ready_for_interaction);
+        w.run_system(mark).unwrap();
+
+        assert!(w.get::<ReadyForInteraction>(adjacent_only).is_none());
+        assert!(w.get::<ReadyForInteraction>(far_reaching).is_some());
+    }
+
+    #[test]
+    fn it_deprioritizes_last_dialog() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let store = GlobalStore::new();
+        store
+            .add_dialog_to_npc(Character::Marie, "marie_blabbering")
+            .add_dialog_to_npc(Character::Marie, "mr_good_water");
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut count_picks = |store: &GlobalStore, namespace: &str| {
+            let namespace = dialog::Namespace::from(namespace.to_owned());
+            (0..1000)
+                .filter(|_| {
+                    pick_dialog_for_npc(store, Character::Marie, &mut rng)
+                        == namespace
+                })
+                .count()
+        };
+
+        // evenly split without history
+        let picks = count_picks(&store, "marie_blabbering");
+        assert!((400..=600).contains(&picks), "{picks}");
+
+        // on the next interaction the other dialog is preferred
+        store.insert_dialog(("marie_blabbering", "root"));
+        let picks = count_picks(&store, "marie_blabbering");
+        assert!(picks < 200, "{picks}");
+        assert!(picks > 0, "the last dialog can still repeat");
+
+        // the weight is respected as well
+        store.add_weighted_dialog_to_npc(
+            Character::Marie,
+            "marie_blabbering",
+            100.0,
+        );
+        let picks = count_picks(&store, "marie_blabbering");
+        assert!(picks > 800, "{picks}");
+    }
+
+    #[test]
+    fn it_falls_back_to_default_greeting() {
+        let store = GlobalStore::new();
+
+        let dialog = pick_dialog_for_npc(
+            &store,
+            Character::Marie,
+            &mut rand::thread_rng(),
+        );
+        assert_eq!(
+            dialog::Namespace::from(dialog::TypedNamespace::DefaultGreeting),
+            dialog
+        );
+    }
+
+    #[test]
+    fn it_does_not_interact_with_npc_behind_wall() {
+        let mut w = World::default();
+        w.init_resource::<NpcInteraction>();
+        w.insert_resource(TileMap::default());
+
+        let player_square = sq(0, 0);
+        let npc_square = sq(3, 0);
+        let player = spawn_actor_at(&mut w, player_square);
+        w.entity_mut(player).insert(Player);
+        let npc = spawn_actor_at(&mut w, npc_square);
+
+        let mark = w.register_system(mark_nearby_as_ready_for_interaction);
+
+        w.run_system(mark).unwrap();
+        assert!(w.get::<ReadyForInteraction>(npc).is_some());
+
+        w.resource_mut::<TileMap>()
+            .squares
+            .insert(sq(2, 0), smallvec![TileKind::Wall]);
+        w.run_system(mark).unwrap();
+        assert!(w.get::<ReadyForInteraction>(npc).is_none());
+    }
+}
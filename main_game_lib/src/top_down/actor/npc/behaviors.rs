@@ -22,31 +22,80 @@ impl From<IdlyWaiting> for BN {
     }
 }
 
-/// Walks a patrol in between points in order.
+/// Walks a patrol in between waypoints in order, pausing at each.
+///
+/// If a waypoint cannot be reached, e.g. something was placed on it, the NPC
+/// skips to the next one without pausing.
 #[derive(Default)]
-pub struct PatrolSequence {
+pub struct Patrol {
     /// Where to go.
-    pub points: Vec<Square>,
-    /// How long to wait at each point.
-    pub wait_at_each: Duration,
+    pub waypoints: Vec<Square>,
+    /// Whether to start over from the first waypoint after the last one.
+    /// Otherwise the behavior ends at the last waypoint.
+    pub loop_: bool,
+    /// How long to wait at each waypoint.
+    pub pause: Duration,
 }
 
-impl From<PatrolSequence> for BN {
+impl From<Patrol> for BN {
     fn from(
-        PatrolSequence {
-            points,
-            wait_at_each,
-        }: PatrolSequence,
+        Patrol {
+            waypoints,
+            loop_,
+            pause,
+        }: Patrol,
     ) -> Self {
-        let points = points.into_iter().map(|point| {
-            let goto = BehaviorLeaf::find_path_to(point);
+        if waypoints.is_empty() {
+            // repeating an empty sequence would never yield a leaf
+            return BN::Sequence(vec![]);
+        }
 
-            BN::Sequence(vec![
-                BN::Infallible(BN::Leaf(goto).into_boxed()),
-                IdlyWaiting(wait_at_each).into(),
-            ])
+        let waypoints = waypoints.into_iter().map(|waypoint| {
+            let goto = BehaviorLeaf::find_path_to(waypoint);
+
+            // if goto fails, the pause is skipped too
+            BN::Infallible(
+                BN::Sequence(vec![BN::Leaf(goto), IdlyWaiting(pause).into()])
+                    .into_boxed(),
+            )
         });
 
-        BN::Repeat(BN::Sequence(points.collect()).into_boxed())
+        let patrol = BN::Sequence(waypoints.collect());
+        if loop_ {
+            BN::Repeat(patrol.into_boxed())
+        } else {
+            patrol
+        }
+    }
+}
+
+/// Walks to random squares around home forever, pausing at each.
+pub struct Wander {
+    /// Center of the area to wander in.
+    pub home: Square,
+    /// How many squares from home in each axis can the NPC go.
+    pub radius: i32,
+    /// How long to wait at each square.
+    pub pause: Duration,
+}
+
+impl From<Wander> for BN {
+    fn from(
+        Wander {
+            home,
+            radius,
+            pause,
+        }: Wander,
+    ) -> Self {
+        let goto = BehaviorLeaf::Wander { home, radius };
+
+        // an unreachable pick is not a reason to stop wandering
+        BN::Repeat(
+            BN::Infallible(
+                BN::Sequence(vec![BN::Leaf(goto), IdlyWaiting(pause).into()])
+                    .into_boxed(),
+            )
+            .into_boxed(),
+        )
     }
 }
@@ -0,0 +1,307 @@
+//! NPCs that glide along their path at a constant speed rather than hop from
+//! square to square.
+//! Looks less robotic for background characters.
+//!
+//! Insert [`Glide`] and a [`Velocity`] to an NPC with [`NpcInTheMap`].
+//! Without the [`Velocity`] the NPC doesn't move at all.
+//! The velocity is steered by [`steer`], applied by
+//! [`common_physics::systems::apply_velocity`] and the result is rounded to
+//! whole pixels by [`round_position`], just like walking actors are.
+//! The [`TerrainSpeed`] of the square the NPC glides to applies too.
+//! Such NPCs are skipped by [`super::run_path`] and
+//! [`crate::top_down::actor::animate_movement`].
+//! The player stays grid based.
+//!
+//! Occupancy is still per square.
+//! While gliding towards the next square of the path, that square is the
+//! [`Actor::current_square`] and the actor occupies the tiles around it just
+//! like an actor that steps onto it.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use common_physics::Velocity;
+use common_story::animation::AnimationState;
+use common_visuals::camera::PIXEL_ZOOM;
+
+use super::{BehaviorPaused, NpcInTheMap, PlanPathEvent};
+use crate::top_down::{
+    actor::{
+        ActorSteppedEvent, Footprint, Knockback, Passability, TerrainSpeed,
+    },
+    layout::Walker,
+    Actor, ActorTarget, Player, TileMap,
+};
+
+/// How close to the center of a square counts as having arrived to it.
+const ARRIVED_WITHIN: f32 = 0.01;
+
+/// Moves an NPC along its planned path off the grid, see the module docs.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+pub struct Glide {
+    /// How many world units per second the NPC moves.
+    pub speed: f32,
+    /// How far [`round_position`] moved the NPC from where the velocity
+    /// took it.
+    /// We steer from the precise position so that slow NPCs don't get stuck
+    /// on a pixel.
+    rounding: Vec2,
+}
+
+impl Glide {
+    /// Glide at the given speed in world units per second.
+    pub fn new(speed: f32) -> Self {
+        Self {
+            speed,
+            rounding: Vec2::ZERO,
+        }
+    }
+}
+
+/// Points the [`Velocity`] of gliding NPCs towards the next square of their
+/// planned path and keeps the tiles they occupy up to date.
+///
+/// Run before [`common_physics::systems::apply_velocity`].
+pub fn steer(
+    time: Res<Time>,
+    terrain_speed: Res<TerrainSpeed>,
+    mut tilemap: ResMut<TileMap>,
+    mut plan_path: EventWriter<PlanPathEvent>,
+    mut stepped: EventWriter<ActorSteppedEvent>,
+
+    mut actors: Query<
+        (
+            Entity,
+            &mut Glide,
+            &mut Actor,
+            &mut NpcInTheMap,
+            &mut Velocity,
+            &mut Transform,
+            &mut TextureAtlas,
+            Option<&Footprint>,
+            Option<&Passability>,
+            Has<Knockback>,
+            Has<BehaviorPaused>,
+        ),
+        Without<Player>,
+    >,
+) {
+    let dt = time.delta_seconds();
+    let layout = *tilemap.layout();
+
+    for (
+        entity,
+        mut glide,
+        mut actor,
+        mut npc,
+        mut velocity,
+        mut transform,
+        mut sprite,
+        footprint,
+        passability,
+        is_knocked_back,
+        is_paused,
+    ) in actors.iter_mut()
+    {
+        if is_knocked_back {
+            // the knockback moves the actor on its own to rounded positions
+            **velocity = Vec2::ZERO;
+            glide.rounding = Vec2::ZERO;
+            continue;
+        }
+
+        let footprint = Footprint::squares_of(footprint);
+        let walker = Walker::new(entity, passability);
+        let rendered_at = transform.translation.truncate();
+        let at = rendered_at - glide.rounding;
+        // the velocity only changes x and y
+        let z = tilemap.ysort(rendered_at);
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+
+        if let Some(target) = actor.walking_to.target() {
+            let to = layout.square_to_world_pos(target.square);
+            if at.distance(to) <= ARRIVED_WITHIN {
+                let square = target.square;
+                transform.translation = to.extend(tilemap.ysort(to));
+                glide.rounding = Vec2::ZERO;
+                stepped.send(ActorSteppedEvent::new(
+                    &tilemap,
+                    entity,
+                    actor.walking_from,
+                    square,
+                ));
+                actor.walking_from = square;
+                actor.walking_to = default();
+                tilemap.replace_actor_tiles(walker, &mut actor, footprint);
+            }
+        }
+
+        // a paused NPC finishes the square it glides to and then waits
+        if actor.walking_to.is_still() && !is_paused {
+            if let Some(next) = npc.next_planned_square() {
+                let from = actor.walking_from;
+                if !tilemap
+                    .can_step_with_footprint(from, next, walker, footprint)
+                {
+                    npc.replan(entity, &mut plan_path);
+                } else if let Some(direction) = from.direction_to(next) {
+                    actor.walking_to = ActorTarget::new(next).into();
+                    actor.direction = direction;
+                    // reserves the square before the actor gets there
+                    tilemap.replace_actor_tiles(walker, &mut actor, footprint);
+                }
+            }
+        }
+
+        let Some(target) = actor.walking_to.target() else {
+            **velocity = Vec2::ZERO;
+            sprite.index = actor.character.sprite_atlas_index(
+                AnimationState::Standing { since: default() },
+                actor.direction,
+                time.elapsed_wrapped(),
+            );
+            continue;
+        };
+
+        // the terrain of the square we're gliding to decides the speed, like
+        // with walking actors
+        let speed =
+            glide.speed * terrain_speed.multiplier_at(&tilemap, target.square);
+        let remaining = layout.square_to_world_pos(target.square) - at;
+        **velocity = if dt > 0.0 && remaining.length() <= speed * dt {
+            // don't overshoot the square
+            remaining / dt
+        } else {
+            remaining.normalize_or_zero() * speed
+        };
+
+        sprite.index = actor.character.sprite_atlas_index(
+            AnimationState::Walking {
+                step_time: Duration::from_secs_f32(layout.square_size / speed),
+            },
+            actor.direction,
+            time.elapsed_wrapped(),
+        );
+    }
+}
+
+/// Rounds the position of gliding NPCs to whole pixels, like
+/// [`crate::top_down::actor::animate_movement`] does with walking actors.
+/// The precise position is kept for [`steer`] in [`Glide`].
+///
+/// Run after [`common_physics::systems::apply_velocity`].
+pub fn round_position(
+    mut actors: Query<(&mut Glide, &mut Transform), Without<Knockback>>,
+) {
+    for (mut glide, mut transform) in actors.iter_mut() {
+        let rendered_at = transform.translation.truncate();
+        let precise = rendered_at - glide.rounding;
+        let rounded = (precise * PIXEL_ZOOM as f32).round() / PIXEL_ZOOM as f32;
+        if rounded == rendered_at {
+            continue;
+        }
+
+        glide.rounding = rounded - precise;
+        transform.translation = rounded.extend(transform.translation.z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_grid_squared::{sq, GridDirection};
+    use common_physics::systems::apply_velocity;
+    use common_story::Character;
+
+    use super::*;
+    use crate::top_down::TileKind;
+
+    #[test]
+    fn it_reserves_square_while_gliding_off_grid() {
+        let mut w = World::default();
+        w.insert_resource(TileMap::default());
+        w.insert_resource(Time::<()>::default());
+        w.init_resource::<TerrainSpeed>();
+        w.init_resource::<Events<PlanPathEvent>>();
+        w.init_resource::<Events<ActorSteppedEvent>>();
+
+        let square_size = w.resource::<TileMap>().layout().square_size;
+        let npc = w
+            .spawn((
+                Actor {
+                    character: Character::Marie,
+                    step_time: Duration::from_secs(1),
+                    turn_delay: default(),
+                    queued: None,
+                    direction: GridDirection::Right,
+                    walking_from: sq(0, 0),
+                    walking_to: default(),
+                    occupies: vec![],
+                },
+                NpcInTheMap {
+                    planned_path: vec![sq(1, 0), sq(2, 0)],
+                    ..default()
+                },
+                // a square per second
+                Glide::new(square_size),
+                Velocity::default(),
+                SpatialBundle::default(),
+                TextureAtlas {
+                    index: 0,
+                    layout: Character::Marie.sprite_atlas_layout_handle(),
+                },
+            ))
+            .id();
+        let steer = w.register_system(steer);
+        let apply_velocity = w.register_system(apply_velocity);
+        let round_position = w.register_system(round_position);
+        let tick = |w: &mut World, millis| {
+            w.resource_mut::<Time>()
+                .advance_by(Duration::from_millis(millis));
+            w.run_system(steer).unwrap();
+            w.run_system(apply_velocity).unwrap();
+            w.run_system(round_position).unwrap();
+        };
+
+        // the first tick plans the glide without moving
+        tick(&mut w, 0);
+        tick(&mut w, 400);
+
+        let at = w.get::<Transform>(npc).unwrap().translation.truncate();
+        assert!(at.x > 0.0 && at.x < square_size, "{at} is off the grid");
+        let pixels = at.x * PIXEL_ZOOM as f32;
+        assert!(
+            (pixels - pixels.round()).abs() < 1e-4,
+            "{at} is off a pixel"
+        );
+        let under = w.resource::<TileMap>().layout().world_pos_to_square(at);
+        assert_eq!(sq(0, 0), under);
+
+        let actor = w.get::<Actor>(npc).unwrap();
+        assert_eq!(sq(1, 0), actor.current_square());
+        let tilemap = w.resource::<TileMap>();
+        for square in [under, actor.current_square()] {
+            assert!(
+                tilemap.is_on(square, TileKind::Actor(npc)),
+                "{square} should be reserved"
+            );
+            assert!(!tilemap.is_walkable(square, Entity::PLACEHOLDER));
+        }
+
+        // glides to the end of the path and stops there
+        for _ in 0..10 {
+            tick(&mut w, 400);
+        }
+        let to = w
+            .resource::<TileMap>()
+            .layout()
+            .square_to_world_pos(sq(2, 0));
+        assert_eq!(to, w.get::<Transform>(npc).unwrap().translation.truncate());
+        assert_eq!(Vec2::ZERO, **w.get::<Velocity>(npc).unwrap());
+        let actor = w.get::<Actor>(npc).unwrap();
+        assert_eq!(sq(2, 0), actor.walking_from);
+        assert!(actor.walking_to.is_still());
+        assert_eq!(2, w.resource::<Events<ActorSteppedEvent>>().len());
+    }
+}
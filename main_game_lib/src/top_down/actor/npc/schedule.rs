@@ -0,0 +1,175 @@
+//! NPCs that are at different places at different times of the day.
+//!
+//! Insert [`Schedule`] to an NPC in the map and [`follow`] walks it to
+//! wherever the schedule says it should be at the current [`TimeOfDay`].
+//! Don't combine it with a [`super::BehaviorTree`] as both would plan paths
+//! for the NPC.
+
+use bevy::prelude::*;
+use bevy_grid_squared::{sq, Square};
+use common_visuals::day_night::{keys_around, TimeOfDay, HOURS_IN_DAY};
+
+use super::{BehaviorPaused, NpcInTheMap, PlanPathEvent};
+use crate::top_down::Actor;
+
+/// Squares keyed by the hour of the day at which the NPC should be there.
+/// Between the entries the square is interpolated linearly, wrapping around
+/// midnight, so the NPC makes its way from one entry to the next over time.
+///
+/// To keep the NPC in place for a while, give two consecutive entries the
+/// same square.
+#[derive(Component, Reflect, Debug, Clone)]
+pub struct Schedule {
+    /// Sorted by the hour.
+    entries: Vec<(f32, Square)>,
+}
+
+impl Schedule {
+    /// At least one entry must be given.
+    /// Hours are wrapped into a single day.
+    pub fn new(entries: impl IntoIterator<Item = (f32, Square)>) -> Self {
+        let mut entries: Vec<_> = entries
+            .into_iter()
+            .map(|(hours, square)| (hours.rem_euclid(HOURS_IN_DAY), square))
+            .collect();
+        debug_assert!(!entries.is_empty(), "Schedule needs at least one entry");
+        entries.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        Self { entries }
+    }
+
+    /// Where the NPC should be at the given hour.
+    pub fn sample(&self, hours: f32) -> Square {
+        let hours = hours.rem_euclid(HOURS_IN_DAY);
+        let ((prev_at, prev), (next_at, next)) =
+            keys_around(&self.entries, hours);
+
+        let span = next_at - prev_at;
+        if span <= f32::EPSILON {
+            return prev;
+        }
+
+        let t = (hours - prev_at) / span;
+        let lerp = |a: i32, b: i32| (a as f32 + (b - a) as f32 * t).round();
+        sq(lerp(prev.x, next.x) as i32, lerp(prev.y, next.y) as i32)
+    }
+}
+
+/// Plans a path for each NPC with a [`Schedule`] whenever the square where
+/// it should be changes.
+///
+/// If the path gets blocked, it's replanned as any other, see
+/// [`super::run_path`].
+pub fn follow(
+    time_of_day: Res<TimeOfDay>,
+    mut plan_path: EventWriter<PlanPathEvent>,
+
+    npcs: Query<
+        (Entity, &Schedule, &Actor, &NpcInTheMap),
+        Without<BehaviorPaused>,
+    >,
+) {
+    for (entity, schedule, actor, npc_in_the_map) in npcs.iter() {
+        let target = schedule.sample(time_of_day.hours);
+
+        if npc_in_the_map.goal != Some(target)
+            && actor.current_square() != target
+        {
+            trace!(
+                "NPC {:?} is scheduled to be at {target:?}",
+                actor.character
+            );
+            plan_path.send(PlanPathEvent(entity, target));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy_grid_squared::GridDirection;
+    use common_story::Character;
+
+    use super::*;
+    use crate::top_down::{actor::npc::plan_path, TileMap};
+
+    fn vendor() -> Schedule {
+        // at the stand during the day, then walks home for the night
+        Schedule::new([
+            (8.0, sq(0, 0)),
+            (18.0, sq(0, 0)),
+            (20.0, sq(10, 0)),
+            (6.0, sq(10, 0)),
+        ])
+    }
+
+    #[test]
+    fn it_samples_schedule_across_midnight() {
+        let schedule = vendor();
+
+        assert_eq!(sq(0, 0), schedule.sample(8.0));
+        assert_eq!(sq(0, 0), schedule.sample(12.0));
+        assert_eq!(sq(5, 0), schedule.sample(19.0));
+        assert_eq!(sq(10, 0), schedule.sample(23.5));
+        assert_eq!(sq(10, 0), schedule.sample(2.0));
+        assert_eq!(sq(5, 0), schedule.sample(7.0));
+        assert_eq!(sq(5, 0), schedule.sample(7.0 + HOURS_IN_DAY));
+
+        let single = Schedule::new([(25.0, sq(1, 1))]);
+        assert_eq!(sq(1, 1), single.sample(0.0));
+        assert_eq!(sq(1, 1), single.sample(13.0));
+    }
+
+    #[test]
+    fn it_targets_scheduled_square_at_given_hour() {
+        let mut w = World::default();
+        w.insert_resource(TileMap::default());
+        w.insert_resource(TimeOfDay::at(12.0));
+        w.init_resource::<Events<PlanPathEvent>>();
+        let follow = w.register_system(follow);
+        let plan_path = w.register_system(plan_path);
+
+        let npc = w
+            .spawn((
+                Actor {
+                    character: Character::Marie,
+                    step_time: Duration::from_secs(1),
+                    turn_delay: default(),
+                    queued: None,
+                    direction: GridDirection::Right,
+                    walking_from: sq(0, 0),
+                    walking_to: default(),
+                    occupies: vec![],
+                },
+                NpcInTheMap::default(),
+                vendor(),
+            ))
+            .id();
+        let sent = |w: &mut World| {
+            w.resource_mut::<Events<PlanPathEvent>>()
+                .drain()
+                .map(|PlanPathEvent(entity, square)| (entity, square))
+                .collect::<Vec<_>>()
+        };
+
+        // already at the stand
+        w.run_system(follow).unwrap();
+        assert!(sent(&mut w).is_empty());
+
+        w.resource_mut::<TimeOfDay>().hours = 22.0;
+        w.run_system(follow).unwrap();
+        w.run_system(plan_path).unwrap();
+        assert_eq!(vec![(npc, sq(10, 0))], sent(&mut w));
+        assert_eq!(Some(sq(10, 0)), w.get::<NpcInTheMap>(npc).unwrap().goal);
+
+        // the path is planned once, not every frame
+        w.run_system(follow).unwrap();
+        assert!(sent(&mut w).is_empty());
+
+        // and replanned once it's time to go back
+        w.resource_mut::<TimeOfDay>().hours = 7.0;
+        w.run_system(follow).unwrap();
+        assert_eq!(vec![(npc, sq(5, 0))], sent(&mut w));
+    }
+}
@@ -1,13 +1,18 @@
 //! Systems related to the player.
 
-use bevy::prelude::*;
+use bevy::{prelude::*, time::Stopwatch};
 use bevy_grid_squared::{GridDirection, Square};
-use common_action::{ActionStateExt, GlobalAction, MovementAction};
+use common_action::{
+    ActionStateExt, GlobalAction, MovementAction, StickMapping,
+};
 use common_ext::QueryExt;
 use leafwing_input_manager::action_state::ActionState;
 
-use super::{Actor, ActorTarget};
-use crate::top_down::layout::TileMap;
+use super::{Actor, ActorTarget, Footprint, Knockback, Passability};
+use crate::top_down::{
+    environmental_objects::pushable::{self, Pushable},
+    layout::{TileMap, Walker},
+};
 
 /// The entity that the player controls.
 /// Bound it with [`Actor`] to allow movement.
@@ -22,18 +27,58 @@ pub struct Player;
 #[derive(Component, Reflect)]
 pub struct TakeAwayPlayerControl;
 
+/// Sets the step time of the player depending on whether they walk or run.
+/// The player walks at the default pace of the character and runs while
+/// [`GlobalAction::Run`] is held.
+///
+/// Runs every frame the player moves around so that pressing or releasing
+/// [`GlobalAction::Run`] changes the pace right away, even mid-step.
+/// See [`Actor::set_step_time`].
+pub fn walk_or_run(
+    controls: Res<ActionState<GlobalAction>>,
+
+    mut player: Query<
+        &mut Actor,
+        (
+            With<Player>,
+            Without<TakeAwayPlayerControl>,
+            Without<Knockback>,
+        ),
+    >,
+) {
+    let Some(mut player) = player.get_single_mut_or_none() else {
+        return;
+    };
+
+    let step_time = if controls.pressed(&GlobalAction::Run) {
+        player.character.run_step_time()
+    } else {
+        player.character.default_step_time()
+    };
+    if player.step_time != step_time {
+        player.set_step_time(step_time);
+    }
+}
+
 /// Use keyboard to move around the player.
 pub fn move_around(
-    map: Res<TileMap>,
+    mut map: ResMut<TileMap>,
     controls: Res<ActionState<GlobalAction>>,
+    stick_mapping: Res<StickMapping>,
+
+    mut crates: Query<&mut Pushable>,
 
     mut player: Query<
-        (Entity, &mut Actor),
-        (With<Player>, Without<TakeAwayPlayerControl>),
+        (Entity, &mut Actor, Option<&Footprint>, Option<&Passability>),
+        (
+            With<Player>,
+            Without<TakeAwayPlayerControl>,
+            Without<Knockback>,
+        ),
     >,
 ) {
     // there must be some user action
-    let Some(action) = controls.movement_action() else {
+    let Some(action) = controls.movement_action_with(&stick_mapping) else {
         return;
     };
     // that leads to a movement command
@@ -43,21 +88,38 @@ pub fn move_around(
         return;
     };
     // there must be someone to move
-    let Some((player_entity, mut player)) = player.get_single_mut_or_none()
+    let Some((player_entity, mut player, footprint, passability)) =
+        player.get_single_mut_or_none()
     else {
         return;
     };
+    let footprint = Footprint::squares_of(footprint);
+    let walker = Walker::new(player_entity, passability);
     // who doesn't yet have all the movement planned
-    if player
-        .walking_to
-        .target()
-        .and_then(|to| to.planned)
-        .is_some()
+    if let Some((_, planned_direction)) =
+        player.walking_to.target().and_then(|to| to.planned)
     {
+        // but a new direction is remembered for when the step is done
+        if planned_direction != primary_steps[0] {
+            player.queued = Some((primary_steps[0], Stopwatch::new()));
+        }
+        return;
+    }
+    // and who isn't just turning around
+    if player.turn_in_place(primary_steps[0]) {
         return;
     }
 
-    player.step_time = player.character.default_step_time();
+    // walking into a crate pushes it if there's space behind it
+    if pushable::try_push(
+        &mut map,
+        player_entity,
+        &mut player,
+        primary_steps[0],
+        crates.iter_mut(),
+    ) {
+        return;
+    }
 
     let plan_from = player.current_square();
 
@@ -65,7 +127,7 @@ pub fn move_around(
     let find_target = |from: Square, options: &[_]| {
         options.iter().copied().find_map(|direction| {
             let target = from.neighbor(direction);
-            map.is_walkable(target, player_entity)
+            map.can_step_with_footprint(from, target, walker, footprint)
                 .then_some((target, direction))
         })
     };
@@ -153,3 +215,179 @@ fn to_direction_commands(
 
     Some(steps)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy_grid_squared::sq;
+    use common_story::Character;
+    use leafwing_input_manager::axislike::DualAxisData;
+
+    use super::*;
+    use crate::top_down::actor::{
+        animate_movement,
+        test_utils::{app_with_assets, spawn_player},
+        ActorMovement, ActorSteppedEvent, TerrainSpeed,
+        QUEUED_DIRECTION_EXPIRES_IN,
+    };
+
+    #[test]
+    fn it_switches_between_walking_and_running() {
+        let mut app = app_with_assets();
+        app.init_resource::<ActionState<GlobalAction>>();
+        let w = app.world_mut();
+        let walk_or_run = w.register_system(walk_or_run);
+
+        let player = spawn_player(w, |builder| {
+            builder.initial_square(sq(0, 0));
+        });
+        let step_time = |w: &World| w.get::<Actor>(player).unwrap().step_time;
+
+        let walking = Character::Winnie.default_step_time();
+        let running = Character::Winnie.run_step_time();
+
+        w.run_system(walk_or_run).unwrap();
+        assert_eq!(walking, step_time(w));
+
+        w.resource_mut::<ActionState<GlobalAction>>()
+            .press(&GlobalAction::Run);
+        w.run_system(walk_or_run).unwrap();
+        assert_eq!(running, step_time(w));
+
+        w.resource_mut::<ActionState<GlobalAction>>()
+            .release(&GlobalAction::Run);
+        w.run_system(walk_or_run).unwrap();
+        assert_eq!(walking, step_time(w));
+
+        w.resource_mut::<ActionState<GlobalAction>>()
+            .press(&GlobalAction::Run);
+        w.run_system(walk_or_run).unwrap();
+        assert_eq!(running, step_time(w));
+
+        // halfway through a step the player stays halfway through it
+        let mut actor = w.get_mut::<Actor>(player).unwrap();
+        let mut target = ActorTarget::new(sq(1, 0));
+        target.since.set_elapsed(running / 2);
+        actor.walking_to = target.into();
+        w.resource_mut::<ActionState<GlobalAction>>()
+            .release(&GlobalAction::Run);
+        w.run_system(walk_or_run).unwrap();
+        let actor = w.get::<Actor>(player).unwrap();
+        assert_eq!(walking, actor.step_time);
+        let since = actor.walking_to.target().unwrap().since.elapsed();
+        assert!(since.abs_diff(walking / 2) < Duration::from_micros(1));
+    }
+
+    #[test]
+    fn it_turns_in_place_on_short_tap() {
+        let mut app = app_with_assets();
+        app.init_resource::<ActionState<GlobalAction>>()
+            .init_resource::<StickMapping>()
+            .insert_resource(TileMap::default());
+        let w = app.world_mut();
+        let move_around = w.register_system(move_around);
+
+        let player = spawn_player(w, |builder| {
+            builder
+                .initial_square(sq(0, 0))
+                .initial_direction(GridDirection::Bottom)
+                .turn_delay(Duration::from_millis(100));
+        });
+
+        let mut controls = w.resource_mut::<ActionState<GlobalAction>>();
+        controls.press(&GlobalAction::Move);
+        controls
+            .action_data_mut_or_default(&GlobalAction::Move)
+            .axis_pair = Some(DualAxisData::new(1.0, 0.0));
+
+        // a tap only turns
+        w.run_system(move_around).unwrap();
+        let actor = w.get::<Actor>(player).unwrap();
+        assert_eq!(GridDirection::Right, actor.direction);
+        assert!(actor.walking_to.is_still());
+
+        // holding it long enough walks
+        let mut actor = w.get_mut::<Actor>(player).unwrap();
+        let ActorMovement::Still { since } = &mut actor.walking_to else {
+            unreachable!()
+        };
+        since.tick(Duration::from_millis(100));
+        w.run_system(move_around).unwrap();
+        let actor = w.get::<Actor>(player).unwrap();
+        assert_eq!(
+            Some(sq(1, 0)),
+            actor.walking_to.target().map(|to| to.square)
+        );
+    }
+
+    #[test]
+    fn it_takes_direction_pressed_mid_step_on_next_step() {
+        let mut app = app_with_assets();
+        app.init_resource::<ActionState<GlobalAction>>()
+            .init_resource::<StickMapping>()
+            .init_resource::<TerrainSpeed>()
+            .init_resource::<Events<ActorSteppedEvent>>()
+            .insert_resource(Time::<()>::default())
+            .insert_resource(TileMap::default());
+        let w = app.world_mut();
+        let move_around = w.register_system(move_around);
+        let animate_movement = w.register_system(animate_movement);
+
+        let player = spawn_player(w, |builder| {
+            builder
+                .initial_square(sq(0, 0))
+                .initial_direction(GridDirection::Right);
+        });
+
+        // mid-step to the right with another step to the right planned
+        let mut actor = w.get_mut::<Actor>(player).unwrap();
+        let step_time = actor.step_time;
+        actor.walking_to = ActorTarget {
+            planned: Some((sq(2, 0), GridDirection::Right)),
+            ..ActorTarget::new(sq(1, 0))
+        }
+        .into();
+
+        let mut controls = w.resource_mut::<ActionState<GlobalAction>>();
+        controls.press(&GlobalAction::Move);
+        controls
+            .action_data_mut_or_default(&GlobalAction::Move)
+            .axis_pair = Some(DualAxisData::new(0.0, 1.0));
+        w.run_system(move_around).unwrap();
+        let actor = w.get::<Actor>(player).unwrap();
+        assert!(matches!(actor.queued, Some((GridDirection::Top, _))));
+
+        let finish_step = |w: &mut World| {
+            w.resource_mut::<Time>()
+                .advance_by(step_time + Duration::from_millis(1));
+            w.run_system(animate_movement).unwrap();
+        };
+
+        // the queued direction is taken instead of the planned one
+        finish_step(w);
+        let actor = w.get::<Actor>(player).unwrap();
+        let up = sq(1, 0).neighbor(GridDirection::Top);
+        assert_eq!(sq(1, 0), actor.walking_from);
+        assert_eq!(Some(up), actor.walking_to.target().map(|to| to.square));
+        assert_eq!(None, actor.walking_to.target().unwrap().planned);
+        assert_eq!(GridDirection::Top, actor.direction);
+        assert!(actor.queued.is_none());
+
+        // but not if it was pressed too long ago
+        let mut actor = w.get_mut::<Actor>(player).unwrap();
+        let up_again = up.neighbor(GridDirection::Top);
+        actor.walking_to.target_mut().unwrap().planned =
+            Some((up_again, GridDirection::Top));
+        let mut since = Stopwatch::new();
+        since.set_elapsed(QUEUED_DIRECTION_EXPIRES_IN);
+        actor.queued = Some((GridDirection::Right, since));
+        finish_step(w);
+        let actor = w.get::<Actor>(player).unwrap();
+        assert_eq!(
+            Some(up_again),
+            actor.walking_to.target().map(|to| to.square)
+        );
+        assert!(actor.queued.is_none());
+    }
+}
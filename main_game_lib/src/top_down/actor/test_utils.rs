@@ -0,0 +1,62 @@
+//! Helpers shared by the tests of the actor modules.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_grid_squared::{GridDirection, Square};
+use common_story::Character;
+
+use crate::top_down::{
+    actor::{CharacterBundleBuilder, CharacterExt},
+    layout::LAYOUT,
+    Actor,
+};
+
+/// An app with just enough plugins to insert character bundles.
+pub(crate) fn app_with_assets() -> App {
+    let mut app = App::new();
+    app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+        .init_asset::<Image>();
+    app
+}
+
+/// Spawns Winnie the way scenes spawn the player, see
+/// [`CharacterBundleBuilder::insert_bundle_into`].
+/// Use the closure to set up e.g. the initial square.
+///
+/// The world must have an [`AssetServer`], see [`app_with_assets`].
+pub(crate) fn spawn_player(
+    w: &mut World,
+    setup: impl FnOnce(&mut CharacterBundleBuilder),
+) -> Entity {
+    let assets = w.resource::<AssetServer>().clone();
+    let mut builder = Character::Winnie.bundle_builder();
+    setup(&mut builder);
+    let mut cmd = w.commands();
+    let mut player = cmd.spawn_empty();
+    builder.insert_bundle_into(&assets, &mut player);
+    let player = player.id();
+    w.flush();
+    player
+}
+
+/// Spawns Marie standing still at the square and facing right.
+/// Change the [`Actor`] or insert more components as the test needs.
+pub(crate) fn spawn_actor_at(w: &mut World, square: Square) -> Entity {
+    w.spawn((
+        Actor {
+            character: Character::Marie,
+            step_time: Duration::from_secs(1),
+            turn_delay: default(),
+            queued: None,
+            direction: GridDirection::Right,
+            walking_from: square,
+            walking_to: default(),
+            occupies: vec![],
+        },
+        GlobalTransform::from_translation(
+            LAYOUT.square_to_world_pos(square).extend(0.0),
+        ),
+    ))
+    .id()
+}
@@ -3,16 +3,18 @@
 use std::time::Duration;
 
 use bevy::{math::vec2, prelude::*, time::Stopwatch};
+use bevy_grid_squared::sq;
 use common_ext::QueryExt;
 use common_visuals::{
     camera::{
-        MainCamera, PIXEL_VISIBLE_HEIGHT, PIXEL_VISIBLE_WIDTH, PIXEL_ZOOM,
+        CameraZoom, MainCamera, PIXEL_VISIBLE_HEIGHT, PIXEL_VISIBLE_WIDTH,
+        PIXEL_ZOOM,
     },
     EASE_IN_OUT,
 };
 use lazy_static::lazy_static;
 
-use crate::top_down::Player;
+use crate::top_down::{Actor, Player, TileMap};
 
 lazy_static! {
     /// If the player leaves this bounding box, the camera follows her.
@@ -52,11 +54,108 @@ pub struct SyncWithPlayer {
     animation_timer: Stopwatch,
 }
 
+/// Insert this resource to have the camera lead the player in the direction
+/// of travel instead of waiting for her to leave the bounding box
+/// [`static@BOUNDING_BOX_SIZE`].
+/// When the player stops, the camera eases back to center on her.
+///
+/// The camera keeps its precise position in [`LeadingPlayer`] and only the
+/// rendered translation is rounded to whole pixels.
+/// Anything that offsets the camera translation after the tracking system,
+/// such as a camera shake, composes on top of the lead: it's shown for that
+/// frame and then overwritten, but never fed back into the lead.
+///
+/// Disabled by default.
+#[derive(Resource, Reflect, Clone, Copy, Debug)]
+#[reflect(Resource)]
+pub struct CameraLead {
+    /// How far ahead of the player is the camera when she's walking.
+    pub distance: f32,
+    /// What portion of the distance to the target the camera travels per
+    /// second.
+    /// The higher the snappier.
+    pub smoothing: f32,
+}
+
+/// Attached to the camera while it's leading the player, see [`CameraLead`].
+///
+/// Removed when the lead is disabled or when a cutscene claims the camera,
+/// after which the lead continues from wherever the camera is.
+#[derive(Component)]
+pub struct LeadingPlayer {
+    /// Where the camera would be if we didn't round it to whole pixels.
+    /// The lead eases from here so that small steps add up instead of being
+    /// rounded away every frame.
+    precise: Vec2,
+}
+
+/// Insert this resource to keep the camera from showing anything beyond the
+/// edges of the map.
+///
+/// The camera translation is clamped such that the visible area, which
+/// depends on the [`CameraZoom`], stays within the bounds.
+/// If the map is smaller than the visible area along an axis, the camera is
+/// centered on the map along that axis instead.
+///
+/// Disabled by default.
+#[derive(Resource, Reflect, Clone, Copy, Debug)]
+#[reflect(Resource)]
+pub struct CameraBounds {
+    /// In world coordinates.
+    pub rect: Rect,
+}
+
 /// If this component is present on [`MainCamera`] systems in this module
 /// won't do anything.
 #[derive(Component)]
 pub struct ManualControl;
 
+impl CameraBounds {
+    /// Covers all squares within [`TileMap::bounds`].
+    pub fn from_tilemap(tilemap: &TileMap) -> Self {
+        let [left, right, bottom, top] = tilemap.bounds();
+        let layout = tilemap.layout();
+        let half_square = layout.square() / 2.0;
+
+        Self {
+            rect: Rect::from_corners(
+                layout.square_to_world_pos(sq(left, bottom)) - half_square,
+                layout.square_to_world_pos(sq(right, top)) + half_square,
+            ),
+        }
+    }
+
+    /// Where the camera must be to keep the visible area of given size
+    /// within the bounds while being as close to the given position as
+    /// possible.
+    pub fn clamp(&self, position: Vec2, visible_size: Vec2) -> Vec2 {
+        let clamp_axis = |position: f32, min: f32, max: f32, visible: f32| {
+            let half_visible = visible / 2.0;
+            if max - min <= visible {
+                // the map is smaller than the screen
+                (min + max) / 2.0
+            } else {
+                position.clamp(min + half_visible, max - half_visible)
+            }
+        };
+
+        vec2(
+            clamp_axis(
+                position.x,
+                self.rect.min.x,
+                self.rect.max.x,
+                visible_size.x,
+            ),
+            clamp_axis(
+                position.y,
+                self.rect.min.y,
+                self.rect.max.y,
+                visible_size.y,
+            ),
+        )
+    }
+}
+
 /// Recommended to run after the player's movement animation:
 ///
 /// ```rust,ignore
@@ -69,37 +168,97 @@ pub struct ManualControl;
 pub fn track_player_with_main_camera(
     cmd: Commands,
     time: Res<Time>,
+    lead: Option<Res<CameraLead>>,
+
+    bounds: Option<Res<CameraBounds>>,
 
-    player: Query<&GlobalTransform, With<Player>>,
+    player: Query<(&GlobalTransform, Option<&Actor>), With<Player>>,
     camera: Query<
-        (Entity, &mut Transform, Option<&mut SyncWithPlayer>),
+        (
+            Entity,
+            &mut Transform,
+            Option<&mut SyncWithPlayer>,
+            Option<&mut LeadingPlayer>,
+            Option<&CameraZoom>,
+        ),
         (With<MainCamera>, Without<ManualControl>),
     >,
 ) {
-    track_player::<MainCamera>(cmd, time, player, camera);
+    track_player::<MainCamera>(cmd, time, lead, bounds, player, camera);
 }
 
 fn track_player<C: Component>(
     mut cmd: Commands,
     time: Res<Time>,
+    lead: Option<Res<CameraLead>>,
+    bounds: Option<Res<CameraBounds>>,
 
-    player: Query<&GlobalTransform, With<Player>>,
+    player: Query<(&GlobalTransform, Option<&Actor>), With<Player>>,
     mut camera: Query<
-        (Entity, &mut Transform, Option<&mut SyncWithPlayer>),
+        (
+            Entity,
+            &mut Transform,
+            Option<&mut SyncWithPlayer>,
+            Option<&mut LeadingPlayer>,
+            Option<&CameraZoom>,
+        ),
         (With<C>, Without<ManualControl>),
     >,
 ) {
-    let Some(player_pos) = player.get_single_or_none() else {
+    let Some((player_pos, player_actor)) = player.get_single_or_none() else {
         return;
     };
 
-    let Some((camera_entity, mut camera, mut state)) =
+    let Some((camera_entity, mut camera, mut state, leading, zoom)) =
         camera.get_single_mut_or_none()
     else {
         return;
     };
 
-    if let Some(SyncWithPlayer {
+    // the pixel zoom is always a whole number
+    let zoom = zoom.map_or(PIXEL_ZOOM as f32, |zoom| zoom.current().round());
+    let visible_size = vec2(PIXEL_VISIBLE_WIDTH, PIXEL_VISIBLE_HEIGHT)
+        * PIXEL_ZOOM as f32
+        / zoom;
+    let clamp = |position: Vec2| {
+        bounds
+            .as_ref()
+            .map_or(position, |bounds| bounds.clamp(position, visible_size))
+    };
+
+    if let Some(lead) = lead {
+        if state.is_some() {
+            cmd.entity(camera_entity).remove::<SyncWithPlayer>();
+        }
+
+        // when the player stops, the offset is zero and the camera eases back
+        let offset = player_actor
+            .filter(|actor| actor.walking_to.target().is_some())
+            .map(|actor| {
+                Vec2::from(actor.direction).normalize_or_zero() * lead.distance
+            })
+            .unwrap_or(Vec2::ZERO);
+        let target = clamp(player_pos.translation().truncate() + offset);
+
+        let lerp_factor = (lead.smoothing * time.delta_seconds()).min(1.0);
+        let from = leading
+            .as_ref()
+            .map_or(camera.translation.truncate(), |leading| leading.precise);
+        // e.g. the zoom changed since the last frame
+        let precise = clamp(from).lerp(target, lerp_factor);
+        if let Some(mut leading) = leading {
+            leading.precise = precise;
+        } else {
+            cmd.entity(camera_entity).insert(LeadingPlayer { precise });
+        }
+
+        // prevents fractions that jitter other objects
+        let rounded = (precise * PIXEL_ZOOM as f32).round() / PIXEL_ZOOM as f32;
+        camera.translation = rounded.extend(camera.translation.z);
+    } else if leading.is_some() {
+        // the lead was disabled, sync with the player as usual from next frame
+        cmd.entity(camera_entity).remove::<LeadingPlayer>();
+    } else if let Some(SyncWithPlayer {
         initial_position,
         final_position,
         animation_timer,
@@ -133,13 +292,111 @@ fn track_player<C: Component>(
             *BOUNDING_BOX_SIZE,
         );
 
-        if !bounding_box.contains(player_pos.translation().truncate()) {
+        let final_position = clamp(player_pos.translation().truncate());
+        // near the map edges the camera might not be able to follow
+        let can_follow = final_position
+            .distance_squared(camera.translation.truncate())
+            > f32::EPSILON;
+
+        if !bounding_box.contains(player_pos.translation().truncate())
+            && can_follow
+        {
             trace!("Player left the bounding box, camera follows her");
             cmd.entity(camera_entity).insert(SyncWithPlayer {
                 initial_position: camera.translation.truncate(),
-                final_position: player_pos.translation().truncate(),
+                final_position,
                 animation_timer: Stopwatch::new(),
             });
         }
     }
+
+    // e.g. the zoom changed or the camera was just spawned
+    let clamped = clamp(camera.translation.truncate());
+    if clamped != camera.translation.truncate() {
+        camera.translation = clamped.extend(camera.translation.z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_clamps_camera_to_map_edges() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+        w.insert_resource(CameraBounds {
+            rect: Rect::new(0.0, 0.0, 1000.0, 1000.0),
+        });
+        let track = w.register_system(track_player_with_main_camera);
+
+        let player = w
+            .spawn((Player, GlobalTransform::from_xyz(5.0, 5.0, 0.0)))
+            .id();
+        let camera = w
+            .spawn((MainCamera, Transform::default(), CameraZoom::default()))
+            .id();
+        let camera_at =
+            |w: &World| w.get::<Transform>(camera).unwrap().translation;
+
+        // player in the bottom left corner
+        for _ in 0..10 {
+            w.resource_mut::<Time>().advance_by(SYNCING_DURATION / 4);
+            w.run_system(track).unwrap();
+        }
+        let half_visible =
+            vec2(PIXEL_VISIBLE_WIDTH, PIXEL_VISIBLE_HEIGHT) / 2.0;
+        assert_eq!(half_visible.extend(0.0), camera_at(&w));
+
+        // map narrower than the screen is centered horizontally, and the
+        // bounds apply to the leading camera too
+        w.insert_resource(CameraLead {
+            distance: 0.0,
+            smoothing: 10.0,
+        });
+        w.resource_mut::<CameraBounds>().rect =
+            Rect::new(0.0, 0.0, 100.0, 1000.0);
+        *w.get_mut::<GlobalTransform>(player).unwrap() =
+            GlobalTransform::from_xyz(95.0, 995.0, 0.0);
+        w.resource_mut::<Time>().advance_by(Duration::from_secs(1));
+        w.run_system(track).unwrap();
+        assert_eq!(
+            vec2(50.0, 1000.0 - half_visible.y).extend(0.0),
+            camera_at(&w)
+        );
+    }
+
+    #[test]
+    fn it_leads_from_precise_position_rather_than_rounded() {
+        let mut w = World::default();
+        w.insert_resource(Time::<()>::default());
+        w.insert_resource(CameraLead {
+            distance: 0.0,
+            smoothing: 1.0,
+        });
+        let track = w.register_system(track_player_with_main_camera);
+
+        w.spawn((Player, GlobalTransform::from_xyz(100.0, 0.0, 0.0)));
+        let camera = w.spawn((MainCamera, Transform::default())).id();
+        let camera_at =
+            |w: &World| w.get::<Transform>(camera).unwrap().translation;
+
+        // each step is about a tenth of a unit, which is less than half a
+        // pixel and would be rounded away if we eased from the translation
+        for _ in 0..10 {
+            w.resource_mut::<Time>()
+                .advance_by(Duration::from_millis(1));
+            w.run_system(track).unwrap();
+        }
+        let x = camera_at(&w).x;
+        assert!(x > 0.0, "Camera stalled at {x}");
+        assert_eq!(x, (x * PIXEL_ZOOM as f32).round() / PIXEL_ZOOM as f32);
+
+        // an offset such as a shake is not fed back into the lead
+        w.get_mut::<Transform>(camera).unwrap().translation.x += 50.0;
+        w.resource_mut::<Time>()
+            .advance_by(Duration::from_millis(1));
+        w.run_system(track).unwrap();
+        assert!(camera_at(&w).x < 2.0);
+    }
 }
@@ -2,22 +2,46 @@
 
 use bevy::{app::Update, ecs::schedule::IntoSystemConfigs};
 
-use super::actor::{self, movement_event_emitted};
+use super::{
+    actor::{self, movement_event_emitted},
+    TopDownAction,
+};
 use crate::in_top_down_running_state;
 
 pub mod door;
+pub mod pushable;
+pub mod trigger;
 
 /// Adds systems related to the top down map's environmental objects.
 pub struct Plugin;
 
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut bevy::app::App) {
+        app.add_event::<door::DoorStateChangedEvent>();
+
         app.add_systems(
             Update,
-            door::toggle
-                .run_if(in_top_down_running_state())
-                .run_if(movement_event_emitted())
-                .after(actor::emit_movement_events),
+            (
+                door::toggle
+                    .run_if(movement_event_emitted())
+                    .after(actor::emit_movement_events),
+                door::close_once_unoccupied,
+            )
+                .chain()
+                .run_if(in_top_down_running_state()),
+        )
+        .add_systems(
+            Update,
+            trigger::fire_on_enter::<TopDownAction>
+                .run_if(in_top_down_running_state()),
+        )
+        .add_systems(
+            Update,
+            (
+                pushable::place.before(actor::player::move_around),
+                pushable::slide.after(actor::player::move_around),
+            )
+                .run_if(in_top_down_running_state()),
         );
     }
 }
@@ -3,6 +3,10 @@
 //! There are different [`DoorOpenCriteria`] that can be used to open the door.
 //! Optionally, the door can have an obstacle that's inserted into the map when
 //! the door is closed.
+//! Since the obstacle is a wall in the map, NPCs plan their paths around
+//! closed doors.
+//!
+//! Whenever a door opens or closes, [`DoorStateChangedEvent`] is emitted.
 
 use bevy::prelude::*;
 use bevy_grid_squared::Square;
@@ -37,6 +41,19 @@ pub struct Door {
     obstacle: Option<DoorObstacle>,
     /// Only when this gets to 0 do we close the door.
     actors_near: usize,
+    /// All actors left the zone but we could not close the door because
+    /// some actor is still standing where the obstacle goes.
+    /// We close the door with [`close_once_unoccupied`] when they step away.
+    close_when_unoccupied: bool,
+}
+
+/// Emitted when a [`Door`] opens or closes.
+#[derive(Event, Reflect, Clone, Copy)]
+pub struct DoorStateChangedEvent {
+    /// The entity with the [`Door`] component.
+    pub door: Entity,
+    /// The state the door is in now.
+    pub state: DoorState,
 }
 
 /// When the door is closed, we insert a wall between these two squares.
@@ -54,7 +71,7 @@ struct DoorObstacle {
 }
 
 /// When all conditions are met, the door opens.
-#[derive(Reflect, Default, Clone, Copy)]
+#[derive(Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DoorState {
     /// The door is open and can be walked through.
     /// If the door has an obstacle, it's removed.
@@ -81,13 +98,16 @@ pub fn toggle(
     audio: Res<Audio>,
     mut tilemap: ResMut<TileMap>,
     mut events: EventReader<ActorMovementEvent>,
+    mut state_changed: EventWriter<DoorStateChangedEvent>,
 
-    mut door: Query<(&mut Door, &mut TextureAtlas)>,
+    mut door: Query<(Entity, &mut Door, &mut TextureAtlas)>,
 ) {
     let events = events.read().collect_vec();
 
-    for (mut door, mut sprite) in door.iter_mut() {
+    for (entity, mut door, mut sprite) in door.iter_mut() {
         for event in &events {
+            let state_before = door.state;
+
             apply_event_to_door_and_map(
                 &asset_server,
                 &audio,
@@ -96,6 +116,35 @@ pub fn toggle(
                 &mut sprite,
                 event,
             );
+
+            if state_before != door.state {
+                state_changed.send(DoorStateChangedEvent {
+                    door: entity,
+                    state: door.state,
+                });
+            }
+        }
+    }
+}
+
+/// Closes doors that could not be closed when the last actor left their zone
+/// because some actor was standing in the doorway.
+pub fn close_once_unoccupied(
+    mut tilemap: ResMut<TileMap>,
+    mut state_changed: EventWriter<DoorStateChangedEvent>,
+
+    mut door: Query<(Entity, &mut Door, &mut TextureAtlas)>,
+) {
+    for (entity, mut door, mut sprite) in door.iter_mut() {
+        if !door.close_when_unoccupied {
+            continue;
+        }
+
+        if door.try_close(&mut tilemap, &mut sprite) {
+            state_changed.send(DoorStateChangedEvent {
+                door: entity,
+                state: door.state,
+            });
         }
     }
 }
@@ -118,6 +167,8 @@ fn apply_event_to_door_and_map(
             if *zone == door.zone_tile_kind =>
         {
             door.actors_near += 1;
+            // someone's coming, no need to close the door anymore
+            door.close_when_unoccupied = false;
 
             if !matches!(door.state, DoorState::Closed) {
                 return;
@@ -162,32 +213,78 @@ fn apply_event_to_door_and_map(
                 return;
             }
 
-            trace!("Close door");
-            door.state = DoorState::Closed;
-            sprite.index = 0;
-
-            if let Some(DoorObstacle {
-                rect: (from, to),
-                layers,
-            }) = door.obstacle.as_mut()
-            {
-                bevy_grid_squared::shapes::rectangle_between(*from, *to)
-                    .for_each(|sq| {
-                        layers.push(
-                            tilemap
-                                .add_tile_to_first_empty_layer(
-                                    sq,
-                                    TileKind::Wall,
-                                )
-                                .expect("doors are always within the map"),
-                        );
-                    });
+            if !matches!(door.state, DoorState::Open) {
+                return;
             }
+
+            door.try_close(tilemap, sprite);
         }
         _ => {}
     };
 }
 
+impl Door {
+    /// The current state of the door.
+    pub fn state(&self) -> DoorState {
+        self.state
+    }
+
+    /// Closes the door unless there's an actor standing where the obstacle
+    /// goes.
+    /// In such case, the door is flagged to be closed by
+    /// [`close_once_unoccupied`].
+    ///
+    /// Returns whether the door was closed.
+    fn try_close(
+        &mut self,
+        tilemap: &mut TileMap,
+        sprite: &mut Mut<'_, TextureAtlas>,
+    ) -> bool {
+        if let Some(DoorObstacle {
+            rect: (from, to), ..
+        }) = &self.obstacle
+        {
+            let is_occupied = bevy_grid_squared::shapes::rectangle_between(
+                *from, *to,
+            )
+            .any(|sq| {
+                tilemap.any_on(sq, |tile| matches!(tile, TileKind::Actor(_)))
+            });
+
+            if is_occupied {
+                if !self.close_when_unoccupied {
+                    trace!("Cannot close door, an actor is in the way");
+                }
+                self.close_when_unoccupied = true;
+                return false;
+            }
+        }
+
+        trace!("Close door");
+        self.close_when_unoccupied = false;
+        self.state = DoorState::Closed;
+        sprite.index = 0;
+
+        if let Some(DoorObstacle {
+            rect: (from, to),
+            layers,
+        }) = self.obstacle.as_mut()
+        {
+            bevy_grid_squared::shapes::rectangle_between(*from, *to).for_each(
+                |sq| {
+                    layers.push(
+                        tilemap
+                            .add_tile_to_first_empty_layer(sq, TileKind::Wall)
+                            .expect("doors are always within the map"),
+                    );
+                },
+            );
+        }
+
+        true
+    }
+}
+
 impl DoorBuilder {
     /// The only required parameter is the zone tile kind that opens the door.
     pub fn new(zone_tile_kind: impl Into<TileKind>) -> Self {
@@ -253,6 +350,7 @@ impl DoorBuilder {
             obstacle,
 
             actors_near: 0,
+            close_when_unoccupied: false,
         }
     }
 
@@ -273,6 +371,88 @@ impl DoorBuilder {
             obstacle: None,
 
             actors_near: 0,
+            close_when_unoccupied: false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_grid_squared::sq;
+    use common_story::Character;
+
+    use super::*;
+    use crate::top_down::{actor::Who, ZoneTileKind};
+
+    #[test]
+    fn it_closes_door_only_once_doorway_is_vacated() {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<bevy_kira_audio::AudioSource>()
+            .init_resource::<Audio>()
+            .init_resource::<Events<ActorMovementEvent>>()
+            .init_resource::<Events<DoorStateChangedEvent>>();
+        let w = app.world_mut();
+        let toggle = w.register_system(toggle);
+        let close_once_unoccupied = w.register_system(close_once_unoccupied);
+
+        let zone = TileKind::Zone(ZoneTileKind::Hallway);
+        let doorway = sq(0, 0);
+        let mut tilemap = TileMap::default();
+        let door = DoorBuilder::new(zone)
+            .with_obstacle_when_closed_between(doorway, doorway)
+            .build_and_insert_obstacle(&mut tilemap);
+        w.insert_resource(tilemap);
+        let door = w.spawn((door, TextureAtlas::default())).id();
+        let actor = w.spawn_empty().id();
+
+        let is_wall = |w: &World| {
+            w.resource::<TileMap>()
+                .any_on(doorway, |tile| matches!(tile, TileKind::Wall))
+        };
+        let state_changes = |w: &mut World| {
+            w.resource_mut::<Events<DoorStateChangedEvent>>()
+                .drain()
+                .map(|event| (event.door, event.state))
+                .collect::<Vec<_>>()
+        };
+        let who = || Who {
+            is_player: true,
+            entity: actor,
+            character: Character::Winnie,
+            at: None,
+        };
+        assert!(is_wall(w));
+
+        w.send_event(ActorMovementEvent::ZoneEntered { zone, who: who() });
+        w.run_system(toggle).unwrap();
+        assert!(!is_wall(w));
+        assert_eq!(vec![(door, DoorState::Open)], state_changes(w));
+
+        // the actor left the zone but stands in the doorway
+        let layer = w
+            .resource_mut::<TileMap>()
+            .add_tile_to_first_empty_layer(doorway, TileKind::Actor(actor))
+            .unwrap();
+        w.send_event(ActorMovementEvent::ZoneLeft { zone, who: who() });
+        w.run_system(toggle).unwrap();
+        w.run_system(close_once_unoccupied).unwrap();
+        assert!(!is_wall(w));
+        assert!(state_changes(w).is_empty());
+        assert_eq!(DoorState::Open, w.get::<Door>(door).unwrap().state());
+
+        w.resource_mut::<TileMap>().set_tile_kind(
+            doorway,
+            layer,
+            TileKind::Empty,
+        );
+        w.run_system(close_once_unoccupied).unwrap();
+        assert!(is_wall(w));
+        assert_eq!(vec![(door, DoorState::Closed)], state_changes(w));
+        assert_eq!(0, w.get::<TextureAtlas>(door).unwrap().index);
+
+        // nothing left to close
+        w.run_system(close_once_unoccupied).unwrap();
+        assert!(state_changes(w).is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+//! Sokoban-style crates that the player pushes one square at a time.
+//!
+//! A [`Pushable`] occupies its square with a [`TileKind::Wall`] so that
+//! nobody walks through it.
+//! When the player walks into it in a cardinal direction and the square
+//! beyond is passable, the crate moves there and the player takes its place.
+//! If the square beyond is blocked, neither moves and the player treats the
+//! crate as any other wall.
+//!
+//! The tiles are moved right away when the push is planned.
+//! The sprite slides over the same duration as the player's step, starting
+//! together with the step into the crate's square.
+//!
+//! Spawn the crate entity with a [`Transform`] on its square and the
+//! [`Pushable`] component, it's placed into the map on the next tick.
+
+use std::{ops::DerefMut, time::Duration};
+
+use bevy::prelude::*;
+use bevy_grid_squared::{GridDirection, Square};
+use common_visuals::BeginInterpolationEvent;
+
+use crate::top_down::{
+    actor::ActorSteppedEvent, Actor, ActorTarget, Player, TileKind, TileMap,
+};
+
+/// A crate that the player can push around.
+/// See the module docs.
+#[derive(Component, Reflect, Debug)]
+pub struct Pushable {
+    square: Square,
+    /// The layer of the wall tile on the square.
+    /// [`None`] until the crate is placed into the map.
+    layer: Option<usize>,
+    /// The crate has been pushed and the sprite is yet to slide.
+    slide: Option<PendingSlide>,
+}
+
+#[derive(Reflect, Debug, Clone, Copy)]
+enum PendingSlide {
+    /// The player was standing still so the slide starts right away.
+    Now(Duration),
+    /// The player was walking to the square in front of the crate.
+    /// The slide starts once the player arrives there.
+    AfterPlayerStep(Duration),
+}
+
+impl Pushable {
+    /// The crate sits on the given square.
+    pub fn new(square: Square) -> Self {
+        Self {
+            square,
+            layer: None,
+            slide: None,
+        }
+    }
+
+    /// Where the crate is in the map.
+    /// When pushed, this is already the square it's sliding to.
+    pub fn square(&self) -> Square {
+        self.square
+    }
+
+    fn place(&mut self, tilemap: &mut TileMap) {
+        debug_assert!(self.layer.is_none(), "Crate already placed");
+        self.layer =
+            tilemap.add_tile_to_first_empty_layer(self.square, TileKind::Wall);
+    }
+}
+
+/// Puts the wall tiles of newly spawned crates into the map.
+pub fn place(
+    mut tilemap: ResMut<TileMap>,
+    mut crates: Query<&mut Pushable, Added<Pushable>>,
+) {
+    for mut pushable in crates.iter_mut() {
+        pushable.place(&mut tilemap);
+    }
+}
+
+/// Called by the player movement when the player wants to step in the given
+/// direction.
+/// If there's a crate in the way that can be pushed, pushes it and plans the
+/// player's step onto its square.
+///
+/// Returns whether the crate was pushed.
+/// If not, the movement logic continues as if the crate was a wall.
+pub(crate) fn try_push<P: DerefMut<Target = Pushable>>(
+    tilemap: &mut TileMap,
+    player_entity: Entity,
+    player: &mut Actor,
+    direction: GridDirection,
+    crates: impl IntoIterator<Item = P>,
+) -> bool {
+    if !matches!(
+        direction,
+        GridDirection::Top
+            | GridDirection::Bottom
+            | GridDirection::Left
+            | GridDirection::Right
+    ) {
+        // diagonal pushes would let crates squeeze between walls
+        return false;
+    }
+
+    let crate_square = player.current_square().neighbor(direction);
+    let Some(mut pushable) =
+        crates.into_iter().find(|p| p.square == crate_square)
+    else {
+        return false;
+    };
+    let Some(layer) = pushable.layer else {
+        return false;
+    };
+    if pushable.slide.is_some() {
+        // still waiting for the previous push to play out
+        return false;
+    }
+
+    let beyond = crate_square.neighbor(direction);
+    if !tilemap.can_enter(crate_square, beyond)
+        || !tilemap.is_walkable(beyond, player_entity)
+        || tilemap.any_on(beyond, |tile| matches!(tile, TileKind::Actor(_)))
+    {
+        trace!("Crate at {crate_square} cannot be pushed to {beyond}");
+        return false;
+    }
+
+    tilemap.set_tile_kind(crate_square, layer, TileKind::Empty);
+    pushable.layer =
+        tilemap.add_tile_to_first_empty_layer(beyond, TileKind::Wall);
+    pushable.square = beyond;
+
+    player.direction = direction;
+    if let Some(walking_to) = player.walking_to.target_mut() {
+        debug_assert!(walking_to.planned.is_none());
+        walking_to.planned = Some((crate_square, direction));
+        pushable.slide = Some(PendingSlide::AfterPlayerStep(player.step_time));
+    } else {
+        player.walking_to = ActorTarget::new(crate_square).into();
+        pushable.slide = Some(PendingSlide::Now(player.step_time));
+    }
+
+    true
+}
+
+/// Slides the sprites of pushed crates to their new squares.
+pub fn slide(
+    mut cmd: Commands,
+    tilemap: Res<TileMap>,
+    mut stepped: EventReader<ActorSteppedEvent>,
+
+    player: Query<(), With<Player>>,
+    mut crates: Query<(Entity, &mut Pushable, &mut Transform)>,
+) {
+    let player_stepped =
+        stepped.read().any(|event| player.contains(event.entity));
+
+    for (entity, mut pushable, mut transform) in crates.iter_mut() {
+        let over = match pushable.slide {
+            Some(PendingSlide::Now(over)) => over,
+            Some(PendingSlide::AfterPlayerStep(over)) if player_stepped => over,
+            _ => continue,
+        };
+        pushable.slide = None;
+
+        let to = tilemap.layout().square_to_world_pos(pushable.square);
+        // sort by where the crate ends up, it won't overlap the player
+        transform.translation.z = tilemap.ysort(to);
+        BeginInterpolationEvent::of_translation(entity, None, to)
+            .over(over)
+            .insert_to(&mut cmd.entity(entity));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_grid_squared::sq;
+
+    use super::*;
+    use crate::top_down::actor::{
+        test_utils::{app_with_assets, spawn_player},
+        ActorMovement,
+    };
+
+    #[test]
+    fn it_pushes_crate_into_open_square_but_not_into_wall() {
+        let mut app = app_with_assets();
+        let w = app.world_mut();
+
+        let mut tilemap = TileMap::default();
+        tilemap.set_tile_kind(sq(3, 0), 0, TileKind::Wall);
+        let mut pushable = Pushable::new(sq(1, 0));
+        pushable.place(&mut tilemap);
+        w.insert_resource(tilemap);
+
+        let player = spawn_player(w, |builder| {
+            builder.initial_square(sq(0, 0));
+        });
+
+        let push = |w: &mut World, pushable: &mut Pushable| {
+            w.resource_scope(|w, mut tilemap: Mut<TileMap>| {
+                let mut actor = w.get_mut::<Actor>(player).unwrap();
+                try_push(
+                    &mut tilemap,
+                    player,
+                    &mut actor,
+                    GridDirection::Right,
+                    [pushable],
+                )
+            })
+        };
+
+        // into the open
+        assert!(push(w, &mut pushable));
+        assert_eq!(sq(2, 0), pushable.square());
+        let tilemap = w.resource::<TileMap>();
+        assert!(tilemap.is_walkable(sq(1, 0), player));
+        assert!(!tilemap.is_walkable(sq(2, 0), player));
+        let actor = w.get::<Actor>(player).unwrap();
+        assert_eq!(Some(sq(1, 0)), actor.walking_to.target().map(|t| t.square));
+
+        // player arrived and the slide played out
+        let mut actor = w.get_mut::<Actor>(player).unwrap();
+        actor.walking_from = sq(1, 0);
+        actor.walking_to = ActorMovement::default();
+        pushable.slide = None;
+
+        // into the wall
+        assert!(!push(w, &mut pushable));
+        assert_eq!(sq(2, 0), pushable.square());
+        assert!(!w.resource::<TileMap>().is_walkable(sq(2, 0), player));
+        let actor = w.get::<Actor>(player).unwrap();
+        assert_eq!(sq(1, 0), actor.current_square());
+        assert!(actor.walking_to.target().is_none());
+    }
+}
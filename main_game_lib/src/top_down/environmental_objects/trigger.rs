@@ -0,0 +1,248 @@
+//! Squares that emit an action event when the player steps onto them.
+//!
+//! In Godot, add a plain node called `Trigger` as a child of a `Node2D`.
+//! The trigger covers the square of its parent and, with the `range`
+//! metadata, the squares around it.
+//! Its `on_enter` metadata names the variant of the action enum that's
+//! emitted when the player steps onto any of the covered squares from outside.
+//! Moving around within the covered squares doesn't emit it again.
+//!
+//! If the parent is an `Area2D` instead, the trigger covers the squares whose
+//! centers lie in the rectangle shapes of the area, see [`SceneArea`].
+//! That way regions such as "enter here to start a cutscene" can be drawn in
+//! Godot rather than painted as zone tiles.
+//!
+//! Prefer this to a hand-written system that watches the player movement only
+//! to emit an action.
+
+use std::str::FromStr;
+
+use bevy::prelude::*;
+use bevy_grid_squared::{Square, SquareLayout};
+
+use crate::{
+    bevy_rscn::SceneArea,
+    top_down::{actor::ActorSteppedEvent, Player, TileMap},
+};
+
+/// Emits `A` when the player enters the covered squares.
+/// See the module docs.
+#[derive(Component, Reflect, Debug, Clone)]
+pub struct EnterTrigger<A> {
+    action: A,
+    /// How many squares around the trigger are covered.
+    /// With 0, it's just the square the trigger is on.
+    /// Ignored if the trigger has a [`SceneArea`].
+    range: i32,
+}
+
+impl<A: FromStr> EnterTrigger<A> {
+    /// Parses the action from the `on_enter` metadata.
+    ///
+    /// # Panics
+    /// If the action is not a variant of `A`.
+    pub fn from_metadata(on_enter: &str) -> Self {
+        let action = A::from_str(on_enter).unwrap_or_else(|_| {
+            panic!(
+                "Trigger on_enter '{on_enter}' is not a variant of {}",
+                std::any::type_name::<A>()
+            )
+        });
+
+        Self { action, range: 0 }
+    }
+}
+
+impl<A> EnterTrigger<A> {
+    /// Emit the action when the player enters this trigger.
+    pub fn new(action: A) -> Self {
+        Self { action, range: 0 }
+    }
+
+    /// Covers all squares at most this many squares away from the trigger.
+    ///
+    /// # Panics
+    /// If the range is negative.
+    /// Such a trigger would cover no squares and never fire.
+    pub fn with_range(mut self, squares: i32) -> Self {
+        assert!(squares >= 0, "Trigger range {squares} mustn't be negative");
+        self.range = squares;
+        self
+    }
+
+    /// The trigger is at the given world position.
+    /// The squares are those of the given layout, see [`TileMap::layout`].
+    fn covers(
+        &self,
+        layout: &SquareLayout,
+        trigger_at: Vec2,
+        area: Option<&SceneArea>,
+        square: Square,
+    ) -> bool {
+        if let Some(SceneArea(area)) = area {
+            let at = layout.square_to_world_pos(square) - trigger_at;
+            area.iter().any(|rect| rect.contains(at))
+        } else {
+            layout
+                .world_pos_to_square(trigger_at)
+                .chebyshev_distance(square)
+                <= self.range
+        }
+    }
+}
+
+/// Sends the action of each [`EnterTrigger`] that the player just entered.
+pub fn fire_on_enter<A: Event + Clone>(
+    tilemap: Res<TileMap>,
+    mut stepped: EventReader<ActorSteppedEvent>,
+    mut actions: EventWriter<A>,
+
+    player: Query<(), With<Player>>,
+    triggers: Query<(&EnterTrigger<A>, &GlobalTransform, Option<&SceneArea>)>,
+) {
+    let layout = tilemap.layout();
+    for ActorSteppedEvent { from, to, .. } in
+        stepped.read().filter(|event| player.contains(event.entity))
+    {
+        for (trigger, transform, area) in triggers.iter() {
+            let trigger_at = transform.translation().truncate();
+
+            if trigger.covers(layout, trigger_at, area, *to)
+                && !trigger.covers(layout, trigger_at, area, *from)
+            {
+                trace!("Player entered trigger at {trigger_at} onto {to}");
+                actions.send(trigger.action.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_grid_squared::sq;
+
+    use super::*;
+    use crate::top_down::{layout::LAYOUT, TopDownAction};
+
+    #[test]
+    fn it_fires_action_only_when_entering_trigger() {
+        let mut w = World::default();
+        w.init_resource::<TileMap>();
+        w.init_resource::<Events<ActorSteppedEvent>>();
+        w.init_resource::<Events<TopDownAction>>();
+        let fire = w.register_system(fire_on_enter::<TopDownAction>);
+
+        let player = w.spawn(Player).id();
+        let npc = w.spawn_empty().id();
+        w.spawn((
+            EnterTrigger::<TopDownAction>::from_metadata("Sleep").with_range(1),
+            GlobalTransform::from_translation(
+                LAYOUT.square_to_world_pos(sq(5, 5)).extend(0.0),
+            ),
+        ));
+
+        let step = |w: &mut World, entity, from, to| {
+            w.send_event(ActorSteppedEvent {
+                entity,
+                from,
+                to,
+                kind: None,
+            });
+            w.run_system(fire).unwrap();
+            w.resource_mut::<Events<TopDownAction>>()
+                .drain()
+                .collect::<Vec<_>>()
+        };
+
+        // outside of range
+        assert!(step(&mut w, player, sq(1, 1), sq(2, 2)).is_empty());
+        // NPCs don't trigger it
+        assert!(step(&mut w, npc, sq(3, 3), sq(4, 4)).is_empty());
+        assert!(matches!(
+            step(&mut w, player, sq(3, 3), sq(4, 4)).as_slice(),
+            [TopDownAction::Sleep]
+        ));
+        // moving within the trigger
+        assert!(step(&mut w, player, sq(4, 4), sq(5, 5)).is_empty());
+        // leaving and entering again
+        assert!(step(&mut w, player, sq(6, 6), sq(7, 7)).is_empty());
+        assert!(matches!(
+            step(&mut w, player, sq(7, 7), sq(6, 6)).as_slice(),
+            [TopDownAction::Sleep]
+        ));
+    }
+
+    #[test]
+    fn it_fires_action_when_entering_area() {
+        // the squares of this map are larger than those of the default one
+        let layout = SquareLayout {
+            square_size: LAYOUT.square_size * 2.0,
+            origin: vec2(-4.0, 0.0),
+        };
+        let mut w = World::default();
+        w.insert_resource(TileMap::default().with_layout(layout));
+        w.init_resource::<Events<ActorSteppedEvent>>();
+        w.init_resource::<Events<TopDownAction>>();
+        let fire = w.register_system(fire_on_enter::<TopDownAction>);
+
+        let player = w.spawn(Player).id();
+        let at = layout.square_to_world_pos(sq(5, 5));
+        // covers a row of squares to the right of the trigger
+        let area = Rect::from_corners(
+            vec2(-0.5, -0.5) * layout.square(),
+            vec2(3.5, 0.5) * layout.square(),
+        );
+        // and the square two rows above the trigger
+        let above = Rect::from_corners(
+            vec2(-0.5, 1.5) * layout.square(),
+            vec2(0.5, 2.5) * layout.square(),
+        );
+        w.spawn((
+            EnterTrigger::<TopDownAction>::from_metadata("Sleep"),
+            SceneArea(vec![area, above]),
+            GlobalTransform::from_translation(at.extend(0.0)),
+        ));
+
+        let mut step = |from, to| {
+            w.send_event(ActorSteppedEvent {
+                entity: player,
+                from,
+                to,
+                kind: None,
+            });
+            w.run_system(fire).unwrap();
+            w.resource_mut::<Events<TopDownAction>>()
+                .drain()
+                .collect::<Vec<_>>()
+        };
+
+        // next to the area
+        assert!(step(sq(4, 4), sq(5, 4)).is_empty());
+        assert!(matches!(
+            step(sq(7, 4), sq(7, 5)).as_slice(),
+            [TopDownAction::Sleep]
+        ));
+        // moving within the area
+        assert!(step(sq(7, 5), sq(6, 5)).is_empty());
+        // beyond the area
+        assert!(step(sq(8, 4), sq(9, 5)).is_empty());
+        // between the rectangles
+        assert!(step(sq(8, 6), sq(7, 6)).is_empty());
+        assert!(matches!(
+            step(sq(6, 7), sq(5, 7)).as_slice(),
+            [TopDownAction::Sleep]
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Trigger on_enter 'SleepTight' is not a variant")]
+    fn it_panics_on_unknown_action() {
+        EnterTrigger::<TopDownAction>::from_metadata("SleepTight");
+    }
+
+    #[test]
+    #[should_panic(expected = "Trigger range -1 mustn't be negative")]
+    fn it_panics_on_negative_range() {
+        EnterTrigger::new(TopDownAction::Sleep).with_range(-1);
+    }
+}
@@ -0,0 +1,241 @@
+//! Which squares of a scene the player has seen, e.g. for the
+//! [`super::minimap`].
+//!
+//! While the player walks around a top down scene, [`reveal`] adds the
+//! squares around them that they have line of sight to into [`Explored`].
+//! The explored squares are saved into the [`GlobalStore`] per scene when the
+//! scene is left and loaded back the next time the scene is entered.
+
+use bevy::{prelude::*, utils::HashSet};
+use bevy_grid_squared::{sq, Square};
+use common_ext::QueryExt;
+use common_store::{ExploredStore, GlobalStore};
+
+use crate::{
+    top_down::{Actor, Player, TileMap},
+    WhichTopDownScene,
+};
+
+/// How many squares away from the player the squares are revealed by
+/// default.
+pub const REVEAL_RADIUS: i32 = 8;
+
+/// Squares of the current scene that the player has seen.
+///
+/// Present while in a top down scene.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct Explored {
+    /// How many squares away from the player the squares are revealed.
+    /// Walls block the sight, see [`TileMap::has_line_of_sight`].
+    pub radius: i32,
+    squares: HashSet<Square>,
+    /// Nothing new can be seen until the player moves from this square.
+    revealed_from: Option<Square>,
+}
+
+impl Explored {
+    /// Whether the player has seen the square.
+    pub fn contains(&self, square: Square) -> bool {
+        self.squares.contains(&square)
+    }
+
+    /// All explored squares in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = Square> + '_ {
+        self.squares.iter().copied()
+    }
+
+    /// How many squares have been explored.
+    pub fn len(&self) -> usize {
+        self.squares.len()
+    }
+
+    /// Whether no square has been explored yet.
+    pub fn is_empty(&self) -> bool {
+        self.squares.is_empty()
+    }
+
+    /// Adds the squares within the radius that can be seen from the square.
+    pub(crate) fn reveal_around(&mut self, tilemap: &TileMap, from: Square) {
+        let r = self.radius;
+        for y in -r..=r {
+            for x in -r..=r {
+                let square = sq(from.x + x, from.y + y);
+                if x * x + y * y <= r * r
+                    && tilemap.contains(square)
+                    && tilemap.has_line_of_sight(from, square)
+                {
+                    self.squares.insert(square);
+                }
+            }
+        }
+
+        self.revealed_from = Some(from);
+    }
+
+    /// See [`ExploredStore::explored_squares`] for the format.
+    fn to_rows(&self) -> Vec<(i32, i32, u32)> {
+        let mut squares: Vec<_> = self
+            .squares
+            .iter()
+            .map(|square| (square.y, square.x))
+            .collect();
+        squares.sort_unstable();
+
+        let mut rows: Vec<(i32, i32, u32)> = Vec::new();
+        for (y, x) in squares {
+            match rows.last_mut() {
+                Some((row_y, first_x, count))
+                    if *row_y == y && *first_x + *count as i32 == x =>
+                {
+                    *count += 1;
+                }
+                _ => rows.push((y, x, 1)),
+            }
+        }
+
+        rows
+    }
+
+    /// Inverse of [`Explored::to_rows`].
+    fn from_rows(rows: Vec<(i32, i32, u32)>) -> Self {
+        Self {
+            squares: rows
+                .into_iter()
+                .flat_map(|(y, first_x, count)| {
+                    (0..count as i32).map(move |i| sq(first_x + i, y))
+                })
+                .collect(),
+            ..default()
+        }
+    }
+}
+
+/// Runs when the scene is loading.
+pub fn load(
+    mut cmd: Commands,
+    store: Res<GlobalStore>,
+    scene: Res<State<WhichTopDownScene>>,
+) {
+    let rows = store
+        .explored_squares(scene.get())
+        .get()
+        .unwrap_or_default();
+    cmd.insert_resource(Explored::from_rows(rows));
+}
+
+/// Runs when the scene is left.
+pub fn save(
+    mut cmd: Commands,
+    store: Res<GlobalStore>,
+    scene: Res<State<WhichTopDownScene>>,
+    explored: Option<Res<Explored>>,
+) {
+    let Some(explored) = explored else {
+        return;
+    };
+
+    trace!(
+        "Saving {} explored squares of {}",
+        explored.len(),
+        scene.get()
+    );
+    store.explored_squares(scene.get()).set(explored.to_rows());
+    cmd.remove_resource::<Explored>();
+}
+
+/// Adds the squares around the player into [`Explored`] whenever they move.
+pub fn reveal(
+    tilemap: Res<TileMap>,
+    mut explored: ResMut<Explored>,
+
+    player: Query<&Actor, With<Player>>,
+) {
+    let Some(player) = player.get_single_or_none() else {
+        return;
+    };
+
+    let at = player.current_square();
+    if explored.revealed_from != Some(at) {
+        explored.reveal_around(&tilemap, at);
+    }
+}
+
+impl Default for Explored {
+    fn default() -> Self {
+        Self {
+            radius: REVEAL_RADIUS,
+            squares: default(),
+            revealed_from: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::top_down::{
+        actor::test_utils::{app_with_assets, spawn_player},
+        TileKind,
+    };
+
+    #[test]
+    fn it_reveals_squares_in_sight_as_player_walks() {
+        let mut app = app_with_assets();
+        let w = app.world_mut();
+        let mut tilemap = TileMap::default();
+        tilemap.add_tile_to_first_empty_layer(sq(1, 0), TileKind::Wall);
+        w.insert_resource(tilemap);
+        w.insert_resource(Explored {
+            radius: 2,
+            ..default()
+        });
+        let reveal = w.register_system(reveal);
+
+        let player = spawn_player(w, |builder| {
+            builder.initial_square(sq(0, 0));
+        });
+
+        w.run_system(reveal).unwrap();
+        let explored = w.resource::<Explored>();
+        // a circle of 13 squares without the one behind the wall
+        assert_eq!(12, explored.len());
+        assert!(explored.contains(sq(1, 0)), "the wall itself is seen");
+        assert!(!explored.contains(sq(2, 0)));
+        assert!(explored.contains(sq(-2, 0)));
+        assert!(explored.contains(sq(1, 1)));
+        assert!(!explored.contains(sq(2, 1)));
+
+        w.get_mut::<Actor>(player).unwrap().walking_from = sq(0, 3);
+        w.run_system(reveal).unwrap();
+        let explored = w.resource::<Explored>();
+        // another circle that overlaps the first one in 2 squares
+        assert_eq!(12 + 13 - 2, explored.len());
+        assert!(explored.contains(sq(0, 5)));
+        assert!(!explored.contains(sq(2, 0)));
+    }
+
+    #[test]
+    fn it_round_trips_explored_squares_through_store() {
+        let mut w = World::default();
+        w.insert_resource(GlobalStore::new());
+        w.insert_resource(State::new(WhichTopDownScene::Downtown));
+
+        let mut explored = Explored::default();
+        explored.reveal_around(&TileMap::default(), sq(-3, 7));
+        explored.squares.insert(sq(100, -100));
+        let squares = explored.squares.clone();
+        // a row per y of the circle and one for the lone square
+        assert_eq!(2 * REVEAL_RADIUS as usize + 2, explored.to_rows().len());
+        w.insert_resource(explored);
+
+        w.run_system_once(save);
+        assert!(!w.contains_resource::<Explored>());
+
+        w.run_system_once(load);
+        assert_eq!(squares, w.resource::<Explored>().squares);
+        assert_eq!(None, w.resource::<Explored>().revealed_from);
+    }
+}
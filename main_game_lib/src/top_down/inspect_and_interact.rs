@@ -16,24 +16,54 @@
 //! with something in that zone.
 //! The zone is represented by a tile kind.
 //! See the [`ZoneToInspectLabelEntity`] resource that simplifies this pattern.
+//!
+//! Another pattern is an object the player can examine, e.g. a bookshelf.
+//! Labels in the [`InspectLabelCategory::Object`] category become ready for
+//! interaction when the player is within their range.
+//! If the label has an object identity, see [`InspectLabel::with_object`],
+//! interacting with it also emits [`InteractedWithObjectEvent`].
+//! That way scenes can handle all objects uniformly.
+//!
+//! # Outlines
+//! While its label is shown, the entity is [`Outlined`] in the color of the
+//! label's category.
+//! Only sprites get an outline, so put the label on the sprite entity:
+//!
+//! ```rust,ignore
+//! cmd.spawn((
+//!     SpriteBundle {
+//!         texture: asset_server.load(assets::BOOKSHELF),
+//!         ..default()
+//!     },
+//!     InspectLabelCategory::Default.into_label("Bookshelf"),
+//! ));
+//! ```
 
 use std::{borrow::Cow, time::Duration};
 
 use bevy::{color::palettes::css::ORANGE, prelude::*, utils::HashMap};
+use bevy_grid_squared::SquareLayout;
 use common_action::{ActionState, GlobalAction};
 use common_ext::QueryExt;
 use common_store::{GlobalStore, InspectAbilityStore};
 use common_visuals::{
-    camera::PIXEL_ZOOM, BeginInterpolationEvent, ColorInterpolation,
+    camera::PIXEL_ZOOM, outline::Outlined, BeginInterpolationEvent,
+    ColorInterpolation,
 };
 use lazy_static::lazy_static;
 use strum::EnumString;
 
 use super::actor::player::TakeAwayPlayerControl;
-use crate::top_down::{ActorMovementEvent, Player, TileKind};
+use crate::top_down::{ActorMovementEvent, Player, TileKind, TileMap};
+
+/// Unless [`InspectLabel::range`] says otherwise, objects are ready for
+/// interaction when the player is at most this many squares away.
+const OBJECT_INTERACTION_RANGE: i32 = 1;
 
 /// Useful for error labels.
 pub const LIGHT_RED: Color = Color::srgb(1.0, 0.7, 0.7);
+/// The color of object labels.
+const LIGHT_BLUE: Color = Color::srgb(0.7, 0.85, 1.0);
 
 /// The label's bg is a rect with a half transparent color.
 const BG_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.65);
@@ -78,10 +108,31 @@ impl<T: Event + Clone> ActionEvent for T {
 pub struct InspectLabel {
     display: Cow<'static, str>,
     category: InspectLabelCategory,
+    /// How many squares away from the player the label is in reach.
+    /// Measured in steps, see
+    /// [`bevy_grid_squared::Square::chebyshev_distance`].
+    ///
+    /// If not set, the label is shown within the radius given by the
+    /// category and NPCs and objects are ready for interaction only when
+    /// close by.
+    range: Option<i32>,
+    /// Identifies the object in [`InteractedWithObjectEvent`].
+    object: Option<Cow<'static, str>>,
     #[reflect(ignore)]
     emit_event_on_interacted: Option<Box<dyn ActionEvent>>,
 }
 
+/// Emitted by the [`interact`] system when the player interacts with a label
+/// that has an object identity.
+/// Sent alongside the label's own action event, if any.
+#[derive(Event, Reflect, Clone, Debug, PartialEq, Eq)]
+pub struct InteractedWithObjectEvent {
+    /// See [`InspectLabel::with_object`].
+    pub object: Cow<'static, str>,
+    /// The entity with the [`InspectLabel`] component.
+    pub entity: Entity,
+}
+
 /// Present in those entities with [`InspectLabel`] that have their label
 /// currently displayed.
 #[derive(Component, Reflect)]
@@ -120,6 +171,9 @@ pub enum InspectLabelCategory {
     Default,
     /// NPCs have a larger radius of visibility.
     Npc,
+    /// Things the player can examine, e.g. a bookshelf.
+    /// Ready for interaction when the player is close by.
+    Object,
 }
 
 /// The text of the label.
@@ -154,8 +208,17 @@ impl ZoneToInspectLabelEntity {
     pub fn get(&self, kind: impl Into<TileKind>) -> Option<&Entity> {
         self.map.get(&kind.into())
     }
+
+    /// Whether the entity is mapped to any zone.
+    pub fn contains_entity(&self, entity: Entity) -> bool {
+        self.map.values().any(|mapped| *mapped == entity)
+    }
 }
 
+/// Inserts or removes [`ReadyForInteraction`] on labels that are mapped to a
+/// zone when the player enters or leaves the zone.
+///
+/// Run this only if there are [`ActorMovementEvent`]s.
 pub(crate) fn match_interact_label_with_action_event(
     mut cmd: Commands,
     mut events: EventReader<ActorMovementEvent>,
@@ -183,6 +246,54 @@ pub(crate) fn match_interact_label_with_action_event(
     }
 }
 
+/// Labels in the [`InspectLabelCategory::Object`] category that aren't mapped
+/// to any zone are ready for interaction while the player is within their
+/// range.
+///
+/// Only checks the labels whose label or transform changed since the last
+/// run, or all of them if the player moved.
+pub(crate) fn mark_objects_in_reach(
+    mut cmd: Commands,
+    tilemap: Res<TileMap>,
+    zone_to_inspect_label_entity: Res<ZoneToInspectLabelEntity>,
+
+    player: Query<Ref<GlobalTransform>, With<Player>>,
+    objects: Query<(
+        Entity,
+        Ref<InspectLabel>,
+        Ref<GlobalTransform>,
+        Has<ReadyForInteraction>,
+    )>,
+) {
+    let Some(player) = player.get_single_or_none() else {
+        return;
+    };
+    let player_moved = player.is_changed();
+    let player = player.translation().truncate();
+
+    for (entity, label, transform, is_ready) in objects.iter() {
+        if !player_moved && !label.is_changed() && !transform.is_changed() {
+            continue;
+        }
+        if !matches!(label.category, InspectLabelCategory::Object)
+            || zone_to_inspect_label_entity.contains_entity(entity)
+        {
+            continue;
+        }
+
+        let in_reach = label.is_in_interaction_range(
+            tilemap.layout(),
+            player,
+            transform.translation().truncate(),
+        );
+        if in_reach && !is_ready {
+            cmd.entity(entity).insert(ReadyForInteraction);
+        } else if !in_reach && is_ready {
+            cmd.entity(entity).remove::<ReadyForInteraction>();
+        }
+    }
+}
+
 /// System in this set consumes [`ChangeHighlightedInspectLabelEvent`]s.
 #[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ChangeHighlightedInspectLabelEventConsumer;
@@ -316,7 +427,7 @@ pub(crate) fn highlight_what_would_be_interacted_with(
         // never be interacted with
         //
         // the system [`interact`] assumes on this condition
-        .filter(|(_, label, ..)| label.emit_event_on_interacted.is_some())
+        .filter(|(_, label, ..)| label.is_interactable())
         .map(|(entity, label, displayed, transform)| {
             let distance = transform.translation().truncate().distance(player);
             (entity, label, displayed, distance)
@@ -368,23 +479,30 @@ pub(crate) fn highlight_what_would_be_interacted_with(
         .insert(displayed);
 }
 
-/// Any logic that listens to [`ActionEvent`]s should be ordered _after_ this.
+/// Any logic that listens to [`ActionEvent`]s or
+/// [`InteractedWithObjectEvent`]s should be ordered _after_ this.
 pub fn interact(
     mut cmd: Commands,
+    mut interacted_with_object: EventWriter<InteractedWithObjectEvent>,
 
-    label: Query<&InspectLabel, With<HighlightedForInteraction>>,
+    label: Query<(Entity, &InspectLabel), With<HighlightedForInteraction>>,
 ) {
-    let Some(InspectLabel {
-        // this will always be Some because we only insert the component
-        // HighlightedForInteraction to inspect labels with an event
-        emit_event_on_interacted: Some(event),
-        ..
-    }) = label.get_single_or_none()
-    else {
+    // we only insert the component HighlightedForInteraction to inspect labels
+    // with an event or an object identity
+    let Some((entity, label)) = label.get_single_or_none() else {
         return;
     };
 
-    event.send_deferred(&mut cmd);
+    if let Some(object) = &label.object {
+        interacted_with_object.send(InteractedWithObjectEvent {
+            object: object.clone(),
+            entity,
+        });
+    }
+
+    if let Some(event) = &label.emit_event_on_interacted {
+        event.send_deferred(&mut cmd);
+    }
 }
 
 /// Run this when action [`GlobalAction::Inspect`] is pressed.
@@ -393,6 +511,7 @@ pub(crate) fn show_all_in_vicinity(
     mut cmd: Commands,
     store: Res<GlobalStore>,
     asset_server: Res<AssetServer>,
+    tilemap: Res<TileMap>,
     mut begin_interpolation: EventWriter<BeginInterpolationEvent>,
 
     player: Query<&GlobalTransform, With<Player>>,
@@ -416,9 +535,11 @@ pub(crate) fn show_all_in_vicinity(
     {
         store.mark_as_seen(&label.display);
 
-        let distance = player.distance(position.translation().truncate());
-        let should_be_shown = distance <= label.category.max_distance()
-            || ready_for_interaction.is_some();
+        let should_be_shown = label.is_in_range(
+            tilemap.layout(),
+            player,
+            position.translation().truncate(),
+        ) || ready_for_interaction.is_some();
 
         match (should_be_shown, displayed) {
             // should not be shown and it's not, do nothing
@@ -427,6 +548,7 @@ pub(crate) fn show_all_in_vicinity(
             // should be shown, but is scheduled to be hidden, cancel that
             (true, Some(mut displayed)) if displayed.being_hidden => {
                 displayed.cancel_hide(&mut cmd, &mut texts, &mut bgs);
+                cmd.entity(entity).insert(label.outline());
             }
             // should be shown and is, we don't have to do anything here
             (true, Some(_)) => {}
@@ -436,6 +558,7 @@ pub(crate) fn show_all_in_vicinity(
             // should not be shown and it is, hide it
             (false, Some(mut displayed)) => {
                 displayed.schedule_hide(&mut begin_interpolation, entity);
+                cmd.entity(entity).remove::<Outlined>();
             }
 
             // should be shown and it's not, show it
@@ -449,7 +572,7 @@ pub(crate) fn show_all_in_vicinity(
                 cmd.entity(entity)
                     .add_child(displayed.bg)
                     .add_child(displayed.text)
-                    .insert(displayed);
+                    .insert((displayed, label.outline()));
             }
         }
     }
@@ -542,7 +665,9 @@ fn spawn_label_bg_and_text(
 
 /// Run this when action [`GlobalAction::Inspect`] was just released.
 /// It schedules removal of all labels by interpolating their color to none.
+/// The outlines are removed right away.
 pub(crate) fn schedule_hide_all(
+    mut cmd: Commands,
     mut begin_interpolation: EventWriter<BeginInterpolationEvent>,
 
     mut inspectable_objects: Query<
@@ -552,6 +677,7 @@ pub(crate) fn schedule_hide_all(
 ) {
     for (entity, mut displayed) in inspectable_objects.iter_mut() {
         displayed.schedule_hide(&mut begin_interpolation, entity);
+        cmd.entity(entity).remove::<Outlined>();
     }
 }
 
@@ -624,6 +750,8 @@ impl InspectLabelCategory {
         InspectLabel {
             category: self,
             display: label.into(),
+            range: None,
+            object: None,
             emit_event_on_interacted: None,
         }
     }
@@ -643,6 +771,79 @@ impl InspectLabel {
     pub fn set_emit_event_on_interacted(&mut self, event: impl ActionEvent) {
         self.emit_event_on_interacted = Some(Box::new(event));
     }
+
+    /// Give the label an object identity, e.g. "bookshelf".
+    /// Interacting with the label then emits [`InteractedWithObjectEvent`].
+    pub fn with_object(mut self, object: impl Into<Cow<'static, str>>) -> Self {
+        self.object = Some(object.into());
+        self
+    }
+
+    /// See [`InspectLabel::with_object`].
+    pub fn set_object(&mut self, object: impl Into<Cow<'static, str>>) {
+        self.object = Some(object.into());
+    }
+
+    /// The object identity, if set.
+    pub fn object(&self) -> Option<&str> {
+        self.object.as_deref()
+    }
+
+    /// Overwrite the reach of the label given by its category.
+    /// See [`InspectLabel::range`].
+    pub fn with_range(mut self, squares: i32) -> Self {
+        debug_assert!(squares >= 0, "Range must not be negative");
+        self.range = Some(squares);
+        self
+    }
+
+    /// How many squares away from the player the label is in reach, if set.
+    pub fn range(&self) -> Option<i32> {
+        self.range
+    }
+
+    /// Whether the label should be shown when the player inspects the world.
+    /// The range is in squares of the given layout, see [`TileMap::layout`].
+    fn is_in_range(
+        &self,
+        layout: &SquareLayout,
+        player: Vec2,
+        position: Vec2,
+    ) -> bool {
+        match self.range {
+            Some(range) => {
+                layout
+                    .world_pos_to_square(player)
+                    .chebyshev_distance(layout.world_pos_to_square(position))
+                    <= range
+            }
+            None => player.distance(position) <= self.category.max_distance(),
+        }
+    }
+
+    /// Whether an object can be interacted with from the player's position.
+    /// The range is in squares of the given layout, see [`TileMap::layout`].
+    fn is_in_interaction_range(
+        &self,
+        layout: &SquareLayout,
+        player: Vec2,
+        position: Vec2,
+    ) -> bool {
+        layout
+            .world_pos_to_square(player)
+            .chebyshev_distance(layout.world_pos_to_square(position))
+            <= self.range.unwrap_or(OBJECT_INTERACTION_RANGE)
+    }
+
+    /// Whether interacting with the label does anything.
+    fn is_interactable(&self) -> bool {
+        self.emit_event_on_interacted.is_some() || self.object.is_some()
+    }
+
+    /// The outline of the labeled sprite while the label is shown.
+    fn outline(&self) -> Outlined {
+        Outlined::new(self.category.color())
+    }
 }
 
 impl InspectLabelCategory {
@@ -650,6 +851,7 @@ impl InspectLabelCategory {
         match self {
             InspectLabelCategory::Default => 125.0,
             InspectLabelCategory::Npc => 175.0,
+            InspectLabelCategory::Object => 125.0,
         }
     }
 
@@ -657,6 +859,7 @@ impl InspectLabelCategory {
         match self {
             InspectLabelCategory::Default => Color::WHITE,
             InspectLabelCategory::Npc => ORANGE.into(),
+            InspectLabelCategory::Object => LIGHT_BLUE,
         }
     }
 
@@ -665,6 +868,143 @@ impl InspectLabelCategory {
         match self {
             InspectLabelCategory::Default => 12.0,
             InspectLabelCategory::Npc => 16.0,
+            InspectLabelCategory::Object => 12.0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_grid_squared::sq;
+
+    use super::*;
+    use crate::top_down::layout::LAYOUT;
+
+    #[test]
+    fn it_shows_label_only_within_its_range() {
+        let player = LAYOUT.square_to_world_pos(sq(0, 0));
+        let object = LAYOUT.square_to_world_pos(sq(2, 1));
+
+        let adjacent = InspectLabelCategory::Default
+            .into_label("Sign")
+            .with_range(1);
+        let far_reaching = InspectLabelCategory::Default
+            .into_label("Sign")
+            .with_range(3);
+
+        assert!(!adjacent.is_in_range(&LAYOUT, player, object));
+        assert!(far_reaching.is_in_range(&LAYOUT, player, object));
+        // falls back to the category radius
+        assert!(InspectLabelCategory::Default
+            .into_label("Sign")
+            .is_in_range(&LAYOUT, player, object));
+    }
+
+    #[test]
+    fn it_measures_range_in_squares_of_the_map() {
+        let large = SquareLayout {
+            square_size: LAYOUT.square_size * 4.0,
+            origin: LAYOUT.origin,
+        };
+        let tilemap = TileMap::default().with_layout(large);
+        let layout = tilemap.layout();
+        let player = layout.square_to_world_pos(sq(0, 0));
+        let object = layout.square_to_world_pos(sq(1, 1));
+        // with the default layout, the object is four times as far
+        assert!(
+            LAYOUT
+                .world_pos_to_square(player)
+                .chebyshev_distance(LAYOUT.world_pos_to_square(object))
+                > 1
+        );
+
+        let label = InspectLabelCategory::Object
+            .into_label("Kettle")
+            .with_range(1);
+        assert!(label.is_in_range(layout, player, object));
+        assert!(label.is_in_interaction_range(layout, player, object));
+        assert!(!label.is_in_interaction_range(&LAYOUT, player, object));
+    }
+
+    #[test]
+    fn it_emits_action_event_once_when_interacting_with_object() {
+        use crate::top_down::TopDownAction;
+
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<Font>()
+            .add_event::<BeginInterpolationEvent>()
+            .add_event::<InteractedWithObjectEvent>()
+            .add_event::<TopDownAction>()
+            .add_event::<ActorMovementEvent>()
+            .init_resource::<ActionState<GlobalAction>>()
+            .init_resource::<TileMap>()
+            .init_resource::<ZoneToInspectLabelEntity>();
+        let w = app.world_mut();
+        let at = |square| {
+            GlobalTransform::from_translation(
+                LAYOUT.square_to_world_pos(square).extend(0.0),
+            )
+        };
+
+        let player = w.spawn((Player, at(sq(0, 0)))).id();
+        let kettle = w
+            .spawn((
+                at(sq(1, 1)),
+                InspectLabelCategory::Object
+                    .into_label("Kettle")
+                    .with_object("kettle")
+                    .with_emit_event_on_interacted(TopDownAction::BrewTea),
+            ))
+            .id();
+        // no object identity nor event, can never be interacted with
+        w.spawn((
+            at(sq(0, 1)),
+            InspectLabelCategory::Object.into_label("Plant"),
+        ));
+
+        let mark = w.register_system(mark_objects_in_reach);
+        let highlight =
+            w.register_system(highlight_what_would_be_interacted_with);
+        let interact = w.register_system(interact);
+
+        w.run_system(mark).unwrap();
+        w.run_system(highlight).unwrap();
+        assert!(w.get::<HighlightedForInteraction>(kettle).is_some());
+
+        w.run_system(interact).unwrap();
+        let actions: Vec<_> =
+            w.resource_mut::<Events<TopDownAction>>().drain().collect();
+        assert!(matches!(actions.as_slice(), [TopDownAction::BrewTea]));
+        let objects: Vec<_> = w
+            .resource_mut::<Events<InteractedWithObjectEvent>>()
+            .drain()
+            .collect();
+        assert_eq!(
+            vec![InteractedWithObjectEvent {
+                object: "kettle".into(),
+                entity: kettle,
+            }],
+            objects
+        );
+
+        // out of reach
+        *w.get_mut::<GlobalTransform>(player).unwrap() = at(sq(-2, 0));
+        w.run_system(mark).unwrap();
+        w.run_system(highlight).unwrap();
+        assert!(w.get::<ReadyForInteraction>(kettle).is_none());
+        assert!(w.get::<HighlightedForInteraction>(kettle).is_none());
+        w.run_system(interact).unwrap();
+        assert!(w.resource::<Events<TopDownAction>>().is_empty());
+
+        // the kettle is brought to the player who doesn't move
+        *w.get_mut::<GlobalTransform>(kettle).unwrap() = at(sq(-2, 1));
+        w.run_system(mark).unwrap();
+        assert!(w.get::<ReadyForInteraction>(kettle).is_some());
+
+        // the label's reach shrinks while nothing moves
+        w.get_mut::<InspectLabel>(kettle).unwrap().range = Some(0);
+        w.run_system(mark).unwrap();
+        assert!(w.get::<ReadyForInteraction>(kettle).is_none());
+    }
+}
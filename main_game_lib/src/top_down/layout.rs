@@ -8,6 +8,8 @@ mod build_pathfinding_graph;
 pub(crate) mod map_maker;
 pub(crate) mod systems;
 
+use std::{collections::BTreeSet, fmt};
+
 use bevy::{
     asset::Asset,
     ecs::{entity::Entity, system::Resource},
@@ -17,14 +19,17 @@ use bevy::{
     reflect::Reflect,
     utils::hashbrown::HashMap,
 };
-use bevy_grid_squared::{Square, SquareLayout};
+use bevy_grid_squared::{shapes, GridDirection, Square, SquareLayout};
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use strum::IntoEnumIterator;
 
-/// Each scene adheres to the same layout definition.
-/// That's because the amount of space the character takes in the tile grid
-/// is constant and tailored to the square size.
+use crate::top_down::actor::Passability;
+
+/// The layout of scenes unless their map says otherwise, see
+/// [`TileMap::layout`].
+/// The amount of space the character takes in the tile grid is tailored to
+/// this square size.
 pub const LAYOUT: SquareLayout = SquareLayout {
     square_size: 4.0,
     // an arbitrary origin
@@ -45,7 +50,16 @@ pub struct TileMap {
     #[serde(default)]
     zones: TileKindMetas,
     /// There can be multiple layers of tiles on a single square.
+    ///
+    /// Squares are written as compact `(x, y)` tuples to keep the map files
+    /// small.
+    #[serde(with = "bevy_grid_squared::compact::map_keys")]
     pub(crate) squares: HashMap<Square, SmallVec<[TileKind; 3]>>,
+    /// Scenes with a different art scale can set their own square size and
+    /// origin in the .ron file of the map.
+    /// If not set, [`LAYOUT`] is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    layout: Option<SquareLayout>,
 }
 
 /// You can change these in the .ron file of the map if you need larger map.
@@ -119,6 +133,12 @@ pub enum TileKind {
     Actor(Entity),
     /// Specific for a given map.
     Zone(ZoneTileKind),
+    /// Can only be stepped onto when moving in the given direction or 45° off
+    /// it, e.g. a ledge to jump down from is `OneWay(Bottom)`.
+    /// Leaving the square is not restricted.
+    ///
+    /// See [`TileMap::can_step_with_footprint`].
+    OneWay(GridDirection),
 }
 
 /// Named zones in the .ron files that are used in particular's scene logic to
@@ -204,6 +224,45 @@ pub enum TileWalkCost {
     Normal = 3,
 }
 
+/// Who is walking over the tiles.
+///
+/// An [`Entity`] converts into a walker with the default [`Passability`],
+/// so that callers that don't care about it can pass just the entity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Walker {
+    /// The walker can always step on its own [`TileKind::Actor`] tiles.
+    pub entity: Entity,
+    /// Which tiles block the walker.
+    pub passability: Passability,
+}
+
+/// Opt-in checks that run once the map of a scene is loaded.
+///
+/// A scene inserts this resource before its map loads, see
+/// [`systems::try_insert_map_as_resource`].
+/// Problems are logged, the map is inserted either way.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct ValidateTileMap {
+    /// Zones that the scene logic refers to.
+    /// Each must be painted on at least one square.
+    /// Any other zone found in the map is reported as undeclared.
+    pub zones: Vec<ZoneTileKind>,
+    /// If known, every zone with a walkable square must be reachable from
+    /// here.
+    pub player_start: Option<Square>,
+}
+
+/// Problem found by [`TileMap::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TileMapIssue {
+    /// The scene declares the zone but no square is painted with it.
+    MissingZone(ZoneTileKind),
+    /// The map has the zone painted but the scene does not declare it.
+    UndeclaredZone(ZoneTileKind),
+    /// The zone cannot be walked to from the player start.
+    UnreachableZone(ZoneTileKind),
+}
+
 /// Group zones into zones that are connected to each other.
 /// This means that if zone A overlaps, neighbors is a subset or superset
 /// of zone B, they both belong to the same group.
@@ -221,6 +280,9 @@ struct ZoneGroup(usize);
 /// It's domain in pixels is from -100_000 to 100_000.
 ///
 /// It's range is from -0.1 to 1.1.
+///
+/// The top down systems sort with [`TileMap::ysort`] instead, use this only
+/// outside of a map.
 pub fn ysort(Vec2 { y, .. }: Vec2) -> f32 {
     // it's easier to just hardcode the range than pass around values
     //
@@ -232,17 +294,101 @@ pub fn ysort(Vec2 { y, .. }: Vec2) -> f32 {
     ((max - y) / size).clamp(-0.1, 1.1)
 }
 
+/// Like [`ysort`] but the z is spread over the given bounds of a map
+/// rather than a hardcoded range, which makes it more precise for small maps.
+/// The bounds are in squares, `[left, right, bottom, top]`, as in
+/// [`TileMap::bounds`].
+///
+/// The top edge of the map is 0 and the bottom edge is 1.
+/// Positions outside of the bounds are clamped to the same range as with
+/// [`ysort`], so that they don't clash with what's rendered above the map.
+///
+/// Don't mix with [`ysort`] in the same scene, the z values are not
+/// comparable.
+/// See [`TileMap::ysort`] for the map's own bounds and layout, which is what
+/// actors, pushables and the `YSort` nodes of scenes are sorted with.
+pub fn ysort_in_bounds(
+    Vec2 { y, .. }: Vec2,
+    [_, _, bottom, top]: [i32; 4],
+    layout: &SquareLayout,
+) -> f32 {
+    let half_square = layout.square_size / 2.0;
+    let min =
+        layout.square_to_world_pos(Square::new(0, bottom)).y - half_square;
+    let max = layout.square_to_world_pos(Square::new(0, top)).y + half_square;
+    let size = max - min;
+
+    ((max - y) / size).clamp(-0.1, 1.1)
+}
+
+/// [`TileMap::ysort`] that doesn't borrow the map.
+/// Useful when the map is borrowed mutably elsewhere, e.g. while spawning a
+/// scene.
+#[derive(Clone, Copy, Debug)]
+pub struct MapYSort {
+    bounds: [i32; 4],
+    layout: SquareLayout,
+}
+
+impl MapYSort {
+    /// The z coordinate for given position, see [`ysort_in_bounds`].
+    pub fn z(&self, pos: Vec2) -> f32 {
+        ysort_in_bounds(pos, self.bounds, &self.layout)
+    }
+}
+
+impl Default for MapYSort {
+    /// Like a [`TileMap::default`].
+    fn default() -> Self {
+        Self {
+            bounds: default_bounds(),
+            layout: LAYOUT,
+        }
+    }
+}
+
+impl fmt::Display for TileMapIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingZone(zone) => {
+                write!(
+                    f,
+                    "zone {zone} is declared but not painted on any square"
+                )
+            }
+            Self::UndeclaredZone(zone) => {
+                write!(
+                    f,
+                    "zone {zone} is painted but not declared by the scene"
+                )
+            }
+            Self::UnreachableZone(zone) => {
+                write!(f, "zone {zone} cannot be reached from the player start")
+            }
+        }
+    }
+}
+
 impl TileKind {
     #[inline]
-    /// Whether the tile can be stepped on by an actor with given entity.
-    pub fn is_walkable(&self, by: Entity) -> bool {
+    /// Whether the tile can be stepped on by the walker.
+    /// Walls and other actors block it unless its [`Passability`] says
+    /// otherwise.
+    pub fn is_walkable(&self, by: impl Into<Walker>) -> bool {
+        let Walker {
+            entity: by,
+            passability,
+        } = by.into();
+
         match self {
             Self::Empty => true,
-            Self::Wall => false,
+            Self::Wall => !passability.blocked_by_walls,
             Self::Trail => true,
             Self::Actor(entity) if *entity == by => true,
-            Self::Actor(_) => false, // don't walk over others
+            // don't walk over others
+            Self::Actor(_) => !passability.blocked_by_actors,
             Self::Zone(_) => true,
+            Self::OneWay(_) => true,
         }
     }
 
@@ -252,14 +398,35 @@ impl TileKind {
     /// The higher the cost, the less likely the character will want to walk
     /// over it.
     #[inline]
-    pub fn walk_cost(&self, by: Entity) -> Option<TileWalkCost> {
+    pub fn walk_cost(&self, by: impl Into<Walker>) -> Option<TileWalkCost> {
+        let Walker {
+            entity: by,
+            passability,
+        } = by.into();
+
         match self {
-            Self::Wall => None,
+            Self::Wall if passability.blocked_by_walls => None,
+            Self::Wall => Some(TileWalkCost::Normal),
             Self::Empty => Some(TileWalkCost::Normal),
             Self::Trail => Some(TileWalkCost::Preferred),
             Self::Actor(entity) if *entity == by => Some(TileWalkCost::Normal),
-            Self::Actor(_) => None, // don't walk over others
+            // don't walk over others
+            Self::Actor(_) if passability.blocked_by_actors => None,
+            Self::Actor(_) => Some(TileWalkCost::Normal),
             Self::Zone(_) => Some(TileWalkCost::Normal),
+            Self::OneWay(_) => Some(TileWalkCost::Normal),
+        }
+    }
+
+    /// Whether the tile can be stepped onto when moving in given direction.
+    /// Only [`TileKind::OneWay`] restricts the direction.
+    #[inline]
+    pub fn can_be_entered_moving(&self, direction: GridDirection) -> bool {
+        match self {
+            Self::OneWay(allowed) => {
+                allowed.with_adjacent().contains(&direction)
+            }
+            _ => true,
         }
     }
 
@@ -282,6 +449,37 @@ impl TileKind {
 }
 
 impl TileMap {
+    /// Size in number of tiles.
+    /// `[left, right, bottom, top]`, inclusive.
+    pub fn bounds(&self) -> [i32; 4] {
+        self.bounds
+    }
+
+    /// Converts between squares and world positions in this map.
+    /// Defaults to [`LAYOUT`].
+    pub fn layout(&self) -> &SquareLayout {
+        self.layout.as_ref().unwrap_or(&LAYOUT)
+    }
+
+    /// The z coordinate for given position, see [`ysort_in_bounds`].
+    pub fn ysort(&self, pos: Vec2) -> f32 {
+        self.map_ysort().z(pos)
+    }
+
+    /// Copies what's needed to [`TileMap::ysort`].
+    pub fn map_ysort(&self) -> MapYSort {
+        MapYSort {
+            bounds: self.bounds,
+            layout: *self.layout(),
+        }
+    }
+
+    /// Overwrite the layout that the map was loaded with.
+    pub fn with_layout(mut self, layout: SquareLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
     /// Whether the given square is inside the map.
     #[inline]
     pub fn contains(&self, square: Square) -> bool {
@@ -317,7 +515,8 @@ impl TileMap {
     /// such as a wall, an object or a character.
     /// Also checks bounds.
     #[inline]
-    pub fn is_walkable(&self, square: Square, by: Entity) -> bool {
+    pub fn is_walkable(&self, square: Square, by: impl Into<Walker>) -> bool {
+        let by = by.into();
         if let Some(tiles) = self.squares.get(&square) {
             tiles.iter().all(|tile| tile.is_walkable(by))
         } else {
@@ -339,6 +538,165 @@ impl TileMap {
             .unwrap_or(false)
     }
 
+    /// Breadth-first search for the closest square with a tile that matches
+    /// the predicate, e.g. the nearest exit zone.
+    ///
+    /// The search doesn't leave the map bounds, doesn't go through walls and
+    /// honors [`TileKind::OneWay`].
+    /// Actors are ignored as they move around.
+    /// Returns `from` if it matches.
+    ///
+    /// If nothing matches, the whole reachable area is explored.
+    pub fn nearest_square_of(
+        &self,
+        from: Square,
+        predicate: impl Fn(TileKind) -> bool,
+    ) -> Option<Square> {
+        let is_passable = |from: Square, to: Square| {
+            self.contains(to)
+                && !self.is_on(to, TileKind::Wall)
+                && self.can_enter(from, to)
+        };
+
+        pathfinding::prelude::bfs(
+            &from,
+            |square: &Square| {
+                square
+                    .neighbors_no_diagonal()
+                    .chain(square.neighbors_only_diagonal())
+                    .filter(|neighbor| is_passable(*square, *neighbor))
+            },
+            |square| self.any_on(*square, &predicate),
+        )
+        .and_then(|path| path.last().copied())
+    }
+
+    /// Breadth-first search for the closest square that the actor can stand
+    /// on with its footprint.
+    /// Returns `from` if it's walkable.
+    ///
+    /// Unlike [`TileMap::nearest_square_of`], the search goes through walls
+    /// so that an actor that ended up in a wall can be pulled out of it.
+    /// The search doesn't leave the map bounds.
+    pub fn nearest_walkable_square(
+        &self,
+        from: Square,
+        by: impl Into<Walker>,
+        footprint: &[Square],
+    ) -> Option<Square> {
+        let by = by.into();
+        pathfinding::prelude::bfs(
+            &from,
+            |square: &Square| {
+                square
+                    .neighbors_no_diagonal()
+                    .chain(square.neighbors_only_diagonal())
+                    .filter(|neighbor| self.contains(*neighbor))
+            },
+            |square| self.is_walkable_with_footprint(*square, by, footprint),
+        )
+        .and_then(|path| path.last().copied())
+    }
+
+    /// Whether an actor standing on `from` can see `to`, i.e. no square on the
+    /// straight line between them has a [`TileKind::Wall`].
+    /// The `from` and `to` squares themselves are not checked.
+    ///
+    /// See [`TileMap::has_line_of_sight_unless`] to choose what blocks sight.
+    pub fn has_line_of_sight(&self, from: Square, to: Square) -> bool {
+        self.has_line_of_sight_unless(from, to, |tile| tile == TileKind::Wall)
+    }
+
+    /// Like [`TileMap::has_line_of_sight`] but any tile for which
+    /// `blocks_sight` returns `true` blocks the line.
+    ///
+    /// The result is the same regardless of which square is `from` and which
+    /// is `to`.
+    pub fn has_line_of_sight_unless(
+        &self,
+        from: Square,
+        to: Square,
+        blocks_sight: impl Fn(TileKind) -> bool,
+    ) -> bool {
+        // the line is not symmetric so we always walk it in the same direction
+        let (from, to) = if (from.x, from.y) <= (to.x, to.y) {
+            (from, to)
+        } else {
+            (to, from)
+        };
+
+        shapes::bresenham_line(from, to)
+            .filter(|square| *square != from && *square != to)
+            .all(|square| !self.any_on(square, &blocks_sight))
+    }
+
+    /// Runs the checks of [`ValidateTileMap`] against this map.
+    ///
+    /// The reachability check is a [`shapes::flood_fill`] within
+    /// [`TileMap::bounds`] that only stops at [`TileKind::Wall`].
+    /// It ignores actors and the direction of [`TileKind::OneWay`] tiles.
+    pub fn validate(&self, validation: &ValidateTileMap) -> Vec<TileMapIssue> {
+        let painted: BTreeSet<ZoneTileKind> = self
+            .squares
+            .values()
+            .flatten()
+            .filter_map(|tile| match tile {
+                TileKind::Zone(zone) => Some(*zone),
+                _ => None,
+            })
+            .collect();
+
+        let mut issues: Vec<_> = validation
+            .zones
+            .iter()
+            .filter(|zone| !painted.contains(zone))
+            .map(|zone| TileMapIssue::MissingZone(*zone))
+            .collect();
+        issues.extend(
+            painted
+                .iter()
+                .filter(|zone| !validation.zones.contains(zone))
+                .map(|zone| TileMapIssue::UndeclaredZone(*zone)),
+        );
+
+        let Some(start) = validation.player_start else {
+            return issues;
+        };
+
+        let reached: BTreeSet<ZoneTileKind> = shapes::flood_fill(
+            start,
+            |square| !self.is_on(square, TileKind::Wall),
+            self.bounds,
+        )
+        .into_iter()
+        .filter_map(|square| self.squares.get(&square))
+        .flatten()
+        .filter_map(|tile| match tile {
+            TileKind::Zone(zone) => Some(*zone),
+            _ => None,
+        })
+        .collect();
+
+        // zones painted only under walls are not meant to be walked to
+        let walkable: BTreeSet<ZoneTileKind> = self
+            .squares
+            .values()
+            .filter(|tiles| !tiles.contains(&TileKind::Wall))
+            .flatten()
+            .filter_map(|tile| match tile {
+                TileKind::Zone(zone) => Some(*zone),
+                _ => None,
+            })
+            .collect();
+        issues.extend(
+            walkable
+                .difference(&reached)
+                .map(|zone| TileMapIssue::UnreachableZone(*zone)),
+        );
+
+        issues
+    }
+
     /// Whether the predicate matches all tiles on the given square.
     /// Returns `false` if the square is out of bounds or has no tiles.
     #[inline]
@@ -471,8 +829,9 @@ impl TileMap {
     pub fn walk_cost(
         &self,
         square: Square,
-        by: Entity,
+        by: impl Into<Walker>,
     ) -> Option<TileWalkCost> {
+        let by = by.into();
         if let Some(tiles) = self.squares.get(&square) {
             // return the lowest cost unless any of the tiles is not walkable
             tiles.iter().try_fold(
@@ -488,6 +847,96 @@ impl TileMap {
         }
     }
 
+    /// Like [`TileMap::is_walkable`] but all squares covered by the
+    /// footprint must be walkable too.
+    /// The footprint is relative to the given square.
+    ///
+    /// See [`crate::top_down::actor::Footprint`].
+    pub fn is_walkable_with_footprint(
+        &self,
+        square: Square,
+        by: impl Into<Walker>,
+        footprint: &[Square],
+    ) -> bool {
+        let by = by.into();
+        self.is_walkable(square, by)
+            && footprint
+                .iter()
+                .all(|offset| self.is_walkable(square + *offset, by))
+    }
+
+    /// Whether the tiles on `to` can be entered when moving there from `from`.
+    /// Walkability is not checked, see [`TileMap::can_step_with_footprint`].
+    ///
+    /// Stepping onto the same square is always allowed.
+    #[inline]
+    pub fn can_enter(&self, from: Square, to: Square) -> bool {
+        let Some(direction) = from.direction_to(to) else {
+            return true;
+        };
+
+        self.squares.get(&to).is_none_or(|tiles| {
+            tiles
+                .iter()
+                .all(|tile| tile.can_be_entered_moving(direction))
+        })
+    }
+
+    /// Whether an actor standing on `from` can step onto `to`.
+    /// Like [`TileMap::is_walkable_with_footprint`] but also honors
+    /// [`TileKind::OneWay`] tiles.
+    ///
+    /// The direction is only checked on `to`, not on the squares covered by
+    /// the footprint.
+    /// That's where the feet of the actor land.
+    pub fn can_step_with_footprint(
+        &self,
+        from: Square,
+        to: Square,
+        by: impl Into<Walker>,
+        footprint: &[Square],
+    ) -> bool {
+        self.can_enter(from, to)
+            && self.is_walkable_with_footprint(to, by, footprint)
+    }
+
+    /// Like [`TileMap::walk_cost_with_footprint`] but returns [`None`] if the
+    /// step is not allowed, see [`TileMap::can_step_with_footprint`].
+    pub fn step_cost_with_footprint(
+        &self,
+        from: Square,
+        to: Square,
+        by: impl Into<Walker>,
+        footprint: &[Square],
+    ) -> Option<TileWalkCost> {
+        if !self.can_enter(from, to) {
+            return None;
+        }
+
+        self.walk_cost_with_footprint(to, by, footprint)
+    }
+
+    /// Like [`TileMap::walk_cost`] but all squares covered by the footprint
+    /// must be walkable too.
+    /// The footprint is relative to the given square.
+    pub fn walk_cost_with_footprint(
+        &self,
+        square: Square,
+        by: impl Into<Walker>,
+        footprint: &[Square],
+    ) -> Option<TileWalkCost> {
+        let by = by.into();
+        footprint.iter().try_fold(
+            self.walk_cost(square, by)?,
+            |highest_cost_so_far, offset| {
+                Some(
+                    self.walk_cost(square + *offset, by)?
+                        .min(highest_cost_so_far),
+                )
+            },
+        )
+    }
+
     /// Access the map of squares to tiles.
     pub fn squares(&self) -> &HashMap<Square, SmallVec<[TileKind; 3]>> {
         &self.squares
@@ -511,7 +960,21 @@ impl TileMap {
     /// minimum spanning tree between zones in the same group.
     pub fn find_partial_path(
         &self,
-        who: Entity,
+        who: impl Into<Walker>,
+        from: Square,
+        to: Square,
+    ) -> Option<Vec<Square>> {
+        self.find_partial_path_with_footprint(who, &[], from, to)
+    }
+
+    /// Like [`TileMap::find_partial_path`] but every square on the path is
+    /// such that all squares covered by the footprint are walkable.
+    ///
+    /// See [`crate::top_down::actor::Footprint`].
+    pub fn find_partial_path_with_footprint(
+        &self,
+        who: impl Into<Walker>,
+        footprint: &[Square],
         from: Square,
         to: Square,
     ) -> Option<Vec<Square>> {
@@ -520,6 +983,7 @@ impl TileMap {
         }
 
         trace!("find_partial_path {from} -> {to}");
+        let who = who.into();
 
         // 3 possible situations:
         //
@@ -584,6 +1048,7 @@ impl TileMap {
 
                         return self.astar_and_stay_in_zone(
                             who,
+                            footprint,
                             from,
                             to,
                             smallest_to_zone,
@@ -591,6 +1056,7 @@ impl TileMap {
                     } else if let Some(solution_to_better_zone) = self
                         .astar_into_strictly_better_zone(
                             who,
+                            footprint,
                             from,
                             to,
                             &sequence_of_zones,
@@ -605,20 +1071,27 @@ impl TileMap {
             } else {
                 // b)
 
-                self.astar_into_zone_group(who, from, to, to_zone_group)
+                self.astar_into_zone_group(
+                    who,
+                    footprint,
+                    from,
+                    to,
+                    to_zone_group,
+                )
             }
         } else {
             // c)
 
             warn!("expensive partial_astar {from} -> {to}");
-            self.partial_astar(who, from, to)
+            self.partial_astar(who, footprint, from, to)
         }
     }
 
     /// The default success cond is max iterations or reaching the target.
     fn partial_astar(
         &self,
-        who: Entity,
+        who: Walker,
+        footprint: &[Square],
         from: Square,
         to: Square,
     ) -> Option<Vec<Square>> {
@@ -640,14 +1113,18 @@ impl TileMap {
                 square
                     .neighbors_no_diagonal()
                     .filter_map(|neighbor| {
-                        self.walk_cost(neighbor, who)
-                            .map(|cost| (neighbor, cost as i32))
+                        self.step_cost_with_footprint(
+                            *square, neighbor, who, footprint,
+                        )
+                        .map(|cost| (neighbor, cost as i32))
                     })
                     .chain(square.neighbors_only_diagonal().filter_map(
                         // diagonal movement is costs more
                         |neighbor| {
-                            self.walk_cost(neighbor, who)
-                                .map(|cost| (neighbor, cost as i32 + 1))
+                            self.step_cost_with_footprint(
+                                *square, neighbor, who, footprint,
+                            )
+                            .map(|cost| (neighbor, cost as i32 + 1))
                         },
                     ))
             },
@@ -671,7 +1148,8 @@ impl TileMap {
 
     fn astar_and_stay_in_zone(
         &self,
-        who: Entity,
+        who: Walker,
+        footprint: &[Square],
         from: Square,
         to: Square,
         zone_to_stay_in: TileKind,
@@ -685,14 +1163,18 @@ impl TileMap {
                 square
                     .neighbors_no_diagonal()
                     .filter_map(|neighbor| {
-                        self.walk_cost(neighbor, who)
-                            .map(|cost| (neighbor, cost as i32))
+                        self.step_cost_with_footprint(
+                            *square, neighbor, who, footprint,
+                        )
+                        .map(|cost| (neighbor, cost as i32))
                     })
                     .chain(square.neighbors_only_diagonal().filter_map(
                         // diagonal movement is costs more
                         |neighbor| {
-                            self.walk_cost(neighbor, who)
-                                .map(|cost| (neighbor, cost as i32 + 1))
+                            self.step_cost_with_footprint(
+                                *square, neighbor, who, footprint,
+                            )
+                            .map(|cost| (neighbor, cost as i32 + 1))
                         },
                     ))
                     .filter(|(neighbor, _)| {
@@ -709,7 +1191,8 @@ impl TileMap {
 
     fn astar_into_strictly_better_zone(
         &self,
-        who: Entity,
+        who: Walker,
+        footprint: &[Square],
         from: Square,
         to: Square,
         allowed_zones: &[TileKind],
@@ -722,14 +1205,18 @@ impl TileMap {
                 square
                     .neighbors_no_diagonal()
                     .filter_map(|neighbor| {
-                        self.walk_cost(neighbor, who)
-                            .map(|cost| (neighbor, cost as i32))
+                        self.step_cost_with_footprint(
+                            *square, neighbor, who, footprint,
+                        )
+                        .map(|cost| (neighbor, cost as i32))
                     })
                     .chain(square.neighbors_only_diagonal().filter_map(
                         // diagonal movement is costs more
                         |neighbor| {
-                            self.walk_cost(neighbor, who)
-                                .map(|cost| (neighbor, cost as i32 + 1))
+                            self.step_cost_with_footprint(
+                                *square, neighbor, who, footprint,
+                            )
+                            .map(|cost| (neighbor, cost as i32 + 1))
                         },
                     ))
                     .filter(|(neighbor, _)| {
@@ -752,7 +1239,8 @@ impl TileMap {
 
     fn astar_into_zone_group(
         &self,
-        who: Entity,
+        who: Walker,
+        footprint: &[Square],
         from: Square,
         to: Square,
         zone_group: ZoneGroup,
@@ -762,8 +1250,10 @@ impl TileMap {
             // successors
             |square: &Square| {
                 square.neighbors_with_diagonal().filter_map(|neighbor| {
-                    self.walk_cost(neighbor, who)
-                        .map(|cost| (neighbor, cost as i32))
+                    self.step_cost_with_footprint(
+                        *square, neighbor, who, footprint,
+                    )
+                    .map(|cost| (neighbor, cost as i32))
                 })
             },
             // heuristic
@@ -816,6 +1306,22 @@ impl TileMap {
     }
 }
 
+impl Walker {
+    /// The [`Passability`] of an actor defaults if it has none.
+    pub fn new(entity: Entity, passability: Option<&Passability>) -> Self {
+        Self {
+            entity,
+            passability: passability.copied().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<Entity> for Walker {
+    fn from(entity: Entity) -> Self {
+        Self::new(entity, None)
+    }
+}
+
 impl From<ZoneTileKind> for TileKind {
     fn from(l: ZoneTileKind) -> Self {
         Self::Zone(l)
@@ -865,6 +1371,7 @@ impl Default for TileMap {
             bounds: default_bounds(),
             zones: TileKindMetas::default(),
             squares: HashMap::default(),
+            layout: None,
         }
     }
 }
@@ -877,6 +1384,31 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn it_ysorts_lower_sprites_in_front() {
+        let tilemap = TileMap {
+            bounds: [-10, 10, -5, 5],
+            ..default()
+        };
+        let z = |y: f32| tilemap.ysort(vec2(0.0, y));
+        let bottom = LAYOUT.square_to_world_pos(sq(0, -5)).y;
+        let top = LAYOUT.square_to_world_pos(sq(0, 5)).y;
+
+        // one pixel apart is enough to tell the sprites apart
+        assert!(z(10.0) > z(11.0));
+        assert!(z(bottom) > z(top));
+        assert!((0.0..=1.0).contains(&z(bottom)));
+        assert!((0.0..=1.0).contains(&z(top)));
+        // and more precise than the hardcoded range
+        assert!(
+            z(10.0) - z(11.0) > ysort(vec2(0.0, 10.0)) - ysort(vec2(0.0, 11.0))
+        );
+
+        // far outside of the map
+        assert_eq!(1.1, z(-10_000.0));
+        assert_eq!(-0.1, z(10_000.0));
+    }
+
     #[test]
     fn it_converts_tile_walk_cost_to_i32() {
         assert_eq!(TileWalkCost::Preferred as i32, 1);
@@ -928,6 +1460,79 @@ mod tests {
         assert_eq!(tilemap.walk_cost(o, Entity::PLACEHOLDER), None);
     }
 
+    #[test]
+    fn it_finds_nearest_square_of_zone() {
+        use TileKind as Tk;
+
+        let mut tilemap = TileMap::default();
+        let far_exit = sq(-6, 0);
+        let near_exit = sq(4, 0);
+        tilemap
+            .squares
+            .insert(far_exit, smallvec![Tk::Zone(ZoneTileKind::Exit)]);
+        tilemap
+            .squares
+            .insert(near_exit, smallvec![Tk::Zone(ZoneTileKind::Exit)]);
+
+        let is_exit = |tile| tile == Tk::Zone(ZoneTileKind::Exit);
+        assert_eq!(
+            Some(near_exit),
+            tilemap.nearest_square_of(sq(0, 0), is_exit)
+        );
+
+        // wall off the near exit, now the other one is closer to walk to
+        for y in -2..=2 {
+            tilemap.squares.insert(sq(2, y), smallvec![Tk::Wall]);
+        }
+        tilemap.bounds = [-10, 10, -2, 2];
+        assert_eq!(
+            Some(far_exit),
+            tilemap.nearest_square_of(sq(0, 0), is_exit)
+        );
+
+        // nothing to find
+        assert_eq!(
+            None,
+            tilemap.nearest_square_of(sq(0, 0), |tile| tile == Tk::Trail)
+        );
+    }
+
+    #[test]
+    fn it_checks_line_of_sight() {
+        use TileKind as Tk;
+
+        let mut tilemap = TileMap::default();
+        // a short vertical wall segment between the two squares
+        for y in -1..=1 {
+            tilemap.squares.insert(sq(0, y), smallvec![Tk::Wall]);
+        }
+
+        assert!(!tilemap.has_line_of_sight(sq(-3, 0), sq(3, 0)));
+        assert!(!tilemap.has_line_of_sight(sq(3, 0), sq(-3, 0)));
+        assert!(!tilemap.has_line_of_sight(sq(-3, -1), sq(3, 1)));
+        // the line goes around the end of the wall
+        assert!(tilemap.has_line_of_sight(sq(-3, 3), sq(3, 3)));
+        assert!(tilemap.has_line_of_sight(sq(-3, 0), sq(-1, 0)));
+        // looking from or at the wall itself is fine
+        assert!(tilemap.has_line_of_sight(sq(0, 0), sq(3, 0)));
+        // neighbors always see each other
+        assert!(tilemap.has_line_of_sight(sq(-1, 0), sq(0, 0)));
+
+        // with a custom predicate the wall is see-through but trail is not
+        tilemap.squares.insert(sq(-2, 3), smallvec![Tk::Trail]);
+        let is_trail = |tile| tile == Tk::Trail;
+        assert!(tilemap.has_line_of_sight_unless(
+            sq(-3, 0),
+            sq(3, 0),
+            is_trail
+        ));
+        assert!(!tilemap.has_line_of_sight_unless(
+            sq(-3, 3),
+            sq(3, 3),
+            is_trail
+        ));
+    }
+
     #[test]
     fn it_adds_tiles_to_first_empty_layer() {
         let mut tilemap = TileMap::default();
@@ -995,6 +1600,179 @@ mod tests {
         assert_eq!(tilemap.set_tile_kind(sq(1001, 0), 0, TileKind::Wall), None);
     }
 
+    #[test]
+    fn it_does_not_let_wide_actor_into_narrow_gap() {
+        let mut tilemap = TileMap::default();
+        for x in -3..=3 {
+            if x != 0 {
+                tilemap.add_tile_to_first_empty_layer(sq(x, 1), TileKind::Wall);
+            }
+        }
+        let gap = sq(0, 1);
+        // one square to the right of the anchor
+        let footprint = [sq(1, 0)];
+        let who = Entity::PLACEHOLDER;
+
+        assert!(tilemap.is_walkable(gap, who));
+        assert!(!tilemap.is_walkable_with_footprint(gap, who, &footprint));
+        assert!(tilemap.is_walkable_with_footprint(sq(0, 0), who, &footprint));
+
+        let narrow_path =
+            tilemap.find_partial_path(who, sq(0, 0), sq(0, 3)).unwrap();
+        assert!(narrow_path.contains(&gap));
+
+        let wide_path = tilemap
+            .find_partial_path_with_footprint(
+                who,
+                &footprint,
+                sq(0, 0),
+                sq(0, 3),
+            )
+            .unwrap();
+        assert!(!wide_path.contains(&gap));
+        assert!(wide_path.iter().all(|square| {
+            tilemap.is_walkable_with_footprint(*square, who, &footprint)
+        }));
+    }
+
+    #[test]
+    fn it_lets_ghost_path_through_wall() {
+        let mut tilemap = TileMap::default();
+        for y in -3..=3 {
+            tilemap.add_tile_to_first_empty_layer(sq(0, y), TileKind::Wall);
+        }
+        let wall = sq(0, 0);
+        let person = Walker::from(Entity::PLACEHOLDER);
+        let ghost = Walker {
+            entity: Entity::PLACEHOLDER,
+            passability: Passability::GHOST,
+        };
+
+        assert!(!tilemap.is_walkable(wall, person));
+        assert!(tilemap.is_walkable(wall, ghost));
+
+        let around = tilemap.find_partial_path(person, sq(-2, 0), sq(2, 0));
+        let around = around.unwrap();
+        assert!(!around.contains(&wall));
+        assert!(around
+            .iter()
+            .all(|square| square.x != 0 || square.y.abs() > 3));
+
+        let through = tilemap
+            .find_partial_path(ghost, sq(-2, 0), sq(2, 0))
+            .unwrap();
+        assert_eq!(
+            vec![sq(-2, 0), sq(-1, 0), wall, sq(1, 0), sq(2, 0)],
+            through
+        );
+    }
+
+    #[test]
+    fn it_enters_one_way_tile_only_in_its_direction() {
+        use GridDirection::*;
+
+        let mut tilemap = TileMap::default();
+        // a ledge to jump down from
+        for x in -3..=3 {
+            tilemap.add_tile_to_first_empty_layer(
+                sq(x, 0),
+                TileKind::OneWay(Bottom),
+            );
+        }
+        let ledge = sq(0, 0);
+        let who = Entity::PLACEHOLDER;
+
+        assert!(tilemap.is_walkable(ledge, who));
+        assert!(tilemap.can_step_with_footprint(
+            ledge.neighbor(Top),
+            ledge,
+            who,
+            &[]
+        ));
+        assert!(tilemap.can_step_with_footprint(
+            ledge.neighbor(TopLeft),
+            ledge,
+            who,
+            &[]
+        ));
+        assert!(!tilemap.can_step_with_footprint(
+            ledge.neighbor(Bottom),
+            ledge,
+            who,
+            &[]
+        ));
+        assert!(!tilemap.can_step_with_footprint(
+            ledge.neighbor(Left),
+            ledge,
+            who,
+            &[]
+        ));
+        assert_eq!(
+            None,
+            tilemap.step_cost_with_footprint(
+                ledge.neighbor(Bottom),
+                ledge,
+                who,
+                &[]
+            )
+        );
+
+        let down_path =
+            tilemap.find_partial_path(who, sq(0, 2), sq(0, -2)).unwrap();
+        assert!(down_path.contains(&ledge));
+
+        let up_path =
+            tilemap.find_partial_path(who, sq(0, -2), sq(0, 2)).unwrap();
+        assert!(!up_path.is_empty());
+        assert!(up_path.iter().all(|square| {
+            !tilemap.any_on(*square, |tile| matches!(tile, TileKind::OneWay(_)))
+        }));
+    }
+
+    #[test]
+    fn it_reports_orphan_and_unreachable_zones() {
+        let mut tilemap = TileMap::default();
+        // the flood fill visits every square within the bounds
+        tilemap.bounds = [-10, 10, -10, 10];
+        // a walled room with no door
+        for x in 4..=6 {
+            for y in 4..=6 {
+                if (x, y) != (5, 5) {
+                    tilemap.add_tile_to_first_empty_layer(
+                        sq(x, y),
+                        TileKind::Wall,
+                    );
+                }
+            }
+        }
+        tilemap.add_tile_to_first_empty_layer(sq(5, 5), ZoneTileKind::Tea);
+        // painted under a wall, not meant to be walked to
+        tilemap.add_tile_to_first_empty_layer(sq(4, 4), ZoneTileKind::Door);
+        tilemap.add_tile_to_first_empty_layer(sq(0, 0), ZoneTileKind::Bed);
+        tilemap.add_tile_to_first_empty_layer(sq(1, 0), ZoneTileKind::Bed);
+        tilemap.add_tile_to_first_empty_layer(sq(2, 2), ZoneTileKind::Hallway);
+
+        let mut validation = ValidateTileMap {
+            zones: vec![
+                ZoneTileKind::Bed,
+                ZoneTileKind::Tea,
+                ZoneTileKind::Elevator,
+            ],
+            player_start: None,
+        };
+        let orphans = vec![
+            TileMapIssue::MissingZone(ZoneTileKind::Elevator),
+            TileMapIssue::UndeclaredZone(ZoneTileKind::Door),
+            TileMapIssue::UndeclaredZone(ZoneTileKind::Hallway),
+        ];
+        assert_eq!(orphans, tilemap.validate(&validation));
+
+        validation.player_start = Some(sq(0, 0));
+        let mut expected = orphans;
+        expected.push(TileMapIssue::UnreachableZone(ZoneTileKind::Tea));
+        assert_eq!(expected, tilemap.validate(&validation));
+    }
+
     /// Useful to track to prevent regressions.
     #[test]
     fn it_has_const_size_of_tilekind() {
@@ -1247,6 +2025,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn it_resolves_world_positions_with_layout_of_each_map() {
+        let default_map: TileMap =
+            ron::de::from_str("(squares: {(1, 0): [Wall]})").unwrap();
+        let large_map: TileMap = ron::de::from_str(
+            "(
+                squares: {(1, 0): [Wall]},
+                layout: (square_size: 8.0, origin: (-4.0, 0.0)),
+            )",
+        )
+        .unwrap();
+
+        assert_eq!(&LAYOUT, default_map.layout());
+        assert_eq!(8.0, large_map.layout().square_size);
+
+        // the same square is at a different place in each scene
+        let wall = sq(1, 0);
+        assert_eq!(
+            LAYOUT.origin + vec2(4.0, 0.0),
+            default_map.layout().square_to_world_pos(wall)
+        );
+        assert_eq!(
+            vec2(4.0, 0.0),
+            large_map.layout().square_to_world_pos(wall)
+        );
+
+        // and the same world position is on a different square
+        let pos = vec2(20.0, 8.0);
+        assert_eq!(sq(3, 1), large_map.layout().world_pos_to_square(pos));
+        assert_eq!(
+            LAYOUT.world_pos_to_square(pos),
+            default_map.layout().world_pos_to_square(pos)
+        );
+        assert_ne!(
+            large_map.layout().world_pos_to_square(pos),
+            default_map.layout().world_pos_to_square(pos)
+        );
+        for map in [&default_map, &large_map] {
+            let layout = map.layout();
+            assert!(!map.is_walkable(
+                layout.world_pos_to_square(layout.square_to_world_pos(wall)),
+                Entity::PLACEHOLDER
+            ));
+        }
+
+        // the default layout is not written to the map files
+        let written = ron::ser::to_string(&default_map).unwrap();
+        assert!(!written.contains("layout"), "{written}");
+        let written = ron::ser::to_string(&large_map).unwrap();
+        let reloaded: TileMap = ron::de::from_str(&written).unwrap();
+        assert_eq!(large_map.layout(), reloaded.layout());
+    }
+
     fn search_for_partial_path(
         tilemap: &TileMap,
         max_partial_steps: usize,
@@ -280,7 +280,10 @@ impl ZoneTileKindGraph {
                 TileKind::Actor(_)
                 | TileKind::Trail
                 | TileKind::Empty
-                | TileKind::Wall => unreachable!("Tile {kind:?} is not a zone"),
+                | TileKind::Wall
+                | TileKind::OneWay(_) => {
+                    unreachable!("Tile {kind:?} is not a zone")
+                }
                 TileKind::Zone(zone) => zone.to_string().to_lowercase(),
             }
         }
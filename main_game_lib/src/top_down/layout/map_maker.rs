@@ -1,7 +1,7 @@
 use std::{collections::BTreeMap, fs};
 
 use bevy::{
-    color::palettes::css::{GOLD, GREEN, RED},
+    color::palettes::css::{BLUE, GOLD, GREEN, RED},
     prelude::*,
     utils::{HashMap, HashSet},
     window::PrimaryWindow,
@@ -16,7 +16,7 @@ use smallvec::SmallVec;
 
 use super::{
     build_pathfinding_graph::{GraphExt, ZoneTileKindGraph},
-    TileKind, TileMap, LAYOUT,
+    TileKind, TileMap,
 };
 use crate::{top_down::layout::TileKindMeta, WhichTopDownScene};
 
@@ -137,7 +137,8 @@ pub(crate) fn show_tiles_around_cursor(
         return;
     }
 
-    let Some(clicked_at) = cursor_to_square(&LAYOUT, windows, cameras) else {
+    let Some(clicked_at) = cursor_to_square(map.layout(), windows, cameras)
+    else {
         return;
     };
 
@@ -157,7 +158,7 @@ pub(crate) fn show_tiles_around_cursor(
             continue;
         }
 
-        let world_pos = LAYOUT.square_to_world_pos(square);
+        let world_pos = map.layout().square_to_world_pos(square);
 
         let kind = map
             .squares
@@ -172,7 +173,7 @@ pub(crate) fn show_tiles_around_cursor(
                 sprite: Sprite {
                     color: kind.color(),
                     // slightly smaller to show borders
-                    custom_size: Some(LAYOUT.square() - 0.25),
+                    custom_size: Some(map.layout().square() - 0.25),
                     ..default()
                 },
                 transform: Transform::from_translation(world_pos.extend(0.0)),
@@ -233,7 +234,8 @@ pub(crate) fn change_square_kind(
         return;
     }
 
-    let Some(clicked_at) = cursor_to_square(&LAYOUT, windows, cameras) else {
+    let Some(clicked_at) = cursor_to_square(map.layout(), windows, cameras)
+    else {
         return;
     };
 
@@ -292,7 +294,7 @@ pub(crate) fn recolor_squares(
 
     let squares_painted: Option<HashSet<_>> =
         toolbar.begin_rect_at.and_then(|begin_rect_at| {
-            let clicked_at = cursor_to_square(&LAYOUT, windows, camera)?;
+            let clicked_at = cursor_to_square(map.layout(), windows, camera)?;
 
             Some(selection_rect(begin_rect_at, clicked_at).collect())
         });
@@ -344,7 +346,10 @@ fn export_map(
                     panic!("Actor tile found in toolbar map");
                 }
                 // these are fine
-                TileKind::Wall | TileKind::Empty | TileKind::Trail => {}
+                TileKind::Wall
+                | TileKind::Empty
+                | TileKind::Trail
+                | TileKind::OneWay(_) => {}
                 // fine for now but we might want to skip some of these in the
                 // future
                 TileKind::Zone(_) => {}
@@ -362,6 +367,7 @@ fn export_map(
         bounds: map.bounds, // copy bounds info
         squares: toolbar.copy_of_map.clone(),
         zones: default(), // this field is being computed, we don't need it
+        layout: map.layout,
     });
     // metadata for pathfinding
     let zones = g.calculate_zone_tile_metadata();
@@ -373,7 +379,10 @@ fn export_map(
     #[derive(Serialize)]
     struct SortedTileMap {
         zones: SortedTileKindMetas,
+        #[serde(with = "bevy_grid_squared::compact::map_keys")]
         squares: BTreeMap<Square, SmallVec<[TileKind; 3]>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        layout: Option<SquareLayout>,
     }
     #[derive(Serialize)]
     struct SortedTileKindMetas {
@@ -383,6 +392,7 @@ fn export_map(
     let tilemap_but_sorted = SortedTileMap {
         zones: SortedTileKindMetas { inner: zones },
         squares: toolbar.copy_of_map.clone().into_iter().collect(),
+        layout: map.layout,
     };
 
     // for internal use only so who cares about unwraps and paths
@@ -428,6 +438,7 @@ impl TileKind {
             Self::Trail => Color::WHITE.with_alpha(0.25),
             Self::Actor { .. } => GOLD.with_alpha(0.25).into(),
             Self::Zone(_) => RED.with_alpha(0.25).into(),
+            Self::OneWay(_) => BLUE.with_alpha(0.25).into(),
         }
     }
 
@@ -439,6 +450,7 @@ impl TileKind {
             // no point as it's not selectable
             Self::Actor { .. } => self.color(),
             Self::Zone(_) => GREEN.with_alpha(0.25).into(),
+            Self::OneWay(_) => BLUE.with_alpha(0.5).into(),
         }
     }
 }
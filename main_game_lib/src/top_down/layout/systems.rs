@@ -3,7 +3,14 @@ use common_ext::QueryExt;
 
 #[cfg(feature = "devtools")]
 use crate::top_down::layout::map_maker;
-use crate::{top_down::TileMap, WhichTopDownScene};
+use crate::{
+    loading_watchdog::LoadingWatchdog,
+    top_down::{
+        layout::{TileMapIssue, ValidateTileMap},
+        TileMap,
+    },
+    WhichTopDownScene,
+};
 
 /// Tells the game to start loading the map.
 /// We need to keep checking for this to be done by calling
@@ -12,11 +19,15 @@ pub(crate) fn start_loading_map(
     mut cmd: Commands,
     assets: Res<AssetServer>,
     scene: Res<State<WhichTopDownScene>>,
+    watchdog: Option<ResMut<LoadingWatchdog>>,
 ) {
     let scene_name = scene.snake_case();
     let asset_path = format!("maps/{scene_name}.ron");
     debug!("Loading map {scene_name} from {asset_path}");
     let handle: Handle<TileMap> = assets.load(asset_path);
+    if let Some(mut watchdog) = watchdog {
+        watchdog.watch(&handle);
+    }
     cmd.spawn((Name::new(format!("TileMap for {scene_name}")), handle));
 }
 
@@ -26,9 +37,14 @@ pub(crate) fn start_loading_map(
 ///
 /// You should then check for the map as a resource in your systems and continue
 /// with your game.
+///
+/// If the scene opted into [`ValidateTileMap`], the loaded map is checked and
+/// any issues are logged.
 pub(crate) fn try_insert_map_as_resource(
     mut cmd: Commands,
     mut map_assets: ResMut<Assets<TileMap>>,
+    scene: Res<State<WhichTopDownScene>>,
+    validation: Option<Res<ValidateTileMap>>,
     map: Query<(Entity, &Handle<TileMap>)>,
 ) {
     let Some((entity, map)) = map.get_single_or_none() else {
@@ -43,6 +59,21 @@ pub(crate) fn try_insert_map_as_resource(
     if map_assets.get(map).is_some() {
         let loaded_map = map_assets.remove(map).unwrap(); // safe ^
 
+        if let Some(validation) = validation {
+            let scene = scene.get();
+            for issue in loaded_map.validate(&validation) {
+                match issue {
+                    TileMapIssue::UndeclaredZone(_) => {
+                        warn!("Map of {scene:?}: {issue}")
+                    }
+                    TileMapIssue::MissingZone(_)
+                    | TileMapIssue::UnreachableZone(_) => {
+                        error!("Map of {scene:?}: {issue}")
+                    }
+                }
+            }
+        }
+
         #[cfg(feature = "devtools")]
         {
             // include the loaded map in the toolbar, which will allow us to
@@ -54,6 +85,7 @@ pub(crate) fn try_insert_map_as_resource(
 
         cmd.insert_resource(loaded_map);
         cmd.init_resource::<crate::top_down::actor::ActorZoneMap>();
+        cmd.init_resource::<crate::top_down::actor::PlayerZones>();
         cmd.entity(entity).despawn_recursive();
     }
 }
@@ -61,6 +93,8 @@ pub(crate) fn try_insert_map_as_resource(
 pub(crate) fn remove_resources(mut cmd: Commands) {
     cmd.remove_resource::<TileMap>();
     cmd.remove_resource::<crate::top_down::actor::ActorZoneMap>();
+    cmd.remove_resource::<crate::top_down::actor::PlayerZones>();
+    cmd.remove_resource::<ValidateTileMap>();
 
     #[cfg(feature = "devtools")]
     {
@@ -0,0 +1,281 @@
+//! A small map in the top right corner of the screen.
+//!
+//! Each pixel of the minimap is a square of the [`TileMap`] around the
+//! player.
+//! Only the squares in [`Explored`] are drawn, walls and zones each in their
+//! own color.
+//! The player is always in the middle.
+//!
+//! Redrawing every frame would be wasteful on large maps, so the image is
+//! redrawn at most once per [`Minimap::redraw_every_nth_frame`] frames and
+//! only if the player moved or explored new squares since.
+//! Press [`GlobalAction::ToggleMinimap`] to show or hide it.
+
+use bevy::render::{
+    render_asset::RenderAssetUsages,
+    render_resource::{Extent3d, TextureDimension, TextureFormat},
+    texture::ImageSampler,
+};
+use bevy_grid_squared::{sq, Square};
+use common_ext::QueryExt;
+use common_visuals::camera::{MainCamera, PIXEL_ZOOM};
+
+use crate::{
+    prelude::*,
+    top_down::{explored::Explored, Actor, TileKind, TileMap},
+};
+
+/// How many squares wide and tall the minimap is.
+/// Odd so that the player is in the middle.
+const SIZE_IN_SQUARES: u32 = 49;
+/// How many squares are on each side of the player.
+const HALF_SIZE: i32 = SIZE_IN_SQUARES as i32 / 2;
+const MARGIN_TOP_PX: f32 = 10.0;
+const MARGIN_RIGHT_PX: f32 = 10.0;
+
+const HIDDEN_COLOR: [u8; 4] = [0, 0, 0, 0];
+const FLOOR_COLOR: [u8; 4] = [13, 14, 31, 200];
+const WALL_COLOR: [u8; 4] = [190, 190, 200, 230];
+const ZONE_COLOR: [u8; 4] = [70, 110, 170, 220];
+const PLAYER_COLOR: [u8; 4] = [255, 214, 79, 255];
+
+/// Settings of the minimap that outlive the scenes.
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct Minimap {
+    /// Toggled with [`GlobalAction::ToggleMinimap`].
+    pub hidden: bool,
+    /// The minimap is redrawn at most once per this many frames.
+    pub redraw_every_nth_frame: u32,
+    frames_since_redraw: u32,
+    /// Where the player was and how many squares were explored when the
+    /// minimap was last drawn.
+    /// Nothing changes on the minimap until either of them changes.
+    drawn_with: Option<(Square, usize)>,
+}
+
+/// The UI node that shows the minimap image.
+#[derive(Component)]
+pub(crate) struct MinimapRoot {
+    image: Handle<Image>,
+}
+
+pub(crate) fn spawn(
+    mut cmd: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut minimap: ResMut<Minimap>,
+
+    camera: Query<Entity, With<MainCamera>>,
+) {
+    // draw on the first opportunity
+    minimap.frames_since_redraw = minimap.redraw_every_nth_frame;
+    minimap.drawn_with = None;
+
+    let image = images.add(empty_image());
+    let mut root = cmd.spawn((
+        Name::new("Minimap"),
+        MinimapRoot {
+            image: image.clone(),
+        },
+        ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+
+                top: Val::Px(MARGIN_TOP_PX),
+                right: Val::Px(MARGIN_RIGHT_PX),
+
+                width: Val::Px((SIZE_IN_SQUARES as i32 * PIXEL_ZOOM) as f32),
+                height: Val::Px((SIZE_IN_SQUARES as i32 * PIXEL_ZOOM) as f32),
+
+                ..default()
+            },
+            image: UiImage::new(image),
+            visibility: visibility(minimap.hidden),
+            ..default()
+        },
+    ));
+    if let Some(camera) = camera.get_single_or_none() {
+        root.insert(TargetCamera(camera));
+    }
+}
+
+pub(crate) fn despawn(
+    mut cmd: Commands,
+
+    root: Query<Entity, With<MinimapRoot>>,
+) {
+    for entity in root.iter() {
+        cmd.entity(entity).despawn_recursive();
+    }
+}
+
+/// Shows or hides the minimap when [`GlobalAction::ToggleMinimap`] is
+/// pressed.
+pub(crate) fn toggle(
+    controls: Res<ActionState<GlobalAction>>,
+    mut minimap: ResMut<Minimap>,
+
+    mut root: Query<&mut Visibility, With<MinimapRoot>>,
+) {
+    if !controls.just_pressed(&GlobalAction::ToggleMinimap) {
+        return;
+    }
+
+    minimap.hidden = !minimap.hidden;
+    for mut root_visibility in root.iter_mut() {
+        *root_visibility = visibility(minimap.hidden);
+    }
+}
+
+/// Draws the explored squares around the player into the minimap image,
+/// see the module docs for how often.
+pub(crate) fn redraw(
+    tilemap: Res<TileMap>,
+    explored: Res<Explored>,
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+
+    player: Query<&Actor, With<Player>>,
+    root: Query<&MinimapRoot>,
+) {
+    minimap.frames_since_redraw += 1;
+    if minimap.hidden
+        || minimap.frames_since_redraw < minimap.redraw_every_nth_frame
+    {
+        return;
+    }
+    minimap.frames_since_redraw = 0;
+
+    let Some(player) = player.get_single_or_none() else {
+        return;
+    };
+    let Some(root) = root.get_single_or_none() else {
+        return;
+    };
+
+    let at = player.current_square();
+    let drawn_with = Some((at, explored.len()));
+    if minimap.drawn_with == drawn_with {
+        return;
+    }
+
+    if let Some(image) = images.get_mut(&root.image) {
+        draw(image, &tilemap, &explored, at);
+        minimap.drawn_with = drawn_with;
+    }
+}
+
+/// Overwrites the image with the squares around the player.
+/// The image must be [`SIZE_IN_SQUARES`] wide and tall.
+fn draw(image: &mut Image, tilemap: &TileMap, explored: &Explored, at: Square) {
+    for (pixel, color) in image.data.chunks_exact_mut(4).enumerate() {
+        let column = (pixel as u32 % SIZE_IN_SQUARES) as i32;
+        let row = (pixel as u32 / SIZE_IN_SQUARES) as i32;
+        // the first row is the top of the map
+        let square = sq(at.x - HALF_SIZE + column, at.y + HALF_SIZE - row);
+
+        color.copy_from_slice(&square_color(tilemap, explored, at, square));
+    }
+}
+
+fn square_color(
+    tilemap: &TileMap,
+    explored: &Explored,
+    player_at: Square,
+    square: Square,
+) -> [u8; 4] {
+    if square == player_at {
+        return PLAYER_COLOR;
+    }
+
+    if !explored.contains(square) {
+        return HIDDEN_COLOR;
+    }
+
+    let tiles = tilemap.get(square).unwrap_or_default();
+    if tiles.contains(&TileKind::Wall) {
+        WALL_COLOR
+    } else if tiles.iter().any(TileKind::is_zone) {
+        ZONE_COLOR
+    } else {
+        FLOOR_COLOR
+    }
+}
+
+fn empty_image() -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: SIZE_IN_SQUARES,
+            height: SIZE_IN_SQUARES,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &HIDDEN_COLOR,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // a pixel per square, keep them crisp
+    image.sampler = ImageSampler::nearest();
+
+    image
+}
+
+fn visibility(hidden: bool) -> Visibility {
+    if hidden {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    }
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self {
+            hidden: false,
+            redraw_every_nth_frame: 10,
+            frames_since_redraw: 0,
+            drawn_with: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_marks_player_square_on_minimap() {
+        let mut tilemap = TileMap::default();
+        tilemap.add_tile_to_first_empty_layer(sq(1, 0), TileKind::Wall);
+        let mut explored = Explored::default();
+        explored.reveal_around(&tilemap, sq(0, 0));
+        let mut image = empty_image();
+        let color_of = |image: &Image, at: Square, square: Square| {
+            let column = HALF_SIZE + square.x - at.x;
+            let row = HALF_SIZE - (square.y - at.y);
+            let pixel = (row * SIZE_IN_SQUARES as i32 + column) as usize;
+            image.data[pixel * 4..pixel * 4 + 4].to_vec()
+        };
+
+        draw(&mut image, &tilemap, &explored, sq(0, 0));
+        assert_eq!(PLAYER_COLOR.to_vec(), color_of(&image, sq(0, 0), sq(0, 0)));
+        assert_eq!(WALL_COLOR.to_vec(), color_of(&image, sq(0, 0), sq(1, 0)));
+        assert_eq!(FLOOR_COLOR.to_vec(), color_of(&image, sq(0, 0), sq(0, 1)));
+        // behind the wall
+        assert_eq!(HIDDEN_COLOR.to_vec(), color_of(&image, sq(0, 0), sq(2, 0)));
+
+        // the player moved within the explored squares
+        draw(&mut image, &tilemap, &explored, sq(0, -1));
+        assert_eq!(
+            PLAYER_COLOR.to_vec(),
+            color_of(&image, sq(0, -1), sq(0, -1))
+        );
+        assert_eq!(FLOOR_COLOR.to_vec(), color_of(&image, sq(0, -1), sq(0, 0)));
+        // the middle pixel
+        let middle = (SIZE_IN_SQUARES * SIZE_IN_SQUARES / 2) as usize;
+        assert_eq!(
+            PLAYER_COLOR.to_vec(),
+            image.data[middle * 4..middle * 4 + 4].to_vec()
+        );
+    }
+}
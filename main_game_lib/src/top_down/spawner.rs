@@ -5,7 +5,8 @@ use std::str::FromStr;
 use bevy::utils::EntityHashMap;
 use bevy_rscn::{EntityDescription, NodeName, RscnNode, TscnSpawnHooks};
 use top_down::{
-    inspect_and_interact::ZoneToInspectLabelEntity, layout::ysort,
+    environmental_objects::trigger::EnterTrigger,
+    inspect_and_interact::ZoneToInspectLabelEntity, layout::MapYSort,
     InspectLabelCategory, TopDownAction, ZoneTileKind,
 };
 
@@ -13,23 +14,28 @@ use crate::prelude::*;
 
 /// The implementation has some knowledge of top down scenes to provide
 /// default implementations for things like [`crate::top_down::InspectLabel`]
-/// that maps actions `A` to labels and Y sorting.
+/// that maps actions `A` to labels, [`EnterTrigger`] and Y sorting.
 ///
 /// Other nodes are delegated to the user implementation `T`.
 pub struct TopDownTsncSpawner<'a, T> {
     inner: &'a mut T,
     zone_to_inspect_label_entity: &'a mut ZoneToInspectLabelEntity,
+    ysort: MapYSort,
 }
 
 impl<'a, T> TopDownTsncSpawner<'a, T> {
     /// Create a new top down spawner with user implementation `T`.
+    /// The `ysort` is the [`crate::top_down::TileMap::map_ysort`] of the
+    /// scene.
     pub fn new(
+        ysort: MapYSort,
         zone_to_inspect_label_entity: &'a mut ZoneToInspectLabelEntity,
         inner: &'a mut T,
     ) -> Self {
         Self {
             inner,
             zone_to_inspect_label_entity,
+            ysort,
         }
     }
 }
@@ -59,10 +65,9 @@ where
                     .expect("YSort must be a 2D node with description");
                 if let Some(parent_description) = descriptions.get_mut(&parent)
                 {
-                    parent_description.z_index = Some(ysort(
-                        parent_description.translation
-                            + own_description.translation,
-                    ));
+                    parent_description.z_index =
+                        Some(self.ysort.z(parent_description.translation
+                            + own_description.translation));
                 }
             }
             _ => {
@@ -89,7 +94,7 @@ where
                     "Label metadata must be present on InspectLabelCategory",
                 );
 
-                let mut label = plain_node
+                let category = plain_node
                     .metadata
                     .remove("category")
                     .map(|cat| {
@@ -97,8 +102,23 @@ where
                             "category must be a valid InspectLabelCategory",
                         )
                     })
-                    .unwrap_or_default()
-                    .into_label(with_label);
+                    .unwrap_or_default();
+                let mut label = category.into_label(with_label);
+
+                // objects are identified by the name of the labeled node
+                // unless the metadata says otherwise
+                if let Some(object) = plain_node.metadata.remove("object") {
+                    label.set_object(object);
+                } else if matches!(category, InspectLabelCategory::Object) {
+                    label.set_object(parent_name.0.clone());
+                }
+
+                if let Some(range) = plain_node.metadata.remove("range") {
+                    label =
+                        label.with_range(range.parse().unwrap_or_else(|_| {
+                            panic!("InspectLabel range '{range}' not valid")
+                        }));
+                }
 
                 if let Some(action) = plain_node.metadata.remove("action") {
                     label.set_emit_event_on_interacted(
@@ -124,7 +144,29 @@ where
                 assert!(
                     plain_node.metadata.is_empty(),
                     "InspectLabel node can only have \
-                    label, category, action and zone metadata"
+                    label, category, range, object, action and zone metadata"
+                );
+            }
+            "Trigger" => {
+                let on_enter = plain_node
+                    .metadata
+                    .remove("on_enter")
+                    .expect("on_enter metadata must be present on Trigger");
+
+                let mut trigger =
+                    EnterTrigger::<TopDownAction>::from_metadata(&on_enter);
+
+                if let Some(range) = plain_node.metadata.remove("range") {
+                    trigger = trigger.with_range(range.parse().unwrap_or_else(
+                        |_| panic!("Trigger range '{range}' not valid"),
+                    ));
+                }
+
+                cmd.entity(parent_entity).insert(trigger);
+
+                assert!(
+                    plain_node.metadata.is_empty(),
+                    "Trigger node can only have on_enter and range metadata"
                 );
             }
             _ => {
@@ -138,3 +180,88 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::world::CommandQueue;
+    use bevy_rscn::EntityDescriptionMap;
+
+    use super::*;
+
+    struct NoopHooks;
+
+    impl TscnSpawnHooks for NoopHooks {
+        fn handle_2d_node(
+            &mut self,
+            _cmd: &mut Commands,
+            _descriptions: &mut EntityDescriptionMap,
+            _parent: Option<(Entity, NodeName)>,
+            _this: (Entity, NodeName),
+        ) {
+        }
+    }
+
+    #[test]
+    fn it_orders_sprites_with_ysort_child_by_y() {
+        let mut world = World::new();
+        let mut queue = CommandQueue::default();
+        let mut cmd = Commands::new(&mut queue, &world);
+
+        let mut zone_to_inspect_label_entity = default();
+        let mut inner = NoopHooks;
+        let ysort = MapYSort::default();
+        let mut spawner = TopDownTsncSpawner::new(
+            ysort,
+            &mut zone_to_inspect_label_entity,
+            &mut inner,
+        );
+
+        let mut descriptions = EntityDescriptionMap::default();
+        let mut spawn_with_ysort = |y: f32| {
+            let sprite = cmd.spawn_empty().id();
+            descriptions.insert(
+                sprite,
+                EntityDescription {
+                    translation: vec2(0.0, y),
+                    ..default()
+                },
+            );
+
+            // the YSort node is at the bottom of the sprite
+            let ysort_node = cmd.spawn_empty().id();
+            descriptions.insert(
+                ysort_node,
+                EntityDescription {
+                    translation: vec2(0.0, -10.0),
+                    ..default()
+                },
+            );
+
+            (sprite, ysort_node)
+        };
+        let (upper, upper_ysort) = spawn_with_ysort(100.0);
+        let (lower, lower_ysort) = spawn_with_ysort(50.0);
+
+        for (parent, ysort_node) in [(upper, upper_ysort), (lower, lower_ysort)]
+        {
+            spawner.handle_2d_node(
+                &mut cmd,
+                &mut descriptions,
+                Some((parent, NodeName("Sprite".to_string()))),
+                (ysort_node, NodeName("YSort".to_string())),
+            );
+        }
+        queue.apply(&mut world);
+
+        // YSort nodes are not spawned
+        assert!(!descriptions.contains_key(&upper_ysort));
+        assert!(!descriptions.contains_key(&lower_ysort));
+
+        let upper_z = descriptions[&upper].z_index.expect("z index set");
+        let lower_z = descriptions[&lower].z_index.expect("z index set");
+        // higher y is further back, hence lower z
+        assert!(upper_z < lower_z, "{upper_z} should be < {lower_z}");
+        assert_eq!(ysort.z(vec2(0.0, 90.0)), upper_z);
+        assert_eq!(ysort.z(vec2(0.0, 40.0)), lower_z);
+    }
+}
@@ -1,9 +1,22 @@
 //! Test with a basic example that I setup.
 //! Contains nested nodes, metadata properties and spritesheets.
 
-use bevy::utils::default;
+use bevy::{
+    ecs::system::RunSystemOnce,
+    math::{Rect, Vec2},
+    prelude::*,
+    utils::default,
+};
+use main_game_lib::bevy_rscn::{
+    Diagnostic, EntityDescriptionMap, In2D, LayerNode, NodeName, RscnNode,
+    Severity, SpriteFrames, SpriteTexture, TscnSpawnHooks, TscnTree,
+};
 
 const TSCN: &str = include_str!("basic.tscn");
+/// Legacy integer ids mixed with Godot 4 string ids.
+const MIXED_IDS_TSCN: &str = include_str!("mixed_ids.tscn");
+/// Two parallax layers and a canvas layer.
+const PARALLAX_TSCN: &str = include_str!("parallax.tscn");
 
 #[test]
 fn it_does_not_panic() {
@@ -37,3 +50,333 @@ fn it_does_not_panic() {
             .y
     );
 }
+
+#[test]
+fn it_reports_stray_ext_resource() {
+    let tscn = r#"[gd_scene load_steps=3 format=3 uid="uid://abc"]
+
+[ext_resource type="Texture2D" uid="uid://def" path="res://assets/used.png" id="1_used"]
+[ext_resource type="Texture2D" uid="uid://ghi" path="res://assets/stray.png" id="2_stray"]
+
+[node name="Root" type="Node2D"]
+
+[node name="Cupboard" type="Sprite2D" parent="."]
+position = Vector2(10, 20)
+texture = ExtResource("1_used")
+"#;
+
+    let (state, diagnostics) =
+        main_game_lib::bevy_rscn::parse_with_diagnostics(tscn, &default());
+
+    assert!(state.root.children.contains_key("Cupboard"));
+    assert_eq!(
+        vec![Diagnostic {
+            severity: Severity::Warning,
+            node: NodeName("Root".to_owned()),
+            message: "ext_resource '2_stray' is never used".to_owned(),
+        }],
+        diagnostics
+    );
+}
+
+#[test]
+fn it_parses_path_with_no_curve_as_empty() {
+    let tscn = r#"[gd_scene format=3 uid="uid://abc"]
+
+[node name="Root" type="Node2D"]
+
+[node name="Path" type="Path2D" parent="."]
+position = Vector2(10, 20)
+"#;
+
+    let (state, diagnostics) =
+        main_game_lib::bevy_rscn::parse_with_diagnostics(tscn, &default());
+
+    assert!(state.root.children["Path"].path_points.is_empty());
+    assert_eq!(
+        vec![Diagnostic {
+            severity: Severity::Warning,
+            node: NodeName("Path".to_owned()),
+            message: "Path2D has no curve, its path is empty".to_owned(),
+        }],
+        diagnostics
+    );
+}
+
+#[test]
+fn it_parses_hidden_nodes() {
+    let tscn = r#"[gd_scene format=3 uid="uid://abc"]
+
+[node name="Root" type="Node2D"]
+
+[node name="SecretDoor" type="Node2D" parent="."]
+visible = false
+position = Vector2(10, 20)
+
+[node name="Handle" type="Node2D" parent="SecretDoor"]
+position = Vector2(1, 2)
+"#;
+
+    let state = main_game_lib::bevy_rscn::from_tscn(tscn, &default());
+
+    let door = state.root.children.get("SecretDoor").unwrap();
+    assert!(!door.in_2d.as_ref().unwrap().visible);
+    // hidden in bevy by inheriting the visibility of the parent
+    let handle = door.children.get("Handle").unwrap();
+    assert!(handle.in_2d.as_ref().unwrap().visible);
+    assert!(state.root.in_2d.as_ref().unwrap().visible);
+}
+
+#[test]
+fn it_resolves_mixed_integer_and_string_ids() {
+    let state = main_game_lib::bevy_rscn::from_tscn(MIXED_IDS_TSCN, &default());
+
+    let sprite = |position, z_index, path: &str, animation| RscnNode {
+        in_2d: Some(In2D {
+            position,
+            z_index: Some(z_index),
+            visible: true,
+            texture: Some(SpriteTexture {
+                path: path.to_owned(),
+                color: None,
+                animation,
+                flip_horizontally: false,
+                flip_vertically: false,
+                scale: 1.0,
+            }),
+        }),
+        metadata: default(),
+        timer: None,
+        path_points: default(),
+        area: default(),
+        layer: None,
+        children: default(),
+    };
+
+    let expected = TscnTree {
+        root_node_name: NodeName("Apartment".to_owned()),
+        root: RscnNode {
+            in_2d: Some(In2D {
+                position: Vec2::ZERO,
+                z_index: None,
+                visible: true,
+                texture: None,
+            }),
+            metadata: default(),
+            timer: None,
+            path_points: default(),
+            area: default(),
+            layer: None,
+            children: [
+                (
+                    NodeName("Bg".to_owned()),
+                    sprite(Vec2::ZERO, -3.0, "apartment/bg.png", None),
+                ),
+                (
+                    NodeName("Elevator".to_owned()),
+                    sprite(
+                        Vec2::new(-201.5, -49.5),
+                        -1.0,
+                        "apartment/elevator_atlas.png",
+                        Some(SpriteFrames {
+                            should_endless_loop: true,
+                            fps: 5.0,
+                            should_autoload: false,
+                            first_index: 0,
+                            frames: vec![
+                                Rect::new(0.0, 0.0, 51.0, 57.0),
+                                Rect::new(55.0, 0.0, 106.0, 57.0),
+                            ],
+                            frame_durations: vec![],
+                            size: Vec2::new(106.0, 57.0),
+                        }),
+                    ),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        },
+    };
+
+    assert_eq!(expected, state);
+}
+
+#[test]
+fn it_scales_positions_by_pixels_per_unit() {
+    let conf = main_game_lib::bevy_rscn::Config {
+        pixels_per_unit: 2.0,
+        ..default()
+    };
+    let state = main_game_lib::bevy_rscn::from_tscn(MIXED_IDS_TSCN, &conf);
+
+    let elevator = state.root.children.get("Elevator").unwrap();
+    let in_2d = elevator.in_2d.as_ref().unwrap();
+    // Y is flipped and then halved
+    assert_eq!(Vec2::new(-100.75, -24.75), in_2d.position);
+
+    // atlas frames address the texture pixels and are left as they are
+    let animation = in_2d.texture.as_ref().unwrap().animation.as_ref().unwrap();
+    assert_eq!(
+        vec![
+            Rect::new(0.0, 0.0, 51.0, 57.0),
+            Rect::new(55.0, 0.0, 106.0, 57.0),
+        ],
+        animation.frames
+    );
+    assert_eq!(Vec2::new(106.0, 57.0), animation.size);
+}
+
+#[test]
+fn it_spawns_sprites_scaled_by_pixels_per_unit() {
+    struct NoHooks;
+
+    impl TscnSpawnHooks for NoHooks {
+        fn handle_2d_node(
+            &mut self,
+            _cmd: &mut Commands,
+            _descriptions: &mut EntityDescriptionMap,
+            _parent: Option<(Entity, NodeName)>,
+            _this: (Entity, NodeName),
+        ) {
+        }
+    }
+
+    let conf = main_game_lib::bevy_rscn::Config {
+        pixels_per_unit: 2.0,
+        ..default()
+    };
+    let mut tree =
+        Some(main_game_lib::bevy_rscn::from_tscn(MIXED_IDS_TSCN, &conf));
+
+    let mut app = App::new();
+    app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>();
+    app.world_mut().run_system_once(
+        move |mut cmd: Commands,
+              mut atlases: ResMut<Assets<TextureAtlasLayout>>,
+              asset_server: Res<AssetServer>| {
+            tree.take().unwrap().spawn_into(
+                &mut cmd,
+                &mut atlases,
+                &asset_server,
+                &mut NoHooks,
+            );
+        },
+    );
+
+    let mut transforms = app.world_mut().query::<(&Name, &Transform)>();
+    let mut transform = |name: &str| {
+        *transforms
+            .iter(app.world())
+            .find(|(n, _)| n.as_str() == name)
+            .unwrap()
+            .1
+    };
+
+    // the root has no sprite and keeps its scale
+    assert_eq!(Vec3::ONE, transform("Apartment").scale);
+    for sprite in ["Bg", "Elevator"] {
+        assert_eq!(Vec3::new(0.5, 0.5, 1.0), transform(sprite).scale);
+    }
+    assert_eq!(
+        Vec2::new(-100.75, -24.75),
+        transform("Elevator").translation.truncate()
+    );
+}
+
+#[test]
+fn it_parses_parallax_background() {
+    let conf = default();
+    let state = main_game_lib::bevy_rscn::from_tscn(PARALLAX_TSCN, &conf);
+
+    let background = state.root.children.get("Background").unwrap();
+    assert_eq!(
+        Some(&LayerNode::ParallaxBackground { layer: -100 }),
+        background.layer.as_ref()
+    );
+    let motion_scale = |name| {
+        let layer = background.children.get(name).unwrap();
+        assert!(layer.children.get("Texture").unwrap().in_2d.is_some());
+        match layer.layer {
+            Some(LayerNode::Parallax { motion_scale }) => motion_scale,
+            ref other => panic!("{name} is not a parallax layer: {other:?}"),
+        }
+    };
+    assert_eq!(Vec2::new(0.1, 0.1), motion_scale("Sky"));
+    assert_eq!(Vec2::new(0.5, 0.25), motion_scale("Hills"));
+
+    let rain = state.root.children.get("Rain").unwrap();
+    assert_eq!(Some(&LayerNode::Canvas { layer: 2 }), rain.layer.as_ref());
+    assert!(rain.in_2d.is_some());
+
+    // the factors survive writing the scene back
+    let written = state.to_tscn(&conf);
+    assert_eq!(state, main_game_lib::bevy_rscn::from_tscn(&written, &conf));
+}
+
+#[test]
+#[should_panic(
+    expected = "property 'motion_mirroring' of ParallaxLayer is not supported"
+)]
+fn it_rejects_unsupported_parallax_properties() {
+    let tscn = r#"[gd_scene format=3 uid="uid://abc"]
+
+[node name="Root" type="Node2D"]
+
+[node name="Background" type="ParallaxBackground" parent="."]
+
+[node name="Sky" type="ParallaxLayer" parent="Background"]
+motion_scale = Vector2(0.1, 0.1)
+motion_mirroring = Vector2(640, 0)
+"#;
+
+    main_game_lib::bevy_rscn::from_tscn(tscn, &default());
+}
+
+#[test]
+fn it_keeps_each_rectangle_shape_of_area() {
+    let tscn = r#"[gd_scene load_steps=3 format=3 uid="uid://abc"]
+
+[sub_resource type="RectangleShape2D" id="RectangleShape2D_a"]
+size = Vector2(20, 10)
+
+[sub_resource type="RectangleShape2D" id="RectangleShape2D_b"]
+size = Vector2(10, 10)
+
+[node name="Root" type="Node2D"]
+
+[node name="CutsceneStart" type="Area2D" parent="."]
+position = Vector2(100, 50)
+
+[node name="CollisionShape2D" type="CollisionShape2D" parent="CutsceneStart"]
+shape = SubResource("RectangleShape2D_a")
+
+[node name="CollisionShape2D2" type="CollisionShape2D" parent="CutsceneStart"]
+position = Vector2(0, -20)
+shape = SubResource("RectangleShape2D_b")
+"#;
+
+    let conf = default();
+    let state = main_game_lib::bevy_rscn::from_tscn(tscn, &conf);
+
+    let area = state.root.children.get("CutsceneStart").unwrap();
+    assert_eq!(
+        Vec2::new(100.0, -50.0),
+        area.in_2d.as_ref().unwrap().position
+    );
+    // collision shapes are not nodes of their own
+    assert!(area.children.is_empty());
+    // godot's Y is flipped, the second shape is above the first one
+    assert_eq!(
+        vec![
+            Rect::new(-10.0, -5.0, 10.0, 5.0),
+            Rect::new(-5.0, 15.0, 5.0, 25.0)
+        ],
+        area.area,
+    );
+
+    // both shapes survive writing the scene back
+    let written = state.to_tscn(&conf);
+    assert_eq!(state, main_game_lib::bevy_rscn::from_tscn(&written, &conf));
+}
@@ -79,6 +79,11 @@ pub enum ExtResourceKind {
 }
 
 /// The unique identifier of an external resource.
+///
+/// Godot 4 writes string uids such as `"1_abcde"` while older scenes use
+/// integers.
+/// Integer ids are stored in their decimal string form, see
+/// [`SpannedValue::into_resource_id`].
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct ExtResourceId(pub String);
 
@@ -89,6 +94,10 @@ pub enum SubResourceKind {
     AtlasTexture,
     /// A sprite frames resource.
     SpriteFrames,
+    /// A curve drawn with a `Path2D` node.
+    Curve2D,
+    /// A rectangle of a `CollisionShape2D` node.
+    RectangleShape2D,
     /// Catch all for any other kind of resource.
     Other(String),
 }
@@ -104,11 +113,28 @@ pub enum NodeKind {
     Sprite2D,
     /// A 2D node that is an animated sprite node.
     AnimatedSprite2D,
+    /// A node that counts down and then times out.
+    Timer,
+    /// A 2D node that holds a curve, e.g. a route for an NPC to walk.
+    Path2D,
+    /// Draws its children on a separate layer, e.g. a sky.
+    CanvasLayer,
+    /// A canvas layer that holds parallax layers.
+    ParallaxBackground,
+    /// A 2D node that moves relative to the camera by its motion scale.
+    ParallaxLayer,
+    /// A 2D node that describes a region by its collision shape children,
+    /// e.g. where a cutscene starts.
+    Area2D,
+    /// A 2D node that gives a shape to its parent `Area2D`.
+    CollisionShape2D,
     /// Catch all for any other kind of node.
     Other(String),
 }
 
 /// The unique identifier of a sub resource.
+///
+/// Same as [`ExtResourceId`], both string and integer ids are accepted.
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct SubResourceId(pub String);
 
@@ -135,6 +161,15 @@ pub enum SubResourceSectionKey {
     /// }]
     /// ```
     Animations,
+    /// e.g.
+    /// ```text
+    /// _data = {
+    ///     "points": PackedVector2Array(0, 0, 0, 0, 10, -20, 0, 0, 0, 0, 30, 5)
+    /// }
+    /// ```
+    CurveData,
+    /// e.g. `size = Vector2(32, 16)`
+    Size,
     /// Catch all for any other kind of key.
     Other(String),
 }
@@ -171,6 +206,20 @@ pub enum NodeSectionKey {
     SelfModulate,
     /// e.g. `frame_progress = 0.00329857`
     FrameProgress,
+    /// e.g. `wait_time = 3.0`
+    WaitTime,
+    /// e.g. `autostart = true`
+    Autostart,
+    /// e.g. `one_shot = true`
+    OneShot,
+    /// e.g. `curve = SubResource("Curve2D_4x7mn")`
+    Curve,
+    /// e.g. `layer = -2`
+    Layer,
+    /// e.g. `motion_scale = Vector2(0.5, 0.5)`
+    MotionScale,
+    /// e.g. `shape = SubResource("RectangleShape2D_x3j1k")`
+    Shape,
     /// Catch all for any other kind of key.
     Other(String),
 }
@@ -249,14 +298,14 @@ impl SpannedValue {
     /// Interprets the value as a "ExtResource" class.
     pub fn try_into_ext_resource(self) -> miette::Result<ExtResourceId> {
         let [id] = self.try_into_this_class_of_len("ExtResource")?;
-        let (_, id) = id.try_into_string()?;
+        let (_, id) = id.try_into_resource_id()?;
         Ok(ExtResourceId(id))
     }
 
     /// Interprets the value as a "SubResource" class.
     pub fn try_into_sub_resource(self) -> miette::Result<SubResourceId> {
         let [id] = self.try_into_this_class_of_len("SubResource")?;
-        let (_, id) = id.try_into_string()?;
+        let (_, id) = id.try_into_resource_id()?;
         Ok(SubResourceId(id))
     }
 
@@ -348,6 +397,64 @@ impl SpannedValue {
             })
             .collect()
     }
+
+    /// Interprets the value as the data of a "Curve2D" sub resource.
+    /// Value part of [SubResourceSectionKey::CurveData].
+    ///
+    /// Returns the baked points of the curve in Godot coordinates.
+    /// Each point is stored as six numbers: the in handle, the out handle and
+    /// the position.
+    /// We only support straight segments, so the handles must be zero.
+    pub fn try_into_curve2d_points(self) -> miette::Result<Vec<(f64, f64)>> {
+        let (span, mut data) = self.try_into_object()?;
+
+        let Some(points) = data.remove("points") else {
+            miette::bail! {
+                labels = vec![
+                    LabeledSpan::at(span, "in this object"),
+                ],
+                "Expected key 'points'",
+            }
+        };
+        let (span, class, numbers) = points.try_into_class()?;
+        if class != "PackedVector2Array" || numbers.len() % 6 != 0 {
+            miette::bail! {
+                labels = vec![
+                    LabeledSpan::at(span, "this class"),
+                ],
+                "Expected PackedVector2Array with 6 numbers per point, \
+                got {class} with {} numbers",
+                numbers.len(),
+            }
+        }
+
+        let numbers = numbers
+            .into_iter()
+            .map(|n| n.try_into_number().map(|(_, n)| n))
+            .collect::<miette::Result<Vec<_>>>()?;
+
+        numbers
+            .chunks_exact(6)
+            .map(|point| {
+                let [in_x, in_y, out_x, out_y, x, y] = point else {
+                    unreachable!("chunks are exact")
+                };
+
+                if [in_x, in_y, out_x, out_y].iter().any(|h| **h != 0.0) {
+                    miette::bail! {
+                        labels = vec![
+                            LabeledSpan::at(span.clone(), "in this curve"),
+                        ],
+                        help = "Remove the control handles in Godot, \
+                        only straight segments between points are supported",
+                        "Point ({x}, {y}) has bezier control handles",
+                    }
+                }
+
+                Ok((*x, *y))
+            })
+            .collect()
+    }
 }
 
 impl From<String> for SubResourceId {
@@ -367,6 +474,8 @@ impl From<String> for SubResourceKind {
         match s.as_str() {
             "AtlasTexture" => Self::AtlasTexture,
             "SpriteFrames" => Self::SpriteFrames,
+            "Curve2D" => Self::Curve2D,
+            "RectangleShape2D" => Self::RectangleShape2D,
             _ => Self::Other(s),
         }
     }
@@ -379,6 +488,13 @@ impl From<String> for NodeKind {
             "Node2D" => Self::Node2D,
             "Sprite2D" => Self::Sprite2D,
             "AnimatedSprite2D" => Self::AnimatedSprite2D,
+            "Timer" => Self::Timer,
+            "Path2D" => Self::Path2D,
+            "CanvasLayer" => Self::CanvasLayer,
+            "ParallaxBackground" => Self::ParallaxBackground,
+            "ParallaxLayer" => Self::ParallaxLayer,
+            "Area2D" => Self::Area2D,
+            "CollisionShape2D" => Self::CollisionShape2D,
             _ => Self::Other(s),
         }
     }
@@ -399,6 +515,13 @@ impl From<String> for NodeSectionKey {
             "flip_h" => Self::FlipHorizontally,
             "flip_v" => Self::FlipVertically,
             "self_modulate" => Self::SelfModulate,
+            "wait_time" => Self::WaitTime,
+            "autostart" => Self::Autostart,
+            "one_shot" => Self::OneShot,
+            "curve" => Self::Curve,
+            "layer" => Self::Layer,
+            "motion_scale" => Self::MotionScale,
+            "shape" => Self::Shape,
             _ => Self::Other(s),
         }
     }
@@ -410,6 +533,8 @@ impl From<String> for SubResourceSectionKey {
             "atlas" => Self::AtlasExtResource,
             "region" => Self::Region,
             "animations" => Self::Animations,
+            "_data" => Self::CurveData,
+            "size" => Self::Size,
             _ => Self::Other(s),
         }
     }
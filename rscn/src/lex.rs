@@ -51,7 +51,9 @@ pub(crate) enum TscnTokenKind {
     False,
     #[regex(r#"-?\d+(\.\d+)?"#, priority = 4)]
     Number,
-    #[regex(r#"&?"[A-Za-z0-9_/?:\. ]+""#, priority = 2)]
+    /// Quotes, backslashes and line breaks within the string are escaped
+    /// with a backslash.
+    #[regex(r#"&?"([^"\\\n]|\\[\\"nrt])+""#, priority = 2)]
     String,
 }
 
@@ -22,7 +22,7 @@ pub fn from_tscn(tscn: &str) -> miette::Result<godot::Scene> {
 mod tests {
     use std::path::PathBuf;
 
-    use super::{lex, parse};
+    use super::{from_tscn, godot::*, lex, parse};
 
     #[test]
     fn it_lexes_and_parses_tscn() -> miette::Result<()> {
@@ -63,4 +63,243 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn it_parses_timer_node() -> miette::Result<()> {
+        let tscn = r#"[gd_scene format=3 uid="uid://abc"]
+
+[node name="Root" type="Node2D"]
+
+[node name="AfterThreeSeconds" type="Timer" parent="."]
+wait_time = 3.0
+one_shot = true
+autostart = true
+metadata/event = "OpenTheGate"
+"#;
+
+        let scene = from_tscn(tscn)?;
+        let timer = scene
+            .nodes
+            .iter()
+            .find(|node| node.name == "AfterThreeSeconds")
+            .expect("Timer node should be parsed");
+
+        assert_eq!(NodeKind::Timer, timer.kind);
+        assert!(timer.section.contains_key(&NodeSectionKey::WaitTime));
+        assert!(timer.section.contains_key(&NodeSectionKey::OneShot));
+        assert!(timer.section.contains_key(&NodeSectionKey::Autostart));
+        assert!(timer.section.contains_key(&NodeSectionKey::StringMetadata));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_parses_path2d_node() -> miette::Result<()> {
+        let tscn = r#"[gd_scene load_steps=2 format=3 uid="uid://abc"]
+
+[sub_resource type="Curve2D" id="Curve2D_4x7mn"]
+_data = {
+"points": PackedVector2Array(0, 0, 0, 0, 10, 20, 0, 0, 0, 0, -30, 40.5, 0, 0, 0, 0, 50, -60)
+}
+point_count = 3
+
+[node name="Root" type="Node2D"]
+
+[node name="GuardRoute" type="Path2D" parent="."]
+position = Vector2(5, 5)
+curve = SubResource("Curve2D_4x7mn")
+"#;
+
+        let mut scene = from_tscn(tscn)?;
+        let route = scene
+            .nodes
+            .iter()
+            .find(|node| node.name == "GuardRoute")
+            .expect("Path2D node should be parsed");
+        assert_eq!(NodeKind::Path2D, route.kind);
+        assert!(route.section.contains_key(&NodeSectionKey::Curve));
+
+        let curve = scene.sub_resources.remove(0);
+        assert_eq!(SubResourceKind::Curve2D, curve.kind);
+        let points = curve
+            .section
+            .into_iter()
+            .find(|(key, _)| key == &SubResourceSectionKey::CurveData)
+            .expect("Curve2D should have data")
+            .1
+            .try_into_curve2d_points()?;
+        assert_eq!(vec![(10.0, 20.0), (-30.0, 40.5), (50.0, -60.0)], points);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_parses_parallax_background() -> miette::Result<()> {
+        let tscn = r#"[gd_scene format=3 uid="uid://abc"]
+
+[node name="Root" type="Node2D"]
+
+[node name="Sky" type="ParallaxBackground" parent="."]
+layer = -2
+
+[node name="Clouds" type="ParallaxLayer" parent="Sky"]
+motion_scale = Vector2(0.25, 0.5)
+"#;
+
+        let mut scene = from_tscn(tscn)?;
+        let sky = scene
+            .nodes
+            .iter()
+            .find(|node| node.name == "Sky")
+            .expect("ParallaxBackground node should be parsed");
+        assert_eq!(NodeKind::ParallaxBackground, sky.kind);
+        assert!(sky.section.contains_key(&NodeSectionKey::Layer));
+
+        let clouds = scene
+            .nodes
+            .iter_mut()
+            .find(|node| node.name == "Clouds")
+            .expect("ParallaxLayer node should be parsed");
+        assert_eq!(NodeKind::ParallaxLayer, clouds.kind);
+        let motion_scale = clouds
+            .section
+            .remove(&NodeSectionKey::MotionScale)
+            .expect("motion_scale should be parsed")
+            .into_vector2()?;
+        assert_eq!((0.25, 0.5), motion_scale);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_parses_area2d_with_rectangle_shape() -> miette::Result<()> {
+        let tscn = r#"[gd_scene load_steps=2 format=3 uid="uid://abc"]
+
+[sub_resource type="RectangleShape2D" id="RectangleShape2D_x3j1k"]
+size = Vector2(32, 16)
+
+[node name="Root" type="Node2D"]
+
+[node name="CutsceneStart" type="Area2D" parent="."]
+position = Vector2(100, 50)
+
+[node name="CollisionShape2D" type="CollisionShape2D" parent="CutsceneStart"]
+position = Vector2(8, -4)
+shape = SubResource("RectangleShape2D_x3j1k")
+"#;
+
+        let mut scene = from_tscn(tscn)?;
+        let area = scene
+            .nodes
+            .iter()
+            .find(|node| node.name == "CutsceneStart")
+            .expect("Area2D node should be parsed");
+        assert_eq!(NodeKind::Area2D, area.kind);
+
+        let shape = scene
+            .nodes
+            .iter_mut()
+            .find(|node| node.name == "CollisionShape2D")
+            .expect("CollisionShape2D node should be parsed");
+        assert_eq!(NodeKind::CollisionShape2D, shape.kind);
+        assert_eq!(Some("CutsceneStart"), shape.parent.as_deref());
+        let id = shape
+            .section
+            .remove(&NodeSectionKey::Shape)
+            .expect("shape should be parsed")
+            .try_into_sub_resource()?;
+
+        let mut rectangle = scene.sub_resources.remove(0);
+        assert_eq!(id, rectangle.id);
+        assert_eq!(SubResourceKind::RectangleShape2D, rectangle.kind);
+        let size = rectangle
+            .section
+            .remove(&SubResourceSectionKey::Size)
+            .expect("size should be parsed")
+            .into_vector2()?;
+        assert_eq!((32.0, 16.0), size);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_curve_with_bezier_handles() -> miette::Result<()> {
+        let tscn = r#"[gd_scene load_steps=2 format=3 uid="uid://abc"]
+
+[sub_resource type="Curve2D" id="Curve2D_4x7mn"]
+_data = {
+"points": PackedVector2Array(0, 0, 0, 0, 10, 20, -5, 0, 5, 0, 30, 40)
+}
+point_count = 2
+
+[node name="Root" type="Node2D"]
+"#;
+
+        let mut scene = from_tscn(tscn)?;
+        let (_, data) =
+            scene.sub_resources.remove(0).section.pop_first().unwrap();
+        let err = data.try_into_curve2d_points().unwrap_err();
+        assert!(err.to_string().contains("bezier"), "{err}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_matches_integer_and_string_resource_ids() -> miette::Result<()> {
+        let tscn = r#"[gd_scene load_steps=4 format=3 uid="uid://abc"]
+
+[ext_resource type="Texture2D" path="res://assets/legacy.png" id=1]
+[ext_resource type="Texture2D" path="res://assets/new.png" id="2_k3j4h"]
+
+[sub_resource type="AtlasTexture" id="AtlasTexture_yvafp"]
+atlas = ExtResource("1")
+region = Rect2(0, 0, 16, 16)
+
+[node name="Root" type="Node2D"]
+
+[node name="Legacy" type="Sprite2D" parent="."]
+texture = ExtResource(1)
+
+[node name="New" type="Sprite2D" parent="."]
+texture = ExtResource("2_k3j4h")
+"#;
+
+        let mut scene = from_tscn(tscn)?;
+        let ext_ids: Vec<_> =
+            scene.ext_resources.iter().map(|res| res.id()).collect();
+        assert_eq!(
+            vec![
+                &ExtResourceId("1".to_string()),
+                &ExtResourceId("2_k3j4h".to_string()),
+            ],
+            ext_ids
+        );
+
+        let atlas = scene
+            .sub_resources
+            .remove(0)
+            .section
+            .remove(&SubResourceSectionKey::AtlasExtResource)
+            .expect("atlas should be parsed")
+            .try_into_ext_resource()?;
+        assert!(scene.ext_resources.iter().any(|res| res.id() == &atlas));
+
+        for name in ["Legacy", "New"] {
+            let texture = scene
+                .nodes
+                .iter_mut()
+                .find(|node| node.name == name)
+                .expect("sprite node should be parsed")
+                .section
+                .remove(&NodeSectionKey::TextureExtResource)
+                .expect("texture should be parsed")
+                .try_into_ext_resource()?;
+            assert!(
+                scene.ext_resources.iter().any(|res| res.id() == &texture),
+                "{name} texture {texture:?} not found"
+            );
+        }
+
+        Ok(())
+    }
 }
@@ -50,12 +50,12 @@ pub(super) fn parse_attributes_into_state(
             attr = EXT_RESOURCE_ID,
         }
     }).and_then(|val| {
-        val.into_string().ok_or_else(|| {
+        val.into_resource_id().ok_or_else(|| {
             miette::miette! {
                 labels = vec![
                     LabeledSpan::at(span.clone(), "this attribute"),
                 ],
-                "Expected string value for '{attr}' attribute in '{section}' section",
+                "Expected string or integer value for '{attr}' attribute in '{section}' section",
                 section = EXT_RESOURCE,
                 attr = EXT_RESOURCE_ID,
             }
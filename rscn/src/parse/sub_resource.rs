@@ -44,12 +44,12 @@ pub(super) fn parse_attributes(
             attr = SUB_RESOURCE_ID,
         }
     }).and_then(|val| {
-        val.into_string().ok_or_else(|| {
+        val.into_resource_id().ok_or_else(|| {
             miette::miette! {
                 labels = vec![
                     LabeledSpan::at(span.clone(), "this attribute"),
                 ],
-                "Expected string value for '{attr}' attribute in '{section}' section",
+                "Expected string or integer value for '{attr}' attribute in '{section}' section",
                 section = SUB_RESOURCE,
                 attr = SUB_RESOURCE_ID,
             }
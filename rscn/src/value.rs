@@ -45,6 +45,18 @@ pub enum SpannedValue {
 }
 
 impl SpannedValue {
+    /// Where in the source the value is.
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Self::Number(span, _)
+            | Self::String(span, _)
+            | Self::Bool(span, _)
+            | Self::Class(span, _, _)
+            | Self::Array(span, _)
+            | Self::Object(span, _) => span.clone(),
+        }
+    }
+
     /// Only returns [Some] for [SpannedValue::String].
     pub fn into_string(self) -> Option<String> {
         match self {
@@ -53,6 +65,48 @@ impl SpannedValue {
         }
     }
 
+    /// Resource ids are strings such as `"1_abcde"` in scenes saved by
+    /// Godot 4, but plain integers such as `1` in scenes saved by older
+    /// versions.
+    /// Both forms are accepted and integers are normalized into their decimal
+    /// string so that `id=1`, `id="1"` and `ExtResource(1)` all refer to the
+    /// same resource.
+    ///
+    /// Only returns [Some] for [SpannedValue::String] or a non-negative whole
+    /// [SpannedValue::Number].
+    pub fn into_resource_id(self) -> Option<String> {
+        self.try_into_resource_id().ok().map(|(_, id)| id)
+    }
+
+    /// Tries to convert the value into a resource id.
+    /// See [`SpannedValue::into_resource_id`].
+    ///
+    /// Returns an error with labelled span if the value is not a resource id.
+    pub fn try_into_resource_id(
+        self,
+    ) -> miette::Result<(Range<usize>, String)> {
+        let got = self.variant_name();
+        let expected = "string or integer resource id";
+        match self {
+            Self::String(span, id) => Ok((span, id)),
+            Self::Number(span, n) if n >= 0.0 && n.fract() == 0.0 => {
+                Ok((span, (n as u64).to_string()))
+            }
+            Self::Class(span, _, _)
+            | Self::Bool(span, _)
+            | Self::Object(span, _)
+            | Self::Number(span, _)
+            | Self::Array(span, _) => {
+                miette::bail! {
+                    labels = vec![
+                        LabeledSpan::at(span, format!("this {got}")),
+                    ],
+                    "Expected {expected}, got {got}",
+                }
+            }
+        }
+    }
+
     /// Tries to convert the value into a class.
     ///
     /// Returns an error with labelled span if the value is not a class.
@@ -326,5 +380,28 @@ pub(super) fn parse_string(
         span.start + 1..span.end - 1
     };
 
-    Ok(source[span].to_owned())
+    let mut unescaped = String::with_capacity(span.len());
+    let mut chars = source[span.clone()].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        let c = match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some(c @ ('"' | '\\')) => c,
+            _ => miette::bail! {
+                labels = vec![
+                    LabeledSpan::at(span.clone(), "this string"),
+                ],
+                "Unknown escape sequence in string",
+            },
+        };
+        unescaped.push(c);
+    }
+
+    Ok(unescaped)
 }
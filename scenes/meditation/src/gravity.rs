@@ -38,6 +38,25 @@ impl ChangeOfBasis {
     pub(crate) fn new(translation: Vec2) -> Self {
         Self(translation)
     }
+
+    /// Unlike [`GridCoords`], the position is not rounded to the nearest grid
+    /// point so that the field can be sampled smoothly with
+    /// [`PoissonsEquation::sample_gradient`].
+    #[inline]
+    pub(crate) fn grid_pos(&self) -> Vec2 {
+        let Self(Vec2 { x, y }) = self;
+
+        vec2(
+            // 0 is the leftmost column
+            // so the more positive x the higher the column
+            (GRAVITY_STAGE_WIDTH / 2.0 + x) / GRAVITY_STAGE_WIDTH
+                * GRAVITY_FIELD_WIDTH as f32,
+            // 0 is the topmost row
+            // so the more positive y the higher the row
+            (GRAVITY_STAGE_HEIGHT / 2.0 - y) / GRAVITY_STAGE_HEIGHT
+                * GRAVITY_FIELD_HEIGHT as f32,
+        )
+    }
 }
 
 impl From<Transform> for ChangeOfBasis {
@@ -49,23 +68,12 @@ impl From<Transform> for ChangeOfBasis {
 
 impl From<ChangeOfBasis> for GridCoords {
     #[inline]
-    fn from(ChangeOfBasis(Vec2 { x, y }): ChangeOfBasis) -> Self {
-        let field_width = GRAVITY_FIELD_WIDTH as f32;
-        let field_height = GRAVITY_FIELD_HEIGHT as f32;
+    fn from(change_of_basis: ChangeOfBasis) -> Self {
+        let Vec2 { x, y } = change_of_basis.grid_pos();
 
         GridCoords {
-            // 0 is the leftmost column
-            // so the more positive x the higher the column
-            x: ((GRAVITY_STAGE_WIDTH / 2.0 + x) / GRAVITY_STAGE_WIDTH
-                * field_width)
-                .round()
-                .clamp(0.0, field_width - 1.0) as usize,
-            // 0 is the topmost row
-            // so the more positive y the higher the row
-            y: ((GRAVITY_STAGE_HEIGHT / 2.0 - y) / GRAVITY_STAGE_HEIGHT
-                * field_height)
-                .round()
-                .clamp(0.0, field_height - 1.0) as usize,
+            x: x.round().clamp(0.0, GRAVITY_FIELD_WIDTH as f32 - 1.0) as usize,
+            y: y.round().clamp(0.0, GRAVITY_FIELD_HEIGHT as f32 - 1.0) as usize,
         }
     }
 }
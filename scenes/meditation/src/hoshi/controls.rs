@@ -69,7 +69,8 @@ pub(super) fn normal(
         movement_action.filter(|a| a.is_in_up_direction()).is_some();
 
     let dt = time.delta_seconds();
-    let gvec = gravity.gradient_at(ChangeOfBasis::from(*transform))
+    let gvec = gravity
+        .sample_gradient(ChangeOfBasis::from(*transform).grid_pos())
         * GRAVITY_MULTIPLIER;
 
     let mut update_horizontal = |dir: MotionDirection| {
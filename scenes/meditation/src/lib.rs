@@ -46,7 +46,11 @@ pub fn add(app: &mut App) {
 
     app.add_systems(
         OnEnter(GlobalGameState::LoadingMeditation),
-        common_assets::store::insert_as_resource::<Meditation>,
+        (
+            common_assets::store::insert_as_resource::<Meditation>,
+            gate_loading_screen_on_assets,
+        )
+            .chain(),
     );
     app.add_systems(
         OnExit(GlobalGameState::QuittingMeditation),
@@ -85,14 +89,21 @@ pub fn add(app: &mut App) {
 
     // 1. start the spawning process (the loading screen is already started)
     app.add_systems(OnEnter(GlobalGameState::LoadingMeditation), spawn);
-    // 2. when everything is loaded, finish the loading process by transitioning
-    //    to the next loading state (this will also spawn the camera)
+    // 2. the loading screen waits for all assets to load, then we finish the
+    //    loading process by transitioning to the next loading state (this will
+    //    also spawn the camera)
     app.add_systems(
         Last,
-        finish_when_everything_loaded
+        common_loading_screen::finish
             .run_if(in_state(GlobalGameState::LoadingMeditation))
             .run_if(in_state(LoadingScreenState::WaitForSignalToFinish)),
     );
+    // report assets that won't load instead of loading forever
+    app.add_systems(
+        Last,
+        main_game_lib::loading_watchdog::watch
+            .run_if(in_state(GlobalGameState::LoadingMeditation)),
+    );
     // 3. ready to enter the game when the loading screen is completely gone
     app.add_systems(
         OnEnter(LoadingScreenState::DespawnLoadingScreen),
@@ -140,18 +151,18 @@ fn despawn(mut cmd: Commands) {
     cmd.remove_resource::<WebpAnimator>();
 }
 
-fn finish_when_everything_loaded(
-    mut next_loading_state: ResMut<NextState<LoadingScreenState>>,
-    asset_server: Res<AssetServer>,
+/// The loading screen won't wait for the finish signal until all assets are
+/// loaded.
+fn gate_loading_screen_on_assets(
     asset_store: Res<AssetStore<Meditation>>,
+    settings: Option<ResMut<LoadingScreenSettings>>,
 ) {
-    if !asset_store.are_all_loaded(&asset_server) {
+    let Some(mut settings) = settings else {
+        warn!("Loading meditation without a loading screen");
         return;
-    }
-
-    debug!("All assets loaded");
+    };
 
-    next_loading_state.set(common_loading_screen::finish_state());
+    settings.gate.extend(asset_store.handles());
 }
 
 fn enter_the_game(mut next_state: ResMut<NextState<GlobalGameState>>) {
@@ -179,7 +190,7 @@ fn all_cleaned_up(
     *since = None;
 
     // be a good guy and don't invade other game loops with "Enter"
-    controls.consume(&GlobalAction::Interact);
+    consume_for_transition(&mut controls);
 
     use GlobalGameStateTransition::*;
     match *transition {
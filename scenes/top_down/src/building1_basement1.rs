@@ -93,6 +93,7 @@ fn spawn(
     asset_server: Res<AssetServer>,
     mut tscn: ResMut<Assets<TscnTree>>,
     mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tilemap: Res<TileMap>,
 
     mut q: Query<&mut TscnTreeHandle<Building1Basement1>>,
 ) {
@@ -103,6 +104,7 @@ fn spawn(
 
     let player = cmd.spawn_empty().id();
     let mut player_builder = common_story::Character::Winnie.bundle_builder();
+    player_builder.tilemap(&tilemap);
     player_builder.initial_step_time(STEP_TIME_ON_EXIT_ELEVATOR);
 
     tscn.spawn_into(
@@ -110,6 +112,7 @@ fn spawn(
         &mut atlas_layouts,
         &asset_server,
         &mut TopDownTsncSpawner::new(
+            tilemap.map_ysort(),
             &mut zone_to_inspect_label_entity,
             &mut Spawner {
                 transition: *transition,
@@ -205,6 +208,7 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
 fn enter_the_elevator(
     mut cmd: Commands,
     mut assets: ResMut<Assets<DialogGraph>>,
+    tilemap: Res<TileMap>,
 
     player: Query<Entity, With<Player>>,
     elevator: Query<Entity, With<Elevator>>,
@@ -216,13 +220,13 @@ fn enter_the_elevator(
         return;
     };
 
-    let point_in_elevator = {
+    let in_elevator = {
         let (_, bevy_rscn::Point(pos)) = points
             .iter()
             .find(|(name, _)| **name == Name::new("InElevator"))
             .expect("InElevator point not found");
 
-        *pos
+        tilemap.layout().world_pos_to_square(*pos)
     };
 
     cutscene::enter_an_elevator::spawn(
@@ -231,7 +235,7 @@ fn enter_the_elevator(
         player,
         elevator.single(),
         camera.single(),
-        point_in_elevator,
+        in_elevator,
         &[
             (
                 GlobalGameStateTransition::Building1Basement1ToPlayerFloor,
@@ -248,6 +252,7 @@ fn enter_the_elevator(
 /// Goes to the next basement level.
 fn enter_basement2(
     mut cmd: Commands,
+    tilemap: Res<TileMap>,
 
     player: Query<Entity, With<Player>>,
     door: Query<Entity, With<DoorToStorageBasement>>,
@@ -261,7 +266,7 @@ fn enter_basement2(
         .iter()
         .find_map(|(name, bevy_rscn::Point(pos))| {
             if name == &Name::new("BasementExit") {
-                Some(*pos)
+                Some(tilemap.layout().world_pos_to_square(*pos))
             } else {
                 None
             }
@@ -65,6 +65,7 @@ fn spawn(
     asset_server: Res<AssetServer>,
     mut tscn: ResMut<Assets<TscnTree>>,
     mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tilemap: Res<TileMap>,
 
     mut q: Query<&mut TscnTreeHandle<Building1Basement2>>,
 ) {
@@ -75,12 +76,14 @@ fn spawn(
 
     let player = cmd.spawn_empty().id();
     let mut player_builder = common_story::Character::Winnie.bundle_builder();
+    player_builder.tilemap(&tilemap);
 
     tscn.spawn_into(
         &mut cmd,
         &mut atlas_layouts,
         &asset_server,
         &mut TopDownTsncSpawner::new(
+            tilemap.map_ysort(),
             &mut zone_to_inspect_label_entity,
             &mut Spawner {
                 player_entity: player,
@@ -137,6 +140,7 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
 fn exit(
     mut cmd: Commands,
     mut action_events: EventReader<TopDownAction>,
+    tilemap: Res<TileMap>,
 
     player: Query<Entity, With<Player>>,
     door: Query<Entity, With<DoorToStorageBasement>>,
@@ -155,7 +159,7 @@ fn exit(
             .iter()
             .find_map(|(name, bevy_rscn::Point(pos))| {
                 if name == &Name::new("Exit") {
-                    Some(*pos)
+                    Some(tilemap.layout().world_pos_to_square(*pos))
                 } else {
                     None
                 }
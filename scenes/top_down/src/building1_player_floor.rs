@@ -6,7 +6,10 @@ use bevy_rscn::RscnNode;
 use common_story::emoji::{
     DisplayEmojiEvent, DisplayEmojiEventConsumer, EmojiKind,
 };
-use common_visuals::camera::render_layer;
+use common_visuals::{
+    camera::render_layer,
+    fade::{fade_from_black, fade_to_black},
+};
 use main_game_lib::{
     cutscene::{
         enter_an_elevator::{
@@ -31,7 +34,7 @@ use top_down::{
     },
     environmental_objects::door::{DoorBuilder, DoorOpenCriteria, DoorState},
     inspect_and_interact::ZoneToInspectLabelEntity,
-    layout::LAYOUT,
+    layout::ValidateTileMap,
     ActorTarget, TileMap,
 };
 
@@ -45,6 +48,10 @@ const WHEN_ENTERING_MEDITATION_SHOW_LOADING_IMAGE_FOR_AT_LEAST: Duration =
 /// Hard coded to make the animation play out.
 const WINNIE_IN_BATHROOM_TRANSITION_FOR_AT_LEAST: Duration = from_millis(3500);
 
+/// The eyes close before the loading screen and open after the new day
+/// spawns.
+const SLEEP_FADE_DURATION: Duration = from_millis(750);
+
 /// Walk down slowly otherwise it'll happen before the player even sees it.
 const STEP_TIME_ONLOAD_FROM_MEDITATION: Duration = from_millis(750);
 
@@ -87,7 +94,10 @@ impl bevy::app::Plugin for Plugin {
 
         app.add_systems(
             OnEnter(THIS_SCENE.loading()),
-            bevy_rscn::start_loading_tscn::<Building1PlayerFloor>,
+            (
+                bevy_rscn::start_loading_tscn::<Building1PlayerFloor>,
+                validate_map,
+            ),
         )
         .add_systems(
             Update,
@@ -109,6 +119,25 @@ impl bevy::app::Plugin for Plugin {
     }
 }
 
+/// Catch zones that were forgotten when painting the map.
+fn validate_map(mut cmd: Commands) {
+    cmd.insert_resource(ValidateTileMap {
+        zones: vec![
+            ZoneTileKind::Apartment2,
+            ZoneTileKind::Apartment2BathroomDoor,
+            ZoneTileKind::Apartment2Door,
+            ZoneTileKind::Bed,
+            ZoneTileKind::Elevator,
+            ZoneTileKind::Hallway,
+            ZoneTileKind::Meditation,
+            ZoneTileKind::PlayerApartment,
+            ZoneTileKind::PlayerDoor,
+            ZoneTileKind::Tea,
+        ],
+        player_start: None,
+    });
+}
+
 /// Hallway is darkened when the player is in the apartment but once the player
 /// approaches the door or is in the hallway, it's lit up.
 #[derive(Component)]
@@ -154,12 +183,14 @@ fn spawn(
     let mut zone_to_inspect_label_entity = ZoneToInspectLabelEntity::default();
     let player = cmd.spawn_empty().id();
     let mut player_builder = common_story::Character::Winnie.bundle_builder();
+    player_builder.tilemap(&tilemap);
 
     tscn.spawn_into(
         &mut cmd,
         &mut atlas_layouts,
         &asset_server,
         &mut TopDownTsncSpawner::new(
+            tilemap.map_ysort(),
             &mut zone_to_inspect_label_entity,
             &mut Spawner {
                 player_entity: player,
@@ -269,7 +300,9 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
                     .translation;
                 self.player_builder.initial_position(translation);
                 self.player_builder.walking_to(ActorTarget::new(
-                    LAYOUT.world_pos_to_square(translation + vec2(0.0, -20.0)),
+                    self.tilemap
+                        .layout()
+                        .world_pos_to_square(translation + vec2(0.0, -20.0)),
                 ));
                 self.player_builder
                     .initial_step_time(STEP_TIME_ONLOAD_FROM_MEDITATION);
@@ -292,7 +325,8 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
                     .translation;
                 self.player_builder.initial_position(translation);
                 self.player_builder.walking_to(ActorTarget::new(
-                    LAYOUT.world_pos_to_square(translation) + sq(0, -2),
+                    self.tilemap.layout().world_pos_to_square(translation)
+                        + sq(0, -2),
                 ));
                 self.player_builder
                     .initial_step_time(STEP_TIME_ON_EXIT_ELEVATOR);
@@ -305,6 +339,7 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
                 self.player_builder.initial_position(translation);
                 self.player_builder.initial_direction(GridDirection::Top);
                 self.daybar_event.send(UpdateDayBarEvent::NewDay);
+                fade_from_black(cmd, SLEEP_FADE_DURATION);
             }
             _ => {}
         }
@@ -401,6 +436,7 @@ fn start_meditation_minigame(
 fn enter_the_elevator(
     mut cmd: Commands,
     mut assets: ResMut<Assets<DialogGraph>>,
+    tilemap: Res<TileMap>,
 
     player: Query<Entity, With<Player>>,
     elevator: Query<Entity, With<Elevator>>,
@@ -410,13 +446,13 @@ fn enter_the_elevator(
     use GlobalGameStateTransition::*;
 
     if let Some(player) = player.get_single_or_none() {
-        let point_in_elevator = {
+        let in_elevator = {
             let (_, bevy_rscn::Point(pos)) = points
                 .iter()
                 .find(|(name, _)| **name == Name::new("InElevator"))
                 .expect("InElevator point not found");
 
-            *pos
+            tilemap.layout().world_pos_to_square(*pos)
         };
 
         cutscene::enter_an_elevator::spawn(
@@ -425,7 +461,7 @@ fn enter_the_elevator(
             player,
             elevator.single(),
             camera.single(),
-            point_in_elevator,
+            in_elevator,
             // LOCALIZATION
             &[
                 (Building1PlayerFloorToDowntown, "go to downtown"),
@@ -482,6 +518,10 @@ fn sleep(mut cmd: Commands, player: Query<Entity, With<Player>>) {
 
     vec![
         CutsceneStep::TakeAwayPlayerControl(player),
+        CutsceneStep::ScheduleCommands(|cmd, _| {
+            fade_to_black(cmd, SLEEP_FADE_DURATION);
+        }),
+        CutsceneStep::Sleep(SLEEP_FADE_DURATION),
         CutsceneStep::ChangeGlobalState {
             to: THIS_SCENE.leaving(),
             with: GlobalGameStateTransition::Sleeping,
@@ -6,7 +6,6 @@ use main_game_lib::{
     cutscene::in_cutscene,
     hud::{daybar::UpdateDayBarEvent, notification::NotificationFifo},
     player_stats::PlayerStats,
-    top_down::layout::LAYOUT,
 };
 use top_down::{
     actor::{CharacterBundleBuilder, CharacterExt},
@@ -46,9 +45,14 @@ impl bevy::app::Plugin for Plugin {
         .add_systems(
             Update,
             (
-                go_to_downtown
-                    .run_if(on_event_variant(TopDownAction::GoToDowntown)),
-                enter_tower.run_if(on_event_variant(TopDownAction::EnterTower)),
+                exit_zone_interaction(
+                    TopDownAction::GoToDowntown,
+                    GlobalGameStateTransition::CompoundToDowntown,
+                ),
+                exit_zone_interaction(
+                    TopDownAction::EnterTower,
+                    GlobalGameStateTransition::CompoundToTower,
+                ),
             )
                 .run_if(in_scene_running_state(THIS_SCENE))
                 .run_if(not(in_cutscene())),
@@ -57,6 +61,7 @@ impl bevy::app::Plugin for Plugin {
 }
 
 struct Spawner<'a> {
+    tilemap: &'a TileMap,
     player_entity: Entity,
     player_builder: &'a mut CharacterBundleBuilder,
     camera_translation: &'a mut Vec3,
@@ -71,6 +76,7 @@ fn spawn(
     asset_server: Res<AssetServer>,
     mut tscn: ResMut<Assets<TscnTree>>,
     mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tilemap: Res<TileMap>,
     transition: Res<GlobalGameStateTransition>,
     mut daybar_event: ResMut<Events<UpdateDayBarEvent>>,
     mut notifications: ResMut<NotificationFifo>,
@@ -87,14 +93,17 @@ fn spawn(
 
     let player = cmd.spawn_empty().id();
     let mut player_builder = common_story::Character::Winnie.bundle_builder();
+    player_builder.tilemap(&tilemap);
 
     tscn.spawn_into(
         &mut cmd,
         &mut atlas_layouts,
         &asset_server,
         &mut TopDownTsncSpawner::new(
+            tilemap.map_ysort(),
             &mut zone_to_inspect_label_entity,
             &mut Spawner {
+                tilemap: &tilemap,
                 player_entity: player,
                 player_builder: &mut player_builder,
                 camera_translation: &mut camera.single_mut().translation,
@@ -107,6 +116,8 @@ fn spawn(
     player_builder.insert_bundle_into(&asset_server, &mut cmd.entity(player));
 
     cmd.insert_resource(zone_to_inspect_label_entity);
+    cmd.init_resource::<DayNightCycle>();
+    cmd.insert_resource(Weather::new(WeatherKind::Snow, 0.4));
 }
 
 fn despawn(mut cmd: Commands, root: Query<Entity, With<LayoutEntity>>) {
@@ -116,6 +127,9 @@ fn despawn(mut cmd: Commands, root: Query<Entity, With<LayoutEntity>>) {
     cmd.entity(root).despawn_recursive();
 
     cmd.remove_resource::<ZoneToInspectLabelEntity>();
+    cmd.remove_resource::<DayNightCycle>();
+    cmd.remove_resource::<SceneTint>();
+    cmd.remove_resource::<Weather>();
 }
 
 impl<'a> TscnSpawnHooks for Spawner<'a> {
@@ -153,7 +167,7 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
                     .initial_step_time(Character::Winnie.slow_step_time() * 4);
                 self.player_builder.initial_position(position);
                 self.player_builder.walking_to(top_down::ActorTarget::new(
-                    LAYOUT.world_pos_to_square(position)
+                    self.tilemap.layout().world_pos_to_square(position)
                         + sq(0, 2 * if face_up { 1 } else { -1 }),
                 ));
                 if face_up {
@@ -167,11 +181,3 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
         }
     }
 }
-
-fn go_to_downtown(mut transition_params: TransitionParams) {
-    transition_params.begin(GlobalGameStateTransition::CompoundToDowntown);
-}
-
-fn enter_tower(mut transition_params: TransitionParams) {
-    transition_params.begin(GlobalGameStateTransition::CompoundToTower);
-}
@@ -2,7 +2,10 @@ use bevy::render::view::RenderLayers;
 use bevy_grid_squared::{sq, Square};
 use bevy_kira_audio::{Audio, AudioControl, AudioInstance, AudioTween};
 use common_story::Character;
-use common_visuals::camera::{render_layer, MainCamera};
+use common_visuals::{
+    camera::{render_layer, MainCamera},
+    DesyncAnimation,
+};
 use main_game_lib::{
     cutscene::in_cutscene,
     hud::{
@@ -17,13 +20,12 @@ use main_game_lib::{
             ChangeHighlightedInspectLabelEventConsumer,
             SpawnLabelBgAndTextParams, ZoneToInspectLabelEntity, LIGHT_RED,
         },
-        npc::behaviors::PatrolSequence,
+        npc::behaviors::Patrol,
         ActorMovementEvent,
     },
 };
 use top_down::{
     actor::{CharacterBundleBuilder, CharacterExt},
-    layout::LAYOUT,
     TileMap,
 };
 
@@ -66,8 +68,10 @@ impl bevy::app::Plugin for Plugin {
         .add_systems(
             Update,
             (
-                enter_building1
-                    .run_if(on_event_variant(TopDownAction::EnterBuilding1)),
+                exit_zone_interaction(
+                    TopDownAction::EnterBuilding1,
+                    GlobalGameStateTransition::DowntownToBuilding1PlayerFloor,
+                ),
                 enter_clinic
                     .run_if(on_event_variant(TopDownAction::EnterClinic)),
                 enter_clinic_ward
@@ -75,13 +79,18 @@ impl bevy::app::Plugin for Plugin {
                 enter_mall.run_if(on_event_variant(TopDownAction::EnterMall)),
                 enter_plant_shop
                     .run_if(on_event_variant(TopDownAction::EnterPlantShop)),
-                enter_sewers
-                    .run_if(on_event_variant(TopDownAction::EnterSewers)),
-                enter_twinpeaks_apartment.run_if(on_event_variant(
+                exit_zone_interaction(
+                    TopDownAction::EnterSewers,
+                    GlobalGameStateTransition::DowntownToSewers,
+                ),
+                exit_zone_interaction(
                     TopDownAction::EnterTwinpeaksApartment,
-                )),
-                enter_compound
-                    .run_if(on_event_variant(TopDownAction::EnterCompound)),
+                    GlobalGameStateTransition::DowntownToTwinpeaksApartment,
+                ),
+                exit_zone_interaction(
+                    TopDownAction::EnterCompound,
+                    GlobalGameStateTransition::DowntownToCompound,
+                ),
             )
                 .before(ChangeHighlightedInspectLabelEventConsumer)
                 .run_if(in_scene_running_state(THIS_SCENE))
@@ -91,6 +100,7 @@ impl bevy::app::Plugin for Plugin {
 }
 
 struct Spawner<'a> {
+    tilemap: &'a TileMap,
     camera_translation: &'a mut Vec3,
     player_builder: &'a mut CharacterBundleBuilder,
     player_entity: Entity,
@@ -112,6 +122,7 @@ fn spawn(
     asset_server: Res<AssetServer>,
     mut tscn: ResMut<Assets<TscnTree>>,
     mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tilemap: Res<TileMap>,
     transition: Res<GlobalGameStateTransition>,
     mut daybar_event: ResMut<Events<UpdateDayBarEvent>>,
     mut notifications: ResMut<NotificationFifo>,
@@ -127,14 +138,17 @@ fn spawn(
     let mut zone_to_inspect_label_entity = ZoneToInspectLabelEntity::default();
     let player = cmd.spawn_empty().id();
     let mut player_builder = common_story::Character::Winnie.bundle_builder();
+    player_builder.tilemap(&tilemap);
 
     let samizdat = cmd.spawn_empty().id();
     let mut samizdat_builder =
         common_story::Character::Samizdat.bundle_builder();
+    samizdat_builder.tilemap(&tilemap);
     let mut samizdat_patrol_points = Vec::new();
 
     let otter = cmd.spawn_empty().id();
     let mut otter_builder = common_story::Character::Otter.bundle_builder();
+    otter_builder.tilemap(&tilemap);
     let mut otter_patrol_points = Vec::new();
 
     tscn.spawn_into(
@@ -142,8 +156,10 @@ fn spawn(
         &mut atlas_layouts,
         &asset_server,
         &mut TopDownTsncSpawner::new(
+            tilemap.map_ysort(),
             &mut zone_to_inspect_label_entity,
             &mut Spawner {
+                tilemap: &tilemap,
                 camera_translation: &mut camera.single_mut().translation,
                 daybar_event: &mut daybar_event,
                 player_builder: &mut player_builder,
@@ -160,6 +176,8 @@ fn spawn(
     );
 
     cmd.insert_resource(zone_to_inspect_label_entity);
+    cmd.init_resource::<DayNightCycle>();
+    cmd.insert_resource(Weather::new(WeatherKind::Rain, 0.6));
 
     player_builder.insert_bundle_into(&asset_server, &mut cmd.entity(player));
 
@@ -169,10 +187,12 @@ fn spawn(
     );
     samizdat_builder
         .initial_square(samizdat_patrol_points.first().copied().unwrap())
-        .behavior_tree(PatrolSequence {
-            wait_at_each: from_millis(7_500),
-            points: samizdat_patrol_points,
-        });
+        .behavior_tree(Patrol {
+            pause: from_millis(7_500),
+            waypoints: samizdat_patrol_points,
+            loop_: true,
+        })
+        .persist_position();
     samizdat_builder
         .insert_bundle_into(&asset_server, &mut cmd.entity(samizdat));
 
@@ -182,10 +202,12 @@ fn spawn(
     );
     otter_builder
         .initial_square(otter_patrol_points.first().copied().unwrap())
-        .behavior_tree(PatrolSequence {
-            wait_at_each: from_millis(12_000),
-            points: otter_patrol_points,
-        });
+        .behavior_tree(Patrol {
+            pause: from_millis(12_000),
+            waypoints: otter_patrol_points,
+            loop_: true,
+        })
+        .persist_position();
     otter_builder.insert_bundle_into(&asset_server, &mut cmd.entity(otter));
 }
 
@@ -204,6 +226,9 @@ fn despawn(
     }
 
     cmd.remove_resource::<ZoneToInspectLabelEntity>();
+    cmd.remove_resource::<DayNightCycle>();
+    cmd.remove_resource::<SceneTint>();
+    cmd.remove_resource::<Weather>();
 }
 
 impl<'a> TscnSpawnHooks for Spawner<'a> {
@@ -230,6 +255,10 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
                 cmd.entity(who).add_child(self.samizdat_entity);
                 cmd.entity(who).add_child(self.otter_entity);
             }
+            // the AC units on the buildings would otherwise spin in unison
+            ("AcAtlas", _) => {
+                cmd.entity(who).insert(DesyncAnimation);
+            }
 
             // transitions
             (
@@ -250,7 +279,8 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
                     .initial_step_time(Character::Winnie.slow_step_time() * 4);
                 self.player_builder.initial_position(position);
                 self.player_builder.walking_to(top_down::ActorTarget::new(
-                    LAYOUT.world_pos_to_square(position) + sq(0, -2),
+                    self.tilemap.layout().world_pos_to_square(position)
+                        + sq(0, -2),
                 ));
 
                 self.daybar_event.send(UpdateDayBarEvent::ChangedScene);
@@ -258,22 +288,17 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
 
             (s, _) if s.starts_with("SamizdatPatrolPoint") => {
                 self.samizdat_patrol_points
-                    .push(LAYOUT.world_pos_to_square(position));
+                    .push(self.tilemap.layout().world_pos_to_square(position));
             }
             (s, _) if s.starts_with("OtterPatrolPoint") => {
                 self.otter_patrol_points
-                    .push(LAYOUT.world_pos_to_square(position));
+                    .push(self.tilemap.layout().world_pos_to_square(position));
             }
             _ => {}
         }
     }
 }
 
-fn enter_building1(mut transition_params: TransitionParams) {
-    transition_params
-        .begin(GlobalGameStateTransition::DowntownToBuilding1PlayerFloor);
-}
-
 fn enter_mall(
     mut transition_params: TransitionParams,
     mut inspect_label_events: EventWriter<ChangeHighlightedInspectLabelEvent>,
@@ -350,19 +375,6 @@ fn enter_plant_shop(
     transition_params.begin(GlobalGameStateTransition::DowntownToPlantShop);
 }
 
-fn enter_twinpeaks_apartment(mut transition_params: TransitionParams) {
-    transition_params
-        .begin(GlobalGameStateTransition::DowntownToTwinpeaksApartment);
-}
-
-fn enter_sewers(mut transition_params: TransitionParams) {
-    transition_params.begin(GlobalGameStateTransition::DowntownToSewers);
-}
-
-fn enter_compound(mut transition_params: TransitionParams) {
-    transition_params.begin(GlobalGameStateTransition::DowntownToCompound);
-}
-
 fn show_label_closed(
     zone_to_inspect_label_entity: &ZoneToInspectLabelEntity,
     inspect_label_events: &mut EventWriter<ChangeHighlightedInspectLabelEvent>,
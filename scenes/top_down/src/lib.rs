@@ -20,6 +20,7 @@ mod sewers;
 mod twinpeaks_apartment;
 
 use common_loading_screen::LoadingScreenState;
+use main_game_lib::loading_watchdog::LoadingWatchdog;
 use prelude::*;
 
 use crate::layout::LayoutEntity;
@@ -52,9 +53,14 @@ pub fn add(app: &mut App) {
         Last,
         finish_when_everything_loaded
             .run_if(in_top_down_loading_state())
-            .run_if(|q: Query<(), With<LayoutEntity>>| !q.is_empty())
             .run_if(in_state(LoadingScreenState::WaitForSignalToFinish)),
     );
+    // report assets that won't load instead of loading forever
+    app.add_systems(
+        Last,
+        main_game_lib::loading_watchdog::watch
+            .run_if(in_top_down_loading_state()),
+    );
     // ready to enter the game when the loading screen is completely gone
     app.add_systems(
         OnEnter(LoadingScreenState::DespawnLoadingScreen),
@@ -77,11 +83,26 @@ pub fn add(app: &mut App) {
     );
 }
 
+/// If any asset the scene waits for failed to load, the loading screen would
+/// never go away.
+/// The game exits with an error instead.
 fn finish_when_everything_loaded(
     mut next_loading_state: ResMut<NextState<LoadingScreenState>>,
+    mut next_state: ResMut<NextState<GlobalGameState>>,
+    scene: Res<State<WhichTopDownScene>>,
+    watchdog: Res<LoadingWatchdog>,
     map: Option<Res<top_down::TileMap>>,
+
+    layout: Query<(), With<LayoutEntity>>,
 ) {
-    if map.is_none() {
+    let failed = watchdog.failed();
+    if !failed.is_empty() {
+        error!("Cannot load {}, assets failed to load: {failed:?}", **scene);
+        next_state.set(GlobalGameState::Exit);
+        return;
+    }
+
+    if map.is_none() || layout.is_empty() {
         return;
     }
 
@@ -107,7 +128,7 @@ fn exit(
     info!("Leaving {}", **scene);
 
     // be a good guy and don't invade other game loops with "Enter"
-    controls.consume(&GlobalAction::Interact);
+    consume_for_transition(&mut controls);
 
     use GlobalGameStateTransition::*;
     match *transition {
@@ -1,14 +1,14 @@
+use std::ops::Range;
+
 use bevy::render::view::RenderLayers;
 use bevy_grid_squared::Square;
 use common_story::Character;
-use common_visuals::camera::render_layer;
+use common_visuals::{camera::render_layer, day_night::TimeOfDay};
 use main_game_lib::{
     cutscene::in_cutscene,
-    hud::notification::NotificationFifo,
+    hud::notification::{Notification, NotificationFifo},
     player_stats::PlayerStats,
-    top_down::{
-        actor::BeginDialogEvent, layout::LAYOUT, npc::behaviors::PatrolSequence,
-    },
+    top_down::{actor::BeginDialogEvent, npc::behaviors::Patrol},
 };
 use rand::prelude::SliceRandom;
 use top_down::{
@@ -20,6 +20,10 @@ use top_down::{
 use crate::prelude::*;
 
 const THIS_SCENE: WhichTopDownScene = WhichTopDownScene::Mall;
+/// Hours of the day during which the ginger cat sells at the Good Water
+/// stand.
+/// The rest of the time the stand is empty.
+const GINGER_CAT_AT_STAND: Range<f32> = 8.0..20.0;
 
 #[derive(TypePath, Default, Debug)]
 struct Mall;
@@ -48,7 +52,13 @@ impl bevy::app::Plugin for Plugin {
         .add_systems(OnExit(THIS_SCENE.leaving()), despawn)
         .add_systems(
             Update,
-            (exit, talk_to_ginger_cat)
+            (
+                exit_zone_interaction(
+                    TopDownAction::Exit,
+                    GlobalGameStateTransition::MallToDowntown,
+                ),
+                talk_to_ginger_cat,
+            )
                 .run_if(on_event::<TopDownAction>())
                 .run_if(in_scene_running_state(THIS_SCENE))
                 .run_if(not(in_cutscene())),
@@ -57,6 +67,7 @@ impl bevy::app::Plugin for Plugin {
 }
 
 struct Spawner<'a> {
+    tilemap: &'a TileMap,
     white_cat_entity: Entity,
     white_cat_patrol_points: &'a mut Vec<Square>,
     player_entity: Entity,
@@ -64,11 +75,13 @@ struct Spawner<'a> {
 }
 
 /// The names are stored in the scene file.
+#[allow(clippy::too_many_arguments)]
 fn spawn(
     mut cmd: Commands,
     asset_server: Res<AssetServer>,
     mut tscn: ResMut<Assets<TscnTree>>,
     mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tilemap: Res<TileMap>,
     mut notifications: ResMut<NotificationFifo>,
     mut player_stats: ResMut<PlayerStats>,
 
@@ -82,10 +95,12 @@ fn spawn(
 
     let player = cmd.spawn_empty().id();
     let mut player_builder = common_story::Character::Winnie.bundle_builder();
+    player_builder.tilemap(&tilemap);
 
     let white_cat = cmd.spawn_empty().id();
     let mut white_cat_builder =
         common_story::Character::WhiteCat.bundle_builder();
+    white_cat_builder.tilemap(&tilemap);
     let mut white_cat_patrol_points = Vec::new();
 
     tscn.spawn_into(
@@ -93,8 +108,10 @@ fn spawn(
         &mut atlas_layouts,
         &asset_server,
         &mut TopDownTsncSpawner::new(
+            tilemap.map_ysort(),
             &mut zone_to_inspect_label_entity,
             &mut Spawner {
+                tilemap: &tilemap,
                 player_entity: player,
                 white_cat_patrol_points: &mut white_cat_patrol_points,
                 player_builder: &mut player_builder,
@@ -125,9 +142,10 @@ fn spawn(
     // SAFETY: we just checked that the vec is not empty
     white_cat_builder
         .initial_square(points.last().copied().unwrap())
-        .behavior_tree(PatrolSequence {
-            wait_at_each: from_millis(10_000),
-            points,
+        .behavior_tree(Patrol {
+            pause: from_millis(10_000),
+            waypoints: points,
+            loop_: true,
         });
     white_cat_builder
         .insert_bundle_into(&asset_server, &mut cmd.entity(white_cat));
@@ -171,28 +189,18 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
                     .get(&who)
                     .expect("Missing description for {name}")
                     .translation;
-                self.white_cat_patrol_points
-                    .push(LAYOUT.world_pos_to_square(translation));
+                self.white_cat_patrol_points.push(
+                    self.tilemap.layout().world_pos_to_square(translation),
+                );
             }
             _ => {}
         }
     }
 }
 
-fn exit(
-    mut transition_params: TransitionParams,
-    mut action_events: EventReader<TopDownAction>,
-) {
-    let is_triggered = action_events
-        .read()
-        .any(|action| matches!(action, TopDownAction::Exit));
-
-    if is_triggered {
-        transition_params.begin(GlobalGameStateTransition::MallToDowntown);
-    }
-}
-
 fn talk_to_ginger_cat(
+    time_of_day: Res<TimeOfDay>,
+    mut notifications: ResMut<NotificationFifo>,
     mut action_events: EventReader<TopDownAction>,
     mut begin_dialog_event: EventWriter<BeginDialogEvent>,
 ) {
@@ -200,7 +208,16 @@ fn talk_to_ginger_cat(
         .read()
         .any(|action| matches!(action, TopDownAction::StartGingerCatDialog));
 
-    if is_triggered {
+    if !is_triggered {
+        return;
+    }
+
+    if GINGER_CAT_AT_STAND.contains(&time_of_day.hours) {
         begin_dialog_event.send(BeginDialogEvent(Character::GingerCat.into()));
+    } else {
+        // LOCALIZATION
+        notifications.push(Notification::PlainText(
+            "The Good Water stand is closed for the night".to_owned(),
+        ));
     }
 }
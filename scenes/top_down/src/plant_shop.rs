@@ -41,9 +41,12 @@ impl bevy::app::Plugin for Plugin {
         .add_systems(OnExit(THIS_SCENE.leaving()), despawn)
         .add_systems(
             Update,
-            exit.run_if(on_event_variant(TopDownAction::Exit))
-                .run_if(in_scene_running_state(THIS_SCENE))
-                .run_if(not(in_cutscene())),
+            exit_zone_interaction(
+                TopDownAction::Exit,
+                GlobalGameStateTransition::PlantShopToDowntown,
+            )
+            .run_if(in_scene_running_state(THIS_SCENE))
+            .run_if(not(in_cutscene())),
         );
     }
 }
@@ -60,11 +63,13 @@ struct Spawner<'a> {
 }
 
 /// The names are stored in the scene file.
+#[allow(clippy::too_many_arguments)]
 fn spawn(
     mut cmd: Commands,
     asset_server: Res<AssetServer>,
     mut tscn: ResMut<Assets<TscnTree>>,
     mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tilemap: Res<TileMap>,
     mut notifications: ResMut<NotificationFifo>,
     mut player_stats: ResMut<PlayerStats>,
 
@@ -78,18 +83,22 @@ fn spawn(
 
     let player = cmd.spawn_empty().id();
     let mut player_builder = common_story::Character::Winnie.bundle_builder();
+    player_builder.tilemap(&tilemap);
 
     let marie = cmd.spawn_empty().id();
     let mut marie_builder = common_story::Character::Marie.bundle_builder();
+    marie_builder.tilemap(&tilemap);
 
     let bolt = cmd.spawn_empty().id();
     let mut bolt_builder = common_story::Character::Bolt.bundle_builder();
+    bolt_builder.tilemap(&tilemap);
 
     tscn.spawn_into(
         &mut cmd,
         &mut atlas_layouts,
         &asset_server,
         &mut TopDownTsncSpawner::new(
+            tilemap.map_ysort(),
             &mut zone_to_inspect_label_entity,
             &mut Spawner {
                 player_entity: player,
@@ -163,7 +172,3 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
         }
     }
 }
-
-fn exit(mut transition_params: TransitionParams) {
-    transition_params.begin(GlobalGameStateTransition::PlantShopToDowntown);
-}
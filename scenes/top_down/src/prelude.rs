@@ -4,7 +4,12 @@ pub(crate) use bevy_rscn::{
     EntityDescriptionMap, NodeName, TscnSpawnHooks, TscnTree, TscnTreeHandle,
 };
 pub(crate) use common_loading_screen::LoadingScreenSettings;
-pub(crate) use common_visuals::camera::{render_layer, MainCamera};
+pub(crate) use common_visuals::{
+    camera::{render_layer, MainCamera},
+    day_night::DayNightCycle,
+    scene_tint::SceneTint,
+    weather::{Weather, WeatherKind},
+};
 pub(crate) use main_game_lib::{
     common_ext::QueryExt,
     cutscene::{self, in_cutscene, IntoCutscene},
@@ -17,6 +22,7 @@ pub(crate) use main_game_lib::{
 };
 pub(crate) use top_down::{
     actor::{CharacterBundleBuilder, CharacterExt},
+    exit_zone_interaction,
     inspect_and_interact::ZoneToInspectLabelEntity,
     TileMap, TopDownAction, TopDownTsncSpawner,
 };
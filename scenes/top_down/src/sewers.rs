@@ -41,9 +41,12 @@ impl bevy::app::Plugin for Plugin {
         .add_systems(OnExit(THIS_SCENE.leaving()), despawn)
         .add_systems(
             Update,
-            exit.run_if(on_event::<TopDownAction>())
-                .run_if(in_scene_running_state(THIS_SCENE))
-                .run_if(not(in_cutscene())),
+            exit_zone_interaction(
+                TopDownAction::Exit,
+                GlobalGameStateTransition::SewersToDowntown,
+            )
+            .run_if(in_scene_running_state(THIS_SCENE))
+            .run_if(not(in_cutscene())),
         );
     }
 }
@@ -56,11 +59,13 @@ struct Spawner<'a> {
 }
 
 /// The names are stored in the scene file.
+#[allow(clippy::too_many_arguments)]
 fn spawn(
     mut cmd: Commands,
     asset_server: Res<AssetServer>,
     mut tscn: ResMut<Assets<TscnTree>>,
     mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tilemap: Res<TileMap>,
     mut notifications: ResMut<NotificationFifo>,
     mut player_stats: ResMut<PlayerStats>,
 
@@ -74,15 +79,18 @@ fn spawn(
 
     let player = cmd.spawn_empty().id();
     let mut player_builder = common_story::Character::Winnie.bundle_builder();
+    player_builder.tilemap(&tilemap);
 
     let cooper = cmd.spawn_empty().id();
     let mut cooper_builder = common_story::Character::Cooper.bundle_builder();
+    cooper_builder.tilemap(&tilemap);
 
     tscn.spawn_into(
         &mut cmd,
         &mut atlas_layouts,
         &asset_server,
         &mut TopDownTsncSpawner::new(
+            tilemap.map_ysort(),
             &mut zone_to_inspect_label_entity,
             &mut Spawner {
                 player_entity: player,
@@ -144,16 +152,3 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
         }
     }
 }
-
-fn exit(
-    mut transition_params: TransitionParams,
-    mut action_events: EventReader<TopDownAction>,
-) {
-    let is_triggered = action_events
-        .read()
-        .any(|action| matches!(action, TopDownAction::Exit));
-
-    if is_triggered {
-        transition_params.begin(GlobalGameStateTransition::SewersToDowntown);
-    }
-}
@@ -1,5 +1,4 @@
 use bevy::render::view::RenderLayers;
-use common_loading_screen::{LoadingScreenSettings, LoadingScreenState};
 use common_visuals::camera::render_layer;
 use main_game_lib::cutscene::in_cutscene;
 use top_down::{
@@ -41,9 +40,12 @@ impl bevy::app::Plugin for Plugin {
         .add_systems(OnExit(THIS_SCENE.leaving()), despawn)
         .add_systems(
             Update,
-            exit.run_if(on_event::<TopDownAction>())
-                .run_if(in_scene_running_state(THIS_SCENE))
-                .run_if(not(in_cutscene())),
+            exit_zone_interaction(
+                TopDownAction::Exit,
+                GlobalGameStateTransition::TwinpeaksApartmentToDowntown,
+            )
+            .run_if(in_scene_running_state(THIS_SCENE))
+            .run_if(not(in_cutscene())),
         );
     }
 }
@@ -62,6 +64,7 @@ fn spawn(
     asset_server: Res<AssetServer>,
     mut tscn: ResMut<Assets<TscnTree>>,
     mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tilemap: Res<TileMap>,
 
     mut q: Query<&mut TscnTreeHandle<TwinpeaksApartment>>,
 ) {
@@ -72,15 +75,18 @@ fn spawn(
 
     let player = cmd.spawn_empty().id();
     let mut player_builder = common_story::Character::Winnie.bundle_builder();
+    player_builder.tilemap(&tilemap);
 
     let phoebe = cmd.spawn_empty().id();
     let mut phoebe_builder = common_story::Character::Phoebe.bundle_builder();
+    phoebe_builder.tilemap(&tilemap);
 
     tscn.spawn_into(
         &mut cmd,
         &mut atlas_layouts,
         &asset_server,
         &mut TopDownTsncSpawner::new(
+            tilemap.map_ysort(),
             &mut zone_to_inspect_label_entity,
             &mut Spawner {
                 player_entity: player,
@@ -142,28 +148,3 @@ impl<'a> TscnSpawnHooks for Spawner<'a> {
         }
     }
 }
-
-fn exit(
-    mut cmd: Commands,
-    mut action_events: EventReader<TopDownAction>,
-    mut transition: ResMut<GlobalGameStateTransition>,
-    mut next_state: ResMut<NextState<GlobalGameState>>,
-    mut next_loading_screen_state: ResMut<NextState<LoadingScreenState>>,
-) {
-    let is_triggered = action_events
-        .read()
-        .any(|action| matches!(action, TopDownAction::Exit));
-
-    if is_triggered {
-        cmd.insert_resource(LoadingScreenSettings {
-            atlas: Some(common_loading_screen::LoadingScreenAtlas::random()),
-            stare_at_loading_screen_for_at_least: Some(from_millis(1000)),
-            ..default()
-        });
-
-        next_loading_screen_state.set(common_loading_screen::start_state());
-
-        *transition = GlobalGameStateTransition::TwinpeaksApartmentToDowntown;
-        next_state.set(THIS_SCENE.leaving());
-    }
-}